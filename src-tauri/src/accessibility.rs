@@ -0,0 +1,183 @@
+//! Full-sentence descriptions of current usage for screen readers. The menu
+//! bar title ("C:25/62%") is unreadable to VoiceOver, so each status item also
+//! gets an accessibility label spelling out every metric and its reset time.
+
+use tauri::{tray::TrayIcon, AppHandle, Manager};
+
+use crate::codex_fetcher::CodexState;
+use crate::i18n;
+use crate::litellm_fetcher::LiteLlmState;
+use crate::openrouter_fetcher::OpenRouterState;
+use crate::privacy;
+use crate::provider::Provider;
+use crate::scheduler::SchedulerState;
+use crate::settings::SettingsState;
+use crate::usage_fetcher::{UsageData, UsageMetric, UsageState};
+
+/// "2 days, 3 hours" or "45 minutes".
+fn duration_phrase(total_minutes: i64) -> String {
+    let unit = |n: i64, one: &'static str, many: &'static str| {
+        i18n::tf(if n == 1 { one } else { many }, &[&n])
+    };
+    let days = total_minutes / (24 * 60);
+    let hours = total_minutes / 60 % 24;
+    let minutes = total_minutes % 60;
+    let parts = if days > 0 {
+        vec![
+            unit(days, "a11y.day", "a11y.days"),
+            unit(hours, "a11y.hour", "a11y.hours"),
+        ]
+    } else if hours > 0 {
+        vec![
+            unit(hours, "a11y.hour", "a11y.hours"),
+            unit(minutes, "a11y.minute", "a11y.minutes"),
+        ]
+    } else {
+        vec![unit(minutes, "a11y.minute", "a11y.minutes")]
+    };
+    parts.join(i18n::t("a11y.separator"))
+}
+
+fn reset_phrase(metric: &UsageMetric) -> String {
+    let Some(at) = metric
+        .resets_at
+        .as_deref()
+        .and_then(|r| chrono::DateTime::parse_from_rfc3339(r).ok())
+    else {
+        return i18n::t("a11y.reset_unknown").into();
+    };
+    let minutes = at.signed_duration_since(chrono::Utc::now()).num_minutes();
+    if minutes <= 0 {
+        i18n::t("a11y.resets_soon").into()
+    } else {
+        i18n::tf("a11y.resets_in", &[&duration_phrase(minutes)])
+    }
+}
+
+fn metric_sentence(name: &str, metric: &UsageMetric) -> String {
+    i18n::tf(
+        "a11y.metric",
+        &[
+            &name,
+            &format!("{:.0}", metric.percent_used),
+            &reset_phrase(metric),
+        ],
+    )
+}
+
+fn usage_sentences(app: &AppHandle, provider: Provider, data: &UsageData) -> String {
+    let name = app
+        .state::<SettingsState>()
+        .get()
+        .display_name(provider.name());
+    let mut sentences = vec![
+        metric_sentence(&i18n::tf("notify.metric_session", &[&name]), &data.session),
+        metric_sentence(
+            &i18n::tf("notify.metric_weekly", &[&name]),
+            &data.weekly_all,
+        ),
+    ];
+    // The third window is only worth reading out when the API reported one
+    if data.weekly_sonnet.resets_at.is_some() {
+        sentences.push(metric_sentence(
+            &format!("{} {}", name, data.weekly_sonnet.label),
+            &data.weekly_sonnet,
+        ));
+    }
+    for w in &data.model_windows {
+        sentences.push(metric_sentence(
+            &format!("{} {}", name, w.metric.label),
+            &w.metric,
+        ));
+    }
+    sentences.push(i18n::tf(
+        "a11y.extra",
+        &[
+            &name,
+            &privacy::money(privacy::is_on(app), data.extra.dollars_spent),
+            &i18n::t(if data.extra.enabled {
+                "tooltip.on"
+            } else {
+                "tooltip.off"
+            }),
+        ],
+    ));
+    if app.state::<SchedulerState>().is_failing(provider) {
+        sentences.push(i18n::tf("a11y.failing", &[&name]));
+    }
+    sentences.join(" ")
+}
+
+/// Description per provider with cached data, keyed by provider id, in tray
+/// order.
+pub fn provider_descriptions(app: &AppHandle) -> Vec<(&'static str, String)> {
+    let mut out = Vec::new();
+    if let Some(d) = app.state::<UsageState>().last_data.lock().unwrap().as_ref() {
+        out.push(("claude", usage_sentences(app, Provider::Claude, d)));
+    }
+    if let Some(d) = app.state::<CodexState>().last_data.lock().unwrap().as_ref() {
+        out.push(("codex", usage_sentences(app, Provider::Codex, d)));
+    }
+    if let Some(d) = app
+        .state::<OpenRouterState>()
+        .last_data
+        .lock()
+        .unwrap()
+        .as_ref()
+    {
+        let name = app
+            .state::<SettingsState>()
+            .get()
+            .display_name(Provider::OpenRouter.name());
+        let remaining = privacy::money(privacy::is_on(app), d.remaining_credits);
+        out.push(("openrouter", i18n::tf("a11y.credits", &[&name, &remaining])));
+    }
+    if let Some(d) = app.state::<LiteLlmState>().last_data.lock().unwrap().as_ref() {
+        let name = app
+            .state::<SettingsState>()
+            .get()
+            .display_name(Provider::LiteLlm.name());
+        let hide = privacy::is_on(app);
+        let text = match d.remaining {
+            Some(remaining) => {
+                i18n::tf("a11y.credits", &[&name, &privacy::money(hide, remaining)])
+            }
+            None => i18n::tf("startup.spent", &[&name, &privacy::money(hide, d.spend)]),
+        };
+        out.push(("litellm", text));
+    }
+    out
+}
+
+/// Every provider's description in one paragraph.
+pub fn usage_description(app: &AppHandle) -> String {
+    let parts = provider_descriptions(app);
+    if parts.is_empty() {
+        return i18n::t("a11y.empty").into();
+    }
+    parts
+        .into_iter()
+        .map(|(_, text)| text)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Sets the VoiceOver label of a menu bar item.
+#[cfg(target_os = "macos")]
+pub fn set_label(tray: &TrayIcon, text: String) {
+    use objc2::MainThreadMarker;
+    use objc2_app_kit::NSAccessibility;
+    use objc2_foundation::NSString;
+
+    let _ = tray.with_inner_tray_icon(move |inner| {
+        let (Some(item), Some(mtm)) = (inner.ns_status_item(), MainThreadMarker::new()) else {
+            return;
+        };
+        if let Some(button) = item.button(mtm) {
+            button.setAccessibilityLabel(Some(&NSString::from_str(&text)));
+        }
+    });
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn set_label(_tray: &TrayIcon, _text: String) {}
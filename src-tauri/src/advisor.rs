@@ -0,0 +1,96 @@
+//! "What can I run?" heuristic. Combines the remaining session and weekly
+//! headroom with the recent burn rate into a one-line recommendation for the
+//! UI and the tray tooltip.
+
+use serde::Serialize;
+
+use crate::history::HistoryState;
+use crate::usage_fetcher::{UsageData, UsageMetric};
+
+/// Session headroom (points) needed to recommend a long run.
+const LONG_RUN_SESSION: f64 = 50.0;
+/// Weekly headroom (points) needed to recommend a long run.
+const LONG_RUN_WEEKLY: f64 = 25.0;
+/// Session headroom below which even short tasks should wait.
+const SHORT_TASK_SESSION: f64 = 15.0;
+/// Weekly headroom below which even short tasks should wait.
+const SHORT_TASK_WEEKLY: f64 = 5.0;
+/// Window the session burn rate is measured over.
+const BURN_WINDOW_SECS: i64 = 3600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdviceLevel {
+    LongRun,
+    ShortTasks,
+    Wait,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Advice {
+    pub provider: String,
+    pub level: AdviceLevel,
+    /// e.g. "Safe for a long agent run", "Wait 1h 40m"
+    pub message: String,
+    /// Seconds until the limiting window resets, when waiting
+    pub wait_secs: Option<i64>,
+    /// Session points used per hour over the last hour
+    pub session_burn_per_hour: f64,
+}
+
+/// Seconds until `metric` resets, if it reports when.
+pub fn secs_until_reset(metric: &UsageMetric) -> Option<i64> {
+    let at = chrono::DateTime::parse_from_rfc3339(metric.resets_at.as_deref()?).ok()?;
+    Some((at.timestamp() - chrono::Utc::now().timestamp()).max(0))
+}
+
+pub fn format_wait(secs: i64) -> String {
+    let (h, m) = (secs / 3600, (secs % 3600) / 60);
+    if h > 0 {
+        format!("{}h {}m", h, m)
+    } else {
+        format!("{}m", m.max(1))
+    }
+}
+
+/// Recommendation for one provider from its latest data and recent history.
+pub fn advise(history: &HistoryState, provider: &str, data: &UsageData) -> Advice {
+    let session_left = 100.0 - data.session.percent_used;
+    let weekly_left = 100.0 - data.weekly_all.percent_used;
+    let since = chrono::Utc::now().timestamp() - BURN_WINDOW_SECS;
+    let burn = history.growth(provider, "session", since).unwrap_or(0.0);
+
+    // Session runs out before it resets at the current pace
+    let outpaced = burn > 0.0
+        && secs_until_reset(&data.session)
+            .is_some_and(|reset| session_left / burn * 3600.0 < reset as f64);
+
+    let (level, wait_secs) = if session_left < SHORT_TASK_SESSION || weekly_left < SHORT_TASK_WEEKLY
+    {
+        let limiting = if weekly_left < SHORT_TASK_WEEKLY {
+            &data.weekly_all
+        } else {
+            &data.session
+        };
+        (AdviceLevel::Wait, secs_until_reset(limiting))
+    } else if session_left >= LONG_RUN_SESSION && weekly_left >= LONG_RUN_WEEKLY && !outpaced {
+        (AdviceLevel::LongRun, None)
+    } else {
+        (AdviceLevel::ShortTasks, None)
+    };
+
+    let message = match (level, wait_secs) {
+        (AdviceLevel::LongRun, _) => "Safe for a long agent run".to_string(),
+        (AdviceLevel::ShortTasks, _) => "Stick to short tasks".to_string(),
+        (AdviceLevel::Wait, Some(secs)) => format!("Wait {}", format_wait(secs)),
+        (AdviceLevel::Wait, None) => "Wait for the next reset".to_string(),
+    };
+
+    Advice {
+        provider: provider.into(),
+        level,
+        message,
+        wait_secs,
+        session_burn_per_hour: burn,
+    }
+}
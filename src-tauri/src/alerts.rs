@@ -0,0 +1,174 @@
+//! Forwards threshold and limit-reached alerts to external channels in
+//! addition to the macOS notification.
+//!
+//! Each enabled channel is sent to in the background; a failing channel never
+//! blocks the others or the fetch that raised the alert.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::discord::{self, DiscordState};
+use crate::email;
+use crate::notifications::Severity;
+use crate::settings::SettingsState;
+use crate::shell_hook;
+use crate::telegram;
+use crate::usage_fetcher::{UsageData, UsageState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// A metric crossed the notification threshold
+    Threshold,
+    /// A metric reached 100%
+    LimitReached,
+    /// A metric is still above the reminder level
+    Reminder,
+    /// Sent from settings to check a channel
+    Test,
+}
+
+/// One metric of the provider that raised the alert.
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertMetric {
+    pub label: String,
+    pub percent: f64,
+    pub reset_info: String,
+    /// RFC 3339, if the provider reports it
+    pub resets_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub severity: Severity,
+    pub provider: String,
+    /// Label of the metric that triggered the alert
+    pub metric: String,
+    pub percent: f64,
+    pub title: String,
+    pub body: String,
+    /// Snapshot of every metric of the provider
+    pub metrics: Vec<AlertMetric>,
+    /// RFC 3339
+    pub at: String,
+}
+
+impl Alert {
+    pub fn new(
+        kind: AlertKind,
+        severity: Severity,
+        provider: &str,
+        title: &str,
+        body: &str,
+    ) -> Self {
+        Self {
+            kind,
+            severity,
+            provider: provider.into(),
+            metric: String::new(),
+            percent: 0.0,
+            title: title.into(),
+            body: body.into(),
+            metrics: Vec::new(),
+            at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Attaches the triggering metric and a snapshot of the provider's usage.
+    pub fn with_metric(mut self, metric: &str, percent: f64, data: &UsageData) -> Self {
+        self.metric = metric.into();
+        self.percent = percent;
+        self.metrics = data
+            .windows()
+            .into_iter()
+            .map(|m| AlertMetric {
+                label: m.label.clone(),
+                percent: m.percent_used,
+                reset_info: m.reset_info.clone(),
+                resets_at: m.resets_at.clone(),
+            })
+            .collect();
+        self
+    }
+
+    /// Plain-text rendering shared by text-only channels.
+    pub fn text(&self) -> String {
+        let mut lines = vec![self.title.clone(), self.body.clone()];
+        if !self.metrics.is_empty() {
+            lines.push(String::new());
+            for m in &self.metrics {
+                lines.push(format!("{}: {:.0}% - {}", m.label, m.percent, m.reset_info));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    Email,
+    Discord,
+    Telegram,
+    Shell,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 4] = [
+        Channel::Email,
+        Channel::Discord,
+        Channel::Telegram,
+        Channel::Shell,
+    ];
+}
+
+/// Sends `alert` through one channel, whether or not it is enabled.
+pub async fn send_to(app: &AppHandle, channel: Channel, alert: &Alert) -> Result<(), String> {
+    let settings = app.state::<SettingsState>().get();
+    match channel {
+        Channel::Email => email::send(&settings.email, alert).await,
+        Channel::Discord => {
+            let client = app.state::<UsageState>().client();
+            discord::send(&client, &app.state::<DiscordState>(), alert).await
+        }
+        Channel::Telegram => telegram::send(&app.state::<UsageState>().client(), alert).await,
+        Channel::Shell => match settings.shell_hook.as_deref() {
+            Some(script) => shell_hook::run(app, script, alert).await,
+            None => Err("No hook script is set".into()),
+        },
+    }
+}
+
+fn enabled(app: &AppHandle, channel: Channel) -> bool {
+    let settings = app.state::<SettingsState>().get();
+    match channel {
+        Channel::Email => settings.email.enabled,
+        Channel::Discord => settings.discord_enabled,
+        Channel::Telegram => settings.telegram_enabled,
+        Channel::Shell => settings.shell_hook.is_some(),
+    }
+}
+
+/// Forwards `alert` to every enabled channel in the background.
+pub fn dispatch(app: &AppHandle, alert: Alert) {
+    for channel in Channel::ALL.into_iter().filter(|c| enabled(app, *c)) {
+        let handle = app.clone();
+        let alert = alert.clone();
+        tauri::async_runtime::spawn(async move {
+            let _ = send_to(&handle, channel, &alert).await;
+        });
+    }
+}
+
+/// Sends a test alert through `channel` so settings can be verified.
+pub async fn test(app: &AppHandle, channel: Channel) -> Result<(), String> {
+    let alert = Alert::new(
+        AlertKind::Test,
+        Severity::Routine,
+        "Usage Widget",
+        "Test alert",
+        "Alert channel is set up correctly.",
+    );
+    send_to(app, channel, &alert).await
+}
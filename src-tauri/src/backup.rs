@@ -0,0 +1,137 @@
+//! Backup and restore of everything the app keeps locally: settings (provider
+//! labels, reset anchors, budgets and the rest) and the usage history, bundled
+//! into one zip archive with a manifest.
+//!
+//! Secrets are left out by design. The OpenRouter key, webhook and bot tokens,
+//! SMTP password and team token live in the Keychain and never enter the
+//! archive, so a restored install asks for them again. Settings that only make
+//! sense on the machine they were made on (a hand-picked cookie file, granted
+//! Keychain access) are kept as they are when restoring.
+//!
+//! Archives carry a format version. Ones written by a newer version of the app
+//! are refused rather than half restored.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::history::HistoryState;
+use crate::i18n;
+use crate::settings::{Settings, SettingsState};
+use crate::tray;
+use crate::widget_window;
+
+const FORMAT: &str = "claude-codex-usage-backup";
+/// Current archive layout; bump when entries change incompatibly.
+const VERSION: u32 = 1;
+const MANIFEST_ENTRY: &str = "manifest.json";
+const SETTINGS_ENTRY: &str = "settings.json";
+const HISTORY_ENTRY: &str = "history.db";
+/// Scratch copy of the history database while it goes in or out of an archive
+const HISTORY_SCRATCH: &str = "history-backup.db";
+
+/// Describes an archive; stored as its first entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: String,
+    pub version: u32,
+    /// Version of the app that wrote the archive
+    pub app_version: String,
+    /// RFC 3339
+    pub created_at: String,
+}
+
+fn scratch_path(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    let dir = app.path().app_cache_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(HISTORY_SCRATCH);
+    let _ = std::fs::remove_file(&path);
+    Ok(path)
+}
+
+/// Writes settings and history to a zip archive at `path`.
+pub fn backup(app: &AppHandle, path: &Path) -> Result<Manifest, String> {
+    let manifest = Manifest {
+        format: FORMAT.into(),
+        version: VERSION,
+        app_version: app.package_info().version.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let scratch = scratch_path(app)?;
+    app.state::<HistoryState>().backup_to(&scratch)?;
+
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut add = |name: &str, bytes: &[u8]| -> Result<(), String> {
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(bytes).map_err(|e| e.to_string())
+    };
+    add(
+        MANIFEST_ENTRY,
+        &serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?,
+    )?;
+    let settings = app.state::<SettingsState>().get();
+    add(
+        SETTINGS_ENTRY,
+        &serde_json::to_vec_pretty(&settings).map_err(|e| e.to_string())?,
+    )?;
+    add(
+        HISTORY_ENTRY,
+        &std::fs::read(&scratch).map_err(|e| e.to_string())?,
+    )?;
+    zip.finish().map_err(|e| e.to_string())?;
+    let _ = std::fs::remove_file(&scratch);
+    Ok(manifest)
+}
+
+/// Replaces settings and history with the contents of the archive at `path`.
+pub fn restore(app: &AppHandle, path: &Path) -> Result<Manifest, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = ZipArchive::new(file).map_err(|_| "Not a backup archive".to_string())?;
+
+    let manifest: Manifest = archive
+        .by_name(MANIFEST_ENTRY)
+        .map_err(|_| "Not a backup archive".to_string())
+        .and_then(|entry| serde_json::from_reader(entry).map_err(|e| e.to_string()))?;
+    if manifest.format != FORMAT {
+        return Err("Not a backup archive".into());
+    }
+    if manifest.version > VERSION {
+        return Err(format!(
+            "This backup was made by a newer version of the app ({}); update before restoring",
+            manifest.app_version
+        ));
+    }
+    let mut restored: Settings = archive
+        .by_name(SETTINGS_ENTRY)
+        .map_err(|e| e.to_string())
+        .and_then(|entry| serde_json::from_reader(entry).map_err(|e| e.to_string()))?;
+
+    let scratch = scratch_path(app)?;
+    {
+        let mut entry = archive.by_name(HISTORY_ENTRY).map_err(|e| e.to_string())?;
+        let mut out = File::create(&scratch).map_err(|e| e.to_string())?;
+        std::io::copy(&mut entry, &mut out).map_err(|e| e.to_string())?;
+    }
+    let result = app.state::<HistoryState>().restore_from(&scratch);
+    let _ = std::fs::remove_file(&scratch);
+    result?;
+
+    let state = app.state::<SettingsState>();
+    let current = state.get();
+    restored.claude_cookies_path = current.claude_cookies_path;
+    restored.claude_cookies_bookmark = current.claude_cookies_bookmark;
+    restored.keychain_access_granted = current.keychain_access_granted;
+    let settings = state.update(|s| *s = restored)?;
+    i18n::set_language(settings.language);
+    i18n::set_currency_display(settings.currency_display);
+    tray::relabel(app);
+    let _ = widget_window::apply_dock_icon(app);
+    Ok(manifest)
+}
@@ -0,0 +1,80 @@
+//! Security-scoped bookmarks for files the user picked by hand. Under the App
+//! Sandbox, access granted through the open panel lasts only until the app
+//! quits; a bookmark made right after the pick keeps it across launches.
+//!
+//! Bookmarks are stored base64-encoded in settings and resolved at startup,
+//! which starts access to the file for the rest of the session.
+
+use std::path::{Path, PathBuf};
+
+/// A read-only security-scoped bookmark for `path`, base64-encoded.
+#[cfg(target_os = "macos")]
+pub fn create(path: &Path) -> Result<String, String> {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use objc2_foundation::{NSString, NSURLBookmarkCreationOptions, NSURL};
+
+    let url = NSURL::fileURLWithPath(&NSString::from_str(&path.to_string_lossy()));
+    let data = url
+        .bookmarkDataWithOptions_includingResourceValuesForKeys_relativeToURL_error(
+            NSURLBookmarkCreationOptions::WithSecurityScope
+                | NSURLBookmarkCreationOptions::SecurityScopeAllowOnlyReadAccess,
+            None,
+            None,
+        )
+        .map_err(|e| {
+            format!(
+                "Failed to bookmark {}: {}",
+                path.display(),
+                e.localizedDescription()
+            )
+        })?;
+    Ok(STANDARD.encode(data.to_vec()))
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn create(_path: &Path) -> Result<String, String> {
+    Err("Bookmarks are only available on macOS".into())
+}
+
+/// Resolves a bookmark from `create` and starts accessing the file. Returns
+/// its current path, and a fresh bookmark when the stored one went stale.
+#[cfg(target_os = "macos")]
+pub fn resolve(bookmark: &str) -> Result<(PathBuf, Option<String>), String> {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use objc2::runtime::Bool;
+    use objc2_foundation::{NSData, NSURLBookmarkResolutionOptions, NSURL};
+
+    let bytes = STANDARD.decode(bookmark).map_err(|e| e.to_string())?;
+    let mut stale = Bool::NO;
+    // SAFETY: `stale` is a valid pointer for the duration of the call
+    let url = unsafe {
+        NSURL::URLByResolvingBookmarkData_options_relativeToURL_bookmarkDataIsStale_error(
+            &NSData::with_bytes(&bytes),
+            NSURLBookmarkResolutionOptions::WithSecurityScope,
+            None,
+            &mut stale,
+        )
+    }
+    .map_err(|e| format!("Failed to resolve bookmark: {}", e.localizedDescription()))?;
+    // Access is kept until the app quits
+    // SAFETY: balanced by the system when the process exits
+    if !unsafe { url.startAccessingSecurityScopedResource() } {
+        return Err("Access to the bookmarked file was denied".into());
+    }
+    let path = PathBuf::from(
+        url.path()
+            .ok_or("The bookmark doesn't point to a file")?
+            .to_string(),
+    );
+    let renewed = if stale.as_bool() {
+        Some(create(&path)?)
+    } else {
+        None
+    };
+    Ok((path, renewed))
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn resolve(_bookmark: &str) -> Result<(PathBuf, Option<String>), String> {
+    Err("Bookmarks are only available on macOS".into())
+}
@@ -0,0 +1,146 @@
+//! Personal usage budgets, softer than provider limits: "keep weekly under 60%
+//! by Thursday". Each budget is compared against a linear schedule from the
+//! start of the usage window to its deadline, and a routine notification goes
+//! out once per window when usage runs ahead of that schedule.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Datelike, Duration, Local, TimeZone, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::i18n;
+use crate::notifications::{self, Severity};
+use crate::settings::{Budget, SettingsState};
+use crate::usage_fetcher::{UsageData, UsageMetric};
+
+/// Points above the schedule before a budget counts as ahead.
+const SLACK_POINTS: f64 = 5.0;
+
+/// Budgets already reported, keyed by budget, with the window they were
+/// reported for, so each window notifies at most once.
+pub struct BudgetState {
+    notified: Mutex<HashMap<String, String>>,
+}
+
+impl BudgetState {
+    pub fn new() -> Self {
+        Self {
+            notified: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetProgress {
+    pub budget: Budget,
+    pub used_percent: f64,
+    /// Where usage would be now on a straight line to the target
+    pub expected_percent: f64,
+    /// RFC 3339
+    pub deadline: String,
+    /// Usage is more than the slack above the schedule, or past the target
+    pub ahead: bool,
+}
+
+fn window_length(metric: &str) -> Duration {
+    match metric {
+        "session" => Duration::hours(5),
+        _ => Duration::days(7),
+    }
+}
+
+fn metric<'a>(data: &'a UsageData, metric: &str) -> &'a UsageMetric {
+    match metric {
+        "session" => &data.session,
+        _ => &data.weekly_all,
+    }
+}
+
+/// End of the first `weekday` after `start` (local time), capped at `reset`.
+fn deadline(budget: &Budget, start: DateTime<Utc>, reset: DateTime<Utc>) -> DateTime<Utc> {
+    let Some(weekday) = budget.by_weekday else {
+        return reset;
+    };
+    let local = start.with_timezone(&Local);
+    let days = (7 + weekday.num_days_from_monday() - local.weekday().num_days_from_monday()) % 7;
+    let day = local.date_naive() + Duration::days(i64::from(days));
+    let end = day
+        .and_hms_opt(23, 59, 59)
+        .and_then(|t| Local.from_local_datetime(&t).single());
+    end.map(|t| t.with_timezone(&Utc))
+        .unwrap_or(reset)
+        .min(reset)
+}
+
+/// Progress of one budget, if the provider reports when the window resets.
+pub fn progress(budget: &Budget, data: &UsageData, now: DateTime<Utc>) -> Option<BudgetProgress> {
+    let m = metric(data, &budget.metric);
+    let reset = DateTime::parse_from_rfc3339(m.resets_at.as_deref()?)
+        .ok()?
+        .with_timezone(&Utc);
+    let start = reset - window_length(&budget.metric);
+    let deadline = deadline(budget, start, reset);
+
+    let span = (deadline - start).num_seconds().max(1) as f64;
+    let elapsed = (now - start).num_seconds().clamp(0, span as i64) as f64;
+    let expected = budget.target_percent * elapsed / span;
+    let ahead = m.percent_used >= budget.target_percent
+        || (now < deadline && m.percent_used > expected + SLACK_POINTS);
+    Some(BudgetProgress {
+        budget: budget.clone(),
+        used_percent: m.percent_used,
+        expected_percent: expected,
+        deadline: deadline.to_rfc3339(),
+        ahead,
+    })
+}
+
+fn key(budget: &Budget) -> String {
+    format!(
+        "{}_{}_{}_{:?}",
+        budget.provider, budget.metric, budget.target_percent, budget.by_weekday
+    )
+}
+
+/// Notifies about budgets for `provider` that run ahead of schedule. Call
+/// after each fetch.
+pub fn check(app: &AppHandle, provider: &str, data: &UsageData) {
+    let settings = app.state::<SettingsState>().get();
+    if !settings.notifications_enabled {
+        return;
+    }
+    let now = Utc::now();
+    let state = app.state::<BudgetState>();
+    for budget in settings.budgets.iter().filter(|b| b.provider == provider) {
+        let Some(p) = progress(budget, data, now) else {
+            continue;
+        };
+        let window = metric(data, &budget.metric)
+            .resets_at
+            .clone()
+            .unwrap_or_default();
+        let mut notified = state.notified.lock().unwrap();
+        if !p.ahead || notified.get(&key(budget)) == Some(&window) {
+            continue;
+        }
+        notified.insert(key(budget), window);
+        drop(notified);
+
+        let name = settings.display_name(provider);
+        let title = i18n::tf(
+            "budget.ahead",
+            &[&name, &metric(data, &budget.metric).label],
+        );
+        let body = i18n::tf(
+            "budget.ahead_body",
+            &[
+                &format!("{:.0}", p.used_percent),
+                &format!("{:.0}", budget.target_percent),
+                &format!("{:.0}", p.expected_percent),
+            ],
+        );
+        notifications::send(app, Severity::Routine, &title, &body);
+    }
+}
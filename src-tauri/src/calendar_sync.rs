@@ -0,0 +1,149 @@
+//! Keeps an "AI limit resets" calendar in Calendar.app in step with the weekly
+//! resets, as an alternative to the one-off `.ics` export in `resets`.
+//!
+//! Calendar.app is driven through AppleScript (`osascript`), which goes to
+//! EventKit underneath without linking it. macOS asks once for permission to
+//! control Calendar. Each provider has at most one upcoming event; when its
+//! reset time moves, the upcoming event is replaced and past ones are kept.
+
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Datelike, Duration, Local, Timelike};
+use tauri::{AppHandle, Manager};
+
+use crate::provider::Provider;
+use crate::resets;
+use crate::settings::SettingsState;
+
+/// Name of the calendar the events go into; created on first sync.
+const CALENDAR_NAME: &str = "AI limit resets";
+
+/// Length of each reset event.
+const EVENT_MINUTES: i64 = 30;
+
+/// Reset times that move by less than this aren't rewritten, since the APIs
+/// report them with some jitter.
+const TOLERANCE_MINS: i64 = 5;
+
+pub struct CalendarSyncState {
+    /// Reset time last written per provider
+    synced: Mutex<HashMap<Provider, DateTime<Local>>>,
+}
+
+impl CalendarSyncState {
+    pub fn new() -> Self {
+        Self {
+            synced: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Forgets what was written, so the next sync writes every event again.
+    pub fn reset(&self) {
+        self.synced.lock().unwrap().clear();
+    }
+}
+
+/// AppleScript string literal.
+fn quoted(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Sets `var` to `at` component by component, as parsing a date string would
+/// depend on the system locale.
+fn date_script(var: &str, at: DateTime<Local>) -> String {
+    format!(
+        "set {v} to current date\n\
+         set day of {v} to 1\n\
+         set year of {v} to {}\n\
+         set month of {v} to {}\n\
+         set day of {v} to {}\n\
+         set time of {v} to {}\n",
+        at.year(),
+        at.month(),
+        at.day(),
+        at.num_seconds_from_midnight(),
+        v = var
+    )
+}
+
+/// Script that creates the calendar if needed, drops the provider's upcoming
+/// reset events and adds the new one.
+fn sync_script(provider: Provider, at: DateTime<Local>) -> String {
+    let summary = quoted(&format!("{} weekly limit resets", provider.name()));
+    let calendar = quoted(CALENDAR_NAME);
+    let end = at + Duration::minutes(EVENT_MINUTES);
+    format!(
+        "{start}{end}\
+         tell application \"Calendar\"\n\
+         if not (exists calendar {calendar}) then \
+         make new calendar with properties {{name:{calendar}}}\n\
+         set cal to calendar {calendar}\n\
+         delete (every event of cal whose summary is {summary} and start date > (current date))\n\
+         make new event at end of events of cal with properties \
+         {{summary:{summary}, start date:startDate, end date:endDate, \
+         description:\"Weekly usage window resets - a good time for big agent runs.\"}}\n\
+         end tell\n",
+        start = date_script("startDate", at),
+        end = date_script("endDate", end),
+    )
+}
+
+fn run(script: &str) -> Result<(), String> {
+    let out = Command::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .output()
+        .map_err(|e| format!("Failed to run osascript: {}", e))?;
+    if !out.status.success() {
+        return Err(format!(
+            "Calendar update failed: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Writes the weekly resets that moved since the last sync, on a background
+/// thread. A no-op unless calendar sync is on. Call after each fetch.
+pub fn sync(app: &AppHandle) {
+    if !app.state::<SettingsState>().get().calendar_sync {
+        return;
+    }
+    let state = app.state::<CalendarSyncState>();
+    let changed: Vec<(Provider, DateTime<Local>)> = {
+        let synced = state.synced.lock().unwrap();
+        resets::next_weekly_resets(app)
+            .into_iter()
+            .filter_map(|r| {
+                let at = DateTime::parse_from_rfc3339(&r.resets_at).ok()?;
+                Some((r.provider, at.with_timezone(&Local)))
+            })
+            .filter(|(provider, at)| match synced.get(provider) {
+                Some(previous) => (*at - *previous).num_minutes().abs() >= TOLERANCE_MINS,
+                None => true,
+            })
+            .collect()
+    };
+    if changed.is_empty() {
+        return;
+    }
+    // Claim them now so overlapping fetches don't write twice
+    state.synced.lock().unwrap().extend(changed.iter().copied());
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        for (provider, at) in changed {
+            if let Err(e) = run(&sync_script(provider, at)) {
+                log::warn!("{}", e);
+                // Try again after the next fetch
+                app.state::<CalendarSyncState>()
+                    .synced
+                    .lock()
+                    .unwrap()
+                    .remove(&provider);
+            }
+        }
+    });
+}
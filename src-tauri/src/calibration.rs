@@ -0,0 +1,185 @@
+//! Estimates how many local tokens one percentage point of a rate-limit window
+//! is worth, by correlating token counts from Claude Code / Codex CLI logs with
+//! the percentage rises observed between history samples.
+//!
+//! A point is worth a different number of tokens on each plan, so only
+//! samples since the latest plan change count. Until those are enough, an
+//! estimate from the previous plan is scaled by the two tiers' advertised
+//! allowances when both are known (Pro, Max 5x, Max 20x).
+
+use serde::Serialize;
+
+use crate::codex_logs::{format_tokens, CodexEventKind};
+use crate::history::{HistoryState, Sample};
+use crate::usage_fetcher::PlanTier;
+use crate::{claude_logs, codex_logs};
+
+/// How far back to look when calibrating.
+const LOOKBACK_SECS: i64 = 7 * 86_400;
+/// Minimum number of rising intervals before an estimate is trusted.
+const MIN_INTERVALS: u32 = 3;
+/// Minimum total percentage rise before an estimate is trusted.
+const MIN_TOTAL_RISE: f64 = 2.0;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowEstimate {
+    /// "session" or "weekly"
+    pub metric: String,
+    pub percent_used: f64,
+    pub tokens_per_percent: Option<f64>,
+    pub tokens_remaining: Option<u64>,
+    /// Number of sample intervals the estimate is based on
+    pub intervals: u32,
+    /// e.g. "~420k tokens remaining in this session"
+    pub summary: String,
+    /// Scaled from samples taken before the latest plan change
+    pub from_previous_plan: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CapacityEstimate {
+    pub provider: String,
+    /// Plan tier the estimate is for, e.g. "Max 5x", when known
+    pub plan: Option<String>,
+    pub windows: Vec<WindowEstimate>,
+}
+
+/// Token count per percentage point from rising intervals between consecutive
+/// samples. `tokens` must be sorted by timestamp.
+fn tokens_per_percent(samples: &[&Sample], tokens: &[(i64, u64)]) -> (Option<f64>, u32) {
+    let mut total_rise = 0.0;
+    let mut total_tokens = 0u64;
+    let mut intervals = 0u32;
+
+    for pair in samples.windows(2) {
+        let (start, end) = (pair[0], pair[1]);
+        let rise = end.percent - start.percent;
+        if rise <= 0.0 {
+            continue;
+        }
+        let lo = tokens.partition_point(|(ts, _)| *ts <= start.ts);
+        let hi = tokens.partition_point(|(ts, _)| *ts <= end.ts);
+        let in_interval: u64 = tokens[lo..hi].iter().map(|(_, n)| n).sum();
+        if in_interval == 0 {
+            continue;
+        }
+        total_rise += rise;
+        total_tokens += in_interval;
+        intervals += 1;
+    }
+
+    if intervals < MIN_INTERVALS || total_rise < MIN_TOTAL_RISE {
+        return (None, intervals);
+    }
+    (Some(total_tokens as f64 / total_rise), intervals)
+}
+
+fn tier_of(plan: Option<&str>) -> Option<PlanTier> {
+    plan.and_then(|p| PlanTier::from_plan(p).or_else(|| PlanTier::from_codex(p)))
+}
+
+/// When the plan changed within the lookback: the time of the change, and the
+/// factor that turns tokens per point on the old plan into the new one, if
+/// both tiers have a known allowance.
+fn plan_change(history: &HistoryState, provider: &str, since: i64) -> Option<(i64, Option<f64>)> {
+    let change = history
+        .annotations_since(Some(provider), since)
+        .ok()?
+        .into_iter()
+        .rev()
+        .find(|a| a.kind == "plan")?;
+    // The first plan ever recorded is not a change
+    let before = history.annotation_before(provider, "plan", change.ts)?;
+    let scale = tier_of(Some(&before))
+        .and_then(PlanTier::multiplier)
+        .zip(tier_of(Some(&change.text)).and_then(PlanTier::multiplier))
+        .map(|(old, new)| new / old);
+    Some((change.ts, scale))
+}
+
+fn estimate_provider(
+    history: &HistoryState,
+    provider: &str,
+    tokens: &[(i64, u64)],
+    since: i64,
+) -> Result<CapacityEstimate, String> {
+    let samples = history.samples_since(Some(provider), since)?;
+    let latest = history.latest(provider);
+    let change = plan_change(history, provider, since);
+    let plan_start = change.map_or(i64::MIN, |(ts, _)| ts);
+
+    let windows = [("session", "in this session"), ("weekly", "this week")]
+        .into_iter()
+        .map(|(metric, window)| {
+            let (current, earlier): (Vec<&Sample>, Vec<&Sample>) = samples
+                .iter()
+                .filter(|s| s.metric == metric)
+                .partition(|s| s.ts >= plan_start);
+            let (mut tpp, mut intervals) = tokens_per_percent(&current, tokens);
+            let mut from_previous_plan = false;
+            if let (None, Some((_, Some(scale)))) = (tpp, change) {
+                if let (Some(old), n) = tokens_per_percent(&earlier, tokens) {
+                    tpp = Some(old * scale);
+                    intervals = n;
+                    from_previous_plan = true;
+                }
+            }
+            let percent_used = latest.get(metric).copied().unwrap_or(0.0);
+            let tokens_remaining =
+                tpp.map(|t| ((100.0 - percent_used).max(0.0) * t).round() as u64);
+            let summary = match tokens_remaining {
+                Some(n) if from_previous_plan => format!(
+                    "~{} tokens remaining {} (scaled from the previous plan)",
+                    format_tokens(n),
+                    window
+                ),
+                Some(n) => format!("~{} tokens remaining {}", format_tokens(n), window),
+                None => "Not enough data to estimate yet".into(),
+            };
+            WindowEstimate {
+                metric: metric.into(),
+                percent_used,
+                tokens_per_percent: tpp,
+                tokens_remaining,
+                intervals,
+                summary,
+                from_previous_plan,
+            }
+        })
+        .collect();
+
+    Ok(CapacityEstimate {
+        provider: provider.into(),
+        plan: tier_of(history.latest_annotation(provider, "plan").as_deref())
+            .map(|t| t.label().to_string()),
+        windows,
+    })
+}
+
+/// Capacity estimates for every provider that has local logs available.
+pub fn capacity_estimates(history: &HistoryState) -> Vec<CapacityEstimate> {
+    let since = chrono::Utc::now().timestamp() - LOOKBACK_SECS;
+    let mut out = Vec::new();
+
+    if let Ok(entries) = claude_logs::read_entries(since) {
+        let tokens: Vec<(i64, u64)> = entries.iter().map(|e| (e.ts, e.billable_tokens())).collect();
+        if let Ok(est) = estimate_provider(history, "Claude", &tokens, since) {
+            out.push(est);
+        }
+    }
+
+    if let Ok(events) = codex_logs::read_events(since) {
+        let tokens: Vec<(i64, u64)> = events
+            .iter()
+            .filter_map(|e| match &e.kind {
+                CodexEventKind::Tokens(t) => Some((e.ts, t.total)),
+                _ => None,
+            })
+            .collect();
+        if let Ok(est) = estimate_provider(history, "Codex", &tokens, since) {
+            out.push(est);
+        }
+    }
+
+    out
+}
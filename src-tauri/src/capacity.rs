@@ -0,0 +1,146 @@
+//! Opt-in machine-readable snapshot of remaining capacity at
+//! `~/.claude-usage/capacity.json`, rewritten after every fetch, so hooks and
+//! CI scripts can check the budget before launching expensive agent runs.
+//!
+//! The file is replaced atomically (write to a temp file, then rename), so
+//! readers never see a partial document.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::advisor::{self, AdviceLevel};
+use crate::codex_fetcher::CodexState;
+use crate::history::HistoryState;
+use crate::litellm_fetcher::{GatewayBudgetData, LiteLlmState};
+use crate::openrouter_fetcher::{OpenRouterCreditsData, OpenRouterState};
+use crate::usage_fetcher::{UsageData, UsageMetric, UsageState};
+
+const DIR: &str = ".claude-usage";
+const FILE: &str = "capacity.json";
+
+#[derive(Serialize)]
+struct Window {
+    used_percent: f64,
+    remaining_percent: f64,
+    /// RFC 3339, if the provider reports it
+    resets_at: Option<String>,
+    /// `resets_at` as Unix seconds, for shell scripts
+    resets_at_unix: Option<i64>,
+}
+
+impl From<&UsageMetric> for Window {
+    fn from(m: &UsageMetric) -> Self {
+        Self {
+            used_percent: m.percent_used,
+            remaining_percent: (100.0 - m.percent_used).max(0.0),
+            resets_at: m.resets_at.clone(),
+            resets_at_unix: m
+                .resets_at
+                .as_deref()
+                .and_then(|r| chrono::DateTime::parse_from_rfc3339(r).ok())
+                .map(|r| r.timestamp()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct UsageCapacity {
+    session: Window,
+    weekly: Window,
+    weekly_model: Window,
+    /// Advisor verdict: "long_run", "short_tasks" or "wait"
+    advice: AdviceLevel,
+    message: String,
+    /// Seconds until the limiting window resets, when waiting
+    wait_secs: Option<i64>,
+    fetched_at: String,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum ProviderCapacity {
+    Usage(Box<UsageCapacity>),
+    Credits(OpenRouterCreditsData),
+    Budget(GatewayBudgetData),
+}
+
+#[derive(Serialize)]
+struct Capacity {
+    /// Bumped on incompatible changes to the format
+    version: u32,
+    updated_at: String,
+    /// `updated_at` as Unix seconds, for shell scripts
+    updated_at_unix: i64,
+    /// Keyed by lowercase provider id ("claude", "codex", "openrouter", "litellm")
+    providers: BTreeMap<&'static str, ProviderCapacity>,
+}
+
+pub fn path() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join(DIR).join(FILE))
+}
+
+fn usage_capacity(history: &HistoryState, provider: &str, data: &UsageData) -> ProviderCapacity {
+    let advice = advisor::advise(history, provider, data);
+    ProviderCapacity::Usage(Box::new(UsageCapacity {
+        session: (&data.session).into(),
+        weekly: (&data.weekly_all).into(),
+        weekly_model: (&data.weekly_sonnet).into(),
+        advice: advice.level,
+        message: advice.message,
+        wait_secs: advice.wait_secs,
+        fetched_at: data.fetched_at.clone(),
+    }))
+}
+
+/// Rewrites the capacity file from the cached data of every provider.
+pub fn write(app: &AppHandle) -> Result<(), String> {
+    let history = app.state::<HistoryState>();
+    let mut providers = BTreeMap::new();
+    if let Some(d) = app.state::<UsageState>().last_data.lock().unwrap().as_ref() {
+        providers.insert("claude", usage_capacity(&history, "Claude", d));
+    }
+    if let Some(d) = app.state::<CodexState>().last_data.lock().unwrap().as_ref() {
+        providers.insert("codex", usage_capacity(&history, "Codex", d));
+    }
+    if let Some(d) = app
+        .state::<OpenRouterState>()
+        .last_data
+        .lock()
+        .unwrap()
+        .as_ref()
+    {
+        providers.insert("openrouter", ProviderCapacity::Credits(d.clone()));
+    }
+    if let Some(d) = app.state::<LiteLlmState>().last_data.lock().unwrap().as_ref() {
+        providers.insert("litellm", ProviderCapacity::Budget(d.clone()));
+    }
+
+    let now = chrono::Utc::now();
+    let capacity = Capacity {
+        version: 1,
+        updated_at: now.to_rfc3339(),
+        updated_at_unix: now.timestamp(),
+        providers,
+    };
+    let json = serde_json::to_string_pretty(&capacity).map_err(|e| e.to_string())?;
+
+    let path = path().ok_or("No home directory")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, &path).map_err(|e| e.to_string())
+}
+
+/// Removes the file when the integration is turned off, so scripts don't act
+/// on stale numbers.
+pub fn remove() -> Result<(), String> {
+    match path().map(std::fs::remove_file) {
+        Some(Err(e)) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,164 @@
+//! Installs a Claude Code hook that warns or blocks when the Claude session
+//! usage is above a ceiling.
+//!
+//! The hook script reads the widget's capacity file (see `capacity`), so it
+//! needs no running server and keeps working from the last fetch while the
+//! widget is paused. A file older than `MAX_AGE_SECS`, or a session window
+//! that has reset since, is ignored, so a widget that was quit can't keep
+//! blocking on an old reading. It is registered for `SessionStart` (warning
+//! shown when a session opens) and `PreToolUse` (exit code 2 blocks the tool
+//! call and tells Claude why) in `~/.claude/settings.json`, leaving other
+//! hooks untouched.
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+
+use crate::capacity;
+
+const SCRIPT_NAME: &str = "usage-widget-guard.sh";
+const EVENTS: [&str; 2] = ["SessionStart", "PreToolUse"];
+
+/// Three fetches at the longest refresh interval.
+const MAX_AGE_SECS: u64 = 3 * 900;
+
+fn claude_dir() -> Result<PathBuf, String> {
+    dirs::home_dir()
+        .map(|h| h.join(".claude"))
+        .ok_or_else(|| "No home directory".into())
+}
+
+fn script(ceiling: u32, block: bool) -> Result<String, String> {
+    let capacity = capacity::path().ok_or("No home directory")?;
+    Ok(format!(
+        r#"#!/bin/sh
+# Installed by Usage Widget; reinstall from its settings to change the ceiling.
+CAPACITY="{capacity}"
+CEILING={ceiling}
+MODE={mode}
+EVENT="$1"
+
+[ -f "$CAPACITY" ] || exit 0
+now=$(date +%s)
+updated=$(plutil -extract updated_at_unix raw -o - "$CAPACITY" 2>/dev/null) || exit 0
+case "$updated" in ''|*[!0-9]*) exit 0 ;; esac
+[ $((now - updated)) -le {max_age} ] || exit 0
+resets=$(plutil -extract providers.claude.session.resets_at_unix raw -o - "$CAPACITY" 2>/dev/null)
+case "$resets" in
+    ''|*[!0-9]*) ;;
+    *) [ "$resets" -gt "$now" ] || exit 0 ;;
+esac
+used=$(plutil -extract providers.claude.session.used_percent raw -o - "$CAPACITY" 2>/dev/null) || exit 0
+used=${{used%%.*}}
+[ "$used" -ge "$CEILING" ] 2>/dev/null || exit 0
+
+msg="Claude session usage is at $used% (ceiling $CEILING%)."
+if [ "$MODE" = block ] && [ "$EVENT" = PreToolUse ]; then
+    echo "$msg Stop and wait for the session window to reset." >&2
+    exit 2
+fi
+printf '{{"systemMessage": "%s"}}\n' "$msg"
+"#,
+        capacity = capacity.display(),
+        ceiling = ceiling,
+        max_age = MAX_AGE_SECS,
+        mode = if block { "block" } else { "warn" },
+    ))
+}
+
+/// Whether a hook matcher group runs our script.
+fn is_ours(group: &Value) -> bool {
+    group["hooks"].as_array().is_some_and(|hooks| {
+        hooks.iter().any(|h| {
+            h["command"]
+                .as_str()
+                .is_some_and(|c| c.contains(SCRIPT_NAME))
+        })
+    })
+}
+
+fn read_settings(path: &Path) -> Result<Value, String> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(json!({})),
+        Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+/// Adds (`Some(command)`) or removes (`None`) our entries in Claude Code's
+/// settings, keeping everything else as it was. The file is replaced
+/// atomically, and the previous version is kept as `settings.json.bak`.
+fn update_settings(command: Option<&str>) -> Result<(), String> {
+    let path = claude_dir()?.join("settings.json");
+    if command.is_none() && !path.exists() {
+        return Ok(());
+    }
+    let mut settings = read_settings(&path)?;
+    let root = settings
+        .as_object_mut()
+        .ok_or("Claude Code settings are not a JSON object")?;
+    let hooks = root.entry("hooks").or_insert_with(|| json!({}));
+    let hooks = hooks
+        .as_object_mut()
+        .ok_or("Claude Code \"hooks\" setting is not an object")?;
+
+    for event in EVENTS {
+        let groups = hooks.entry(event).or_insert_with(|| json!([]));
+        let Some(list) = groups.as_array_mut() else {
+            continue;
+        };
+        list.retain(|g| !is_ours(g));
+        if let Some(cmd) = command {
+            let mut group = json!({
+                "hooks": [{ "type": "command", "command": format!("{} {}", cmd, event) }]
+            });
+            if event == "PreToolUse" {
+                group["matcher"] = json!("*");
+            }
+            list.push(group);
+        }
+        if list.is_empty() {
+            hooks.remove(event);
+        }
+    }
+    if hooks.is_empty() {
+        root.remove("hooks");
+    }
+
+    let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    // Write next to the real file when settings.json is a symlink
+    let path = std::fs::canonicalize(&path).unwrap_or(path);
+    let write_err = |e: std::io::Error| format!("Failed to write {}: {}", path.display(), e);
+    if path.exists() {
+        std::fs::copy(&path, path.with_extension("json.bak")).map_err(write_err)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(write_err)?;
+    std::fs::rename(&tmp, &path).map_err(write_err)
+}
+
+/// Writes the hook script and registers it, replacing any earlier install.
+/// Returns the script path.
+pub fn install(ceiling: u32, block: bool) -> Result<String, String> {
+    let dir = claude_dir()?.join("hooks");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(SCRIPT_NAME);
+    std::fs::write(&path, script(ceiling, block)?).map_err(|e| e.to_string())?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+        .map_err(|e| e.to_string())?;
+
+    let command = format!("\"{}\"", path.display());
+    update_settings(Some(&command))?;
+    Ok(path.display().to_string())
+}
+
+pub fn uninstall() -> Result<(), String> {
+    update_settings(None)?;
+    let path = claude_dir()?.join("hooks").join(SCRIPT_NAME);
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,238 @@
+//! Parses Claude Code session transcripts for local token accounting.
+//!
+//! Claude Code writes one JSONL transcript per session under
+//! `~/.claude/projects/<encoded-cwd>/<session-id>.jsonl`. Assistant entries carry
+//! the model, working directory, timestamp, and a `usage` block with token counts,
+//! which lets us break down consumption by project even though the claude.ai API
+//! only reports account-wide percentages.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+const CLAUDE_PROJECTS_PATH: &str = ".claude/projects";
+
+/// Token usage from a single assistant response.
+#[derive(Debug, Clone)]
+pub struct TokenEntry {
+    /// Unix timestamp (seconds)
+    pub ts: i64,
+    /// Working directory the session ran in
+    pub project: String,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+}
+
+impl TokenEntry {
+    /// Tokens that count meaningfully against limits. Cache reads are excluded
+    /// because they are both huge and heavily discounted.
+    pub fn billable_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cache_creation_tokens
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectUsage {
+    /// Full working-directory path
+    pub project: String,
+    /// Last path component, for display
+    pub name: String,
+    pub billable_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub messages: u32,
+    /// Fraction (0-1) of billable tokens in the range
+    pub share: f64,
+    /// Weekly-percentage growth attributed to this project
+    pub est_weekly_percent: f64,
+}
+
+/// Time range accepted by the breakdown commands.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageRange {
+    Day,
+    Week,
+    Month,
+}
+
+impl UsageRange {
+    pub fn seconds(self) -> i64 {
+        match self {
+            UsageRange::Day => 86_400,
+            UsageRange::Week => 7 * 86_400,
+            UsageRange::Month => 30 * 86_400,
+        }
+    }
+}
+
+// --- Transcript line types ---
+
+#[derive(Deserialize)]
+struct TranscriptLine {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    timestamp: Option<String>,
+    cwd: Option<String>,
+    #[serde(rename = "requestId")]
+    request_id: Option<String>,
+    message: Option<TranscriptMessage>,
+}
+
+#[derive(Deserialize)]
+struct TranscriptMessage {
+    id: Option<String>,
+    model: Option<String>,
+    usage: Option<TranscriptUsage>,
+}
+
+#[derive(Deserialize)]
+struct TranscriptUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    cache_creation_input_tokens: u64,
+    #[serde(default)]
+    cache_read_input_tokens: u64,
+}
+
+fn projects_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join(CLAUDE_PROJECTS_PATH))
+}
+
+/// Reads all assistant token entries newer than `since` (unix seconds).
+pub fn read_entries(since: i64) -> Result<Vec<TokenEntry>, String> {
+    let dir = projects_dir().ok_or("Cannot find home directory")?;
+    if !dir.is_dir() {
+        return Err("Claude Code transcripts not found (~/.claude/projects)".into());
+    }
+
+    let mut entries = Vec::new();
+    // Responses are sometimes logged more than once (e.g. resumed sessions)
+    let mut seen = HashSet::new();
+
+    let project_dirs = std::fs::read_dir(&dir).map_err(|e| e.to_string())?;
+    for project_dir in project_dirs.flatten() {
+        let Ok(files) = std::fs::read_dir(project_dir.path()) else {
+            continue;
+        };
+        for file in files.flatten() {
+            let path = file.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                continue;
+            }
+            if !modified_since(&path, since) {
+                continue;
+            }
+            let fallback = project_dir.file_name().to_string_lossy().to_string();
+            read_file(&path, &fallback, since, &mut seen, &mut entries);
+        }
+    }
+
+    entries.sort_by_key(|e| e.ts);
+    Ok(entries)
+}
+
+fn modified_since(path: &Path, since: i64) -> bool {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map(|t| chrono::DateTime::<chrono::Utc>::from(t).timestamp() >= since)
+        .unwrap_or(true)
+}
+
+fn read_file(
+    path: &Path,
+    fallback_project: &str,
+    since: i64,
+    seen: &mut HashSet<String>,
+    entries: &mut Vec<TokenEntry>,
+) {
+    let Ok(file) = std::fs::File::open(path) else {
+        return;
+    };
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        let Ok(parsed) = serde_json::from_str::<TranscriptLine>(&line) else {
+            continue;
+        };
+        if parsed.kind.as_deref() != Some("assistant") {
+            continue;
+        }
+        let Some(message) = parsed.message else {
+            continue;
+        };
+        let Some(usage) = message.usage else {
+            continue;
+        };
+        let Some(ts) = parsed
+            .timestamp
+            .as_deref()
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.timestamp())
+        else {
+            continue;
+        };
+        if ts < since {
+            continue;
+        }
+        if let (Some(id), Some(req)) = (&message.id, &parsed.request_id) {
+            if !seen.insert(format!("{}:{}", id, req)) {
+                continue;
+            }
+        }
+
+        entries.push(TokenEntry {
+            ts,
+            project: parsed.cwd.unwrap_or_else(|| fallback_project.to_string()),
+            model: message.model.unwrap_or_else(|| "unknown".into()),
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cache_creation_tokens: usage.cache_creation_input_tokens,
+            cache_read_tokens: usage.cache_read_input_tokens,
+        });
+    }
+}
+
+/// Groups entries by project and splits `weekly_growth` (percentage points the
+/// weekly window grew over the same range) proportionally to billable tokens.
+pub fn usage_by_project(entries: &[TokenEntry], weekly_growth: f64) -> Vec<ProjectUsage> {
+    let mut by_project: HashMap<&str, (u64, u64, u32)> = HashMap::new();
+    for e in entries {
+        let slot = by_project.entry(e.project.as_str()).or_default();
+        slot.0 += e.billable_tokens();
+        slot.1 += e.cache_read_tokens;
+        slot.2 += 1;
+    }
+
+    let total: u64 = by_project.values().map(|v| v.0).sum();
+    let mut out: Vec<ProjectUsage> = by_project
+        .into_iter()
+        .map(|(project, (billable, cache_read, messages))| {
+            let share = if total > 0 {
+                billable as f64 / total as f64
+            } else {
+                0.0
+            };
+            ProjectUsage {
+                project: project.to_string(),
+                name: Path::new(project)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| project.to_string()),
+                billable_tokens: billable,
+                cache_read_tokens: cache_read,
+                messages,
+                share,
+                est_weekly_percent: weekly_growth * share,
+            }
+        })
+        .collect();
+
+    out.sort_by_key(|p| std::cmp::Reverse(p.billable_tokens));
+    out
+}
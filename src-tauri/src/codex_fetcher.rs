@@ -4,13 +4,24 @@
 //! calls `GET https://chatgpt.com/backend-api/wham/usage` to retrieve rate limit
 //! data including primary (session) and secondary (weekly) windows, model-specific
 //! limits, and credit balance.
+//!
+//! The plan comes from the payload's `plan_type`. For Team, Business,
+//! Enterprise and Edu plans the workspace seat is read from the claims of the
+//! CLI's ID token, which the usage endpoint doesn't report.
 
 use std::sync::Mutex;
 
-use reqwest::Client;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use reqwest::{Client, RequestBuilder, StatusCode};
 use serde::Deserialize;
+use zeroize::Zeroizing;
 
-use crate::usage_fetcher::{ExtraUsage, UsageData, UsageMetric};
+use crate::http_cache::{self, Fetched, Validators};
+use crate::http_client;
+use crate::i18n;
+use crate::secret::Secret;
+use crate::usage_fetcher::{ExtraUsage, PlanTier, Seat, UsageData, UsageMetric};
 
 pub struct CodexState {
     pub last_data: Mutex<Option<UsageData>>,
@@ -34,6 +45,31 @@ struct CodexAuth {
 #[derive(Deserialize)]
 struct CodexTokens {
     access_token: Option<String>,
+    #[serde(default)]
+    id_token: Option<String>,
+}
+
+/// The part of the ID token's claims that describes the ChatGPT account.
+#[derive(Deserialize)]
+struct IdTokenClaims {
+    #[serde(rename = "https://api.openai.com/auth", default)]
+    auth: Option<AccountClaims>,
+}
+
+#[derive(Deserialize)]
+struct AccountClaims {
+    #[serde(default)]
+    organizations: Vec<OrganizationClaim>,
+}
+
+#[derive(Deserialize)]
+struct OrganizationClaim {
+    #[serde(default)]
+    is_default: bool,
+    #[serde(default)]
+    role: Option<String>,
+    #[serde(default)]
+    title: Option<String>,
 }
 
 // --- API response types ---
@@ -83,77 +119,137 @@ struct Credits {
     balance: Option<String>,
 }
 
-fn read_codex_token() -> Result<String, String> {
-    let home = dirs::home_dir().ok_or("Cannot find home directory")?;
-    let auth_path = home.join(".codex/auth.json");
+/// Location of the Codex CLI's OAuth credentials.
+pub fn auth_path() -> Option<std::path::PathBuf> {
+    dirs::home_dir().map(|home| home.join(".codex/auth.json"))
+}
+
+fn read_auth() -> Result<CodexAuth, String> {
+    let auth_path = auth_path().ok_or("Cannot find home directory")?;
 
     if !auth_path.exists() {
         return Err("Codex CLI not configured (~/.codex/auth.json not found)".into());
     }
 
-    let content =
-        std::fs::read_to_string(&auth_path).map_err(|e| format!("Failed to read auth.json: {}", e))?;
+    // auth.json also holds the refresh token; wipe it once parsed
+    let content = std::fs::read_to_string(&auth_path)
+        .map(Zeroizing::new)
+        .map_err(|e| format!("Failed to read auth.json: {}", e))?;
 
-    let auth: CodexAuth =
-        serde_json::from_str(&content).map_err(|e| format!("Failed to parse auth.json: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse auth.json: {}", e))
+}
 
-    auth.tokens
+fn read_codex_token() -> Result<Secret, String> {
+    read_auth()?
+        .tokens
         .and_then(|t| t.access_token)
+        .map(Secret::new)
         .filter(|t| !t.is_empty())
         .ok_or_else(|| "No access token found in Codex auth.json".into())
 }
 
-pub async fn fetch_codex_usage(client: &Client) -> Result<UsageData, String> {
-    let token = read_codex_token()?;
+/// Workspace seat from the default organization in the ID token's claims.
+/// The token isn't verified; it only labels the account.
+fn read_seat() -> Option<Seat> {
+    let id_token = read_auth().ok()?.tokens?.id_token.map(Zeroizing::new)?;
+    let payload = id_token.split('.').nth(1)?;
+    let bytes = Zeroizing::new(URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?);
+    let claims: IdTokenClaims = serde_json::from_slice(&bytes).ok()?;
+    let orgs = claims.auth?.organizations;
+    let org = orgs
+        .iter()
+        .find(|o| o.is_default)
+        .or_else(|| orgs.first())?;
+    Some(Seat {
+        workspace: org.title.clone().filter(|t| !t.is_empty()),
+        role: org.role.clone().filter(|r| !r.is_empty()),
+    })
+}
 
-    let resp = client
+fn usage_request(client: &Client, token: &Secret) -> RequestBuilder {
+    client
         .get("https://chatgpt.com/backend-api/wham/usage")
-        .header("Authorization", format!("Bearer {}", token))
+        .bearer_auth(token.expose())
         .header("User-Agent", "codex-cli")
         .header("Accept", "application/json")
+}
+
+/// The usage payload as returned, for debugging schema changes.
+pub async fn fetch_raw(client: &Client) -> Result<serde_json::Value, String> {
+    let token = read_codex_token()?;
+    http_client::raw_json("Codex", usage_request(client, &token)).await
+}
+
+pub async fn fetch_codex_usage(
+    client: &Client,
+    validators: Option<&Validators>,
+) -> Result<Fetched<UsageData>, String> {
+    let token = read_codex_token()?;
+
+    let req = usage_request(client, &token);
+    let resp = http_cache::conditional(req, validators)
         .send()
         .await
-        .map_err(|e| format!("Codex request failed: {}", e))?;
+        .map_err(|e| http_client::request_error("Codex", e))?;
 
+    if resp.status() == StatusCode::NOT_MODIFIED {
+        return Ok(Fetched::NotModified);
+    }
     if !resp.status().is_success() {
         let status = resp.status();
         let body = resp.text().await.unwrap_or_default();
         return Err(format!("Codex API returned {}: {}", status, &body[..body.len().min(200)]));
     }
 
+    let fresh = Validators::from_response(&resp);
     let payload: WhamUsageResponse = resp
         .json()
         .await
         .map_err(|e| format!("Failed to parse Codex response: {}", e))?;
 
-    Ok(convert_payload(payload))
+    let mut data = convert_payload(payload);
+    if data.plan_tier.is_some_and(PlanTier::is_workspace) {
+        data.seat = read_seat();
+    }
+    Ok(Fetched::Fresh(data, fresh))
 }
 
 fn format_seconds(secs: i64) -> String {
     if secs <= 0 {
-        return "Resets soon".into();
+        return i18n::t("reset.soon").into();
     }
     let hours = secs / 3600;
     let mins = (secs % 3600) / 60;
     if hours > 0 {
-        format!("Resets in {}h {}m", hours, mins)
+        i18n::tf("reset.in_hours", &[&hours, &mins])
     } else {
-        format!("Resets in {}m", mins)
+        i18n::tf("reset.in_minutes", &[&mins])
     }
 }
 
+/// Absolute reset time for a window that resets `secs` from now.
+fn reset_time(secs: i64) -> Option<String> {
+    (secs > 0).then(|| (chrono::Utc::now() + chrono::Duration::seconds(secs)).to_rfc3339())
+}
+
 fn window_label(secs: i64) -> String {
     let hours = secs / 3600;
     if hours >= 24 {
         let days = hours / 24;
-        format!("{}-day window", days)
+        i18n::tf("usage.day_window", &[&days])
     } else {
-        format!("{}-hour window", hours)
+        i18n::tf("usage.hour_window", &[&hours])
     }
 }
 
 fn convert_payload(payload: WhamUsageResponse) -> UsageData {
-    let plan = payload.plan_type.unwrap_or_else(|| "unknown".into());
+    let plan_type = payload.plan_type.filter(|p| !p.is_empty());
+    let plan_tier = plan_type.as_deref().and_then(PlanTier::from_codex);
+    let plan = match (plan_tier, &plan_type) {
+        (Some(tier), _) => tier.label().to_string(),
+        (None, Some(p)) => p.clone(),
+        (None, None) => "unknown".into(),
+    };
 
     // Primary window (5-hour session)
     let mut session = match payload.rate_limit.as_ref().and_then(|rl| rl.primary_window.as_ref()) {
@@ -161,16 +257,18 @@ fn convert_payload(payload: WhamUsageResponse) -> UsageData {
             label: window_label(w.limit_window_seconds),
             percent_used: w.used_percent as f64,
             reset_info: format_seconds(w.reset_after_seconds),
+            resets_at: reset_time(w.reset_after_seconds),
         },
         None => UsageMetric {
-            label: "Session".into(),
+            label: i18n::t("usage.session").into(),
             percent_used: 0.0,
-            reset_info: "No data".into(),
+            reset_info: i18n::t("usage.no_data").into(),
+            resets_at: None,
         },
     };
 
     if payload.rate_limit.as_ref().map(|rl| rl.limit_reached).unwrap_or(false) {
-        session.reset_info = format!("LIMIT REACHED - {}", session.reset_info);
+        session.reset_info = i18n::tf("usage.limit_reached", &[&session.reset_info]);
     }
 
     // Secondary window (7-day weekly)
@@ -179,11 +277,13 @@ fn convert_payload(payload: WhamUsageResponse) -> UsageData {
             label: window_label(w.limit_window_seconds),
             percent_used: w.used_percent as f64,
             reset_info: format_seconds(w.reset_after_seconds),
+            resets_at: reset_time(w.reset_after_seconds),
         },
         None => UsageMetric {
-            label: "Weekly".into(),
+            label: i18n::t("usage.weekly").into(),
             percent_used: 0.0,
-            reset_info: "No data".into(),
+            reset_info: i18n::t("usage.no_data").into(),
+            resets_at: None,
         },
     };
 
@@ -199,12 +299,14 @@ fn convert_payload(payload: WhamUsageResponse) -> UsageData {
                 label: l.limit_name.clone(),
                 percent_used: pw.used_percent as f64,
                 reset_info: format_seconds(pw.reset_after_seconds),
+                resets_at: reset_time(pw.reset_after_seconds),
             })
         })
         .unwrap_or_else(|| UsageMetric {
-            label: format!("Plan: {}", plan),
+            label: i18n::tf("usage.plan", &[&plan]),
             percent_used: 0.0,
             reset_info: "---".into(),
+            resets_at: None,
         });
 
     let extra = match payload.credits {
@@ -213,7 +315,11 @@ fn convert_payload(payload: WhamUsageResponse) -> UsageData {
             ExtraUsage {
                 dollars_spent: balance,
                 percent_used: 0.0,
-                reset_date: if c.unlimited { "Unlimited".into() } else { "---".into() },
+                reset_date: if c.unlimited {
+                    i18n::t("usage.unlimited").into()
+                } else {
+                    "---".into()
+                },
                 enabled: c.has_credits || c.unlimited,
             }
         }
@@ -229,7 +335,13 @@ fn convert_payload(payload: WhamUsageResponse) -> UsageData {
         session,
         weekly_all: weekly,
         weekly_sonnet: model_limit,
+        model_windows: Vec::new(),
         extra,
         fetched_at: chrono::Utc::now().to_rfc3339(),
+        plan: plan_type,
+        plan_tier,
+        seat: None,
+        redacted: false,
+        api_weekly_resets_at: None,
     }
 }
@@ -0,0 +1,268 @@
+//! Parses Codex CLI session logs for local task, turn, and token counts.
+//!
+//! The Codex CLI records each session as a JSONL "rollout" under
+//! `~/.codex/sessions/YYYY/MM/DD/rollout-*.jsonl`. Every line has a timestamp and
+//! a typed payload; we count one task per rollout file, one turn per
+//! `user_message` event, and sum `last_token_usage` from `token_count` events.
+//! The wham API only reports percentages, so this is the only source of
+//! absolute numbers for Codex.
+
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+
+const CODEX_SESSIONS_PATH: &str = ".codex/sessions";
+
+#[derive(Debug, Clone)]
+pub enum CodexEventKind {
+    /// A new session (rollout file) started
+    Task,
+    /// The user sent a message
+    Turn,
+    /// Tokens consumed by one model response
+    Tokens(CodexTokens),
+}
+
+#[derive(Debug, Clone)]
+pub struct CodexTokens {
+    /// Model from the most recent `turn_context`, if any
+    pub model: String,
+    pub input: u64,
+    pub cached_input: u64,
+    pub output: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CodexEvent {
+    /// Unix timestamp (seconds)
+    pub ts: i64,
+    pub kind: CodexEventKind,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CodexDayActivity {
+    /// Local date, YYYY-MM-DD
+    pub date: String,
+    pub tasks: u32,
+    pub turns: u32,
+    pub tokens: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CodexActivity {
+    /// Most recent day first
+    pub days: Vec<CodexDayActivity>,
+    /// e.g. "today: 14 tasks, ~230k tokens"
+    pub summary: String,
+}
+
+// --- Rollout line types ---
+
+#[derive(Deserialize)]
+struct RolloutLine {
+    timestamp: Option<String>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    payload: Option<RolloutPayload>,
+}
+
+#[derive(Deserialize)]
+struct RolloutPayload {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    model: Option<String>,
+    info: Option<TokenCountInfo>,
+}
+
+#[derive(Deserialize)]
+struct TokenCountInfo {
+    last_token_usage: Option<TokenUsage>,
+}
+
+#[derive(Deserialize)]
+struct TokenUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    cached_input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    total_tokens: u64,
+}
+
+fn sessions_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join(CODEX_SESSIONS_PATH))
+}
+
+/// Reads all task, turn, and token events newer than `since` (unix seconds).
+pub fn read_events(since: i64) -> Result<Vec<CodexEvent>, String> {
+    let dir = sessions_dir().ok_or("Cannot find home directory")?;
+    if !dir.is_dir() {
+        return Err("Codex session logs not found (~/.codex/sessions)".into());
+    }
+
+    let mut files = Vec::new();
+    collect_rollouts(&dir, since, &mut files);
+
+    let mut events = Vec::new();
+    for path in files {
+        read_rollout(&path, since, &mut events);
+    }
+    events.sort_by_key(|e| e.ts);
+    Ok(events)
+}
+
+fn collect_rollouts(dir: &Path, since: i64, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_rollouts(&path, since, out);
+        } else if path.extension().and_then(|e| e.to_str()) == Some("jsonl") {
+            let recent = std::fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .map(|t| chrono::DateTime::<chrono::Utc>::from(t).timestamp() >= since)
+                .unwrap_or(true);
+            if recent {
+                out.push(path);
+            }
+        }
+    }
+}
+
+fn read_rollout(path: &Path, since: i64, events: &mut Vec<CodexEvent>) {
+    let Ok(file) = std::fs::File::open(path) else {
+        return;
+    };
+
+    let mut started = false;
+    let mut model = String::from("unknown");
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        let Ok(parsed) = serde_json::from_str::<RolloutLine>(&line) else {
+            continue;
+        };
+        let Some(ts) = parsed
+            .timestamp
+            .as_deref()
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.timestamp())
+        else {
+            continue;
+        };
+
+        // The first timestamped line marks when the task started
+        if !started {
+            started = true;
+            if ts >= since {
+                events.push(CodexEvent {
+                    ts,
+                    kind: CodexEventKind::Task,
+                });
+            }
+        }
+        let Some(payload) = parsed.payload else {
+            continue;
+        };
+        if parsed.kind.as_deref() == Some("turn_context") {
+            if let Some(m) = payload.model {
+                model = m;
+            }
+            continue;
+        }
+        if ts < since || parsed.kind.as_deref() != Some("event_msg") {
+            continue;
+        }
+
+        match payload.kind.as_deref() {
+            Some("user_message") => events.push(CodexEvent {
+                ts,
+                kind: CodexEventKind::Turn,
+            }),
+            Some("token_count") => {
+                let Some(usage) = payload.info.and_then(|i| i.last_token_usage) else {
+                    continue;
+                };
+                if usage.total_tokens > 0 {
+                    events.push(CodexEvent {
+                        ts,
+                        kind: CodexEventKind::Tokens(CodexTokens {
+                            model: model.clone(),
+                            input: usage.input_tokens,
+                            cached_input: usage.cached_input_tokens,
+                            output: usage.output_tokens,
+                            total: usage.total_tokens,
+                        }),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Buckets events into local calendar days, most recent first.
+pub fn daily_activity(events: &[CodexEvent]) -> Vec<CodexDayActivity> {
+    let mut days: BTreeMap<String, CodexDayActivity> = BTreeMap::new();
+    for e in events {
+        let Some(local) = Local.timestamp_opt(e.ts, 0).single() else {
+            continue;
+        };
+        let date = local.format("%Y-%m-%d").to_string();
+        let day = days.entry(date.clone()).or_insert(CodexDayActivity {
+            date,
+            tasks: 0,
+            turns: 0,
+            tokens: 0,
+        });
+        match &e.kind {
+            CodexEventKind::Task => day.tasks += 1,
+            CodexEventKind::Turn => day.turns += 1,
+            CodexEventKind::Tokens(t) => day.tokens += t.total,
+        }
+    }
+    days.into_values().rev().collect()
+}
+
+/// Compact token count for display: 950, 230k, 1.2M.
+pub fn format_tokens(tokens: u64) -> String {
+    if tokens >= 1_000_000 {
+        format!("{:.1}M", tokens as f64 / 1_000_000.0)
+    } else if tokens >= 1_000 {
+        format!("{}k", tokens / 1_000)
+    } else {
+        tokens.to_string()
+    }
+}
+
+/// Task/turn/token counts for the last `days` local days.
+pub fn activity(days: u32) -> Result<CodexActivity, String> {
+    let start_of_today = Local::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|dt| Local.from_local_datetime(&dt).single())
+        .map(|dt| dt.timestamp())
+        .unwrap_or_else(|| chrono::Utc::now().timestamp());
+    let since = start_of_today - i64::from(days.saturating_sub(1)) * 86_400;
+
+    let events = read_events(since)?;
+    let days = daily_activity(&events);
+
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    let summary = match days.iter().find(|d| d.date == today) {
+        Some(d) => format!(
+            "today: {} tasks, ~{} tokens",
+            d.tasks,
+            format_tokens(d.tokens)
+        ),
+        None => "today: no Codex activity".into(),
+    };
+
+    Ok(CodexActivity { days, summary })
+}
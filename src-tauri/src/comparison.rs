@@ -0,0 +1,217 @@
+//! Side-by-side comparison of every provider with cached data, normalized to
+//! the same columns (percent used, time to reset, estimated capacity left) so
+//! the UI can lay them out as one table.
+//!
+//! The most constrained provider is the one whose tightest window has the
+//! least room left. OpenRouter and LiteLLM count with the share of their
+//! credits or budget spent; they have no reset time, so a tie goes to the
+//! provider that resets last.
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::advisor;
+use crate::calibration::{self, CapacityEstimate};
+use crate::codex_fetcher::CodexState;
+use crate::history::HistoryState;
+use crate::litellm_fetcher::LiteLlmState;
+use crate::openrouter_fetcher::OpenRouterState;
+use crate::privacy;
+use crate::provider::Provider;
+use crate::scheduler::SchedulerState;
+use crate::settings::SettingsState;
+use crate::usage_fetcher::{UsageData, UsageMetric, UsageState};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComparedWindow {
+    /// "session", "weekly", "sonnet", or a model family
+    pub metric: String,
+    pub label: String,
+    pub percent_used: f64,
+    pub remaining_percent: f64,
+    /// RFC 3339, if the provider reports it
+    pub resets_at: Option<String>,
+    pub secs_to_reset: Option<i64>,
+    /// Tokens left in the window, from local log calibration
+    pub est_tokens_remaining: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComparisonRow {
+    /// "Claude", "Codex", "OpenRouter" or "LiteLLM"
+    pub provider: String,
+    pub display_name: String,
+    /// Plan tier label, e.g. "Max 5x"
+    pub plan: Option<String>,
+    pub session: Option<ComparedWindow>,
+    pub weekly: Option<ComparedWindow>,
+    /// Window with the least room left, model windows included
+    pub tightest: Option<ComparedWindow>,
+    /// Percent of the tightest window used, or of credits or budget spent
+    pub constraint_percent: f64,
+    /// OpenRouter credits or LiteLLM budget left; hidden in privacy mode
+    pub credits_remaining_usd: Option<f64>,
+    /// The last refresh failed, so the numbers may be stale
+    pub failing: bool,
+    pub fetched_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComparisonSummary {
+    pub rows: Vec<ComparisonRow>,
+    /// Provider id of the row with the least room left
+    pub most_constrained: Option<String>,
+    /// RFC 3339
+    pub generated_at: String,
+}
+
+fn window(metric: &str, m: &UsageMetric, estimate: Option<&CapacityEstimate>) -> ComparedWindow {
+    let est_tokens_remaining = estimate
+        .and_then(|e| e.windows.iter().find(|w| w.metric == metric))
+        .and_then(|w| w.tokens_remaining);
+    ComparedWindow {
+        metric: metric.into(),
+        label: m.label.clone(),
+        percent_used: m.percent_used,
+        remaining_percent: (100.0 - m.percent_used).max(0.0),
+        resets_at: m.resets_at.clone(),
+        secs_to_reset: advisor::secs_until_reset(m),
+        est_tokens_remaining,
+    }
+}
+
+fn usage_row(
+    app: &AppHandle,
+    provider: Provider,
+    data: &UsageData,
+    estimates: &[CapacityEstimate],
+) -> ComparisonRow {
+    let estimate = estimates.iter().find(|e| e.provider == provider.name());
+    let mut windows = vec![
+        window("session", &data.session, estimate),
+        window("weekly", &data.weekly_all, estimate),
+    ];
+    // The third window only counts when the API reported one
+    if data.weekly_sonnet.resets_at.is_some() {
+        windows.push(window("sonnet", &data.weekly_sonnet, estimate));
+    }
+    windows.extend(
+        data.model_windows
+            .iter()
+            .map(|w| window(&w.family, &w.metric, estimate)),
+    );
+    let tightest = windows
+        .iter()
+        .max_by(|a, b| a.percent_used.total_cmp(&b.percent_used))
+        .cloned();
+    let data = privacy::usage(privacy::is_on(app), data.clone());
+    ComparisonRow {
+        provider: provider.name().into(),
+        display_name: app
+            .state::<SettingsState>()
+            .get()
+            .display_name(provider.name()),
+        plan: data.plan_tier.map(|t| t.label().to_string()),
+        constraint_percent: tightest.as_ref().map_or(0.0, |w| w.percent_used),
+        session: Some(windows[0].clone()),
+        weekly: Some(windows[1].clone()),
+        tightest,
+        credits_remaining_usd: None,
+        failing: app.state::<SchedulerState>().is_failing(provider),
+        fetched_at: data.fetched_at,
+    }
+}
+
+/// Row of a provider that spends money instead of filling windows.
+fn spend_row(
+    app: &AppHandle,
+    provider: Provider,
+    percent: f64,
+    remaining: Option<f64>,
+    fetched_at: &str,
+) -> ComparisonRow {
+    ComparisonRow {
+        provider: provider.name().into(),
+        display_name: app
+            .state::<SettingsState>()
+            .get()
+            .display_name(provider.name()),
+        plan: None,
+        session: None,
+        weekly: None,
+        tightest: None,
+        constraint_percent: percent,
+        credits_remaining_usd: remaining.filter(|_| !privacy::is_on(app)),
+        failing: app.state::<SchedulerState>().is_failing(provider),
+        fetched_at: fetched_at.into(),
+    }
+}
+
+/// Rows for every provider with cached data, in tray order.
+pub fn summary(app: &AppHandle) -> ComparisonSummary {
+    let estimates = calibration::capacity_estimates(&app.state::<HistoryState>());
+    let mut rows = Vec::new();
+    if let Some(d) = app.state::<UsageState>().last_data.lock().unwrap().as_ref() {
+        rows.push(usage_row(app, Provider::Claude, d, &estimates));
+    }
+    if let Some(d) = app.state::<CodexState>().last_data.lock().unwrap().as_ref() {
+        rows.push(usage_row(app, Provider::Codex, d, &estimates));
+    }
+    if let Some(d) = app
+        .state::<OpenRouterState>()
+        .last_data
+        .lock()
+        .unwrap()
+        .as_ref()
+    {
+        let spent = if d.total_credits > 0.0 {
+            (d.total_usage / d.total_credits * 100.0).min(100.0)
+        } else {
+            0.0
+        };
+        rows.push(spend_row(
+            app,
+            Provider::OpenRouter,
+            spent,
+            Some(d.remaining_credits),
+            &d.fetched_at,
+        ));
+    }
+    if let Some(d) = app
+        .state::<LiteLlmState>()
+        .last_data
+        .lock()
+        .unwrap()
+        .as_ref()
+    {
+        rows.push(spend_row(
+            app,
+            Provider::LiteLlm,
+            d.percent_used,
+            d.remaining,
+            &d.fetched_at,
+        ));
+    }
+
+    // Least room first; among equals, the one that frees up last
+    let reset = |r: &ComparisonRow| {
+        r.tightest
+            .as_ref()
+            .and_then(|w| w.secs_to_reset)
+            .unwrap_or(i64::MAX)
+    };
+    let most_constrained = rows
+        .iter()
+        .max_by(|a, b| {
+            a.constraint_percent
+                .total_cmp(&b.constraint_percent)
+                .then(reset(a).cmp(&reset(b)))
+        })
+        .map(|r| r.provider.clone());
+
+    ComparisonSummary {
+        rows,
+        most_constrained,
+        generated_at: chrono::Utc::now().to_rfc3339(),
+    }
+}
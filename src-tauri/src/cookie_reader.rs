@@ -2,11 +2,34 @@
 //!
 //! The Claude desktop app (Electron-based) stores cookies in a SQLite database at
 //! `~/Library/Application Support/Claude/Cookies`. Cookie values are encrypted using
-//! AES-128-CBC with a key derived via PBKDF2 from the "Claude Safe Storage" macOS
-//! Keychain entry. The encrypted format is: `v10` prefix (3 bytes) + nonce (16 bytes)
-//! + IV (16 bytes) + ciphertext.
+//! AES-128-CBC (IV of 16 spaces) with a key derived via PBKDF2 from the "Claude Safe
+//! Storage" macOS Keychain entry. The encrypted format is a version prefix (`v10` or
+//! `v11`, 3 bytes) + ciphertext. Databases at schema version 24 or later prepend the
+//! SHA-256 of the cookie's host to the plaintext, which is checked and stripped.
+//!
+//! `v11` values are tried with the Keychain key first and then with Chromium's
+//! single-iteration derivation used by some channels. Any other version prefix is
+//! reported as unsupported rather than decoded into garbage. The decrypted
+//! `sessionKey` and org id must also look structurally right, so a bad
+//! Keychain entry is caught here instead of surfacing as an opaque 401.
+//!
+//! The Keychain password, derived key, decrypted values and the temporary copy
+//! of the database are wiped once the cookie header has been built.
+//!
+//! Under stricter sandboxing or TCC the `security` CLI and the direct copy can
+//! be denied. The key lookup then falls back to Security.framework, and the
+//! database can be read from a path the user picked in a file dialog, which
+//! grants this app access to it.
+//!
+//! The derived key (or a refused lookup) is cached for the session so the
+//! Keychain prompts at most once. After the user grants access explicitly,
+//! the lookup goes through Security.framework from this app, so "Always
+//! Allow" adds the app itself to the item's access list rather than the
+//! shared `security` tool.
 
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::{Arc, Mutex};
 
 use aes::Aes128;
 use cbc::{
@@ -16,23 +39,39 @@ use cbc::{
 use pbkdf2::pbkdf2_hmac;
 use rusqlite::Connection;
 use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::secret::{self, Secret};
 
 type Aes128CbcDec = Decryptor<Aes128>;
 
 const CLAUDE_COOKIES_PATH: &str = "Library/Application Support/Claude/Cookies";
+const SAFE_STORAGE_SERVICE: &str = "Claude Safe Storage";
 const PBKDF2_ITERATIONS: u32 = 1003;
+/// Iterations used by Chromium channels that derive the `v11` key differently
+const PBKDF2_ITERATIONS_ALT: u32 = 1;
+const CBC_IV: [u8; 16] = [b' '; 16];
+/// First `meta` schema version whose plaintexts start with SHA-256(host_key)
+const HOST_HASH_DB_VERSION: i64 = 24;
 const SALT: &[u8] = b"saltysalt";
 
 #[derive(Debug, thiserror::Error)]
 pub enum CookieError {
     #[error("Claude desktop app cookies not found")]
     DbNotFound,
+    #[error("Access to the Claude cookie database was denied - choose it in Settings")]
+    AccessDenied,
     #[error("Required cookie not found: {0}")]
     CookieNotFound(String),
-    #[error("Failed to get Claude Safe Storage key from Keychain")]
+    #[error("Failed to get Claude Safe Storage key from Keychain - grant access in Settings")]
     KeychainError,
     #[error("Decryption failed: {0}")]
     DecryptionError(String),
+    #[error("Unsupported cookie encryption version: {0}")]
+    UnsupportedVersion(String),
+    #[error("Decrypted cookies look invalid: {0}")]
+    Implausible(String),
     #[error("SQLite error: {0}")]
     SqliteError(#[from] rusqlite::Error),
 }
@@ -40,115 +79,358 @@ pub enum CookieError {
 pub struct ClaudeCookies {
     pub org_id: String,
     /// All cookies formatted as "name=value; name=value" for the HTTP Cookie header.
-    pub all_cookies: String,
+    pub all_cookies: Secret,
+    /// Cookie database these were read from
+    pub source: PathBuf,
 }
 
-fn get_safe_storage_key() -> Result<String, CookieError> {
+/// Temporary copy of the cookie database, scrubbed and deleted on drop so it
+/// doesn't linger on any early return.
+struct TempCopy(PathBuf);
+
+impl Drop for TempCopy {
+    fn drop(&mut self) {
+        secret::scrub_file(&self.0);
+        // SQLite may leave a rollback journal next to the copy
+        secret::scrub_file(&self.0.with_extension("journal"));
+    }
+}
+
+/// Looks up the Safe Storage password. `framework_first` skips the
+/// `security` CLI once the app has its own access entry.
+fn get_safe_storage_key(framework_first: bool) -> Result<Secret, CookieError> {
+    if framework_first {
+        return framework_safe_storage_key();
+    }
     let output = Command::new("security")
         .args([
             "find-generic-password",
             "-s",
-            "Claude Safe Storage",
+            SAFE_STORAGE_SERVICE,
             "-w",
         ])
-        .output()
-        .map_err(|_| CookieError::KeychainError)?;
+        .output();
 
-    if !output.status.success() {
-        return Err(CookieError::KeychainError);
+    match output {
+        Ok(output) if output.status.success() => Ok(Secret::from_bytes(output.stdout)),
+        _ => framework_safe_storage_key(),
     }
+}
+
+/// Looks the key up through Security.framework directly, for setups where
+/// spawning `security` is blocked.
+#[cfg(target_os = "macos")]
+fn framework_safe_storage_key() -> Result<Secret, CookieError> {
+    use security_framework::item::{ItemClass, ItemSearchOptions, SearchResult};
+
+    let results = ItemSearchOptions::new()
+        .class(ItemClass::generic_password())
+        .service(SAFE_STORAGE_SERVICE)
+        .load_data(true)
+        .limit(1)
+        .search()
+        .map_err(|_| CookieError::KeychainError)?;
 
-    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    results
+        .into_iter()
+        .find_map(|r| match r {
+            SearchResult::Data(data) => Some(Secret::from_bytes(data)),
+            _ => None,
+        })
+        .ok_or(CookieError::KeychainError)
 }
 
-fn derive_key(password: &str) -> [u8; 16] {
-    let mut key = [0u8; 16];
-    pbkdf2_hmac::<Sha1>(password.as_bytes(), SALT, PBKDF2_ITERATIONS, &mut key);
+#[cfg(not(target_os = "macos"))]
+fn framework_safe_storage_key() -> Result<Secret, CookieError> {
+    Err(CookieError::KeychainError)
+}
+
+fn derive_key(password: &Secret, iterations: u32) -> Zeroizing<[u8; 16]> {
+    let mut key = Zeroizing::new([0u8; 16]);
+    pbkdf2_hmac::<Sha1>(password.expose().as_bytes(), SALT, iterations, &mut *key);
     key
 }
 
-fn decrypt_cookie_value(encrypted: &[u8], key: &[u8; 16]) -> Result<String, CookieError> {
-    // Unencrypted cookie
-    if encrypted.len() < 3 || &encrypted[0..3] != b"v10" {
-        return Ok(String::from_utf8_lossy(encrypted).to_string());
+/// Keys derived from the Safe Storage password, one per derivation scheme.
+struct CookieKeys {
+    primary: Zeroizing<[u8; 16]>,
+    alternate: Zeroizing<[u8; 16]>,
+}
+
+impl CookieKeys {
+    fn new(password: &Secret) -> Self {
+        Self {
+            primary: derive_key(password, PBKDF2_ITERATIONS),
+            alternate: derive_key(password, PBKDF2_ITERATIONS_ALT),
+        }
+    }
+
+    /// Keys to try for a version prefix, in order.
+    fn for_version(&self, version: &str) -> Result<Vec<&[u8; 16]>, CookieError> {
+        match version {
+            "v10" => Ok(vec![&self.primary]),
+            "v11" => Ok(vec![&self.primary, &self.alternate]),
+            other => Err(CookieError::UnsupportedVersion(other.to_string())),
+        }
+    }
+}
+
+enum KeyLookup {
+    Keys(Arc<CookieKeys>),
+    /// The Keychain refused or had no entry; not asked again this session
+    Refused,
+}
+
+/// Session cache of the cookie keys, so the Keychain is asked at most once.
+pub struct SafeStorageState {
+    lookup: Mutex<Option<KeyLookup>>,
+}
+
+impl SafeStorageState {
+    pub fn new() -> Self {
+        Self {
+            lookup: Mutex::new(None),
+        }
+    }
+
+    fn keys(&self, framework_first: bool) -> Result<Arc<CookieKeys>, CookieError> {
+        let mut lookup = self.lookup.lock().unwrap();
+        match &*lookup {
+            Some(KeyLookup::Keys(keys)) => return Ok(keys.clone()),
+            Some(KeyLookup::Refused) => return Err(CookieError::KeychainError),
+            None => {}
+        }
+        match get_safe_storage_key(framework_first) {
+            Ok(password) => {
+                let keys = Arc::new(CookieKeys::new(&password));
+                *lookup = Some(KeyLookup::Keys(keys.clone()));
+                Ok(keys)
+            }
+            Err(e) => {
+                *lookup = Some(KeyLookup::Refused);
+                Err(e)
+            }
+        }
+    }
+
+    /// Asks for the key through Security.framework, prompting if needed.
+    /// Choosing "Always Allow" there gives this app its own access entry.
+    pub fn grant_access(&self) -> Result<(), CookieError> {
+        self.reset();
+        self.keys(true).map(|_| ())
+    }
+
+    /// Forgets the cached key or refusal so the next read asks again.
+    pub fn reset(&self) {
+        *self.lookup.lock().unwrap() = None;
     }
+}
 
-    let data = &encrypted[3..];
+/// The `vNN` prefix of an encrypted value, if it has one.
+fn version_prefix(encrypted: &[u8]) -> Option<&str> {
+    match encrypted {
+        [b'v', a, b, ..] if a.is_ascii_digit() && b.is_ascii_digit() => {
+            std::str::from_utf8(&encrypted[..3]).ok()
+        }
+        _ => None,
+    }
+}
 
-    // Need at least 3 AES blocks (48 bytes): nonce(16) + iv(16) + ciphertext(16+)
-    if data.len() < 48 || data.len() % 16 != 0 {
+/// AES-128-CBC decrypt with strict PKCS7 unpadding; a wrong key shows up
+/// here as bad padding instead of garbled output.
+fn decrypt_cbc(ciphertext: &[u8], key: &[u8; 16]) -> Result<Zeroizing<Vec<u8>>, CookieError> {
+    if ciphertext.is_empty() || !ciphertext.len().is_multiple_of(16) {
         return Err(CookieError::DecryptionError(
-            "ciphertext too short".to_string(),
+            "ciphertext is not a whole number of blocks".to_string(),
         ));
     }
 
-    // Claude desktop app format: first 16 bytes are opaque, next 16 are the CBC IV
-    let iv: [u8; 16] = data[16..32].try_into().unwrap();
-    let ciphertext = &data[32..];
-
-    let mut buf = ciphertext.to_vec();
-    Aes128CbcDec::new(key.into(), &iv.into())
+    let mut buf = Zeroizing::new(ciphertext.to_vec());
+    Aes128CbcDec::new(key.into(), &CBC_IV.into())
         .decrypt_padded_mut::<NoPadding>(&mut buf)
         .map_err(|e| CookieError::DecryptionError(e.to_string()))?;
 
-    // Remove PKCS7 padding manually
-    if let Some(&pad_len) = buf.last() {
-        if pad_len >= 1 && pad_len <= 16 && buf.len() >= pad_len as usize {
-            buf.truncate(buf.len() - pad_len as usize);
-        }
+    let pad_len = buf.last().copied().unwrap_or(0) as usize;
+    let padded = (1..=16).contains(&pad_len)
+        && buf.len() >= pad_len
+        && buf[buf.len() - pad_len..]
+            .iter()
+            .all(|&b| b as usize == pad_len);
+    if !padded {
+        return Err(CookieError::DecryptionError(
+            "bad padding (wrong key?)".to_string(),
+        ));
+    }
+    let len = buf.len() - pad_len;
+    buf.truncate(len);
+    Ok(buf)
+}
+
+fn decrypt_cookie_value(
+    encrypted: &[u8],
+    host_key: &str,
+    keys: &CookieKeys,
+    host_hashed: bool,
+) -> Result<String, CookieError> {
+    // Unencrypted cookie
+    let Some(version) = version_prefix(encrypted) else {
+        return Ok(String::from_utf8_lossy(encrypted).to_string());
+    };
+
+    let mut last_err = None;
+    for key in keys.for_version(version)? {
+        let plain = match decrypt_cbc(&encrypted[3..], key) {
+            Ok(plain) => plain,
+            Err(e) => {
+                last_err = Some(e);
+                continue;
+            }
+        };
+        let value = if host_hashed {
+            let hash = Sha256::digest(host_key.as_bytes());
+            if plain.len() < hash.len() || plain[..hash.len()] != hash[..] {
+                last_err = Some(CookieError::DecryptionError(
+                    "host hash mismatch (wrong key?)".to_string(),
+                ));
+                continue;
+            }
+            &plain[hash.len()..]
+        } else {
+            &plain[..]
+        };
+        return String::from_utf8(value.to_vec())
+            .map_err(|_| CookieError::DecryptionError("value is not UTF-8".to_string()));
     }
+    Err(last_err.unwrap_or_else(|| CookieError::DecryptionError("no key".to_string())))
+}
 
-    Ok(String::from_utf8_lossy(&buf).to_string())
+/// Whether the database's schema prefixes plaintexts with the host hash.
+/// Assumes it does when the version can't be read, as current builds do.
+fn host_hashed(conn: &Connection) -> bool {
+    conn.query_row("SELECT value FROM meta WHERE key = 'version'", [], |row| {
+        row.get::<_, String>(0)
+    })
+    .ok()
+    .and_then(|v| v.parse::<i64>().ok())
+    .is_none_or(|v| v >= HOST_HASH_DB_VERSION)
 }
 
-pub fn read_claude_cookies() -> Result<ClaudeCookies, CookieError> {
-    let home = dirs::home_dir().ok_or(CookieError::DbNotFound)?;
-    let cookies_path = home.join(CLAUDE_COOKIES_PATH);
+/// Location of the Claude desktop app's cookie database.
+pub fn cookies_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(CLAUDE_COOKIES_PATH))
+}
 
-    if !cookies_path.exists() {
+/// Copies the database to avoid locking issues, telling a permission refusal
+/// apart from a missing file.
+fn copy_db(from: &Path) -> Result<TempCopy, CookieError> {
+    if !from.exists() {
         return Err(CookieError::DbNotFound);
     }
+    let temp = TempCopy(std::env::temp_dir().join("claude_widget_cookies"));
+    match std::fs::copy(from, &temp.0) {
+        Ok(_) => Ok(temp),
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+            Err(CookieError::AccessDenied)
+        }
+        Err(_) => Err(CookieError::DbNotFound),
+    }
+}
 
-    // Copy to avoid locking issues
-    let temp_path = std::env::temp_dir().join("claude_widget_cookies");
-    std::fs::copy(&cookies_path, &temp_path).map_err(|_| CookieError::DbNotFound)?;
+/// Cookie databases to try, in order: the default location, then `picked`
+/// (a path the user chose in the file dialog).
+pub fn sources(picked: Option<&Path>) -> Vec<PathBuf> {
+    let mut sources: Vec<PathBuf> = cookies_path().into_iter().collect();
+    if let Some(picked) = picked {
+        if !sources.iter().any(|s| s == picked) {
+            sources.push(picked.to_path_buf());
+        }
+    }
+    sources
+}
+
+/// Session keys are `sk-ant-...` tokens of printable ASCII.
+fn plausible_session_key(value: &str) -> bool {
+    value.starts_with("sk-ant-")
+        && (40..=512).contains(&value.len())
+        && value.bytes().all(|b| b.is_ascii_graphic())
+}
+
+/// Org ids are UUIDs.
+fn plausible_org_id(value: &str) -> bool {
+    value.len() == 36
+        && value.chars().enumerate().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
 
-    let conn = Connection::open(&temp_path)?;
-    let password = get_safe_storage_key()?;
-    let key = derive_key(&password);
+pub fn read_claude_cookies(
+    path: &Path,
+    safe_storage: &SafeStorageState,
+    framework_first: bool,
+) -> Result<ClaudeCookies, CookieError> {
+    let temp = copy_db(path)?;
+
+    let conn = Connection::open(&temp.0)?;
+    let keys = safe_storage.keys(framework_first)?;
+    let host_hashed = host_hashed(&conn);
 
     let mut stmt = conn.prepare(
-        "SELECT name, encrypted_value FROM cookies WHERE host_key LIKE '%claude.ai%'",
+        "SELECT name, host_key, encrypted_value FROM cookies WHERE host_key LIKE '%claude.ai%'",
     )?;
 
     let rows = stmt.query_map([], |row| {
         let name: String = row.get(0)?;
-        let encrypted: Vec<u8> = row.get(1)?;
-        Ok((name, encrypted))
+        let host_key: String = row.get(1)?;
+        let encrypted: Vec<u8> = row.get(2)?;
+        Ok((name, host_key, encrypted))
     })?;
 
     let mut org_id = None;
-    let mut cookie_parts = Vec::new();
+    let mut cookie_parts = Zeroizing::new(Vec::new());
+    let mut decrypt_err = None;
+    let mut session_key_ok = None;
 
     for row in rows {
-        let (name, encrypted) = row?;
-        match decrypt_cookie_value(&encrypted, &key) {
-            Ok(value) if !value.is_empty() => {
+        let (name, host_key, encrypted) = row?;
+        match decrypt_cookie_value(&encrypted, &host_key, &keys, host_hashed) {
+            Ok(mut value) if !value.is_empty() => {
                 if name == "lastActiveOrg" {
                     org_id = Some(value.clone());
                 }
+                if name == "sessionKey" {
+                    session_key_ok = Some(plausible_session_key(&value));
+                }
                 cookie_parts.push(format!("{}={}", name, value));
+                value.zeroize();
             }
-            _ => {}
+            Ok(_) => {}
+            Err(e) => decrypt_err = Some(e),
         }
     }
 
-    let _ = std::fs::remove_file(temp_path);
+    drop(stmt);
+    drop(conn);
+    drop(temp);
 
-    let org_id = org_id.ok_or_else(|| CookieError::CookieNotFound("lastActiveOrg".into()))?;
+    // A cookie that failed to decrypt explains a missing org better than "not found"
+    let org_id = match (org_id, decrypt_err) {
+        (Some(org_id), _) => org_id,
+        (None, Some(e)) => return Err(e),
+        (None, None) => return Err(CookieError::CookieNotFound("lastActiveOrg".into())),
+    };
+    match session_key_ok {
+        None => return Err(CookieError::CookieNotFound("sessionKey".into())),
+        Some(false) => return Err(CookieError::Implausible("malformed sessionKey".into())),
+        Some(true) => {}
+    }
+    if !plausible_org_id(&org_id) {
+        return Err(CookieError::Implausible("malformed org id".into()));
+    }
 
     Ok(ClaudeCookies {
         org_id,
-        all_cookies: cookie_parts.join("; "),
+        all_cookies: Secret::new(cookie_parts.join("; ")),
+        source: path.to_path_buf(),
     })
 }
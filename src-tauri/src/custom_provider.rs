@@ -0,0 +1,208 @@
+//! Generic provider for smaller AI services that report a balance or a quota
+//! over a plain bearer-token API: one `GET`, with the value picked out of the
+//! JSON response by JSON pointer. Ready-made templates cover a few services
+//! users keep asking about; any other endpoint of the same shape can be added
+//! with its own URL and pointers.
+//!
+//! Configs live in settings (`custom_providers`) and tokens in the Keychain
+//! under `custom:<id>`. They are manual-only: fetched when the frontend asks
+//! (`fetch_custom_provider`), never by the scheduler, so there is no periodic
+//! refresh, backoff or `provider-fetch-*` event for them, and they don't
+//! appear in the menu bar text. Each fetch is capped by `FETCH_TIMEOUT_SECS`.
+//! URLs must be `https://`, as the token travels with every request.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::http_client;
+use crate::keychain;
+
+/// Longest a fetch may take, connecting included.
+const FETCH_TIMEOUT_SECS: u64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueKind {
+    /// Money or credits left
+    Balance,
+    /// Share of a quota used
+    Percent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomProvider {
+    /// Lowercase letters, digits and dashes, e.g. "deepseek"
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    /// JSON pointer to the value, e.g. "/data/balance"; numbers in strings
+    /// are accepted
+    pub value_pointer: String,
+    pub kind: ValueKind,
+    /// JSON pointer to the currency or unit, if the response has one
+    #[serde(default)]
+    pub unit_pointer: Option<String>,
+    /// Unit when there is no pointer or it finds nothing, e.g. "USD"
+    #[serde(default)]
+    pub unit: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CustomProviderData {
+    pub id: String,
+    pub name: String,
+    pub kind: ValueKind,
+    pub value: f64,
+    pub unit: Option<String>,
+    pub fetched_at: String,
+    /// Balance replaced with zero (privacy mode)
+    pub redacted: bool,
+}
+
+pub struct CustomProviderState {
+    /// Last data per provider id
+    pub last_data: Mutex<HashMap<String, CustomProviderData>>,
+}
+
+impl CustomProviderState {
+    pub fn new() -> Self {
+        Self {
+            last_data: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+fn template(
+    id: &str,
+    name: &str,
+    url: &str,
+    value_pointer: &str,
+    unit_pointer: Option<&str>,
+    unit: Option<&str>,
+) -> CustomProvider {
+    CustomProvider {
+        id: id.into(),
+        name: name.into(),
+        url: url.into(),
+        value_pointer: value_pointer.into(),
+        kind: ValueKind::Balance,
+        unit_pointer: unit_pointer.map(Into::into),
+        unit: unit.map(Into::into),
+    }
+}
+
+/// Ready-made configs; only the API key has to be added.
+pub fn templates() -> Vec<CustomProvider> {
+    vec![
+        template(
+            "deepseek",
+            "DeepSeek",
+            "https://api.deepseek.com/user/balance",
+            "/balance_infos/0/total_balance",
+            Some("/balance_infos/0/currency"),
+            None,
+        ),
+        template(
+            "moonshot",
+            "Moonshot (Kimi)",
+            "https://api.moonshot.ai/v1/users/me/balance",
+            "/data/available_balance",
+            None,
+            None,
+        ),
+        template(
+            "siliconflow",
+            "SiliconFlow",
+            "https://api.siliconflow.cn/v1/user/info",
+            "/data/totalBalance",
+            None,
+            Some("CNY"),
+        ),
+    ]
+}
+
+pub fn key_account(id: &str) -> String {
+    format!("custom:{}", id)
+}
+
+impl CustomProvider {
+    pub fn validate(&self) -> Result<(), String> {
+        let id_ok = !self.id.is_empty()
+            && self
+                .id
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !id_ok {
+            return Err("The id may only contain lowercase letters, digits and dashes".into());
+        }
+        if self.name.trim().is_empty() {
+            return Err("The provider needs a name".into());
+        }
+        if !self.url.trim().starts_with("https://") {
+            return Err("The URL must start with https://, as the API key is sent to it".into());
+        }
+        let pointers = std::iter::once(&self.value_pointer).chain(self.unit_pointer.as_ref());
+        for pointer in pointers {
+            if !pointer.starts_with('/') {
+                return Err(format!("\"{}\" is not a JSON pointer", pointer));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn number(v: &Value) -> Option<f64> {
+    match v {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+pub async fn fetch(
+    client: &Client,
+    provider: &CustomProvider,
+) -> Result<CustomProviderData, String> {
+    // settings.json may have been edited by hand
+    provider.validate()?;
+    let key = keychain::read_secret(&key_account(&provider.id))?
+        .ok_or_else(|| format!("No {} API key is set", provider.name))?;
+    let req = client
+        .get(provider.url.trim())
+        .bearer_auth(key.expose())
+        .header("Accept", "application/json")
+        .timeout(Duration::from_secs(FETCH_TIMEOUT_SECS));
+    let payload = http_client::raw_json(&provider.name, req).await?;
+
+    let value = payload
+        .pointer(&provider.value_pointer)
+        .and_then(number)
+        .ok_or_else(|| {
+            format!(
+                "{} response has no number at {}",
+                provider.name, provider.value_pointer
+            )
+        })?;
+    let unit = provider
+        .unit_pointer
+        .as_deref()
+        .and_then(|p| payload.pointer(p))
+        .and_then(Value::as_str)
+        .map(String::from)
+        .or_else(|| provider.unit.clone());
+
+    Ok(CustomProviderData {
+        id: provider.id.clone(),
+        name: provider.name.clone(),
+        kind: provider.kind,
+        value,
+        unit,
+        fetched_at: chrono::Utc::now().to_rfc3339(),
+        redacted: false,
+    })
+}
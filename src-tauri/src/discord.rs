@@ -0,0 +1,125 @@
+//! Posts alerts to a Discord webhook as embeds: colored by severity, one field
+//! per metric, with reset countdowns Discord renders in the reader's timezone.
+//!
+//! Discord allows roughly 5 webhook posts per 2 seconds and 30 per minute per
+//! channel. Posts are queued locally to stay under both, and a 429 response is
+//! retried once after the `retry_after` Discord asks for.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use reqwest::Client;
+use serde_json::json;
+
+use crate::alerts::Alert;
+use crate::keychain;
+use crate::notifications::Severity;
+
+/// Keychain account holding the webhook URL (it embeds the webhook token).
+pub const WEBHOOK_ACCOUNT: &str = "discord_webhook";
+
+const BURST_LIMIT: usize = 5;
+const BURST_WINDOW: Duration = Duration::from_secs(2);
+const MINUTE_LIMIT: usize = 30;
+const MINUTE_WINDOW: Duration = Duration::from_secs(60);
+
+const COLOR_ROUTINE: u32 = 0xF5A623;
+const COLOR_CRITICAL: u32 = 0xD0021B;
+
+/// Send times of recent posts, oldest first. The lock is held for the whole
+/// post so queued alerts go out one at a time.
+pub struct DiscordState {
+    sent: tokio::sync::Mutex<VecDeque<Instant>>,
+}
+
+impl DiscordState {
+    pub fn new() -> Self {
+        Self {
+            sent: tokio::sync::Mutex::new(VecDeque::new()),
+        }
+    }
+}
+
+/// How long to wait before another post fits both rate windows.
+fn wait_needed(sent: &VecDeque<Instant>, now: Instant) -> Option<Duration> {
+    [(BURST_LIMIT, BURST_WINDOW), (MINUTE_LIMIT, MINUTE_WINDOW)]
+        .into_iter()
+        .filter_map(|(limit, window)| {
+            let recent: Vec<_> = sent
+                .iter()
+                .filter(|t| now.duration_since(**t) < window)
+                .collect();
+            (recent.len() >= limit).then(|| window - now.duration_since(*recent[0]))
+        })
+        .max()
+}
+
+fn embed(alert: &Alert) -> serde_json::Value {
+    let color = match alert.severity {
+        Severity::Routine => COLOR_ROUTINE,
+        Severity::Critical => COLOR_CRITICAL,
+    };
+    let fields: Vec<_> = alert
+        .metrics
+        .iter()
+        .map(|m| {
+            // <t:...:R> shows a live "in 3 hours" countdown
+            let reset = m
+                .resets_at
+                .as_deref()
+                .and_then(|r| chrono::DateTime::parse_from_rfc3339(r).ok())
+                .map(|r| format!("Resets <t:{}:R>", r.timestamp()))
+                .unwrap_or_else(|| m.reset_info.clone());
+            json!({
+                "name": m.label,
+                "value": format!("**{:.0}%**\n{}", m.percent, reset),
+                "inline": true,
+            })
+        })
+        .collect();
+    json!({
+        "title": alert.title,
+        "description": alert.body,
+        "color": color,
+        "fields": fields,
+        "footer": { "text": alert.provider },
+        "timestamp": alert.at,
+    })
+}
+
+pub async fn send(client: &Client, state: &DiscordState, alert: &Alert) -> Result<(), String> {
+    let url = keychain::read_secret(WEBHOOK_ACCOUNT)?.ok_or("Discord webhook is not set")?;
+    let payload = json!({ "username": "Usage Widget", "embeds": [embed(alert)] });
+
+    let mut sent = state.sent.lock().await;
+    while let Some(wait) = wait_needed(&sent, Instant::now()) {
+        tokio::time::sleep(wait).await;
+    }
+
+    for attempt in 0..2 {
+        sent.push_back(Instant::now());
+        while sent.len() > MINUTE_LIMIT {
+            sent.pop_front();
+        }
+
+        let resp = client
+            .post(url.expose())
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| format!("Discord request failed: {}", e.without_url()))?;
+
+        let status = resp.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        if status.as_u16() == 429 && attempt == 0 {
+            let body: serde_json::Value = resp.json().await.unwrap_or_default();
+            let retry_after = body["retry_after"].as_f64().unwrap_or(1.0);
+            tokio::time::sleep(Duration::from_secs_f64(retry_after.clamp(0.0, 60.0))).await;
+            continue;
+        }
+        return Err(format!("Discord returned {}", status));
+    }
+    Err("Discord kept rate limiting the webhook".into())
+}
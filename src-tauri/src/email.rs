@@ -0,0 +1,71 @@
+//! Emails alerts through a user-configured SMTP server, so they can be routed
+//! with ordinary mail filters.
+
+use std::time::Duration;
+
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+use crate::alerts::Alert;
+use crate::keychain;
+use crate::settings::EmailSettings;
+
+/// Keychain account holding the SMTP password.
+pub const PASSWORD_ACCOUNT: &str = "smtp_password";
+
+const SEND_TIMEOUT_SECS: u64 = 20;
+
+pub async fn send(settings: &EmailSettings, alert: &Alert) -> Result<(), String> {
+    if settings.smtp_host.trim().is_empty() || settings.to.trim().is_empty() {
+        return Err("Email alerts need an SMTP server and a recipient".into());
+    }
+
+    let from = if settings.from.trim().is_empty() {
+        &settings.username
+    } else {
+        &settings.from
+    };
+    let message = Message::builder()
+        .from(
+            from.parse()
+                .map_err(|e| format!("Invalid sender address: {}", e))?,
+        )
+        .to(settings
+            .to
+            .parse()
+            .map_err(|e| format!("Invalid recipient address: {}", e))?)
+        .subject(format!("[Usage Widget] {}", alert.title))
+        .header(ContentType::TEXT_PLAIN)
+        .body(alert.text())
+        .map_err(|e| format!("Failed to build email: {}", e))?;
+
+    // Port 465 speaks TLS from the start; anything else upgrades via STARTTLS
+    let host = settings.smtp_host.trim();
+    let builder = if settings.smtp_port == 465 {
+        AsyncSmtpTransport::<Tokio1Executor>::relay(host)
+    } else {
+        AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+    }
+    .map_err(|e| format!("Invalid SMTP server: {}", e))?
+    .port(settings.smtp_port)
+    .timeout(Some(Duration::from_secs(SEND_TIMEOUT_SECS)));
+
+    let builder = if settings.username.is_empty() {
+        builder
+    } else {
+        let password =
+            keychain::read_secret(PASSWORD_ACCOUNT)?.ok_or("SMTP password is not set")?;
+        builder.credentials(Credentials::new(
+            settings.username.clone(),
+            password.expose().to_string(),
+        ))
+    };
+
+    builder
+        .build()
+        .send(message)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("Failed to send email: {}", e))
+}
@@ -0,0 +1,128 @@
+//! Failover suggestions for people who route work between Claude and Codex:
+//! when one provider's tightest window crosses the critical level while the
+//! paired one still has plenty of room, a notification and a
+//! `failover-suggested` event say so ("Claude weekly at 95% - Codex at 20%").
+//!
+//! Each pair suggests once per crossing and re-arms after the source drops
+//! back below the critical level by the notification hysteresis.
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::codex_fetcher::CodexState;
+use crate::i18n;
+use crate::notifications::{self, Severity};
+use crate::provider::Provider;
+use crate::settings::{FailoverPair, SettingsState};
+use crate::usage_fetcher::{UsageData, UsageState};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FailoverSuggestion {
+    pub from: Provider,
+    pub to: Provider,
+    /// "session" or "weekly"
+    pub from_metric: String,
+    pub from_percent: f64,
+    pub to_percent: f64,
+    pub message: String,
+}
+
+pub struct FailoverState {
+    /// Pairs already suggested for the current crossing
+    suggested: Mutex<HashSet<(Provider, Provider)>>,
+}
+
+impl FailoverState {
+    pub fn new() -> Self {
+        Self {
+            suggested: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+/// The fuller of the session and weekly windows.
+fn tightest(data: &UsageData) -> (&'static str, f64) {
+    if data.weekly_all.percent_used >= data.session.percent_used {
+        ("weekly", data.weekly_all.percent_used)
+    } else {
+        ("session", data.session.percent_used)
+    }
+}
+
+fn cached(app: &AppHandle, provider: Provider) -> Option<UsageData> {
+    match provider {
+        Provider::Claude => app.state::<UsageState>().last_data.lock().unwrap().clone(),
+        Provider::Codex => app.state::<CodexState>().last_data.lock().unwrap().clone(),
+        Provider::OpenRouter | Provider::LiteLlm | Provider::Ollama => None,
+    }
+}
+
+fn suggest(app: &AppHandle, pair: FailoverPair, data: &UsageData, to_percent: f64) {
+    let settings = app.state::<SettingsState>().get();
+    let (metric, from_percent) = tightest(data);
+    let from_name = settings.display_name(pair.from.name());
+    let to_name = settings.display_name(pair.to.name());
+    let window = match metric {
+        "weekly" => i18n::tf("notify.metric_weekly", &[&from_name]),
+        _ => i18n::tf("notify.metric_session", &[&from_name]),
+    };
+    let title = i18n::tf("notify.failover", &[&to_name]);
+    let message = i18n::tf(
+        "notify.failover_body",
+        &[
+            &window,
+            &format!("{:.0}", from_percent),
+            &to_name,
+            &format!("{:.0}", to_percent),
+        ],
+    );
+    if settings.notifications_enabled {
+        notifications::send(app, Severity::Routine, &title, &message);
+    }
+    let _ = app.emit(
+        "failover-suggested",
+        FailoverSuggestion {
+            from: pair.from,
+            to: pair.to,
+            from_metric: metric.into(),
+            from_percent,
+            to_percent,
+            message,
+        },
+    );
+}
+
+/// Checks the pairs that start at `provider` after it fetched `data`.
+pub fn check(app: &AppHandle, provider: &str, data: &UsageData) {
+    let settings = app.state::<SettingsState>().get();
+    let failover = &settings.failover;
+    if !failover.enabled {
+        return;
+    }
+    let (_, percent) = tightest(data);
+    let critical = failover.critical_percent as f64;
+    let rearm_below = critical - settings.notify_hysteresis_points as f64;
+    let state = app.state::<FailoverState>();
+
+    for pair in failover.pairs.iter().filter(|p| p.from.name() == provider) {
+        let key = (pair.from, pair.to);
+        if percent < rearm_below {
+            state.suggested.lock().unwrap().remove(&key);
+            continue;
+        }
+        if percent < critical || state.suggested.lock().unwrap().contains(&key) {
+            continue;
+        }
+        let Some(target) = cached(app, pair.to) else {
+            continue;
+        };
+        let (_, to_percent) = tightest(&target);
+        if to_percent <= failover.max_target_percent as f64 {
+            state.suggested.lock().unwrap().insert(key);
+            suggest(app, *pair, data, to_percent);
+        }
+    }
+}
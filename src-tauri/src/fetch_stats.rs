@@ -0,0 +1,129 @@
+//! Per-provider request latency and success rate over the most recent fetches,
+//! kept in memory. Slow or failing fetches of one provider point at that
+//! provider; all of them slowing down at once points at the local network.
+//!
+//! Also builds the plain-text diagnostics report users can paste into issues.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::provider::Provider;
+use crate::scheduler::SchedulerState;
+
+/// Fetches kept per provider.
+const WINDOW: usize = 200;
+
+#[derive(Debug, Clone, Copy)]
+struct Attempt {
+    latency: Duration,
+    ok: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FetchStats {
+    pub provider: Provider,
+    /// Fetches in the window, at most `WINDOW`
+    pub fetches: usize,
+    pub success_rate: f64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub last_ms: u64,
+}
+
+pub struct FetchStatsState {
+    attempts: Mutex<HashMap<Provider, VecDeque<Attempt>>>,
+}
+
+impl FetchStatsState {
+    pub fn new() -> Self {
+        Self {
+            attempts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn record(&self, provider: Provider, latency: Duration, ok: bool) {
+        let mut attempts = self.attempts.lock().unwrap();
+        let list = attempts.entry(provider).or_default();
+        if list.len() == WINDOW {
+            list.pop_front();
+        }
+        list.push_back(Attempt { latency, ok });
+    }
+
+    /// Stats for every provider fetched since launch.
+    pub fn stats(&self) -> Vec<FetchStats> {
+        let attempts = self.attempts.lock().unwrap();
+        Provider::ALL
+            .into_iter()
+            .filter_map(|provider| {
+                let list = attempts.get(&provider).filter(|l| !l.is_empty())?;
+                let mut ms: Vec<u64> = list.iter().map(|a| a.latency.as_millis() as u64).collect();
+                let last_ms = *ms.last()?;
+                ms.sort_unstable();
+                let ok = list.iter().filter(|a| a.ok).count();
+                Some(FetchStats {
+                    provider,
+                    fetches: list.len(),
+                    success_rate: ok as f64 / list.len() as f64,
+                    p50_ms: percentile(&ms, 0.50),
+                    p95_ms: percentile(&ms, 0.95),
+                    last_ms,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Nearest-rank percentile of sorted values.
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Plain-text report of the app version, provider health and fetch latency.
+pub fn diagnostics_report(app: &AppHandle) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Claude/Codex Usage {} ({} {})",
+        app.package_info().version,
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let _ = writeln!(out, "\nProvider health:");
+    for h in app.state::<SchedulerState>().provider_health() {
+        let _ = writeln!(
+            out,
+            "  {:?}: {:?}, {} consecutive failures{}",
+            h.provider,
+            h.status,
+            h.consecutive_failures,
+            h.last_error
+                .map(|e| format!(", last error: {}", e))
+                .unwrap_or_default()
+        );
+    }
+    let _ = writeln!(out, "\nFetch latency (recent fetches):");
+    let stats = app.state::<FetchStatsState>().stats();
+    if stats.is_empty() {
+        let _ = writeln!(out, "  no fetches yet");
+    }
+    for s in stats {
+        let _ = writeln!(
+            out,
+            "  {:?}: p50 {} ms, p95 {} ms, last {} ms, {:.0}% of {} succeeded",
+            s.provider,
+            s.p50_ms,
+            s.p95_ms,
+            s.last_ms,
+            s.success_rate * 100.0,
+            s.fetches
+        );
+    }
+    out
+}
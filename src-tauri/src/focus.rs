@@ -0,0 +1,47 @@
+//! Detects whether a macOS Focus mode (including Do Not Disturb) is on.
+//!
+//! macOS 12+ records active Focus assertions in
+//! `~/Library/DoNotDisturb/DB/Assertions.json`; older releases keep a
+//! `doNotDisturb` flag in the notification center preferences. If neither can
+//! be read, Focus is treated as off so alerts are never lost silently.
+
+use std::process::Command;
+
+const ASSERTIONS_PATH: &str = "Library/DoNotDisturb/DB/Assertions.json";
+
+/// Whether any Focus assertion is active per the macOS 12+ database.
+fn assertions_active() -> Option<bool> {
+    let path = dirs::home_dir()?.join(ASSERTIONS_PATH);
+    let content = std::fs::read_to_string(path).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let records = json.get("data")?.as_array()?;
+    Some(records.iter().any(|entry| {
+        entry
+            .get("storeAssertionRecords")
+            .and_then(|r| r.as_array())
+            .is_some_and(|r| !r.is_empty())
+    }))
+}
+
+/// Pre-Monterey Do Not Disturb flag.
+fn legacy_dnd_active() -> Option<bool> {
+    let out = Command::new("defaults")
+        .args([
+            "-currentHost",
+            "read",
+            "com.apple.notificationcenterui",
+            "doNotDisturb",
+        ])
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&out.stdout).trim() == "1")
+}
+
+pub fn is_active() -> bool {
+    assertions_active()
+        .or_else(legacy_dnd_active)
+        .unwrap_or(false)
+}
@@ -0,0 +1,952 @@
+//! Persists usage samples to a SQLite database in the app's data directory.
+//!
+//! Every successful fetch writes one row per metric, annotated with whether the
+//! user was active or away at the time, so later analysis can tell interactive
+//! use apart from unattended background consumption.
+//!
+//! All-time peaks and the moments a limit was hit are kept in their own tables
+//! so they survive once old samples are gone. Notable events such as plan
+//! changes are stored as annotations alongside the samples. Credit balances
+//! (OpenRouter) are kept as running spend totals, so spend over any period
+//! can be worked out later.
+//!
+//! Session utilization can be bucketed by day of week and hour of day into a
+//! heatmap of when quota is burned.
+//!
+//! Daily token totals imported from other trackers (see `importer`) are kept
+//! in their own table, one row per day, provider and source.
+//!
+//! Raw samples are kept for a limited time: older ones are folded into hourly
+//! rollups (peak and mean per metric), and rollups are dropped in turn once
+//! they pass their own retention. A background task prunes once a day.
+//! Readers fill the time before the oldest raw sample from the rollups, one
+//! sample per hour at its peak, so reports and charts keep covering it.
+//!
+//! Named work sessions keep a snapshot of every metric from when they started
+//! and, once stopped, the percentage points each metric grew in between.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+use chrono::{Datelike, Timelike};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::settings::{Retention, SettingsState};
+use crate::usage_fetcher::UsageData;
+
+const HISTORY_FILE: &str = "history.db";
+/// Window for the rolling peak and limit-hit count.
+const ROLLING_SECS: i64 = 30 * 86_400;
+/// Samples older than the session window say nothing about the current one,
+/// so `latest` ignores them.
+const LATEST_MAX_AGE_SECS: i64 = 5 * 3600;
+/// How often the background task applies the retention settings.
+const PRUNE_INTERVAL_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sample {
+    /// Unix timestamp (seconds)
+    pub ts: i64,
+    pub provider: String,
+    pub metric: String,
+    pub percent: f64,
+    /// Whether the user was at the computer when the sample was taken
+    pub active: bool,
+    pub idle_secs: Option<u64>,
+}
+
+/// Peak utilization and limit hits for one provider metric.
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageRecord {
+    pub provider: String,
+    pub metric: String,
+    pub all_time_peak: f64,
+    /// Unix timestamp (seconds)
+    pub all_time_peak_at: i64,
+    pub peak_30d: Option<f64>,
+    pub peak_30d_at: Option<i64>,
+    pub limit_hits_30d: usize,
+    /// Unix timestamps of every time the metric reached 100%, newest first
+    pub limit_hits: Vec<i64>,
+}
+
+/// A note attached to the usage timeline, e.g. a plan change.
+#[derive(Debug, Clone, Serialize)]
+pub struct Annotation {
+    /// Unix timestamp (seconds)
+    pub ts: i64,
+    pub provider: String,
+    /// Annotation type, e.g. "plan"
+    pub kind: String,
+    pub text: String,
+}
+
+/// Usage one metric consumed during a work session.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkSessionUsage {
+    pub provider: String,
+    pub metric: String,
+    pub start_percent: f64,
+    /// Percentage points consumed; None while the session is running
+    pub consumed: Option<f64>,
+}
+
+/// A named stretch of work, e.g. "auth refactor".
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkSession {
+    pub id: i64,
+    pub name: String,
+    /// Unix timestamp (seconds)
+    pub started_at: i64,
+    /// Unix timestamp (seconds); None while the session is running
+    pub ended_at: Option<i64>,
+    pub usage: Vec<WorkSessionUsage>,
+}
+
+/// Token totals for one day, imported from another tracker.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DailyTokens {
+    /// Local date, YYYY-MM-DD
+    pub date: String,
+    pub provider: String,
+    /// Tracker the day came from, e.g. "ccusage"
+    pub source: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_tokens: u64,
+    /// Cost the tracker reported, if any
+    pub cost_usd: Option<f64>,
+}
+
+/// Size and contents of the history database.
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseStats {
+    pub size_bytes: u64,
+    pub samples: u64,
+    /// Unix timestamp (seconds) of the oldest raw sample
+    pub oldest_sample: Option<i64>,
+    pub rollups: u64,
+    /// Unix timestamp (seconds) of the oldest hourly rollup
+    pub oldest_rollup: Option<i64>,
+    pub credit_samples: u64,
+    pub annotations: u64,
+}
+
+/// What one pruning pass removed.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PruneResult {
+    /// Raw samples folded into hourly rollups
+    pub samples_rolled_up: usize,
+    pub rollups_removed: usize,
+    pub credit_samples_removed: usize,
+}
+
+/// Session usage in one hour of the week, local time.
+#[derive(Debug, Clone, Serialize)]
+pub struct HeatmapCell {
+    /// Day of the week, 0 = Monday
+    pub weekday: u32,
+    /// Hour of the day, 0-23
+    pub hour: u32,
+    /// Percentage points the session metric grew during this hour
+    pub consumed: f64,
+    /// Mean session utilization across the samples in this hour
+    pub average_percent: Option<f64>,
+    pub samples: usize,
+}
+
+/// Sums only rises between consecutive percentages, so window resets don't
+/// cancel out usage.
+fn sum_rises(mut prev: Option<f64>, percents: impl Iterator<Item = f64>) -> f64 {
+    let mut total = 0.0;
+    for percent in percents {
+        if let Some(p) = prev {
+            if percent > p {
+                total += percent - p;
+            }
+        }
+        prev = Some(percent);
+    }
+    total
+}
+
+pub struct HistoryState {
+    conn: Mutex<Connection>,
+}
+
+impl HistoryState {
+    /// Opens (or creates) the history database. Falls back to an in-memory
+    /// database so the app keeps working if the file can't be opened.
+    pub fn new(data_dir: &Path) -> Self {
+        let conn = std::fs::create_dir_all(data_dir)
+            .ok()
+            .and_then(|_| Connection::open(data_dir.join(HISTORY_FILE)).ok())
+            .unwrap_or_else(|| Connection::open_in_memory().expect("in-memory sqlite"));
+        let _ = Self::migrate(&conn);
+        Self {
+            conn: Mutex::new(conn),
+        }
+    }
+
+    fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS samples (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                ts INTEGER NOT NULL,
+                provider TEXT NOT NULL,
+                metric TEXT NOT NULL,
+                percent REAL NOT NULL,
+                active INTEGER NOT NULL DEFAULT 1,
+                idle_secs INTEGER
+            );
+            CREATE INDEX IF NOT EXISTS idx_samples_lookup
+                ON samples (provider, metric, ts);
+            CREATE TABLE IF NOT EXISTS peaks (
+                provider TEXT NOT NULL,
+                metric TEXT NOT NULL,
+                percent REAL NOT NULL,
+                ts INTEGER NOT NULL,
+                PRIMARY KEY (provider, metric)
+            );
+            INSERT OR IGNORE INTO peaks (provider, metric, percent, ts)
+                SELECT provider, metric, MAX(percent), ts FROM samples
+                GROUP BY provider, metric;
+            CREATE TABLE IF NOT EXISTS limit_hits (
+                ts INTEGER NOT NULL,
+                provider TEXT NOT NULL,
+                metric TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS annotations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                ts INTEGER NOT NULL,
+                provider TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                text TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS hourly_rollups (
+                hour_ts INTEGER NOT NULL,
+                provider TEXT NOT NULL,
+                metric TEXT NOT NULL,
+                max_percent REAL NOT NULL,
+                avg_percent REAL NOT NULL,
+                samples INTEGER NOT NULL,
+                PRIMARY KEY (hour_ts, provider, metric)
+            );
+            CREATE TABLE IF NOT EXISTS daily_tokens (
+                date TEXT NOT NULL,
+                provider TEXT NOT NULL,
+                source TEXT NOT NULL,
+                input_tokens INTEGER NOT NULL,
+                output_tokens INTEGER NOT NULL,
+                cache_creation_tokens INTEGER NOT NULL,
+                cache_read_tokens INTEGER NOT NULL,
+                total_tokens INTEGER NOT NULL,
+                cost_usd REAL,
+                PRIMARY KEY (date, provider, source)
+            );
+            CREATE TABLE IF NOT EXISTS credit_samples (
+                ts INTEGER NOT NULL,
+                provider TEXT NOT NULL,
+                total_usage REAL NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS work_sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                started_at INTEGER NOT NULL,
+                ended_at INTEGER
+            );
+            CREATE TABLE IF NOT EXISTS work_session_usage (
+                session_id INTEGER NOT NULL,
+                provider TEXT NOT NULL,
+                metric TEXT NOT NULL,
+                start_percent REAL NOT NULL,
+                consumed REAL
+            );",
+        )
+    }
+
+    /// Writes a consistent copy of the whole database to `path`.
+    pub fn backup_to(&self, path: &Path) -> Result<(), String> {
+        self.conn
+            .lock()
+            .unwrap()
+            .backup(rusqlite::DatabaseName::Main, path, None)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Replaces the whole database with the one at `path`, bringing its schema
+    /// up to date if it came from an older version.
+    pub fn restore_from(&self, path: &Path) -> Result<(), String> {
+        let mut conn = self.conn.lock().unwrap();
+        conn.restore(
+            rusqlite::DatabaseName::Main,
+            path,
+            None::<fn(rusqlite::backup::Progress)>,
+        )
+        .map_err(|e| e.to_string())?;
+        Self::migrate(&conn).map_err(|e| e.to_string())
+    }
+
+    /// Writes one sample per metric for a provider's fresh usage data.
+    pub fn record(
+        &self,
+        provider: &str,
+        data: &UsageData,
+        active: bool,
+        idle_secs: Option<u64>,
+    ) -> Result<(), String> {
+        let ts = chrono::Utc::now().timestamp();
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        for (metric, percent) in data.metric_percents() {
+            let previous: Option<f64> = tx
+                .query_row(
+                    "SELECT percent FROM samples WHERE provider = ?1 AND metric = ?2
+                     ORDER BY ts DESC, id DESC LIMIT 1",
+                    params![provider, metric],
+                    |row| row.get(0),
+                )
+                .ok();
+            if percent >= 100.0 && previous.is_none_or(|p| p < 100.0) {
+                tx.execute(
+                    "INSERT INTO limit_hits (ts, provider, metric) VALUES (?1, ?2, ?3)",
+                    params![ts, provider, metric],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            tx.execute(
+                "INSERT INTO peaks (provider, metric, percent, ts) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (provider, metric) DO UPDATE
+                 SET percent = excluded.percent, ts = excluded.ts
+                 WHERE excluded.percent > peaks.percent",
+                params![provider, metric, percent, ts],
+            )
+            .map_err(|e| e.to_string())?;
+            tx.execute(
+                "INSERT INTO samples (ts, provider, metric, percent, active, idle_secs)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    ts,
+                    provider,
+                    metric,
+                    percent,
+                    active,
+                    idle_secs.map(|s| s as i64)
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    /// Folds raw samples older than the retention into hourly rollups and
+    /// drops rollups and credit totals past theirs. Peaks, limit hits and
+    /// annotations are kept.
+    pub fn prune(&self, retention: &Retention) -> Result<PruneResult, String> {
+        let now = chrono::Utc::now().timestamp();
+        let days = |d: u32| now - i64::from(d) * 86_400;
+        let mut result = PruneResult::default();
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        if retention.raw_days > 0 {
+            let cutoff = days(retention.raw_days);
+            // Merges into an existing rollup of the same hour, weighting the
+            // means by their sample counts
+            tx.execute(
+                "INSERT INTO hourly_rollups
+                     (hour_ts, provider, metric, max_percent, avg_percent, samples)
+                 SELECT ts / 3600 * 3600, provider, metric, MAX(percent), AVG(percent), COUNT(*)
+                 FROM samples WHERE ts < ?1
+                 GROUP BY ts / 3600, provider, metric
+                 ON CONFLICT (hour_ts, provider, metric) DO UPDATE SET
+                     max_percent = MAX(max_percent, excluded.max_percent),
+                     avg_percent = (avg_percent * samples + excluded.avg_percent * excluded.samples)
+                         / (samples + excluded.samples),
+                     samples = samples + excluded.samples",
+                params![cutoff],
+            )
+            .map_err(|e| e.to_string())?;
+            result.samples_rolled_up = tx
+                .execute("DELETE FROM samples WHERE ts < ?1", params![cutoff])
+                .map_err(|e| e.to_string())?;
+        }
+        if retention.rollup_days > 0 {
+            let cutoff = days(retention.rollup_days);
+            result.rollups_removed = tx
+                .execute("DELETE FROM hourly_rollups WHERE hour_ts < ?1", params![cutoff])
+                .map_err(|e| e.to_string())?;
+            result.credit_samples_removed = tx
+                .execute("DELETE FROM credit_samples WHERE ts < ?1", params![cutoff])
+                .map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(result)
+    }
+
+    /// Prunes, then rewrites the database file to give the freed space back.
+    pub fn compact(&self, retention: &Retention) -> Result<PruneResult, String> {
+        let result = self.prune(retention)?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute_batch("VACUUM")
+            .map_err(|e| e.to_string())?;
+        Ok(result)
+    }
+
+    pub fn database_stats(&self) -> Result<DatabaseStats, String> {
+        let conn = self.conn.lock().unwrap();
+        let count = |table: &str| -> Result<u64, String> {
+            conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map(|n| n as u64)
+            .map_err(|e| e.to_string())
+        };
+        let oldest = |sql: &str| -> Result<Option<i64>, String> {
+            conn.query_row(sql, [], |row| row.get(0))
+                .map_err(|e| e.to_string())
+        };
+        let page_count: i64 = conn
+            .query_row("PRAGMA page_count", [], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        let page_size: i64 = conn
+            .query_row("PRAGMA page_size", [], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        Ok(DatabaseStats {
+            size_bytes: (page_count * page_size) as u64,
+            samples: count("samples")?,
+            oldest_sample: oldest("SELECT MIN(ts) FROM samples")?,
+            rollups: count("hourly_rollups")?,
+            oldest_rollup: oldest("SELECT MIN(hour_ts) FROM hourly_rollups")?,
+            credit_samples: count("credit_samples")?,
+            annotations: count("annotations")?,
+        })
+    }
+
+    /// Stores imported days, replacing any earlier import of the same day,
+    /// provider and source.
+    pub fn import_daily(&self, days: &[DailyTokens]) -> Result<(), String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        for d in days {
+            tx.execute(
+                "INSERT OR REPLACE INTO daily_tokens (date, provider, source, input_tokens,
+                     output_tokens, cache_creation_tokens, cache_read_tokens, total_tokens,
+                     cost_usd)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    d.date,
+                    d.provider,
+                    d.source,
+                    d.input_tokens as i64,
+                    d.output_tokens as i64,
+                    d.cache_creation_tokens as i64,
+                    d.cache_read_tokens as i64,
+                    d.total_tokens as i64,
+                    d.cost_usd
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    /// Imported days, optionally for one provider, oldest first.
+    pub fn daily_tokens(&self, provider: Option<&str>) -> Result<Vec<DailyTokens>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT date, provider, source, input_tokens, output_tokens,
+                        cache_creation_tokens, cache_read_tokens, total_tokens, cost_usd
+                 FROM daily_tokens WHERE ?1 IS NULL OR provider = ?1
+                 ORDER BY date, provider, source",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![provider], |row| {
+                Ok(DailyTokens {
+                    date: row.get(0)?,
+                    provider: row.get(1)?,
+                    source: row.get(2)?,
+                    input_tokens: row.get::<_, i64>(3)? as u64,
+                    output_tokens: row.get::<_, i64>(4)? as u64,
+                    cache_creation_tokens: row.get::<_, i64>(5)? as u64,
+                    cache_read_tokens: row.get::<_, i64>(6)? as u64,
+                    total_tokens: row.get::<_, i64>(7)? as u64,
+                    cost_usd: row.get(8)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| e.to_string())
+    }
+
+    /// Records the running total a credit-based provider has spent, in USD.
+    pub fn record_credits(&self, provider: &str, total_usage: f64) -> Result<(), String> {
+        let ts = chrono::Utc::now().timestamp();
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO credit_samples (ts, provider, total_usage) VALUES (?1, ?2, ?3)",
+                params![ts, provider, total_usage],
+            )
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// USD a credit-based provider spent between `since` and `until` (unix
+    /// seconds), measured from the last total before the period, or the first
+    /// one in it. None without samples in the period.
+    pub fn credit_spend(&self, provider: &str, since: i64, until: i64) -> Option<f64> {
+        let conn = self.conn.lock().unwrap();
+        let end: f64 = conn
+            .query_row(
+                "SELECT MAX(total_usage) FROM credit_samples
+                 WHERE provider = ?1 AND ts >= ?2 AND ts < ?3",
+                params![provider, since, until],
+                |row| row.get(0),
+            )
+            .ok()?;
+        let start: f64 = conn
+            .query_row(
+                "SELECT total_usage FROM credit_samples WHERE provider = ?1 AND ts < ?2
+                 ORDER BY ts DESC LIMIT 1",
+                params![provider, since],
+                |row| row.get(0),
+            )
+            .or_else(|_| {
+                conn.query_row(
+                    "SELECT MIN(total_usage) FROM credit_samples
+                     WHERE provider = ?1 AND ts >= ?2 AND ts < ?3",
+                    params![provider, since, until],
+                    |row| row.get(0),
+                )
+            })
+            .ok()?;
+        Some((end - start).max(0.0))
+    }
+
+    /// Percentages from the most recent sample of a provider, keyed by metric.
+    /// Empty when that sample is older than the session window.
+    pub fn latest(&self, provider: &str) -> HashMap<String, f64> {
+        let since = chrono::Utc::now().timestamp() - LATEST_MAX_AGE_SECS;
+        let conn = self.conn.lock().unwrap();
+        let mut out = HashMap::new();
+        let Ok(mut stmt) = conn.prepare(
+            "SELECT metric, percent FROM samples
+             WHERE provider = ?1 AND ts >= ?2
+               AND ts = (SELECT MAX(ts) FROM samples WHERE provider = ?1)",
+        ) else {
+            return out;
+        };
+        if let Ok(rows) = stmt.query_map(params![provider, since], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+        }) {
+            for (metric, percent) in rows.flatten() {
+                out.insert(metric, percent);
+            }
+        }
+        out
+    }
+
+    /// All samples since `since` (unix seconds), optionally for one provider,
+    /// oldest first. Hours whose raw samples were pruned come from the
+    /// rollups, one sample at the hour's peak, counted as active.
+    pub fn samples_since(&self, provider: Option<&str>, since: i64) -> Result<Vec<Sample>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT hour_ts AS ts, provider, metric, max_percent, 1, NULL, 0 AS id
+                 FROM hourly_rollups r
+                 WHERE hour_ts >= ?1 AND (?2 IS NULL OR provider = ?2)
+                   AND NOT EXISTS (SELECT 1 FROM samples s
+                       WHERE s.provider = r.provider AND s.metric = r.metric
+                         AND s.ts <= r.hour_ts)
+                 UNION ALL
+                 SELECT ts, provider, metric, percent, active, idle_secs, id FROM samples
+                 WHERE ts >= ?1 AND (?2 IS NULL OR provider = ?2)
+                 ORDER BY ts, id",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![since, provider], |row| {
+                Ok(Sample {
+                    ts: row.get(0)?,
+                    provider: row.get(1)?,
+                    metric: row.get(2)?,
+                    percent: row.get(3)?,
+                    active: row.get(4)?,
+                    idle_secs: row.get::<_, Option<i64>>(5)?.map(|s| s as u64),
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| e.to_string())
+    }
+
+    /// Total percentage points a metric grew since `since`, summing only rises
+    /// between consecutive samples so window resets don't cancel out usage.
+    pub fn growth(&self, provider: &str, metric: &str, since: i64) -> Result<f64, String> {
+        let samples = self.samples_since(Some(provider), since)?;
+        Ok(sum_rises(
+            None,
+            samples
+                .iter()
+                .filter(|s| s.metric == metric)
+                .map(|s| s.percent),
+        ))
+    }
+
+    /// Session utilization since `since` (unix seconds), optionally for one
+    /// provider, as all 168 hours of the week, Monday 0:00 first. Each rise
+    /// between consecutive samples counts toward the hour it was observed in.
+    pub fn heatmap(&self, provider: Option<&str>, since: i64) -> Result<Vec<HeatmapCell>, String> {
+        let mut cells: Vec<HeatmapCell> = (0..7 * 24)
+            .map(|i| HeatmapCell {
+                weekday: i / 24,
+                hour: i % 24,
+                consumed: 0.0,
+                average_percent: None,
+                samples: 0,
+            })
+            .collect();
+        let mut sums = vec![0.0; cells.len()];
+        let mut previous: HashMap<String, f64> = HashMap::new();
+        for s in self
+            .samples_since(provider, since)?
+            .into_iter()
+            .filter(|s| s.metric == "session")
+        {
+            let Some(at) = chrono::DateTime::from_timestamp(s.ts, 0) else {
+                continue;
+            };
+            let at = at.with_timezone(&chrono::Local);
+            let i = (at.weekday().num_days_from_monday() * 24 + at.hour()) as usize;
+            if let Some(p) = previous.insert(s.provider, s.percent) {
+                if s.percent > p {
+                    cells[i].consumed += s.percent - p;
+                }
+            }
+            sums[i] += s.percent;
+            cells[i].samples += 1;
+        }
+        for (cell, sum) in cells.iter_mut().zip(sums) {
+            if cell.samples > 0 {
+                cell.average_percent = Some(sum / cell.samples as f64);
+            }
+        }
+        Ok(cells)
+    }
+
+    /// All-time and rolling 30-day peaks plus limit hits for every metric seen.
+    pub fn usage_records(&self) -> Result<Vec<UsageRecord>, String> {
+        let since = chrono::Utc::now().timestamp() - ROLLING_SECS;
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "WITH recent AS (
+                     SELECT provider, metric, percent, ts FROM samples WHERE ts >= ?1
+                     UNION ALL
+                     SELECT provider, metric, max_percent, hour_ts FROM hourly_rollups
+                     WHERE hour_ts >= ?1
+                 )
+                 SELECT p.provider, p.metric, p.percent, p.ts,
+                        (SELECT MAX(percent) FROM recent s
+                         WHERE s.provider = p.provider AND s.metric = p.metric),
+                        (SELECT ts FROM recent s
+                         WHERE s.provider = p.provider AND s.metric = p.metric
+                         ORDER BY percent DESC, ts DESC LIMIT 1)
+                 FROM peaks p ORDER BY p.provider, p.metric",
+            )
+            .map_err(|e| e.to_string())?;
+        let mut records = stmt
+            .query_map(params![since], |row| {
+                Ok(UsageRecord {
+                    provider: row.get(0)?,
+                    metric: row.get(1)?,
+                    all_time_peak: row.get(2)?,
+                    all_time_peak_at: row.get(3)?,
+                    peak_30d: row.get(4)?,
+                    peak_30d_at: row.get(5)?,
+                    limit_hits_30d: 0,
+                    limit_hits: Vec::new(),
+                })
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| e.to_string())?;
+
+        let mut hits = conn
+            .prepare(
+                "SELECT ts FROM limit_hits WHERE provider = ?1 AND metric = ?2 ORDER BY ts DESC",
+            )
+            .map_err(|e| e.to_string())?;
+        for r in &mut records {
+            r.limit_hits = hits
+                .query_map(params![r.provider, r.metric], |row| row.get(0))
+                .map_err(|e| e.to_string())?
+                .collect::<rusqlite::Result<Vec<i64>>>()
+                .map_err(|e| e.to_string())?;
+            r.limit_hits_30d = r.limit_hits.iter().filter(|ts| **ts >= since).count();
+        }
+        Ok(records)
+    }
+
+    /// Every limit hit between `since` and `until` (unix seconds) as
+    /// `(provider, metric, ts)`, oldest first.
+    pub fn limit_hits_between(
+        &self,
+        since: i64,
+        until: i64,
+    ) -> Result<Vec<(String, String, i64)>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT provider, metric, ts FROM limit_hits
+                 WHERE ts >= ?1 AND ts < ?2 ORDER BY ts",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![since, until], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn annotate(&self, provider: &str, kind: &str, text: &str) -> Result<(), String> {
+        let ts = chrono::Utc::now().timestamp();
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO annotations (ts, provider, kind, text) VALUES (?1, ?2, ?3, ?4)",
+                params![ts, provider, kind, text],
+            )
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Text of the newest annotation of `kind` for a provider.
+    pub fn latest_annotation(&self, provider: &str, kind: &str) -> Option<String> {
+        self.conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT text FROM annotations WHERE provider = ?1 AND kind = ?2
+                 ORDER BY ts DESC, id DESC LIMIT 1",
+                params![provider, kind],
+                |row| row.get(0),
+            )
+            .ok()
+    }
+
+    /// Text of the newest annotation of `kind` for a provider made before
+    /// `until` (unix seconds).
+    pub fn annotation_before(&self, provider: &str, kind: &str, until: i64) -> Option<String> {
+        self.conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT text FROM annotations WHERE provider = ?1 AND kind = ?2 AND ts < ?3
+                 ORDER BY ts DESC, id DESC LIMIT 1",
+                params![provider, kind, until],
+                |row| row.get(0),
+            )
+            .ok()
+    }
+
+    /// Annotations since `since` (unix seconds), optionally for one provider, oldest first.
+    pub fn annotations_since(
+        &self,
+        provider: Option<&str>,
+        since: i64,
+    ) -> Result<Vec<Annotation>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT ts, provider, kind, text FROM annotations
+                 WHERE ts >= ?1 AND (?2 IS NULL OR provider = ?2)
+                 ORDER BY ts, id",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![since, provider], |row| {
+                Ok(Annotation {
+                    ts: row.get(0)?,
+                    provider: row.get(1)?,
+                    kind: row.get(2)?,
+                    text: row.get(3)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| e.to_string())
+    }
+
+    /// Starts a work session, snapshotting the latest sample of each provider.
+    /// Only one session runs at a time.
+    pub fn start_work_session(
+        &self,
+        name: &str,
+        providers: &[&str],
+    ) -> Result<WorkSession, String> {
+        if let Some(active) = self.active_work_session()? {
+            return Err(format!("\"{}\" is still running", active.name));
+        }
+        let snapshots: Vec<_> = providers.iter().map(|p| (*p, self.latest(p))).collect();
+        let started_at = chrono::Utc::now().timestamp();
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute(
+            "INSERT INTO work_sessions (name, started_at) VALUES (?1, ?2)",
+            params![name, started_at],
+        )
+        .map_err(|e| e.to_string())?;
+        let id = tx.last_insert_rowid();
+        for (provider, metrics) in &snapshots {
+            for (metric, percent) in metrics {
+                tx.execute(
+                    "INSERT INTO work_session_usage (session_id, provider, metric, start_percent)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![id, provider, metric, percent],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+        drop(conn);
+        self.work_session(id)
+    }
+
+    /// Stops the running work session, recording how far each snapshotted
+    /// metric grew since it started.
+    pub fn stop_work_session(&self) -> Result<WorkSession, String> {
+        let session = self
+            .active_work_session()?
+            .ok_or("No work session is running")?;
+        let samples = self.samples_since(None, session.started_at)?;
+        let ended_at = chrono::Utc::now().timestamp();
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        for u in &session.usage {
+            let consumed = sum_rises(
+                Some(u.start_percent),
+                samples
+                    .iter()
+                    .filter(|s| s.provider == u.provider && s.metric == u.metric)
+                    .map(|s| s.percent),
+            );
+            tx.execute(
+                "UPDATE work_session_usage SET consumed = ?1
+                 WHERE session_id = ?2 AND provider = ?3 AND metric = ?4",
+                params![consumed, session.id, u.provider, u.metric],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        tx.execute(
+            "UPDATE work_sessions SET ended_at = ?1 WHERE id = ?2",
+            params![ended_at, session.id],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())?;
+        drop(conn);
+        self.work_session(session.id)
+    }
+
+    pub fn active_work_session(&self) -> Result<Option<WorkSession>, String> {
+        let id: Option<i64> = self
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT id FROM work_sessions WHERE ended_at IS NULL ORDER BY id DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .ok();
+        id.map(|id| self.work_session(id)).transpose()
+    }
+
+    /// The newest `limit` work sessions, running one included, newest first.
+    pub fn work_sessions(&self, limit: u32) -> Result<Vec<WorkSession>, String> {
+        let ids = {
+            let conn = self.conn.lock().unwrap();
+            let mut stmt = conn
+                .prepare("SELECT id FROM work_sessions ORDER BY id DESC LIMIT ?1")
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(params![limit], |row| row.get::<_, i64>(0))
+                .map_err(|e| e.to_string())?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()
+                .map_err(|e| e.to_string())?
+        };
+        ids.into_iter().map(|id| self.work_session(id)).collect()
+    }
+
+    fn work_session(&self, id: i64) -> Result<WorkSession, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut session = conn
+            .query_row(
+                "SELECT name, started_at, ended_at FROM work_sessions WHERE id = ?1",
+                params![id],
+                |row| {
+                    Ok(WorkSession {
+                        id,
+                        name: row.get(0)?,
+                        started_at: row.get(1)?,
+                        ended_at: row.get(2)?,
+                        usage: Vec::new(),
+                    })
+                },
+            )
+            .map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT provider, metric, start_percent, consumed FROM work_session_usage
+                 WHERE session_id = ?1 ORDER BY provider, metric",
+            )
+            .map_err(|e| e.to_string())?;
+        session.usage = stmt
+            .query_map(params![id], |row| {
+                Ok(WorkSessionUsage {
+                    provider: row.get(0)?,
+                    metric: row.get(1)?,
+                    start_percent: row.get(2)?,
+                    consumed: row.get(3)?,
+                })
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| e.to_string())?;
+        Ok(session)
+    }
+}
+
+/// Applies the retention settings at launch and then once a day.
+pub fn spawn_pruning(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let retention = handle.state::<SettingsState>().get().retention;
+            match handle.state::<HistoryState>().prune(&retention) {
+                Ok(r) if r.samples_rolled_up + r.rollups_removed > 0 => log::info!(
+                    "Pruned history: {} samples rolled up, {} rollups removed",
+                    r.samples_rolled_up,
+                    r.rollups_removed
+                ),
+                Ok(_) => {}
+                Err(e) => log::warn!("Failed to prune history: {}", e),
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(PRUNE_INTERVAL_SECS)).await;
+        }
+    });
+}
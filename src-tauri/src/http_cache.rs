@@ -0,0 +1,87 @@
+//! Conditional-request support for the provider fetchers.
+//!
+//! Each fetcher remembers the `ETag` / `Last-Modified` validators of its last
+//! successful response and sends them back as `If-None-Match` /
+//! `If-Modified-Since`. A `304 Not Modified` reply skips JSON parsing and the
+//! caller keeps its cached data.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{RequestBuilder, Response};
+
+use crate::provider::Provider;
+
+/// Cache validators from a previous response.
+#[derive(Debug, Clone, Default)]
+pub struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl Validators {
+    pub fn from_response(resp: &Response) -> Self {
+        let header = |name| {
+            resp.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from)
+        };
+        Self {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// Adds conditional headers to `req` when validators are available.
+pub fn conditional(req: RequestBuilder, validators: Option<&Validators>) -> RequestBuilder {
+    let Some(v) = validators else {
+        return req;
+    };
+    let req = match &v.etag {
+        Some(etag) => req.header(IF_NONE_MATCH, etag),
+        None => req,
+    };
+    match &v.last_modified {
+        Some(date) => req.header(IF_MODIFIED_SINCE, date),
+        None => req,
+    }
+}
+
+/// Outcome of a conditional fetch.
+pub enum Fetched<T> {
+    Fresh(T, Validators),
+    NotModified,
+}
+
+/// Validators for each provider's last successful response.
+pub struct HttpCacheState {
+    validators: Mutex<HashMap<Provider, Validators>>,
+}
+
+impl HttpCacheState {
+    pub fn new() -> Self {
+        Self {
+            validators: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, provider: Provider) -> Option<Validators> {
+        self.validators.lock().unwrap().get(&provider).cloned()
+    }
+
+    pub fn set(&self, provider: Provider, validators: Validators) {
+        let mut map = self.validators.lock().unwrap();
+        if validators.is_empty() {
+            map.remove(&provider);
+        } else {
+            map.insert(provider, validators);
+        }
+    }
+}
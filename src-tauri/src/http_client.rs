@@ -0,0 +1,66 @@
+//! Builds the shared `reqwest::Client` from the network settings and turns
+//! transport errors into messages that tell a timeout apart from other failures.
+
+use std::time::Duration;
+
+use reqwest::{Client, RequestBuilder};
+
+use crate::settings::NetworkOptions;
+
+/// How often idle pooled connections are probed to keep them alive.
+const TCP_KEEPALIVE_SECS: u64 = 60;
+
+pub fn build(options: &NetworkOptions) -> Result<Client, String> {
+    let builder = Client::builder()
+        .connect_timeout(Duration::from_secs(options.connect_timeout_secs.max(1)))
+        .read_timeout(Duration::from_secs(options.read_timeout_secs.max(1)))
+        .pool_idle_timeout(Duration::from_secs(options.pool_idle_timeout_secs))
+        .tcp_keepalive(Duration::from_secs(TCP_KEEPALIVE_SECS));
+    // HTTP/2 is negotiated via ALPN when preferred; otherwise pin HTTP/1.1
+    let builder = if options.prefer_http2 {
+        builder
+    } else {
+        builder.http1_only()
+    };
+    builder
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
+/// The base URL of a self-hosted server without trailing slashes, if it looks
+/// like one.
+pub fn normalize_base_url(url: &str) -> Result<String, String> {
+    let url = url.trim().trim_end_matches('/');
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err("The server URL must start with http:// or https://".into());
+    }
+    Ok(url.to_string())
+}
+
+/// Describes a failed request to `service`, calling out timeouts and
+/// connection failures separately. The URL is left out, as it may carry
+/// account ids or tokens.
+pub fn request_error(service: &str, e: reqwest::Error) -> String {
+    let e = e.without_url();
+    if e.is_timeout() {
+        format!(
+            "{} request timed out - the network may be slow or offline",
+            service
+        )
+    } else if e.is_connect() {
+        format!("Could not connect to {}: {}", service, e)
+    } else {
+        format!("{} request failed: {}", service, e)
+    }
+}
+
+/// Sends `req` and returns the body as untyped JSON, for inspecting payloads.
+pub async fn raw_json(service: &str, req: RequestBuilder) -> Result<serde_json::Value, String> {
+    let resp = req.send().await.map_err(|e| request_error(service, e))?;
+    if !resp.status().is_success() {
+        return Err(format!("{} API returned {}", service, resp.status()));
+    }
+    resp.json()
+        .await
+        .map_err(|e| format!("{} response isn't JSON: {}", service, e))
+}
@@ -0,0 +1,909 @@
+//! Localized backend strings: the tray menu and tooltips, usage labels, reset
+//! times, notifications, window titles and monthly reports. The language
+//! follows the macOS preferred language unless the user picks one in settings;
+//! anything missing from a table falls back to English.
+//!
+//! Strings are looked up by key and may carry positional `{0}`, `{1}`, ...
+//! placeholders, since word order differs between languages. Error messages
+//! stay in English, as they mostly end up in bug reports.
+//!
+//! Numbers and dollar amounts follow the language's separators and currency
+//! placement, with the currency shown as a symbol, a code or not at all.
+//!
+//! The current language is process-wide rather than managed state because
+//! reset strings are formatted deep inside the fetchers, without an app handle.
+
+use std::process::Command;
+use std::sync::RwLock;
+
+use crate::settings::{CurrencyDisplay, Language};
+
+static CURRENT: RwLock<Language> = RwLock::new(Language::En);
+static CURRENCY: RwLock<CurrencyDisplay> = RwLock::new(CurrencyDisplay::Symbol);
+
+type Table = &'static [(&'static str, &'static str)];
+
+const EN: Table = &[
+    ("menu.show_widget", "Show Widget"),
+    ("menu.refresh_now", "Refresh Now"),
+    ("menu.pause", "Pause Monitoring"),
+    ("menu.refresh_every", "Refresh Every"),
+    ("menu.minutes", "{0} min"),
+    ("menu.seconds", "{0} s"),
+    ("menu.custom", "Custom: {0}"),
+    ("menu.notify_at", "Notify At"),
+    ("menu.off", "Off"),
+    ("menu.start_login", "Start at Login"),
+    ("menu.separate_items", "Separate Menu Bar Items"),
+    ("menu.combine_items", "Combine Menu Bar Items"),
+    ("menu.privacy", "Privacy Mode"),
+    ("menu.click_through", "Click-Through Pinned Widget"),
+    ("menu.dock_icon", "Show Dock Icon"),
+    ("menu.monthly_report", "Generate Last Month's Report"),
+    ("menu.notifications_off", "Notifications Are Off - Open Settings..."),
+    ("menu.upcoming_resets", "Upcoming Resets"),
+    ("menu.no_resets", "No reset times yet"),
+    ("menu.reconfigure", "Reconfigure Providers..."),
+    ("menu.settings", "Settings..."),
+    ("menu.quit", "Quit"),
+    ("tooltip.app", "Usage Widget"),
+    ("tooltip.provider_usage", "{0} usage"),
+    ("tooltip.extra", "Extra usage: {0} ({1})"),
+    ("tooltip.on", "on"),
+    ("tooltip.off", "off"),
+    ("tooltip.updated", "Updated {0}"),
+    ("tooltip.remaining", "Remaining: {0}"),
+    ("tooltip.spent", "Spent: {0}"),
+    ("tooltip.local_models", "Loaded: {0}"),
+    ("tooltip.local_none", "No models loaded"),
+    ("tooltip.local_requests", "Requests in the last hour: {0}"),
+    ("tooltip.local_off", "Not running"),
+    ("preview.extra", "Extra usage"),
+    ("preview.credits", "Credits remaining"),
+    ("preview.budget", "Budget remaining"),
+    ("preview.spent", "Spent"),
+    ("tooltip.used_of", "Used: {0} of {1}"),
+    ("usage.current_session", "Current session"),
+    ("usage.all_models", "All models"),
+    ("usage.sonnet_only", "Sonnet only"),
+    ("usage.opus_only", "Opus only"),
+    ("usage.haiku_only", "Haiku only"),
+    ("usage.monthly", "Monthly"),
+    ("usage.session", "Session"),
+    ("usage.weekly", "Weekly"),
+    ("usage.day_window", "{0}-day window"),
+    ("usage.hour_window", "{0}-hour window"),
+    ("usage.plan", "Plan: {0}"),
+    ("usage.unlimited", "Unlimited"),
+    ("usage.no_data", "No data"),
+    ("usage.limit_reached", "LIMIT REACHED - {0}"),
+    ("reset.in_hours", "Resets in {0}h {1}m"),
+    ("reset.in_minutes", "Resets in {0}m"),
+    ("reset.soon", "Resets soon"),
+    ("notify.metric_session", "{0} session"),
+    ("notify.metric_weekly", "{0} weekly"),
+    ("notify.metric_extra", "{0} extra usage"),
+    ("notify.threshold", "{0} at {1}%"),
+    ("notify.delta_minutes", "(+{0}% in last {1} min)"),
+    ("notify.delta_hours", "(+{0}% in last {1} h)"),
+    ("notify.reminder", "Still {0} at {1}% (reminder {2})"),
+    ("notify.idle_spike", "{0} rose {1}% while you were away"),
+    (
+        "notify.idle_spike_body",
+        "Now at {0}% - something may be running unattended.",
+    ),
+    ("notify.plan_changed", "{0} plan changed"),
+    (
+        "notify.plan_changed_body",
+        "{0} -> {1}. Limits may behave differently from now on.",
+    ),
+    ("notify.failover", "Consider switching to {0}"),
+    ("notify.failover_body", "{0} at {1}% - {2} at {3}%"),
+    ("notify.focus_one", "1 alert while Focus was on"),
+    ("notify.focus_many", "{0} alerts while Focus was on"),
+    ("notify.stale", "{0} data may be stuck"),
+    (
+        "notify.stale_body",
+        "Nothing has changed in {0} hours of use although every refresh succeeded. Try Reconfigure Providers or sign in again.",
+    ),
+    ("budget.ahead", "{0} {1} ahead of budget"),
+    (
+        "budget.ahead_body",
+        "At {0}% - on track for your {1}% budget would be {2}% by now.",
+    ),
+    ("work.finished", "\"{0}\" finished"),
+    ("work.of_session", "{0}% of session"),
+    ("work.of_weekly", "{0}% of weekly"),
+    (
+        "work.no_data",
+        "No usage data was available for this session.",
+    ),
+    ("a11y.metric", "{0}: {1} percent used, {2}."),
+    ("a11y.resets_in", "resets in {0}"),
+    ("a11y.resets_soon", "resets soon"),
+    ("a11y.reset_unknown", "reset time unknown"),
+    ("a11y.day", "{0} day"),
+    ("a11y.days", "{0} days"),
+    ("a11y.hour", "{0} hour"),
+    ("a11y.hours", "{0} hours"),
+    ("a11y.minute", "{0} minute"),
+    ("a11y.minutes", "{0} minutes"),
+    ("a11y.separator", ", "),
+    ("a11y.extra", "{0} extra usage: {1}, {2}."),
+    ("a11y.credits", "{0}: {1} of credit remaining."),
+    (
+        "a11y.failing",
+        "{0}: the last update failed, so these figures may be out of date.",
+    ),
+    ("a11y.empty", "No usage data yet."),
+    ("startup.title", "Usage Widget is running"),
+    ("startup.weekly", "{0} {1}% of weekly - resets {2}"),
+    ("startup.weekly_no_reset", "{0} {1}% of weekly"),
+    ("startup.credits", "{0} {1} remaining"),
+    ("startup.spent", "{0} {1} spent"),
+    ("startup.not_configured", "{0} not configured"),
+    ("startup.failing", "{0} not responding"),
+    ("startup.mon", "Monday"),
+    ("startup.tue", "Tuesday"),
+    ("startup.wed", "Wednesday"),
+    ("startup.thu", "Thursday"),
+    ("startup.fri", "Friday"),
+    ("startup.sat", "Saturday"),
+    ("startup.sun", "Sunday"),
+    ("startup.today", "today"),
+    ("window.usage", "Usage"),
+    ("report.title", "Usage report for {0}"),
+    ("report.empty", "No usage was recorded this month."),
+    ("report.columns", "| Metric | Peak | Peak on | Used over the month |"),
+    ("report.extra", "Extra usage"),
+    ("report.points", "{0} points"),
+    ("report.no_limits", "No limits were hit."),
+    ("report.limits_hit", "Limits hit on {0} day(s): {1}"),
+    ("report.hidden", "Dollar amounts and plans are hidden while privacy mode is on."),
+    ("report.api_title", "API-equivalent cost"),
+    ("report.api_intro", "What the month's tokens would have cost on pay-per-token pricing."),
+    ("report.api_line", "{0}: {1} for {2} tokens"),
+];
+
+const DE: Table = &[
+    ("menu.show_widget", "Widget anzeigen"),
+    ("menu.refresh_now", "Jetzt aktualisieren"),
+    ("menu.pause", "Überwachung pausieren"),
+    ("menu.refresh_every", "Aktualisieren alle"),
+    ("menu.minutes", "{0} Min."),
+    ("menu.seconds", "{0} Sek."),
+    ("menu.custom", "Benutzerdefiniert: {0}"),
+    ("menu.notify_at", "Benachrichtigen bei"),
+    ("menu.off", "Aus"),
+    ("menu.start_login", "Bei Anmeldung starten"),
+    ("menu.separate_items", "Getrennte Menüleisten-Einträge"),
+    ("menu.combine_items", "Menüleisten-Einträge zusammenfassen"),
+    ("menu.privacy", "Privatsphäre-Modus"),
+    ("menu.click_through", "Angeheftetes Widget durchklickbar"),
+    ("menu.dock_icon", "Dock-Symbol anzeigen"),
+    ("menu.monthly_report", "Bericht für letzten Monat erstellen"),
+    ("menu.notifications_off", "Mitteilungen sind aus - Einstellungen öffnen..."),
+    ("menu.upcoming_resets", "Nächste Zurücksetzungen"),
+    ("menu.no_resets", "Noch keine Zeiten bekannt"),
+    ("menu.reconfigure", "Anbieter neu einrichten..."),
+    ("menu.settings", "Einstellungen..."),
+    ("menu.quit", "Beenden"),
+    ("tooltip.app", "Nutzungs-Widget"),
+    ("tooltip.provider_usage", "{0}-Nutzung"),
+    ("tooltip.extra", "Zusatznutzung: {0} ({1})"),
+    ("tooltip.on", "an"),
+    ("tooltip.off", "aus"),
+    ("tooltip.updated", "Aktualisiert {0}"),
+    ("tooltip.remaining", "Verbleibend: {0}"),
+    ("tooltip.spent", "Ausgegeben: {0}"),
+    ("tooltip.local_models", "Geladen: {0}"),
+    ("tooltip.local_none", "Keine Modelle geladen"),
+    ("tooltip.local_requests", "Anfragen in der letzten Stunde: {0}"),
+    ("tooltip.local_off", "Läuft nicht"),
+    ("preview.extra", "Zusatznutzung"),
+    ("preview.credits", "Verbleibendes Guthaben"),
+    ("preview.budget", "Verbleibendes Budget"),
+    ("preview.spent", "Ausgegeben"),
+    ("tooltip.used_of", "Verbraucht: {0} von {1}"),
+    ("usage.current_session", "Aktuelle Sitzung"),
+    ("usage.all_models", "Alle Modelle"),
+    ("usage.sonnet_only", "Nur Sonnet"),
+    ("usage.opus_only", "Nur Opus"),
+    ("usage.haiku_only", "Nur Haiku"),
+    ("usage.monthly", "Monatlich"),
+    ("usage.session", "Sitzung"),
+    ("usage.weekly", "Wöchentlich"),
+    ("usage.day_window", "{0}-Tage-Fenster"),
+    ("usage.hour_window", "{0}-Stunden-Fenster"),
+    ("usage.plan", "Tarif: {0}"),
+    ("usage.unlimited", "Unbegrenzt"),
+    ("usage.no_data", "Keine Daten"),
+    ("usage.limit_reached", "LIMIT ERREICHT - {0}"),
+    ("reset.in_hours", "Zurückgesetzt in {0} Std. {1} Min."),
+    ("reset.in_minutes", "Zurückgesetzt in {0} Min."),
+    ("reset.soon", "Wird bald zurückgesetzt"),
+    ("notify.metric_session", "{0} Sitzung"),
+    ("notify.metric_weekly", "{0} wöchentlich"),
+    ("notify.metric_extra", "{0} Zusatznutzung"),
+    ("notify.threshold", "{0} bei {1}%"),
+    ("notify.delta_minutes", "(+{0}% in den letzten {1} Min.)"),
+    ("notify.delta_hours", "(+{0}% in den letzten {1} Std.)"),
+    ("notify.reminder", "Weiterhin {0} bei {1}% (Erinnerung {2})"),
+    (
+        "notify.idle_spike",
+        "{0} ist in Ihrer Abwesenheit um {1}% gestiegen",
+    ),
+    (
+        "notify.idle_spike_body",
+        "Jetzt bei {0}% - möglicherweise läuft etwas unbeaufsichtigt.",
+    ),
+    ("notify.plan_changed", "{0}-Tarif geändert"),
+    (
+        "notify.plan_changed_body",
+        "{0} -> {1}. Die Limits können sich ab jetzt anders verhalten.",
+    ),
+    ("notify.failover", "Wechsel zu {0} erwägen"),
+    ("notify.failover_body", "{0} bei {1}% - {2} bei {3}%"),
+    ("notify.focus_one", "1 Hinweis während des Fokus"),
+    ("notify.focus_many", "{0} Hinweise während des Fokus"),
+    ("notify.stale", "{0}-Daten hängen möglicherweise"),
+    (
+        "notify.stale_body",
+        "In {0} Stunden Nutzung hat sich nichts geändert, obwohl jede Aktualisierung geklappt hat. Anbieter neu einrichten oder erneut anmelden.",
+    ),
+    ("budget.ahead", "{0} {1} über dem Budget"),
+    (
+        "budget.ahead_body",
+        "Bei {0}% - im Plan für Ihr {1}%-Budget wären es jetzt {2}%.",
+    ),
+    ("work.finished", "\"{0}\" beendet"),
+    ("work.of_session", "{0}% der Sitzung"),
+    ("work.of_weekly", "{0}% des Wochenlimits"),
+    (
+        "work.no_data",
+        "Für diese Sitzung waren keine Nutzungsdaten verfügbar.",
+    ),
+    ("a11y.metric", "{0}: {1} Prozent verbraucht, {2}."),
+    ("a11y.resets_in", "wird in {0} zurückgesetzt"),
+    ("a11y.resets_soon", "wird bald zurückgesetzt"),
+    (
+        "a11y.reset_unknown",
+        "Zeitpunkt der Zurücksetzung unbekannt",
+    ),
+    ("a11y.day", "{0} Tag"),
+    ("a11y.days", "{0} Tagen"),
+    ("a11y.hour", "{0} Stunde"),
+    ("a11y.hours", "{0} Stunden"),
+    ("a11y.minute", "{0} Minute"),
+    ("a11y.minutes", "{0} Minuten"),
+    ("a11y.separator", ", "),
+    ("a11y.extra", "{0} Zusatznutzung: {1}, {2}."),
+    ("a11y.credits", "{0}: {1} Guthaben übrig."),
+    (
+        "a11y.failing",
+        "{0}: Die letzte Aktualisierung ist fehlgeschlagen, die Werte sind eventuell veraltet.",
+    ),
+    ("a11y.empty", "Noch keine Nutzungsdaten."),
+    ("startup.title", "Nutzungs-Widget läuft"),
+    ("startup.weekly", "{0} {1}% der Woche - Zurücksetzung {2}"),
+    ("startup.weekly_no_reset", "{0} {1}% der Woche"),
+    ("startup.credits", "{0} {1} übrig"),
+    ("startup.spent", "{0} {1} ausgegeben"),
+    ("startup.not_configured", "{0} nicht eingerichtet"),
+    ("startup.failing", "{0} antwortet nicht"),
+    ("startup.mon", "am Montag"),
+    ("startup.tue", "am Dienstag"),
+    ("startup.wed", "am Mittwoch"),
+    ("startup.thu", "am Donnerstag"),
+    ("startup.fri", "am Freitag"),
+    ("startup.sat", "am Samstag"),
+    ("startup.sun", "am Sonntag"),
+    ("startup.today", "heute"),
+    ("window.usage", "Nutzung"),
+    ("report.title", "Nutzungsbericht für {0}"),
+    ("report.empty", "In diesem Monat wurde keine Nutzung erfasst."),
+    ("report.columns", "| Messwert | Spitze | Spitze am | Verbraucht im Monat |"),
+    ("report.extra", "Zusatznutzung"),
+    ("report.points", "{0} Punkte"),
+    ("report.no_limits", "Es wurden keine Limits erreicht."),
+    ("report.limits_hit", "Limits an {0} Tag(en) erreicht: {1}"),
+    ("report.hidden", "Beträge und Tarife sind im Privatsphäre-Modus ausgeblendet."),
+    ("report.api_title", "Kosten zu API-Preisen"),
+    ("report.api_intro", "Was die Tokens des Monats bei Abrechnung pro Token gekostet hätten."),
+    ("report.api_line", "{0}: {1} für {2} Tokens"),
+];
+
+const FR: Table = &[
+    ("menu.show_widget", "Afficher le widget"),
+    ("menu.refresh_now", "Actualiser maintenant"),
+    ("menu.pause", "Suspendre la surveillance"),
+    ("menu.refresh_every", "Actualiser toutes les"),
+    ("menu.minutes", "{0} min"),
+    ("menu.seconds", "{0} s"),
+    ("menu.custom", "Personnalisé : {0}"),
+    ("menu.notify_at", "Notifier à"),
+    ("menu.off", "Désactivé"),
+    ("menu.start_login", "Ouvrir à la connexion"),
+    ("menu.separate_items", "Éléments de barre des menus séparés"),
+    (
+        "menu.combine_items",
+        "Regrouper les éléments de barre des menus",
+    ),
+    ("menu.privacy", "Mode confidentialité"),
+    ("menu.click_through", "Widget épinglé traversable aux clics"),
+    ("menu.dock_icon", "Afficher l'icône dans le Dock"),
+    ("menu.monthly_report", "Générer le rapport du mois dernier"),
+    ("menu.notifications_off", "Notifications désactivées - Ouvrir les réglages..."),
+    ("menu.upcoming_resets", "Prochaines réinitialisations"),
+    ("menu.no_resets", "Aucune heure connue pour l'instant"),
+    ("menu.reconfigure", "Reconfigurer les fournisseurs..."),
+    ("menu.settings", "Réglages..."),
+    ("menu.quit", "Quitter"),
+    ("tooltip.app", "Widget d'utilisation"),
+    ("tooltip.provider_usage", "Utilisation de {0}"),
+    ("tooltip.extra", "Utilisation supplémentaire : {0} ({1})"),
+    ("tooltip.on", "activée"),
+    ("tooltip.off", "désactivée"),
+    ("tooltip.updated", "Mis à jour à {0}"),
+    ("tooltip.remaining", "Restant : {0}"),
+    ("tooltip.spent", "Dépensé : {0}"),
+    ("tooltip.local_models", "Chargés : {0}"),
+    ("tooltip.local_none", "Aucun modèle chargé"),
+    ("tooltip.local_requests", "Requêtes dans la dernière heure : {0}"),
+    ("tooltip.local_off", "Arrêté"),
+    ("preview.extra", "Utilisation supplémentaire"),
+    ("preview.credits", "Crédits restants"),
+    ("preview.budget", "Budget restant"),
+    ("preview.spent", "Dépensé"),
+    ("tooltip.used_of", "Utilisé : {0} sur {1}"),
+    ("usage.current_session", "Session en cours"),
+    ("usage.all_models", "Tous les modèles"),
+    ("usage.sonnet_only", "Sonnet uniquement"),
+    ("usage.opus_only", "Opus uniquement"),
+    ("usage.haiku_only", "Haiku uniquement"),
+    ("usage.monthly", "Mensuel"),
+    ("usage.session", "Session"),
+    ("usage.weekly", "Hebdomadaire"),
+    ("usage.day_window", "Fenêtre de {0} jours"),
+    ("usage.hour_window", "Fenêtre de {0} heures"),
+    ("usage.plan", "Forfait : {0}"),
+    ("usage.unlimited", "Illimité"),
+    ("usage.no_data", "Aucune donnée"),
+    ("usage.limit_reached", "LIMITE ATTEINTE - {0}"),
+    ("reset.in_hours", "Réinitialisation dans {0} h {1} min"),
+    ("reset.in_minutes", "Réinitialisation dans {0} min"),
+    ("reset.soon", "Réinitialisation imminente"),
+    ("notify.metric_session", "Session {0}"),
+    ("notify.metric_weekly", "{0} hebdomadaire"),
+    ("notify.metric_extra", "Utilisation supplémentaire {0}"),
+    ("notify.threshold", "{0} à {1} %"),
+    ("notify.delta_minutes", "(+{0} % en {1} min)"),
+    ("notify.delta_hours", "(+{0} % en {1} h)"),
+    ("notify.reminder", "Toujours {0} à {1} % (rappel {2})"),
+    (
+        "notify.idle_spike",
+        "{0} a augmenté de {1} % pendant votre absence",
+    ),
+    (
+        "notify.idle_spike_body",
+        "Maintenant à {0} % - quelque chose tourne peut-être sans surveillance.",
+    ),
+    ("notify.plan_changed", "Forfait {0} modifié"),
+    (
+        "notify.plan_changed_body",
+        "{0} -> {1}. Les limites peuvent désormais se comporter différemment.",
+    ),
+    ("notify.failover", "Pensez à passer à {0}"),
+    ("notify.failover_body", "{0} à {1} % - {2} à {3} %"),
+    ("notify.focus_one", "1 alerte pendant la concentration"),
+    ("notify.focus_many", "{0} alertes pendant la concentration"),
+    ("notify.stale", "Les données {0} sont peut-être bloquées"),
+    (
+        "notify.stale_body",
+        "Rien n'a changé en {0} heures d'utilisation alors que chaque actualisation a réussi. Essayez de reconfigurer les fournisseurs ou de vous reconnecter.",
+    ),
+    ("budget.ahead", "{0} {1} en avance sur le budget"),
+    (
+        "budget.ahead_body",
+        "À {0} % - pour tenir votre budget de {1} %, vous devriez être à {2} %.",
+    ),
+    ("work.finished", "« {0} » terminée"),
+    ("work.of_session", "{0} % de la session"),
+    ("work.of_weekly", "{0} % de l'hebdomadaire"),
+    (
+        "work.no_data",
+        "Aucune donnée d'utilisation n'était disponible pour cette session.",
+    ),
+    ("a11y.metric", "{0} : {1} pour cent utilisés, {2}."),
+    ("a11y.resets_in", "réinitialisation dans {0}"),
+    ("a11y.resets_soon", "réinitialisation imminente"),
+    ("a11y.reset_unknown", "heure de réinitialisation inconnue"),
+    ("a11y.day", "{0} jour"),
+    ("a11y.days", "{0} jours"),
+    ("a11y.hour", "{0} heure"),
+    ("a11y.hours", "{0} heures"),
+    ("a11y.minute", "{0} minute"),
+    ("a11y.minutes", "{0} minutes"),
+    ("a11y.separator", ", "),
+    ("a11y.extra", "Utilisation supplémentaire {0} : {1}, {2}."),
+    ("a11y.credits", "{0} : {1} de crédit restant."),
+    (
+        "a11y.failing",
+        "{0} : la dernière mise à jour a échoué, ces chiffres peuvent être obsolètes.",
+    ),
+    ("a11y.empty", "Pas encore de données d'utilisation."),
+    ("startup.title", "Widget d'utilisation actif"),
+    (
+        "startup.weekly",
+        "{0} {1} % de l'hebdomadaire - réinitialisation {2}",
+    ),
+    ("startup.weekly_no_reset", "{0} {1} % de l'hebdomadaire"),
+    ("startup.credits", "{0} {1} restants"),
+    ("startup.spent", "{0} {1} dépensés"),
+    ("startup.not_configured", "{0} non configuré"),
+    ("startup.failing", "{0} ne répond pas"),
+    ("startup.mon", "lundi"),
+    ("startup.tue", "mardi"),
+    ("startup.wed", "mercredi"),
+    ("startup.thu", "jeudi"),
+    ("startup.fri", "vendredi"),
+    ("startup.sat", "samedi"),
+    ("startup.sun", "dimanche"),
+    ("startup.today", "aujourd'hui"),
+    ("window.usage", "Utilisation"),
+    ("report.title", "Rapport d'utilisation pour {0}"),
+    ("report.empty", "Aucune utilisation n'a été enregistrée ce mois-ci."),
+    ("report.columns", "| Mesure | Pic | Date du pic | Consommé sur le mois |"),
+    ("report.extra", "Utilisation supplémentaire"),
+    ("report.points", "{0} points"),
+    ("report.no_limits", "Aucune limite n'a été atteinte."),
+    ("report.limits_hit", "Limites atteintes sur {0} jour(s) : {1}"),
+    ("report.hidden", "Les montants et forfaits sont masqués en mode confidentialité."),
+    ("report.api_title", "Coût équivalent API"),
+    ("report.api_intro", "Ce qu'auraient coûté les tokens du mois au tarif par token."),
+    ("report.api_line", "{0} : {1} pour {2} tokens"),
+];
+
+const ES: Table = &[
+    ("menu.show_widget", "Mostrar widget"),
+    ("menu.refresh_now", "Actualizar ahora"),
+    ("menu.pause", "Pausar supervisión"),
+    ("menu.refresh_every", "Actualizar cada"),
+    ("menu.minutes", "{0} min"),
+    ("menu.seconds", "{0} s"),
+    ("menu.custom", "Personalizado: {0}"),
+    ("menu.notify_at", "Notificar al"),
+    ("menu.off", "Desactivado"),
+    ("menu.start_login", "Abrir al iniciar sesión"),
+    (
+        "menu.separate_items",
+        "Separar elementos de la barra de menús",
+    ),
+    (
+        "menu.combine_items",
+        "Combinar elementos de la barra de menús",
+    ),
+    ("menu.privacy", "Modo privado"),
+    ("menu.click_through", "Clics a través del widget fijado"),
+    ("menu.dock_icon", "Mostrar icono en el Dock"),
+    ("menu.monthly_report", "Generar informe del mes pasado"),
+    ("menu.notifications_off", "Notificaciones desactivadas - Abrir ajustes..."),
+    ("menu.upcoming_resets", "Próximos restablecimientos"),
+    ("menu.no_resets", "Aún no hay horas de restablecimiento"),
+    ("menu.reconfigure", "Reconfigurar proveedores..."),
+    ("menu.settings", "Ajustes..."),
+    ("menu.quit", "Salir"),
+    ("tooltip.app", "Widget de uso"),
+    ("tooltip.provider_usage", "Uso de {0}"),
+    ("tooltip.extra", "Uso adicional: {0} ({1})"),
+    ("tooltip.on", "activado"),
+    ("tooltip.off", "desactivado"),
+    ("tooltip.updated", "Actualizado {0}"),
+    ("tooltip.remaining", "Restante: {0}"),
+    ("tooltip.spent", "Gastado: {0}"),
+    ("tooltip.local_models", "Cargados: {0}"),
+    ("tooltip.local_none", "Ningún modelo cargado"),
+    ("tooltip.local_requests", "Solicitudes en la última hora: {0}"),
+    ("tooltip.local_off", "No se está ejecutando"),
+    ("preview.extra", "Uso adicional"),
+    ("preview.credits", "Créditos restantes"),
+    ("preview.budget", "Presupuesto restante"),
+    ("preview.spent", "Gastado"),
+    ("tooltip.used_of", "Usado: {0} de {1}"),
+    ("usage.current_session", "Sesión actual"),
+    ("usage.all_models", "Todos los modelos"),
+    ("usage.sonnet_only", "Solo Sonnet"),
+    ("usage.opus_only", "Solo Opus"),
+    ("usage.haiku_only", "Solo Haiku"),
+    ("usage.monthly", "Mensual"),
+    ("usage.session", "Sesión"),
+    ("usage.weekly", "Semanal"),
+    ("usage.day_window", "Ventana de {0} días"),
+    ("usage.hour_window", "Ventana de {0} horas"),
+    ("usage.plan", "Plan: {0}"),
+    ("usage.unlimited", "Ilimitado"),
+    ("usage.no_data", "Sin datos"),
+    ("usage.limit_reached", "LÍMITE ALCANZADO - {0}"),
+    ("reset.in_hours", "Se restablece en {0} h {1} min"),
+    ("reset.in_minutes", "Se restablece en {0} min"),
+    ("reset.soon", "Se restablece pronto"),
+    ("notify.metric_session", "Sesión de {0}"),
+    ("notify.metric_weekly", "{0} semanal"),
+    ("notify.metric_extra", "Uso adicional de {0}"),
+    ("notify.threshold", "{0} al {1}%"),
+    ("notify.delta_minutes", "(+{0}% en los últimos {1} min)"),
+    ("notify.delta_hours", "(+{0}% en las últimas {1} h)"),
+    ("notify.reminder", "{0} sigue al {1}% (recordatorio {2})"),
+    (
+        "notify.idle_spike",
+        "{0} subió {1}% mientras no estabas",
+    ),
+    (
+        "notify.idle_spike_body",
+        "Ahora al {0}%: puede que algo se esté ejecutando sin supervisión.",
+    ),
+    ("notify.plan_changed", "Cambió el plan de {0}"),
+    (
+        "notify.plan_changed_body",
+        "{0} -> {1}. Los límites pueden comportarse de otra forma a partir de ahora.",
+    ),
+    ("notify.failover", "Considera cambiar a {0}"),
+    ("notify.failover_body", "{0} al {1}% - {2} al {3}%"),
+    ("notify.focus_one", "1 alerta durante el modo Concentración"),
+    (
+        "notify.focus_many",
+        "{0} alertas durante el modo Concentración",
+    ),
+    ("notify.stale", "Los datos de {0} pueden estar atascados"),
+    (
+        "notify.stale_body",
+        "No ha cambiado nada en {0} horas de uso aunque cada actualización funcionó. Prueba a reconfigurar los proveedores o a iniciar sesión de nuevo.",
+    ),
+    ("budget.ahead", "{0} {1} por encima del presupuesto"),
+    (
+        "budget.ahead_body",
+        "Al {0}%: para cumplir tu presupuesto del {1}% deberías estar al {2}%.",
+    ),
+    ("work.finished", "\"{0}\" terminada"),
+    ("work.of_session", "{0}% de la sesión"),
+    ("work.of_weekly", "{0}% del semanal"),
+    (
+        "work.no_data",
+        "No había datos de uso disponibles para esta sesión.",
+    ),
+    ("a11y.metric", "{0}: {1} por ciento usado, {2}."),
+    ("a11y.resets_in", "se restablece en {0}"),
+    ("a11y.resets_soon", "se restablece pronto"),
+    ("a11y.reset_unknown", "hora de restablecimiento desconocida"),
+    ("a11y.day", "{0} día"),
+    ("a11y.days", "{0} días"),
+    ("a11y.hour", "{0} hora"),
+    ("a11y.hours", "{0} horas"),
+    ("a11y.minute", "{0} minuto"),
+    ("a11y.minutes", "{0} minutos"),
+    ("a11y.separator", ", "),
+    ("a11y.extra", "Uso adicional de {0}: {1}, {2}."),
+    ("a11y.credits", "{0}: quedan {1} de crédito."),
+    (
+        "a11y.failing",
+        "{0}: la última actualización falló, así que estas cifras pueden estar desactualizadas.",
+    ),
+    ("a11y.empty", "Aún no hay datos de uso."),
+    ("startup.title", "Widget de uso en marcha"),
+    ("startup.weekly", "{0} {1}% del semanal - se restablece {2}"),
+    ("startup.weekly_no_reset", "{0} {1}% del semanal"),
+    ("startup.credits", "{0}: quedan {1}"),
+    ("startup.spent", "{0}: {1} gastados"),
+    ("startup.not_configured", "{0} no configurado"),
+    ("startup.failing", "{0} no responde"),
+    ("startup.mon", "el lunes"),
+    ("startup.tue", "el martes"),
+    ("startup.wed", "el miércoles"),
+    ("startup.thu", "el jueves"),
+    ("startup.fri", "el viernes"),
+    ("startup.sat", "el sábado"),
+    ("startup.sun", "el domingo"),
+    ("startup.today", "hoy"),
+    ("window.usage", "Uso"),
+    ("report.title", "Informe de uso de {0}"),
+    ("report.empty", "No se registró uso este mes."),
+    ("report.columns", "| Métrica | Pico | Fecha del pico | Consumido en el mes |"),
+    ("report.extra", "Uso adicional"),
+    ("report.points", "{0} puntos"),
+    ("report.no_limits", "No se alcanzó ningún límite."),
+    ("report.limits_hit", "Límites alcanzados en {0} día(s): {1}"),
+    ("report.hidden", "Los importes y planes se ocultan en el modo privacidad."),
+    ("report.api_title", "Coste equivalente en la API"),
+    ("report.api_intro", "Lo que habrían costado los tokens del mes con precios por token."),
+    ("report.api_line", "{0}: {1} por {2} tokens"),
+];
+
+const JA: Table = &[
+    ("menu.show_widget", "ウィジェットを表示"),
+    ("menu.refresh_now", "今すぐ更新"),
+    ("menu.pause", "監視を一時停止"),
+    ("menu.refresh_every", "更新間隔"),
+    ("menu.minutes", "{0}分"),
+    ("menu.seconds", "{0}秒"),
+    ("menu.custom", "カスタム: {0}"),
+    ("menu.notify_at", "通知する使用率"),
+    ("menu.off", "オフ"),
+    ("menu.start_login", "ログイン時に起動"),
+    ("menu.separate_items", "メニューバー項目を分ける"),
+    ("menu.combine_items", "メニューバー項目をまとめる"),
+    ("menu.privacy", "プライバシーモード"),
+    ("menu.click_through", "固定したウィジェットをクリック透過"),
+    ("menu.dock_icon", "Dockにアイコンを表示"),
+    ("menu.monthly_report", "先月のレポートを作成"),
+    ("menu.notifications_off", "通知がオフです - 設定を開く..."),
+    ("menu.upcoming_resets", "次のリセット"),
+    ("menu.no_resets", "リセット時刻はまだありません"),
+    ("menu.reconfigure", "プロバイダを再設定..."),
+    ("menu.settings", "設定..."),
+    ("menu.quit", "終了"),
+    ("tooltip.app", "使用量ウィジェット"),
+    ("tooltip.provider_usage", "{0}の使用量"),
+    ("tooltip.extra", "追加使用量: {0}({1})"),
+    ("tooltip.on", "オン"),
+    ("tooltip.off", "オフ"),
+    ("tooltip.updated", "{0}に更新"),
+    ("tooltip.remaining", "残り: {0}"),
+    ("tooltip.spent", "使用済み: {0}"),
+    ("tooltip.local_models", "読み込み済み: {0}"),
+    ("tooltip.local_none", "読み込まれたモデルはありません"),
+    ("tooltip.local_requests", "直近1時間のリクエスト: {0}"),
+    ("tooltip.local_off", "停止中"),
+    ("preview.extra", "追加使用量"),
+    ("preview.credits", "残りクレジット"),
+    ("preview.budget", "残り予算"),
+    ("preview.spent", "使用済み"),
+    ("tooltip.used_of", "使用済み: {0} / {1}"),
+    ("usage.current_session", "現在のセッション"),
+    ("usage.all_models", "すべてのモデル"),
+    ("usage.sonnet_only", "Sonnetのみ"),
+    ("usage.opus_only", "Opusのみ"),
+    ("usage.haiku_only", "Haikuのみ"),
+    ("usage.monthly", "毎月"),
+    ("usage.session", "セッション"),
+    ("usage.weekly", "週間"),
+    ("usage.day_window", "{0}日間の枠"),
+    ("usage.hour_window", "{0}時間の枠"),
+    ("usage.plan", "プラン: {0}"),
+    ("usage.unlimited", "無制限"),
+    ("usage.no_data", "データなし"),
+    ("usage.limit_reached", "上限に到達 - {0}"),
+    ("reset.in_hours", "{0}時間{1}分後にリセット"),
+    ("reset.in_minutes", "{0}分後にリセット"),
+    ("reset.soon", "まもなくリセット"),
+    ("notify.metric_session", "{0} セッション"),
+    ("notify.metric_weekly", "{0} 週間"),
+    ("notify.metric_extra", "{0} 追加使用量"),
+    ("notify.threshold", "{0}が{1}%に到達"),
+    ("notify.delta_minutes", "(直近{1}分で+{0}%)"),
+    ("notify.delta_hours", "(直近{1}時間で+{0}%)"),
+    ("notify.reminder", "{0}はまだ{1}%です(リマインダー{2})"),
+    ("notify.idle_spike", "離席中に{0}が{1}%増加しました"),
+    (
+        "notify.idle_spike_body",
+        "現在{0}%です。何かが無人で実行されている可能性があります。",
+    ),
+    ("notify.plan_changed", "{0}のプランが変更されました"),
+    (
+        "notify.plan_changed_body",
+        "{0} -> {1}。今後は上限の動作が変わる可能性があります。",
+    ),
+    ("notify.failover", "{0}への切り替えを検討してください"),
+    ("notify.failover_body", "{0}が{1}% - {2}は{3}%"),
+    ("notify.focus_one", "集中モード中の通知 1件"),
+    ("notify.focus_many", "集中モード中の通知 {0}件"),
+    ("notify.stale", "{0}のデータが更新されていない可能性があります"),
+    (
+        "notify.stale_body",
+        "更新はすべて成功していますが、{0}時間の使用中に値が変わっていません。プロバイダを再設定するか、再度サインインしてください。",
+    ),
+    ("budget.ahead", "{0}の{1}が予算を上回っています"),
+    (
+        "budget.ahead_body",
+        "現在{0}%です。{1}%の予算どおりなら今は{2}%のはずです。",
+    ),
+    ("work.finished", "「{0}」が終了しました"),
+    ("work.of_session", "セッションの{0}%"),
+    ("work.of_weekly", "週間の{0}%"),
+    (
+        "work.no_data",
+        "このセッションの使用データはありませんでした。",
+    ),
+    ("a11y.metric", "{0}: {1}パーセント使用、{2}。"),
+    ("a11y.resets_in", "{0}後にリセット"),
+    ("a11y.resets_soon", "まもなくリセット"),
+    ("a11y.reset_unknown", "リセット時刻は不明"),
+    ("a11y.day", "{0}日"),
+    ("a11y.days", "{0}日"),
+    ("a11y.hour", "{0}時間"),
+    ("a11y.hours", "{0}時間"),
+    ("a11y.minute", "{0}分"),
+    ("a11y.minutes", "{0}分"),
+    ("a11y.separator", ""),
+    ("a11y.extra", "{0} 追加使用量: {1}、{2}。"),
+    ("a11y.credits", "{0}: 残りクレジット {1}。"),
+    (
+        "a11y.failing",
+        "{0}: 前回の更新に失敗したため、数値が古い可能性があります。",
+    ),
+    ("a11y.empty", "使用データはまだありません。"),
+    ("startup.title", "使用量ウィジェットが起動しました"),
+    ("startup.weekly", "{0} 週間の{1}% - {2}にリセット"),
+    ("startup.weekly_no_reset", "{0} 週間の{1}%"),
+    ("startup.credits", "{0} 残り{1}"),
+    ("startup.spent", "{0} 使用済み{1}"),
+    ("startup.not_configured", "{0}は未設定"),
+    ("startup.failing", "{0}が応答しません"),
+    ("startup.mon", "月曜日"),
+    ("startup.tue", "火曜日"),
+    ("startup.wed", "水曜日"),
+    ("startup.thu", "木曜日"),
+    ("startup.fri", "金曜日"),
+    ("startup.sat", "土曜日"),
+    ("startup.sun", "日曜日"),
+    ("startup.today", "今日"),
+    ("window.usage", "使用状況"),
+    ("report.title", "{0} の使用状況レポート"),
+    ("report.empty", "今月の使用記録はありません。"),
+    ("report.columns", "| 指標 | ピーク | ピーク日 | 月間使用量 |"),
+    ("report.extra", "追加使用量"),
+    ("report.points", "{0} ポイント"),
+    ("report.no_limits", "上限に達した日はありません。"),
+    ("report.limits_hit", "上限に達した日数 {0} 日: {1}"),
+    ("report.hidden", "プライバシーモード中は金額とプランを表示しません。"),
+    ("report.api_title", "API 換算コスト"),
+    ("report.api_intro", "今月のトークンを従量課金で使った場合の費用です。"),
+    ("report.api_line", "{0}: {2} トークンで {1}"),
+];
+
+fn table(lang: Language) -> Table {
+    match lang {
+        Language::En => EN,
+        Language::De => DE,
+        Language::Fr => FR,
+        Language::Es => ES,
+        Language::Ja => JA,
+    }
+}
+
+fn lookup(table: Table, key: &str) -> Option<&'static str> {
+    table.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+/// Language from a locale identifier such as "de-DE" or "fr_FR.UTF-8".
+fn parse_locale(locale: &str) -> Option<Language> {
+    match locale.get(..2)?.to_ascii_lowercase().as_str() {
+        "en" => Some(Language::En),
+        "de" => Some(Language::De),
+        "fr" => Some(Language::Fr),
+        "es" => Some(Language::Es),
+        "ja" => Some(Language::Ja),
+        _ => None,
+    }
+}
+
+/// The first supported language in the macOS preferred languages, falling
+/// back to `LANG`, then English.
+pub fn system_language() -> Language {
+    let preferred = Command::new("defaults")
+        .args(["read", "-g", "AppleLanguages"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .map(|out| String::from_utf8_lossy(&out.stdout).into_owned())
+        .unwrap_or_default();
+    preferred
+        .lines()
+        .map(|l| l.trim().trim_matches(|c| c == '"' || c == ',' || c == ' '))
+        .find_map(parse_locale)
+        .or_else(|| std::env::var("LANG").ok().as_deref().and_then(parse_locale))
+        .unwrap_or(Language::En)
+}
+
+/// Switches to `language`, or the system language when None.
+pub fn set_language(language: Option<Language>) {
+    *CURRENT.write().unwrap() = language.unwrap_or_else(system_language);
+}
+
+pub fn current() -> Language {
+    *CURRENT.read().unwrap()
+}
+
+/// The string for `key` in the current language. Unknown keys come back as
+/// the key itself, so a typo shows up rather than an empty label.
+pub fn t(key: &'static str) -> &'static str {
+    lookup(table(current()), key)
+        .or_else(|| lookup(EN, key))
+        .unwrap_or(key)
+}
+
+/// `t` with `{0}`, `{1}`, ... replaced by `args`, in a single pass so an
+/// argument that itself contains `{1}` is left as it is.
+pub fn tf(key: &'static str, args: &[&dyn std::fmt::Display]) -> String {
+    let mut rest = t(key);
+    let mut out = String::with_capacity(rest.len());
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let arg = after.find('}').and_then(|close| {
+            let i: usize = after[..close].parse().ok()?;
+            Some((close, args.get(i)?))
+        });
+        match arg {
+            Some((close, arg)) => {
+                out.push_str(&arg.to_string());
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Decimal and grouping separators of `lang`, and whether the currency goes
+/// after the amount.
+fn number_style(lang: Language) -> (char, &'static str, bool) {
+    match lang {
+        Language::En | Language::Ja => ('.', ",", false),
+        Language::De | Language::Es => (',', ".", true),
+        Language::Fr => (',', "\u{202F}", true),
+    }
+}
+
+/// `value` with `decimals` places and the current language's separators,
+/// e.g. "1,234.50" or "1.234,50".
+pub fn number(value: f64, decimals: usize) -> String {
+    let (decimal, group, _) = number_style(current());
+    let plain = format!("{:.*}", decimals, value.abs());
+    let (int, frac) = plain.split_once('.').unwrap_or((&plain, ""));
+    let mut grouped = String::new();
+    for (i, digit) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i) % 3 == 0 {
+            grouped.push_str(group);
+        }
+        grouped.push(digit);
+    }
+    let sign = if value < 0.0 && plain.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        "-"
+    } else {
+        ""
+    };
+    if frac.is_empty() {
+        format!("{}{}", sign, grouped)
+    } else {
+        format!("{}{}{}{}", sign, grouped, decimal, frac)
+    }
+}
+
+/// Adds the currency to already formatted `amount` as the user chose to
+/// display it. Amounts from the APIs are always US dollars.
+pub fn currency(amount: &str) -> String {
+    let (_, _, after) = number_style(current());
+    let marker = match *CURRENCY.read().unwrap() {
+        CurrencyDisplay::Symbol => "$",
+        CurrencyDisplay::Code => "USD",
+        CurrencyDisplay::Plain => return amount.to_string(),
+    };
+    match (after, marker) {
+        (true, _) => format!("{} {}", amount, marker),
+        (false, "$") => format!("{}{}", marker, amount),
+        (false, _) => format!("{} {}", marker, amount),
+    }
+}
+
+/// A dollar amount with cents, e.g. "$1,234.50" or "1.234,50 USD".
+pub fn money(amount: f64) -> String {
+    currency(&number(amount, 2))
+}
+
+pub fn set_currency_display(display: CurrencyDisplay) {
+    *CURRENCY.write().unwrap() = display;
+}
@@ -0,0 +1,34 @@
+//! Reads how long the user has been away from the keyboard and mouse.
+//!
+//! macOS exposes the time since the last HID event as `HIDIdleTime` (in
+//! nanoseconds) on the `IOHIDSystem` registry entry, which we read via `ioreg`.
+
+use std::process::Command;
+
+/// Seconds since the last keyboard/mouse input, or `None` if it can't be read.
+pub fn idle_seconds() -> Option<u64> {
+    let out = Command::new("ioreg")
+        .args(["-c", "IOHIDSystem", "-d", "4"])
+        .output()
+        .ok()?;
+
+    if !out.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    stdout
+        .lines()
+        .find(|line| line.contains("\"HIDIdleTime\""))
+        .and_then(|line| line.split('=').nth(1))
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(|nanos| nanos / 1_000_000_000)
+}
+
+/// Whether the user counts as away given the configured idle cutoff.
+pub fn is_idle(idle_secs: Option<u64>, idle_after_secs: u64) -> bool {
+    match idle_secs {
+        Some(secs) => idle_after_secs > 0 && secs >= idle_after_secs,
+        None => false,
+    }
+}
@@ -0,0 +1,234 @@
+//! Imports daily token totals exported by other usage trackers, so people who
+//! tracked usage before installing the widget keep their history.
+//!
+//! Understood formats:
+//!
+//! - ccusage JSON (`ccusage daily --json`, also the Codex flavor), either the
+//!   `{ "daily": [...] }` object or a bare array of days
+//! - CSV with a header row; columns are matched by name (`date`, `input`,
+//!   `output`, `cache_creation`, `cache_read`, `total`, `cost`, in any of the
+//!   usual spellings) and rows of the same day are added up
+//!
+//! Days are stored per provider and source, so importing the same export
+//! again replaces those days instead of counting them twice.
+//!
+//! Imported days count toward API-equivalent costs in monthly reports, on the
+//! dates the local logs don't cover. They carry tokens only, not percentages,
+//! so the usage history and its charts still start at install.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::history::{DailyTokens, HistoryState};
+use crate::provider::Provider;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportSummary {
+    /// "ccusage" or "csv"
+    pub source: String,
+    pub days: usize,
+    /// First and last imported date, YYYY-MM-DD
+    pub first_date: Option<String>,
+    pub last_date: Option<String>,
+    pub total_tokens: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CcusageDay {
+    date: String,
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    cache_creation_tokens: u64,
+    #[serde(default, alias = "cachedInputTokens")]
+    cache_read_tokens: u64,
+    #[serde(default)]
+    total_tokens: u64,
+    #[serde(default, alias = "costUSD", alias = "totalCostUSD")]
+    total_cost: Option<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CcusageExport {
+    Wrapped {
+        #[serde(alias = "data")]
+        daily: Vec<CcusageDay>,
+    },
+    Bare(Vec<CcusageDay>),
+}
+
+/// "2025-06-01", or "Jun 1, 2025" as some exports print it.
+fn parse_date(s: &str) -> Option<String> {
+    let s = s.trim();
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(s, "%b %d, %Y"))
+        .ok()
+        .map(|d| d.format("%Y-%m-%d").to_string())
+}
+
+/// Adds `day` to the running total of its date.
+fn add_day(days: &mut BTreeMap<String, DailyTokens>, day: DailyTokens) {
+    let slot = days.entry(day.date.clone()).or_insert_with(|| DailyTokens {
+        date: day.date.clone(),
+        ..Default::default()
+    });
+    slot.input_tokens += day.input_tokens;
+    slot.output_tokens += day.output_tokens;
+    slot.cache_creation_tokens += day.cache_creation_tokens;
+    slot.cache_read_tokens += day.cache_read_tokens;
+    slot.total_tokens += day.total_tokens;
+    slot.cost_usd = match (slot.cost_usd, day.cost_usd) {
+        (Some(a), Some(b)) => Some(a + b),
+        (a, b) => a.or(b),
+    };
+}
+
+fn from_ccusage(text: &str) -> Result<Vec<DailyTokens>, String> {
+    let export: CcusageExport =
+        serde_json::from_str(text).map_err(|e| format!("Not a ccusage export: {}", e))?;
+    let days = match export {
+        CcusageExport::Wrapped { daily } => daily,
+        CcusageExport::Bare(days) => days,
+    };
+    let mut out = BTreeMap::new();
+    for d in days {
+        let date = parse_date(&d.date).ok_or_else(|| format!("Unknown date \"{}\"", d.date))?;
+        add_day(
+            &mut out,
+            DailyTokens {
+                date,
+                input_tokens: d.input_tokens,
+                output_tokens: d.output_tokens,
+                cache_creation_tokens: d.cache_creation_tokens,
+                cache_read_tokens: d.cache_read_tokens,
+                total_tokens: d.total_tokens,
+                cost_usd: d.total_cost,
+                ..Default::default()
+            },
+        );
+    }
+    Ok(out.into_values().collect())
+}
+
+/// Splits one CSV line, honoring double-quoted fields.
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Column a header names, ignoring case and punctuation.
+fn column(header: &str) -> Option<&'static str> {
+    let key: String = header
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    Some(match key.as_str() {
+        "date" | "day" => "date",
+        "input" | "inputtokens" => "input",
+        "output" | "outputtokens" => "output",
+        "cachecreate" | "cachecreation" | "cachecreationtokens" | "cachewrite" => "cache_creation",
+        "cacheread" | "cachereadtokens" | "cachedinput" | "cachedinputtokens" => "cache_read",
+        "total" | "totaltokens" | "tokens" => "total",
+        "cost" | "costusd" | "totalcost" | "totalcostusd" => "cost",
+        _ => return None,
+    })
+}
+
+/// Number from a cell, tolerating thousands separators and a dollar sign.
+fn number(cell: &str) -> f64 {
+    cell.trim()
+        .trim_start_matches('$')
+        .replace(',', "")
+        .parse()
+        .unwrap_or(0.0)
+}
+
+fn from_csv(text: &str) -> Result<Vec<DailyTokens>, String> {
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+    let header = lines.next().ok_or("The CSV file is empty")?;
+    let columns: Vec<Option<&str>> = split_csv(header).iter().map(|h| column(h)).collect();
+    if !columns.contains(&Some("date")) {
+        return Err("The CSV file has no date column".into());
+    }
+    let mut out = BTreeMap::new();
+    for line in lines {
+        let mut day = DailyTokens::default();
+        for (cell, col) in split_csv(line).iter().zip(&columns) {
+            match col {
+                Some("date") => {
+                    day.date =
+                        parse_date(cell).ok_or_else(|| format!("Unknown date \"{}\"", cell))?;
+                }
+                Some("input") => day.input_tokens = number(cell) as u64,
+                Some("output") => day.output_tokens = number(cell) as u64,
+                Some("cache_creation") => day.cache_creation_tokens = number(cell) as u64,
+                Some("cache_read") => day.cache_read_tokens = number(cell) as u64,
+                Some("total") => day.total_tokens = number(cell) as u64,
+                Some("cost") => day.cost_usd = Some(number(cell)),
+                _ => {}
+            }
+        }
+        // A totals row at the bottom has no date
+        if !day.date.is_empty() {
+            add_day(&mut out, day);
+        }
+    }
+    Ok(out.into_values().collect())
+}
+
+/// Reads an export file and stores its days under `provider`.
+pub fn import_file(
+    history: &HistoryState,
+    path: &Path,
+    provider: Provider,
+) -> Result<ImportSummary, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let json = matches!(text.trim_start().chars().next(), Some('{' | '['));
+    let (source, mut days) = if json {
+        ("ccusage", from_ccusage(&text)?)
+    } else {
+        ("csv", from_csv(&text)?)
+    };
+    if days.is_empty() {
+        return Err("The file contains no days of usage".into());
+    }
+    for d in &mut days {
+        d.provider = provider.name().into();
+        d.source = source.into();
+        if d.total_tokens == 0 {
+            d.total_tokens =
+                d.input_tokens + d.output_tokens + d.cache_creation_tokens + d.cache_read_tokens;
+        }
+    }
+    history.import_daily(&days)?;
+    Ok(ImportSummary {
+        source: source.into(),
+        days: days.len(),
+        first_date: days.first().map(|d| d.date.clone()),
+        last_date: days.last().map(|d| d.date.clone()),
+        total_tokens: days.iter().map(|d| d.total_tokens).sum(),
+    })
+}
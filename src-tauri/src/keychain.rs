@@ -0,0 +1,74 @@
+//! Stores secrets for alert channels (SMTP password, bot tokens, ...) in the
+//! macOS Keychain, one generic password per account name.
+//!
+//! Secrets are written through Security.framework rather than `security
+//! add-generic-password -w`, which would expose them in the process list.
+
+use std::process::Command;
+
+use crate::secret::Secret;
+
+const SERVICE: &str = "com.israelmirsky.claude-codex-usage.alerts";
+
+pub fn read_secret(account: &str) -> Result<Option<Secret>, String> {
+    let out = Command::new("security")
+        .args(["find-generic-password", "-a", account, "-s", SERVICE, "-w"])
+        .output()
+        .map_err(|e| format!("Failed to query macOS Keychain: {}", e))?;
+
+    if out.status.success() {
+        let secret = Secret::from_bytes(out.stdout);
+        Ok((!secret.is_empty()).then_some(secret))
+    } else {
+        let stderr = String::from_utf8_lossy(&out.stderr).to_lowercase();
+        if stderr.contains("could not be found") {
+            Ok(None)
+        } else {
+            Err(format!(
+                "Failed to read {} from Keychain: {}",
+                account,
+                String::from_utf8_lossy(&out.stderr).trim()
+            ))
+        }
+    }
+}
+
+pub fn set_secret(account: &str, secret: &str) -> Result<(), String> {
+    let secret = secret.trim();
+    if secret.is_empty() {
+        return Err("Secret cannot be empty".into());
+    }
+
+    write_password(account, secret.as_bytes())
+        .map_err(|e| format!("Failed to save {} to Keychain: {}", account, e))
+}
+
+/// Adds the item, or updates it when it already exists.
+#[cfg(target_os = "macos")]
+fn write_password(account: &str, secret: &[u8]) -> Result<(), String> {
+    security_framework::passwords::set_generic_password(SERVICE, account, secret)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn write_password(_account: &str, _secret: &[u8]) -> Result<(), String> {
+    Err("the Keychain is only available on macOS".into())
+}
+
+pub fn clear_secret(account: &str) -> Result<(), String> {
+    let out = Command::new("security")
+        .args(["delete-generic-password", "-a", account, "-s", SERVICE])
+        .output()
+        .map_err(|e| format!("Failed to delete from macOS Keychain: {}", e))?;
+
+    let stderr = String::from_utf8_lossy(&out.stderr).to_lowercase();
+    if out.status.success() || stderr.contains("could not be found") {
+        Ok(())
+    } else {
+        Err(format!(
+            "Failed to clear {} from Keychain: {}",
+            account,
+            String::from_utf8_lossy(&out.stderr).trim()
+        ))
+    }
+}
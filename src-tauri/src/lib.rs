@@ -4,90 +4,190 @@
 //! plus OpenRouter credit balance, manages a system tray icon with live usage stats,
 //! and serves data to the React frontend via Tauri IPC commands.
 
+mod accessibility;
+mod advisor;
+mod alerts;
+mod backup;
+mod bookmark;
+mod budgets;
+mod calendar_sync;
+mod calibration;
+mod claude_hooks;
+mod capacity;
+mod claude_logs;
 mod codex_fetcher;
+mod comparison;
+mod codex_logs;
 mod cookie_reader;
+mod custom_provider;
+mod discord;
+mod email;
+mod failover;
+mod fetch_stats;
+mod focus;
+mod history;
+mod http_cache;
+mod http_client;
+mod i18n;
+mod idle;
+mod importer;
+mod keychain;
+mod litellm_fetcher;
+mod notification_permission;
 mod notifications;
+mod ollama_fetcher;
+mod onboarding;
+mod pacing;
 mod openrouter_fetcher;
 mod openrouter_keychain;
+mod presentation;
+mod preview;
+mod pricing;
+mod privacy;
+mod provider;
+mod report;
+mod resets;
+mod scheduler;
+mod secret;
 mod settings;
+mod shell_hook;
+mod smoothing;
+mod startup;
+mod sync;
+mod team;
+mod telegram;
+mod tray;
 mod usage_fetcher;
+mod watchdog;
+mod widget_data;
+mod widget_window;
+mod work_sessions;
 
 use codex_fetcher::CodexState;
+use history::HistoryState;
+use http_cache::HttpCacheState;
 use notifications::NotificationState;
 use openrouter_fetcher::{OpenRouterCreditsData, OpenRouterState};
 use openrouter_keychain::OpenRouterKeyStatus;
+use scheduler::SchedulerState;
 use settings::SettingsState;
-use tauri::{
-    menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
-    tray::TrayIconBuilder,
-    Emitter, Manager,
-};
+use tauri::{Emitter, Manager};
 use tauri_plugin_autostart::MacosLauncher;
+use tauri_plugin_dialog::DialogExt;
 use usage_fetcher::{UsageData, UsageState};
 
 // --- Tauri commands ---
 
 #[tauri::command]
-async fn fetch_claude_usage(
+async fn fetch_claude_usage(app: tauri::AppHandle) -> Result<UsageData, String> {
+    match scheduler::refresh_provider(&app, provider::Provider::Claude).await? {
+        provider::ProviderData::Usage(data) => Ok(privacy::usage(privacy::is_on(&app), *data)),
+        _ => Err("Unexpected Claude response".into()),
+    }
+}
+
+#[tauri::command]
+fn get_cached_claude(
     app: tauri::AppHandle,
     state: tauri::State<'_, UsageState>,
-    settings: tauri::State<'_, SettingsState>,
-    notif_state: tauri::State<'_, NotificationState>,
-) -> Result<UsageData, String> {
-    let cookies = cookie_reader::read_claude_cookies().map_err(|e| e.to_string())?;
-    let data = usage_fetcher::fetch_usage(&cookies, &state.client).await?;
-    *state.last_data.lock().unwrap() = Some(data.clone());
+) -> Option<UsageData> {
+    let data = state.last_data.lock().unwrap().clone();
+    data.map(|d| privacy::usage(privacy::is_on(&app), d))
+}
 
-    let s = settings.get();
-    notifications::check_and_notify(
-        &app, "Claude", &data, s.notify_threshold, s.notifications_enabled, &notif_state,
-    );
+#[tauri::command]
+async fn fetch_codex_usage(app: tauri::AppHandle) -> Result<UsageData, String> {
+    match scheduler::refresh_provider(&app, provider::Provider::Codex).await? {
+        provider::ProviderData::Usage(data) => Ok(privacy::usage(privacy::is_on(&app), *data)),
+        _ => Err("Unexpected Codex response".into()),
+    }
+}
 
-    Ok(data)
+#[tauri::command]
+fn get_cached_codex(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, CodexState>,
+) -> Option<UsageData> {
+    let data = state.last_data.lock().unwrap().clone();
+    data.map(|d| privacy::usage(privacy::is_on(&app), d))
 }
 
 #[tauri::command]
-fn get_cached_claude(state: tauri::State<'_, UsageState>) -> Option<UsageData> {
-    state.last_data.lock().unwrap().clone()
+async fn fetch_openrouter_credits(app: tauri::AppHandle) -> Result<OpenRouterCreditsData, String> {
+    match scheduler::refresh_provider(&app, provider::Provider::OpenRouter).await? {
+        provider::ProviderData::Credits(data) => Ok(privacy::credits(privacy::is_on(&app), data)),
+        _ => Err("Unexpected OpenRouter response".into()),
+    }
 }
 
 #[tauri::command]
-async fn fetch_codex_usage(
+async fn refresh_provider(
     app: tauri::AppHandle,
-    usage_state: tauri::State<'_, UsageState>,
-    codex_state: tauri::State<'_, CodexState>,
-    settings: tauri::State<'_, SettingsState>,
-    notif_state: tauri::State<'_, NotificationState>,
-) -> Result<UsageData, String> {
-    let data = codex_fetcher::fetch_codex_usage(&usage_state.client).await?;
-    *codex_state.last_data.lock().unwrap() = Some(data.clone());
+    provider: provider::Provider,
+) -> Result<provider::ProviderData, String> {
+    let data = scheduler::refresh_provider(&app, provider).await?;
+    Ok(privacy::provider_data(privacy::is_on(&app), data))
+}
 
-    let s = settings.get();
-    notifications::check_and_notify(
-        &app, "Codex", &data, s.notify_threshold, s.notifications_enabled, &notif_state,
-    );
+#[tauri::command]
+async fn refresh_all(app: tauri::AppHandle) {
+    scheduler::refresh_all(&app).await
+}
 
-    Ok(data)
+#[tauri::command]
+fn set_paused(app: tauri::AppHandle, paused: bool) -> scheduler::PauseStatus {
+    scheduler::set_paused(&app, paused)
 }
 
 #[tauri::command]
-fn get_cached_codex(state: tauri::State<'_, CodexState>) -> Option<UsageData> {
-    state.last_data.lock().unwrap().clone()
+fn get_pause_status(state: tauri::State<'_, SchedulerState>) -> scheduler::PauseStatus {
+    state.pause_status()
 }
 
+/// "Try again now": lifts `provider`'s retry backoff and fetches it right away.
 #[tauri::command]
-async fn fetch_openrouter_credits(
-    state: tauri::State<'_, OpenRouterState>,
-    usage_state: tauri::State<'_, UsageState>,
-) -> Result<OpenRouterCreditsData, String> {
-    let data = openrouter_fetcher::fetch_openrouter_credits(&usage_state.client).await?;
-    *state.last_data.lock().unwrap() = Some(data.clone());
-    Ok(data)
+async fn clear_backoff(
+    app: tauri::AppHandle,
+    provider: provider::Provider,
+) -> Result<provider::ProviderData, String> {
+    app.state::<SchedulerState>().clear_backoff(provider);
+    let data = scheduler::refresh_provider(&app, provider).await?;
+    Ok(privacy::provider_data(privacy::is_on(&app), data))
 }
 
 #[tauri::command]
-fn get_cached_openrouter(state: tauri::State<'_, OpenRouterState>) -> Option<OpenRouterCreditsData> {
-    state.last_data.lock().unwrap().clone()
+fn get_provider_health(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SchedulerState>,
+) -> Vec<scheduler::ProviderHealth> {
+    let on = privacy::is_on(&app);
+    let mut list = state.provider_health();
+    for h in list.iter_mut() {
+        h.last_error = h.last_error.take().map(|e| privacy::error(on, e));
+    }
+    list
+}
+
+/// Latency percentiles and success rate per provider over recent fetches.
+#[tauri::command]
+fn get_fetch_stats(
+    state: tauri::State<'_, fetch_stats::FetchStatsState>,
+) -> Vec<fetch_stats::FetchStats> {
+    state.stats()
+}
+
+#[tauri::command]
+fn get_diagnostics_report(app: tauri::AppHandle) -> String {
+    fetch_stats::diagnostics_report(&app)
+}
+
+#[tauri::command]
+fn get_cached_openrouter(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, OpenRouterState>,
+) -> Option<OpenRouterCreditsData> {
+    let data = state.last_data.lock().unwrap().clone();
+    data.map(|d| privacy::credits(privacy::is_on(&app), d))
 }
 
 #[tauri::command]
@@ -96,54 +196,529 @@ fn get_openrouter_key_status() -> Result<OpenRouterKeyStatus, String> {
 }
 
 #[tauri::command]
-fn set_openrouter_key(api_key: String) -> Result<(), String> {
-    openrouter_keychain::set_openrouter_api_key(&api_key)
+fn set_openrouter_key(app: tauri::AppHandle, api_key: String) -> Result<(), String> {
+    openrouter_keychain::set_openrouter_api_key(&api_key)?;
+    onboarding::detect(&app);
+    Ok(())
 }
 
 #[tauri::command]
-fn clear_openrouter_key(state: tauri::State<'_, OpenRouterState>) -> Result<(), String> {
+fn clear_openrouter_key(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, OpenRouterState>,
+) -> Result<(), String> {
     openrouter_keychain::clear_openrouter_api_key()?;
     *state.last_data.lock().unwrap() = None;
+    tray::update_from_cache(&app);
+    onboarding::detect(&app);
     Ok(())
 }
 
 #[tauri::command]
-fn update_tray_text(
+async fn fetch_litellm_budget(
+    app: tauri::AppHandle,
+) -> Result<litellm_fetcher::GatewayBudgetData, String> {
+    match scheduler::refresh_provider(&app, provider::Provider::LiteLlm).await? {
+        provider::ProviderData::Gateway(data) => Ok(privacy::gateway(privacy::is_on(&app), data)),
+        _ => Err("Unexpected LiteLLM response".into()),
+    }
+}
+
+#[tauri::command]
+fn get_cached_litellm(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, litellm_fetcher::LiteLlmState>,
+) -> Option<litellm_fetcher::GatewayBudgetData> {
+    let data = state.last_data.lock().unwrap().clone();
+    data.map(|d| privacy::gateway(privacy::is_on(&app), d))
+}
+
+/// Points the LiteLLM provider at a gateway. The key is kept when `api_key`
+/// is `None`, so only the URL can be changed.
+#[tauri::command]
+fn set_litellm_gateway(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    base_url: String,
+    api_key: Option<String>,
+) -> Result<settings::Settings, String> {
+    let base_url = http_client::normalize_base_url(&base_url)?;
+    if let Some(key) = api_key {
+        keychain::set_secret(litellm_fetcher::KEY_ACCOUNT, &key)?;
+    }
+    let settings = state.update(|s| s.litellm_base_url = Some(base_url))?;
+    onboarding::detect(&app);
+    Ok(settings)
+}
+
+#[tauri::command]
+fn clear_litellm_gateway(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    cache: tauri::State<'_, litellm_fetcher::LiteLlmState>,
+) -> Result<settings::Settings, String> {
+    keychain::clear_secret(litellm_fetcher::KEY_ACCOUNT)?;
+    let settings = state.update(|s| s.litellm_base_url = None)?;
+    *cache.last_data.lock().unwrap() = None;
+    tray::update_from_cache(&app);
+    onboarding::detect(&app);
+    Ok(settings)
+}
+
+/// Ready-made configs for `add_custom_provider`.
+#[tauri::command]
+fn get_custom_provider_templates() -> Vec<custom_provider::CustomProvider> {
+    custom_provider::templates()
+}
+
+/// Adds a bearer-token provider, or replaces the one with the same id. The
+/// key is kept when `api_key` is `None`.
+#[tauri::command]
+fn add_custom_provider(
+    state: tauri::State<'_, SettingsState>,
+    provider: custom_provider::CustomProvider,
+    api_key: Option<String>,
+) -> Result<settings::Settings, String> {
+    provider.validate()?;
+    if let Some(key) = api_key {
+        keychain::set_secret(&custom_provider::key_account(&provider.id), &key)?;
+    }
+    state.update(|s| {
+        s.custom_providers.retain(|p| p.id != provider.id);
+        s.custom_providers.push(provider);
+    })
+}
+
+#[tauri::command]
+fn remove_custom_provider(
+    state: tauri::State<'_, SettingsState>,
+    cache: tauri::State<'_, custom_provider::CustomProviderState>,
+    id: String,
+) -> Result<settings::Settings, String> {
+    keychain::clear_secret(&custom_provider::key_account(&id))?;
+    cache.last_data.lock().unwrap().remove(&id);
+    state.update(|s| s.custom_providers.retain(|p| p.id != id))
+}
+
+/// Fetches one custom provider now. Custom providers are manual-only: the
+/// scheduler never fetches them, so this is the only way they refresh.
+#[tauri::command]
+async fn fetch_custom_provider(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<custom_provider::CustomProviderData, String> {
+    let provider = app
+        .state::<SettingsState>()
+        .get()
+        .custom_providers
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| format!("No custom provider \"{}\"", id))?;
+    let client = app.state::<UsageState>().client();
+    let data = custom_provider::fetch(&client, &provider).await?;
+    app.state::<custom_provider::CustomProviderState>()
+        .last_data
+        .lock()
+        .unwrap()
+        .insert(id, data.clone());
+    Ok(privacy::custom(privacy::is_on(&app), data))
+}
+
+#[tauri::command]
+fn get_cached_custom_providers(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, custom_provider::CustomProviderState>,
+) -> Vec<custom_provider::CustomProviderData> {
+    let on = privacy::is_on(&app);
+    let mut list: Vec<_> = state.last_data.lock().unwrap().values().cloned().collect();
+    list.sort_by(|a, b| a.id.cmp(&b.id));
+    list.into_iter().map(|d| privacy::custom(on, d)).collect()
+}
+
+#[tauri::command]
+async fn fetch_ollama_activity(
+    app: tauri::AppHandle,
+) -> Result<ollama_fetcher::LocalActivityData, String> {
+    match scheduler::refresh_provider(&app, provider::Provider::Ollama).await? {
+        provider::ProviderData::Local(data) => Ok(data),
+        _ => Err("Unexpected Ollama response".into()),
+    }
+}
+
+#[tauri::command]
+fn get_cached_ollama(
+    state: tauri::State<'_, ollama_fetcher::OllamaState>,
+) -> Option<ollama_fetcher::LocalActivityData> {
+    state.last_data.lock().unwrap().clone()
+}
+
+/// Turns the local Ollama monitor on or off; `base_url` of `None` uses the
+/// default localhost port.
+#[tauri::command]
+fn set_ollama_monitor(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    cache: tauri::State<'_, ollama_fetcher::OllamaState>,
+    enabled: bool,
+    base_url: Option<String>,
+) -> Result<settings::Settings, String> {
+    let base_url = base_url
+        .filter(|u| !u.trim().is_empty())
+        .map(|u| http_client::normalize_base_url(&u))
+        .transpose()?;
+    let settings = state.update(|s| {
+        s.ollama_enabled = enabled;
+        s.ollama_base_url = base_url;
+    })?;
+    if !enabled {
+        *cache.last_data.lock().unwrap() = None;
+        tray::update_from_cache(&app);
+    }
+    onboarding::detect(&app);
+    Ok(settings)
+}
+
+#[tauri::command]
+async fn toggle_pin(
     app: tauri::AppHandle,
-    claude_session: i32,
-    claude_weekly: i32,
-    codex_session: i32,
-    codex_weekly: i32,
-    openrouter_remaining: f64,
+    window: tauri::WebviewWindow,
+    pinned: bool,
 ) -> Result<(), String> {
-    if let Some(tray) = app.tray_by_id("main") {
-        let mut parts = Vec::new();
-        if claude_session >= 0 && claude_weekly >= 0 {
-            parts.push(format!("C:{}/{}%", claude_session, claude_weekly));
+    widget_window::set_pinned(&app, &window, pinned)
+}
+
+#[tauri::command]
+fn set_widget_opacity(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    opacity: f64,
+) -> Result<settings::Settings, String> {
+    let opacity = opacity.clamp(widget_window::MIN_OPACITY, 1.0);
+    let settings = state.update(|s| s.pinned_opacity = opacity)?;
+    widget_window::apply_hud(&app)?;
+    Ok(settings)
+}
+
+/// Lets clicks pass through the pinned widget; also in the tray menu.
+#[tauri::command]
+fn set_click_through(app: tauri::AppHandle, enabled: bool) -> Result<settings::Settings, String> {
+    tray::set_click_through(&app, enabled)
+}
+
+/// Shows or hides the Dock icon; also in the tray menu.
+#[tauri::command]
+fn set_dock_icon(app: tauri::AppHandle, visible: bool) -> Result<settings::Settings, String> {
+    tray::set_dock_icon(&app, visible)
+}
+
+/// Keeps the floating widget on every Space and over fullscreen apps.
+#[tauri::command]
+fn set_all_spaces(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<settings::Settings, String> {
+    let settings = state.update(|s| s.all_spaces = enabled)?;
+    widget_window::apply_spaces(&app)?;
+    Ok(settings)
+}
+
+/// Labels dollar amounts with a symbol, a currency code or nothing.
+#[tauri::command]
+fn set_currency_display(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    display: settings::CurrencyDisplay,
+) -> Result<settings::Settings, String> {
+    let settings = state.update(|s| s.currency_display = display)?;
+    i18n::set_currency_display(display);
+    tray::update_tooltips(&app);
+    Ok(settings)
+}
+
+#[tauri::command]
+fn set_startup_summary(
+    state: tauri::State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<settings::Settings, String> {
+    state.update(|s| s.startup_summary = enabled)
+}
+
+/// Whether the widget starts hidden, and when it opens on its own at launch.
+#[tauri::command]
+fn set_launch_behavior(
+    state: tauri::State<'_, SettingsState>,
+    launch_minimized: bool,
+    show_on_first_data: bool,
+    show_above_percent: u32,
+) -> Result<settings::Settings, String> {
+    if show_above_percent > 100 {
+        return Err("The level must be between 0 and 100 percent".into());
+    }
+    state.update(|s| {
+        s.launch_minimized = launch_minimized;
+        s.show_on_first_data = show_on_first_data;
+        s.show_above_percent = show_above_percent;
+    })
+}
+
+/// Screen-reader friendly sentences describing every current metric and reset.
+#[tauri::command]
+fn get_usage_description(app: tauri::AppHandle) -> String {
+    accessibility::usage_description(&app)
+}
+
+/// Picks the language of menus and notifications; None follows the system.
+#[tauri::command]
+fn set_language(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    language: Option<settings::Language>,
+) -> Result<settings::Settings, String> {
+    let settings = state.update(|s| s.language = language)?;
+    i18n::set_language(language);
+    tray::relabel(&app);
+    Ok(settings)
+}
+
+/// Switches the widget between the full panel and the one-line mini strip.
+#[tauri::command]
+fn set_window_mode(
+    app: tauri::AppHandle,
+    mode: settings::WindowMode,
+) -> Result<settings::Settings, String> {
+    widget_window::set_mode(&app, mode)
+}
+
+#[tauri::command]
+fn set_mini_corner(
+    app: tauri::AppHandle,
+    corner: settings::ScreenCorner,
+) -> Result<settings::Settings, String> {
+    widget_window::set_mini_corner(&app, corner)
+}
+
+/// Hides the widget whenever it loses focus, unless it is pinned.
+#[tauri::command]
+fn set_hide_on_blur(
+    state: tauri::State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<settings::Settings, String> {
+    state.update(|s| s.hide_on_blur = enabled)
+}
+
+#[tauri::command]
+fn set_tray_max_chars(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    max_chars: u32,
+) -> Result<settings::Settings, String> {
+    let settings = state.update(|s| s.tray_max_chars = max_chars)?;
+    tray::rerender(&app);
+    Ok(settings)
+}
+
+#[tauri::command]
+fn set_tray_indicator(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    indicator: settings::TrayIndicator,
+) -> Result<settings::Settings, String> {
+    let settings = state.update(|s| s.tray_indicator = indicator)?;
+    tray::rerender(&app);
+    Ok(settings)
+}
+
+#[tauri::command]
+fn set_tray_pacing(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<settings::Settings, String> {
+    let settings = state.update(|s| s.tray_pacing = enabled)?;
+    tray::rerender(&app);
+    Ok(settings)
+}
+
+#[tauri::command]
+fn set_tray_flash(
+    state: tauri::State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<settings::Settings, String> {
+    state.update(|s| s.tray_flash = enabled)
+}
+
+#[tauri::command]
+fn set_smooth_tray(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<settings::Settings, String> {
+    let settings = state.update(|s| s.smooth_tray = enabled)?;
+    tray::refresh_values(&app);
+    Ok(settings)
+}
+
+/// Weekly usage against an even daily pace, per provider with cached data.
+#[tauri::command]
+fn get_pacing_status(app: tauri::AppHandle) -> Vec<pacing::PacingStatus> {
+    pacing::statuses(&app)
+}
+
+/// Renames a provider in the menu bar, the widget and notifications; empty
+/// strings restore the defaults. Emits `provider-labels-changed`.
+#[tauri::command]
+fn set_provider_label(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    provider: provider::Provider,
+    tray: String,
+    name: String,
+) -> Result<settings::Settings, String> {
+    let key = provider.name().to_lowercase();
+    let label = settings::ProviderLabel {
+        tray: tray.trim().to_string(),
+        name: name.trim().to_string(),
+    };
+    let settings = state.update(|s| {
+        if label.tray.is_empty() && label.name.is_empty() {
+            s.labels.remove(&key);
+        } else {
+            s.labels.insert(key, label);
         }
-        if codex_session >= 0 && codex_weekly >= 0 {
-            parts.push(format!("X:{}/{}%", codex_session, codex_weekly));
+    })?;
+    tray::rerender(&app);
+    tray::update_tooltips(&app);
+    widget_data::refresh(&app);
+    let _ = app.emit("provider-labels-changed", get_provider_labels(state));
+    Ok(settings)
+}
+
+/// Display name per provider id, with custom labels applied.
+#[tauri::command]
+fn get_provider_labels(
+    state: tauri::State<'_, SettingsState>,
+) -> std::collections::BTreeMap<String, String> {
+    let settings = state.get();
+    provider::Provider::ALL
+        .into_iter()
+        .map(|p| (p.name().to_lowercase(), settings.display_name(p.name())))
+        .collect()
+}
+
+#[tauri::command]
+fn set_left_click_action(
+    app: tauri::AppHandle,
+    action: settings::LeftClickAction,
+) -> Result<settings::Settings, String> {
+    tray::set_left_click(&app, action)
+}
+
+/// Shows the next provider alone in the menu bar title; `None` means all.
+#[tauri::command]
+fn cycle_tray_provider(app: tauri::AppHandle) -> Option<&'static str> {
+    tray::cycle_focus(&app)
+}
+
+#[tauri::command]
+fn set_budgets(
+    state: tauri::State<'_, SettingsState>,
+    budgets: Vec<settings::Budget>,
+) -> Result<settings::Settings, String> {
+    for b in &budgets {
+        if !matches!(b.provider.as_str(), "Claude" | "Codex") {
+            return Err(format!("Budgets aren't supported for {}", b.provider));
         }
-        if openrouter_remaining >= 0.0 {
-            parts.push(format!("OR:${:.2}", openrouter_remaining));
+        if !matches!(b.metric.as_str(), "session" | "weekly") {
+            return Err(format!("Unknown budget metric: {}", b.metric));
+        }
+        if !(1.0..=100.0).contains(&b.target_percent) {
+            return Err("Budget target must be between 1% and 100%".into());
         }
-        let text = if parts.is_empty() {
-            "Usage: --".to_string()
-        } else {
-            parts.join("  ")
-        };
-        tray.set_title(Some(&text)).map_err(|e| e.to_string())?;
     }
-    Ok(())
+    state.update(|s| s.budgets = budgets)
 }
 
 #[tauri::command]
-async fn toggle_pin(window: tauri::WebviewWindow, pinned: bool) -> Result<(), String> {
-    window
-        .set_always_on_top(pinned)
-        .map_err(|e| e.to_string())?;
-    window.set_decorations(pinned).map_err(|e| e.to_string())?;
-    Ok(())
+fn get_budget_progress(
+    settings: tauri::State<'_, SettingsState>,
+    claude: tauri::State<'_, UsageState>,
+    codex: tauri::State<'_, CodexState>,
+) -> Vec<budgets::BudgetProgress> {
+    let claude = claude.last_data.lock().unwrap().clone();
+    let codex = codex.last_data.lock().unwrap().clone();
+    let now = chrono::Utc::now();
+    settings
+        .get()
+        .budgets
+        .iter()
+        .filter_map(|b| {
+            let data = match b.provider.as_str() {
+                "Claude" => claude.as_ref(),
+                _ => codex.as_ref(),
+            }?;
+            budgets::progress(b, data, now)
+        })
+        .collect()
+}
+
+#[tauri::command]
+async fn start_work_session(
+    app: tauri::AppHandle,
+    name: String,
+) -> Result<history::WorkSession, String> {
+    work_sessions::start(&app, &name).await
+}
+
+/// Stops the running work session and returns how much usage it consumed.
+#[tauri::command]
+async fn stop_work_session(app: tauri::AppHandle) -> Result<history::WorkSession, String> {
+    work_sessions::stop(&app).await
+}
+
+/// Newest work sessions first, the running one included.
+#[tauri::command]
+fn get_work_sessions(
+    state: tauri::State<'_, HistoryState>,
+    limit: u32,
+) -> Result<Vec<history::WorkSession>, String> {
+    state.work_sessions(limit)
+}
+
+#[tauri::command]
+fn set_privacy_mode(app: tauri::AppHandle, enabled: bool) -> Result<settings::Settings, String> {
+    tray::set_privacy_mode(&app, enabled)
+}
+
+#[tauri::command]
+fn open_presentation_window(app: tauri::AppHandle) -> Result<(), String> {
+    presentation::open(&app)
+}
+
+#[tauri::command]
+fn get_presentation_snapshot(app: tauri::AppHandle) -> Vec<presentation::PresentationRow> {
+    presentation::snapshot(&app)
+}
+
+#[tauri::command]
+fn get_preview_snapshot(app: tauri::AppHandle) -> Vec<preview::PreviewSection> {
+    preview::snapshot(&app)
+}
+
+/// Turns the hover preview on or off and sets how long to hover first.
+#[tauri::command]
+fn set_hover_preview(
+    state: tauri::State<'_, SettingsState>,
+    enabled: bool,
+    delay_ms: u64,
+) -> Result<settings::Settings, String> {
+    if !(preview::MIN_DELAY_MS..=preview::MAX_DELAY_MS).contains(&delay_ms) {
+        return Err("The hover delay must be between 100 and 3000 milliseconds".into());
+    }
+    state.update(|s| {
+        s.hover_preview = enabled;
+        s.hover_preview_delay_ms = delay_ms;
+    })
 }
 
 #[tauri::command]
@@ -151,32 +726,802 @@ fn get_settings(state: tauri::State<'_, SettingsState>) -> settings::Settings {
     state.get()
 }
 
+#[tauri::command]
+fn get_usage_history(
+    state: tauri::State<'_, HistoryState>,
+    provider: Option<String>,
+    hours: u32,
+) -> Result<Vec<history::Sample>, String> {
+    let since = chrono::Utc::now().timestamp() - i64::from(hours) * 3600;
+    state.samples_since(provider.as_deref(), since)
+}
+
+/// Session usage by day of week and hour of day over the last `days`.
+#[tauri::command]
+fn get_usage_heatmap(
+    state: tauri::State<'_, HistoryState>,
+    provider: Option<String>,
+    days: u32,
+) -> Result<Vec<history::HeatmapCell>, String> {
+    let since = chrono::Utc::now().timestamp() - i64::from(days) * 86_400;
+    state.heatmap(provider.as_deref(), since)
+}
+
+/// Saves settings and history (never secrets) to a zip archive at `path`.
+#[tauri::command]
+async fn backup_app_data(app: tauri::AppHandle, path: String) -> Result<backup::Manifest, String> {
+    backup::backup(&app, std::path::Path::new(&path))
+}
+
+/// Replaces settings and history with a backup made by `backup_app_data`.
+#[tauri::command]
+async fn restore_app_data(app: tauri::AppHandle, path: String) -> Result<backup::Manifest, String> {
+    backup::restore(&app, std::path::Path::new(&path))
+}
+
+#[tauri::command]
+fn get_database_stats(
+    state: tauri::State<'_, HistoryState>,
+) -> Result<history::DatabaseStats, String> {
+    state.database_stats()
+}
+
+/// Applies the retention settings right away and shrinks the database file.
+#[tauri::command]
+async fn compact_history(
+    history: tauri::State<'_, HistoryState>,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<history::DatabaseStats, String> {
+    history.compact(&settings.get().retention)?;
+    history.database_stats()
+}
+
+#[tauri::command]
+fn set_history_retention(
+    state: tauri::State<'_, SettingsState>,
+    retention: settings::Retention,
+) -> Result<settings::Settings, String> {
+    retention.validate()?;
+    state.update(|s| s.retention = retention)
+}
+
+#[tauri::command]
+fn set_failover(
+    state: tauri::State<'_, SettingsState>,
+    failover: settings::Failover,
+) -> Result<settings::Settings, String> {
+    failover.validate()?;
+    state.update(|s| s.failover = failover)
+}
+
+/// Sets the order of providers in the menu bar text and the windows each
+/// shows, e.g. after drag-and-drop in settings.
+#[tauri::command]
+fn set_tray_layout(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    layout: Vec<settings::TraySlot>,
+) -> Result<settings::Settings, String> {
+    settings::validate_tray_layout(&layout)?;
+    let settings = state.update(|s| s.tray_layout = layout)?;
+    tray::refresh_values(&app);
+    Ok(settings)
+}
+
+#[tauri::command]
+fn get_usage_records(
+    state: tauri::State<'_, HistoryState>,
+) -> Result<Vec<history::UsageRecord>, String> {
+    state.usage_records()
+}
+
+#[tauri::command]
+fn get_annotations(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, HistoryState>,
+    provider: Option<String>,
+    hours: u32,
+) -> Result<Vec<history::Annotation>, String> {
+    let since = chrono::Utc::now().timestamp() - i64::from(hours) * 3600;
+    let list = state.annotations_since(provider.as_deref(), since)?;
+    Ok(privacy::annotations(privacy::is_on(&app), list))
+}
+
+#[tauri::command]
+async fn get_usage_by_project(
+    app: tauri::AppHandle,
+    range: claude_logs::UsageRange,
+) -> Result<Vec<claude_logs::ProjectUsage>, String> {
+    let since = chrono::Utc::now().timestamp() - range.seconds();
+    tauri::async_runtime::spawn_blocking(move || {
+        let entries = claude_logs::read_entries(since)?;
+        let growth = app.state::<HistoryState>().growth("Claude", "weekly", since)?;
+        let list = claude_logs::usage_by_project(&entries, growth);
+        Ok(privacy::projects(privacy::is_on(&app), list))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn get_codex_activity(days: u32) -> Result<codex_logs::CodexActivity, String> {
+    tauri::async_runtime::spawn_blocking(move || codex_logs::activity(days.max(1)))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn get_capacity_estimate(
+    app: tauri::AppHandle,
+) -> Result<Vec<calibration::CapacityEstimate>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        calibration::capacity_estimates(&app.state::<HistoryState>())
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Every provider's metrics side by side, with the most constrained one.
+#[tauri::command]
+async fn get_comparison_summary(
+    app: tauri::AppHandle,
+) -> Result<comparison::ComparisonSummary, String> {
+    Ok(comparison::summary(&app))
+}
+
+/// Whether macOS lets the app show notifications, read fresh.
+#[tauri::command]
+fn get_notification_permission(app: tauri::AppHandle) -> notification_permission::Permission {
+    notification_permission::check(&app)
+}
+
+/// Providers whose values haven't moved for the configured hours of active use.
+#[tauri::command]
+fn get_stale_providers(
+    state: tauri::State<'_, watchdog::WatchdogState>,
+) -> Vec<watchdog::StaleProvider> {
+    state.stale()
+}
+
+#[tauri::command]
+fn get_advice(
+    history: tauri::State<'_, HistoryState>,
+    claude: tauri::State<'_, UsageState>,
+    codex: tauri::State<'_, CodexState>,
+) -> Vec<advisor::Advice> {
+    let claude = claude.last_data.lock().unwrap().clone();
+    let codex = codex.last_data.lock().unwrap().clone();
+    [("Claude", claude), ("Codex", codex)]
+        .into_iter()
+        .filter_map(|(name, data)| Some(advisor::advise(&history, name, data.as_ref()?)))
+        .collect()
+}
+
+#[tauri::command]
+async fn get_cost_equivalent(
+    app: tauri::AppHandle,
+) -> Result<Vec<pricing::CostEquivalent>, String> {
+    if privacy::is_on(&app) {
+        return Err("Costs are hidden while privacy mode is on".into());
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        pricing::cost_equivalents(&app.state::<HistoryState>())
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Builds the report for `month` ("YYYY-MM") and saves it as JSON and
+/// Markdown.
+#[tauri::command]
+async fn generate_monthly_report(
+    app: tauri::AppHandle,
+    month: String,
+) -> Result<report::SavedReport, String> {
+    tauri::async_runtime::spawn_blocking(move || report::generate(&app, &month))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+fn set_sync_dir(
+    state: tauri::State<'_, SettingsState>,
+    path: Option<String>,
+) -> Result<settings::Settings, String> {
+    let path = path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    if let Some(p) = &path {
+        std::fs::create_dir_all(p).map_err(|e| format!("Cannot use sync folder: {}", e))?;
+    }
+    state.update(|s| s.sync_dir = path)
+}
+
+fn sync_dir(settings: &SettingsState) -> Result<std::path::PathBuf, String> {
+    settings
+        .get()
+        .sync_dir
+        .map(std::path::PathBuf::from)
+        .ok_or_else(|| "No sync folder configured".to_string())
+}
+
+#[tauri::command]
+async fn get_machine_usage(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<sync::MergedUsage, String> {
+    let usage = sync::merged_usage(&sync_dir(&settings)?)?;
+    Ok(privacy::machines(privacy::is_on(&app), usage))
+}
+
+#[tauri::command]
+async fn get_merged_history(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SettingsState>,
+    provider: Option<String>,
+    hours: u32,
+) -> Result<Vec<sync::MachineSample>, String> {
+    let since = chrono::Utc::now().timestamp() - i64::from(hours) * 3600;
+    let list = sync::merged_samples(&sync_dir(&settings)?, provider.as_deref(), since)?;
+    Ok(privacy::machine_samples(privacy::is_on(&app), list))
+}
+
+#[tauri::command]
+fn set_network_options(
+    settings: tauri::State<'_, SettingsState>,
+    usage: tauri::State<'_, UsageState>,
+    options: settings::NetworkOptions,
+) -> Result<settings::Settings, String> {
+    usage.set_client(http_client::build(&options)?);
+    settings.update(|s| s.network = options)
+}
+
+#[tauri::command]
+fn set_active_hours(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    hours: settings::ActiveHours,
+) -> Result<settings::Settings, String> {
+    hours.validate()?;
+    let updated = state.update(|s| s.active_hours = hours)?;
+    scheduler::check_schedule(&app);
+    Ok(updated)
+}
+
+#[tauri::command]
+async fn get_capability_report(
+    app: tauri::AppHandle,
+) -> Result<onboarding::CapabilityReport, String> {
+    Ok(onboarding::detect(&app))
+}
+
+/// Lets the user pick the Claude cookie database by hand; picking it in the
+/// open panel grants access where sandboxing or TCC blocked the direct copy.
+#[tauri::command]
+async fn choose_claude_cookies(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+) -> Result<settings::Settings, String> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let mut dialog = app
+        .dialog()
+        .file()
+        .set_title("Choose the Claude Cookies file");
+    if let Some(dir) = cookie_reader::cookies_path().as_deref().and_then(|p| p.parent()) {
+        dialog = dialog.set_directory(dir);
+    }
+    dialog.pick_file(move |picked| {
+        let _ = tx.send(picked);
+    });
+    let Some(picked) = rx.await.map_err(|e| e.to_string())? else {
+        return Ok(state.get());
+    };
+    let path = picked.into_path().map_err(|e| e.to_string())?;
+    let bookmark = bookmark::create(&path)
+        .inspect_err(|e| log::warn!("{}", e))
+        .ok();
+    let updated = state.update(|s| {
+        s.claude_cookies_path = Some(path.to_string_lossy().into_owned());
+        s.claude_cookies_bookmark = bookmark;
+    })?;
+    onboarding::detect(&app);
+    Ok(updated)
+}
+
+/// Imports daily totals from a ccusage JSON or CSV export, asking for the
+/// file when no path is given. Returns None when the picker is cancelled.
+#[tauri::command]
+async fn import_usage_history(
+    app: tauri::AppHandle,
+    history: tauri::State<'_, HistoryState>,
+    provider: provider::Provider,
+    path: Option<String>,
+) -> Result<Option<importer::ImportSummary>, String> {
+    let path = match path {
+        Some(p) => std::path::PathBuf::from(p),
+        None => {
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            app.dialog()
+                .file()
+                .set_title("Choose a usage export")
+                .add_filter("Usage export", &["json", "csv"])
+                .pick_file(move |picked| {
+                    let _ = tx.send(picked);
+                });
+            let Some(picked) = rx.await.map_err(|e| e.to_string())? else {
+                return Ok(None);
+            };
+            picked.into_path().map_err(|e| e.to_string())?
+        }
+    };
+    importer::import_file(&history, &path, provider).map(Some)
+}
+
+#[tauri::command]
+fn get_imported_usage(
+    state: tauri::State<'_, HistoryState>,
+    provider: Option<String>,
+) -> Result<Vec<history::DailyTokens>, String> {
+    state.daily_tokens(provider.as_deref())
+}
+
+#[tauri::command]
+fn reset_claude_cookies(state: tauri::State<'_, SettingsState>) -> Result<settings::Settings, String> {
+    state.update(|s| {
+        s.claude_cookies_path = None;
+        s.claude_cookies_bookmark = None;
+    })
+}
+
+/// Asks the Keychain for Claude Safe Storage from this app, so one "Always
+/// Allow" covers every later read.
+#[tauri::command]
+fn grant_keychain_access(
+    safe_storage: tauri::State<'_, cookie_reader::SafeStorageState>,
+    state: tauri::State<'_, SettingsState>,
+) -> Result<settings::Settings, String> {
+    safe_storage.grant_access().map_err(|e| e.to_string())?;
+    state.update(|s| s.keychain_access_granted = true)
+}
+
+#[tauri::command]
+fn reconfigure_providers(app: tauri::AppHandle) -> onboarding::CapabilityReport {
+    onboarding::reconfigure(&app)
+}
+
+#[tauri::command]
+fn complete_onboarding(state: tauri::State<'_, SettingsState>) -> Result<settings::Settings, String> {
+    state.update(|s| s.onboarding_complete = true)
+}
+
+#[tauri::command]
+fn get_next_resets(app: tauri::AppHandle) -> Vec<resets::ResetTime> {
+    resets::next_weekly_resets(&app)
+}
+
+/// Sets or clears a provider's weekly reset anchor and applies the change to
+/// the cached data right away; clearing it restores the API's reset time.
+#[tauri::command]
+fn set_reset_anchor(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    provider: provider::Provider,
+    anchor: Option<settings::ResetAnchor>,
+) -> Result<settings::Settings, String> {
+    let cache = match provider {
+        provider::Provider::Claude => &app.state::<UsageState>().last_data,
+        provider::Provider::Codex => &app.state::<CodexState>().last_data,
+        provider::Provider::OpenRouter
+        | provider::Provider::LiteLlm
+        | provider::Provider::Ollama => {
+            return Err(format!("{} has no weekly window", provider.name()))
+        }
+    };
+    if let Some(a) = &anchor {
+        if a.hour > 23 || a.minute > 59 {
+            return Err("Reset time must be a valid time of day".into());
+        }
+    }
+    let key = provider.name().to_lowercase();
+    let settings = state.update(|s| match anchor {
+        Some(a) => {
+            s.reset_anchors.insert(key, a);
+        }
+        None => {
+            s.reset_anchors.remove(&key);
+        }
+    })?;
+    if let Some(data) = cache.lock().unwrap().as_mut() {
+        resets::apply_anchor(&settings, provider, data);
+    }
+    Ok(settings)
+}
+
+#[tauri::command]
+fn add_reset_to_calendar(
+    app: tauri::AppHandle,
+    provider: provider::Provider,
+) -> Result<String, String> {
+    let dir = app.path().app_cache_dir().map_err(|e| e.to_string())?;
+    resets::add_to_calendar(&app, provider, &dir).map(|p| p.display().to_string())
+}
+
+/// Turns the synced "AI limit resets" calendar on or off. Turning it on
+/// writes the known resets right away; turning it off leaves the calendar.
+#[tauri::command]
+fn set_calendar_sync(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<settings::Settings, String> {
+    let settings = state.update(|s| s.calendar_sync = enabled)?;
+    app.state::<calendar_sync::CalendarSyncState>().reset();
+    calendar_sync::sync(&app);
+    Ok(settings)
+}
+
+#[tauri::command]
+fn set_muted_metrics(
+    state: tauri::State<'_, SettingsState>,
+    metrics: Vec<String>,
+) -> Result<settings::Settings, String> {
+    state.update(|s| s.muted_metrics = metrics.into_iter().collect())
+}
+
+/// Sets the alert threshold of one metric ("opus" or "claude_opus"), or
+/// clears it back to the global threshold with `None`.
+#[tauri::command]
+fn set_metric_threshold(
+    state: tauri::State<'_, SettingsState>,
+    metric: String,
+    threshold: Option<u32>,
+) -> Result<settings::Settings, String> {
+    if threshold.is_some_and(|t| t > 100) {
+        return Err("Threshold must be between 0 and 100".into());
+    }
+    state.update(|s| match threshold {
+        Some(t) => {
+            s.metric_thresholds.insert(metric, t);
+        }
+        None => {
+            s.metric_thresholds.remove(&metric);
+        }
+    })
+}
+
+/// Sets how far a metric must drop before it alerts again, at most the
+/// threshold itself, and the minimum gap between alerts, at most a day.
+#[tauri::command]
+fn set_notification_rearm(
+    state: tauri::State<'_, SettingsState>,
+    hysteresis_points: u32,
+    min_interval_secs: u64,
+) -> Result<settings::Settings, String> {
+    state.update(|s| {
+        s.notify_hysteresis_points = hysteresis_points.min(s.notify_threshold);
+        s.renotify_min_secs = min_interval_secs.min(86_400);
+    })
+}
+
+#[tauri::command]
+fn set_reminders(
+    state: tauri::State<'_, SettingsState>,
+    enabled: bool,
+    level: u32,
+    interval_mins: u64,
+) -> Result<settings::Settings, String> {
+    state.update(|s| {
+        s.reminders_enabled = enabled;
+        s.reminder_level = level.min(100);
+        s.reminder_interval_mins = interval_mins.max(1);
+    })
+}
+
+#[tauri::command]
+fn set_focus_behavior(
+    state: tauri::State<'_, SettingsState>,
+    suppress: bool,
+    allow_critical: bool,
+) -> Result<settings::Settings, String> {
+    state.update(|s| {
+        s.suppress_during_focus = suppress;
+        s.focus_allow_critical = allow_critical;
+    })
+}
+
+#[tauri::command]
+fn get_suppressed_alerts(
+    state: tauri::State<'_, NotificationState>,
+) -> Vec<notifications::SuppressedAlert> {
+    state.suppressed()
+}
+
+#[tauri::command]
+fn set_email_settings(
+    state: tauri::State<'_, SettingsState>,
+    email: settings::EmailSettings,
+) -> Result<settings::Settings, String> {
+    state.update(|s| s.email = email)
+}
+
+#[tauri::command]
+fn set_smtp_password(password: String) -> Result<(), String> {
+    keychain::set_secret(email::PASSWORD_ACCOUNT, &password)
+}
+
+#[tauri::command]
+fn clear_smtp_password() -> Result<(), String> {
+    keychain::clear_secret(email::PASSWORD_ACCOUNT)
+}
+
+#[tauri::command]
+fn set_discord_webhook(url: String) -> Result<(), String> {
+    let url = url.trim();
+    if !["https://discord.com/api/webhooks/", "https://discordapp.com/api/webhooks/"]
+        .iter()
+        .any(|p| url.starts_with(p))
+    {
+        return Err("Not a Discord webhook URL".into());
+    }
+    keychain::set_secret(discord::WEBHOOK_ACCOUNT, url)
+}
+
+#[tauri::command]
+fn clear_discord_webhook() -> Result<(), String> {
+    keychain::clear_secret(discord::WEBHOOK_ACCOUNT)
+}
+
+#[tauri::command]
+fn set_discord_enabled(
+    state: tauri::State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<settings::Settings, String> {
+    state.update(|s| s.discord_enabled = enabled)
+}
+
+#[tauri::command]
+fn set_telegram_bot(token: String, chat_id: String) -> Result<(), String> {
+    let chat_id = chat_id.trim();
+    if chat_id.parse::<i64>().is_err() {
+        return Err("Chat ID must be a number".into());
+    }
+    keychain::set_secret(telegram::TOKEN_ACCOUNT, &token)?;
+    keychain::set_secret(telegram::CHAT_ACCOUNT, chat_id)
+}
+
+#[tauri::command]
+fn clear_telegram_bot() -> Result<(), String> {
+    keychain::clear_secret(telegram::TOKEN_ACCOUNT)?;
+    keychain::clear_secret(telegram::CHAT_ACCOUNT)
+}
+
+#[tauri::command]
+fn set_telegram_enabled(
+    state: tauri::State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<settings::Settings, String> {
+    state.update(|s| s.telegram_enabled = enabled)
+}
+
+#[tauri::command]
+fn set_shell_hook(
+    state: tauri::State<'_, SettingsState>,
+    script: Option<String>,
+) -> Result<settings::Settings, String> {
+    let script = script.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    if let Some(path) = &script {
+        if !std::path::Path::new(path).is_file() {
+            return Err(format!("{} does not exist", path));
+        }
+    }
+    state.update(|s| s.shell_hook = script)
+}
+
+#[tauri::command]
+fn set_capacity_file(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<settings::Settings, String> {
+    let settings = state.update(|s| s.capacity_file = enabled)?;
+    if enabled {
+        capacity::write(&app)?;
+    } else {
+        capacity::remove()?;
+    }
+    Ok(settings)
+}
+
+#[tauri::command]
+fn set_widget_data(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<settings::Settings, String> {
+    let settings = state.update(|s| s.widget_data = enabled)?;
+    if enabled {
+        widget_data::write(&app)?;
+    } else {
+        widget_data::remove()?;
+    }
+    Ok(settings)
+}
+
+#[tauri::command]
+fn install_claude_hooks(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    ceiling: u32,
+    block: bool,
+) -> Result<String, String> {
+    let ceiling = ceiling.clamp(1, 100);
+    // The hook reads capacity.json, so keep it up to date
+    state.update(|s| {
+        s.hook_ceiling = ceiling;
+        s.hook_block = block;
+        s.capacity_file = true;
+    })?;
+    capacity::write(&app)?;
+    claude_hooks::install(ceiling, block)
+}
+
+#[tauri::command]
+fn uninstall_claude_hooks() -> Result<(), String> {
+    claude_hooks::uninstall()
+}
+
+#[tauri::command]
+fn set_team_settings(
+    state: tauri::State<'_, SettingsState>,
+    enabled: bool,
+    endpoint: String,
+) -> Result<settings::Settings, String> {
+    state.update(|s| {
+        s.team.enabled = enabled;
+        s.team.endpoint = endpoint.trim().to_string();
+        if s.team.member_id.is_empty() {
+            s.team.member_id = team::new_member_id();
+        }
+    })
+}
+
+#[tauri::command]
+fn set_team_token(token: Option<String>) -> Result<(), String> {
+    match token {
+        Some(token) => keychain::set_secret(team::TOKEN_ACCOUNT, &token),
+        None => keychain::clear_secret(team::TOKEN_ACCOUNT),
+    }
+}
+
+#[tauri::command]
+async fn get_team_aggregate(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SettingsState>,
+    usage: tauri::State<'_, UsageState>,
+) -> Result<team::TeamAggregate, String> {
+    let team = settings.get().team;
+    if !team.enabled {
+        return Err("Team mode is off".into());
+    }
+    let aggregate = team::aggregate(&usage.client(), &team).await?;
+    Ok(privacy::team(privacy::is_on(&app), aggregate))
+}
+
+#[tauri::command]
+async fn test_alert_channel(app: tauri::AppHandle, channel: alerts::Channel) -> Result<(), String> {
+    alerts::test(&app, channel).await
+}
+
+#[tauri::command]
+fn set_alert_style(
+    state: tauri::State<'_, SettingsState>,
+    critical: bool,
+    style: settings::AlertStyle,
+) -> Result<settings::Settings, String> {
+    state.update(|s| {
+        if critical {
+            s.critical_alert = style;
+        } else {
+            s.routine_alert = style;
+        }
+    })
+}
+
+#[tauri::command]
+fn set_debug_tools(
+    state: tauri::State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<settings::Settings, String> {
+    state.update(|s| s.debug_tools = enabled)
+}
+
+/// The provider's raw API payload with secrets blanked, for diagnosing
+/// schema changes. Requires the debug tools setting.
+#[tauri::command]
+async fn fetch_raw(
+    app: tauri::AppHandle,
+    provider: provider::Provider,
+) -> Result<serde_json::Value, String> {
+    if !app.state::<SettingsState>().get().debug_tools {
+        return Err("Turn on debug tools in Settings first".into());
+    }
+    provider::fetch_raw(&app, provider).await
+}
+
 #[tauri::command]
 fn get_refresh_interval(state: tauri::State<'_, SettingsState>) -> u64 {
     state.get().refresh_interval_secs
 }
 
+/// Sets any refresh interval from 30 seconds to 2 hours, not just the presets
+/// in the tray menu.
+#[tauri::command]
+fn set_refresh_interval(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    secs: u64,
+) -> Result<settings::Settings, String> {
+    if !(scheduler::MIN_INTERVAL_SECS..=scheduler::MAX_INTERVAL_SECS).contains(&secs) {
+        return Err("The refresh interval must be between 30 seconds and 2 hours".into());
+    }
+    let settings = state.update(|s| s.refresh_interval_secs = secs)?;
+    let _ = app.emit("refresh-interval-changed", secs);
+    Ok(settings)
+}
+
 // --- App setup ---
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        // Must come first: a second launch hands over to the running instance
+        // (focusing its window) instead of polling and notifying twice
+        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+            if let Some(w) = app.get_webview_window("main") {
+                let _ = w.show();
+                let _ = w.set_focus();
+            }
+        }))
+        .plugin(
+            tauri_plugin_log::Builder::new()
+                .level(log::LevelFilter::Info)
+                .build(),
+        )
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_autostart::init(
             MacosLauncher::LaunchAgent,
             None::<Vec<&str>>,
         ))
-        .manage(UsageState::new())
         .manage(CodexState::new())
         .manage(OpenRouterState::new())
+        .manage(litellm_fetcher::LiteLlmState::new())
+        .manage(ollama_fetcher::OllamaState::new())
+        .manage(custom_provider::CustomProviderState::new())
+        .manage(calendar_sync::CalendarSyncState::new())
+        .manage(sync::SyncState::new())
+        .manage(watchdog::WatchdogState::new())
+        .manage(notification_permission::PermissionState::new())
+        .manage(SchedulerState::new())
+        .manage(HttpCacheState::new())
+        .manage(discord::DiscordState::new())
+        .manage(cookie_reader::SafeStorageState::new())
+        .manage(budgets::BudgetState::new())
+        .manage(smoothing::SmoothingState::new())
+        .manage(fetch_stats::FetchStatsState::new())
+        .manage(preview::PreviewState::new())
+        .manage(failover::FailoverState::new())
+        .manage(widget_window::WidgetWindowState::new())
         .setup(|app| {
             // Initialize settings
             let data_dir = app.path().app_data_dir().expect("no app data dir");
-            let settings_state = SettingsState::new(data_dir);
+            let settings_state = SettingsState::new(data_dir.clone(), app.handle().clone());
             let initial_settings = settings_state.get();
             app.manage(settings_state);
+            i18n::set_language(initial_settings.language);
+            i18n::set_currency_display(initial_settings.currency_display);
+            app.manage(UsageState::new(http_client::build(&initial_settings.network)?));
             app.manage(NotificationState::new());
+            app.manage(HistoryState::new(&data_dir));
 
             // Sync autostart with saved setting
             {
@@ -189,223 +1534,38 @@ pub fn run() {
                 }
             }
 
-            // --- Build tray context menu ---
-            let show_hide =
-                MenuItem::with_id(app, "show_hide", "Show Widget", true, None::<&str>)?;
-            let sep1 = PredefinedMenuItem::separator(app)?;
-            let refresh_now =
-                MenuItem::with_id(app, "refresh_now", "Refresh Now", true, None::<&str>)?;
-
-            // Refresh interval submenu (radio-style check items)
-            let intervals: [(u64, &str); 5] = [
-                (60, "1 min"),
-                (120, "2 min"),
-                (300, "5 min"),
-                (600, "10 min"),
-                (900, "15 min"),
-            ];
-            let mut interval_items: Vec<CheckMenuItem<tauri::Wry>> = Vec::new();
-            for (secs, label) in &intervals {
-                let item = CheckMenuItem::with_id(
-                    app,
-                    format!("interval_{}", secs),
-                    *label,
-                    true,
-                    *secs == initial_settings.refresh_interval_secs,
-                    None::<&str>,
-                )?;
-                interval_items.push(item);
-            }
-            let interval_refs: Vec<&dyn tauri::menu::IsMenuItem<tauri::Wry>> = interval_items
-                .iter()
-                .map(|i| i as &dyn tauri::menu::IsMenuItem<tauri::Wry>)
-                .collect();
-            let refresh_sub = Submenu::with_id_and_items(
-                app,
-                "refresh_sub",
-                "Refresh Every",
-                true,
-                &interval_refs,
-            )?;
-
-            // Notification threshold submenu (radio-style check items)
-            let thresholds: [(u32, &str); 5] =
-                [(70, "70%"), (80, "80%"), (90, "90%"), (95, "95%"), (0, "Off")];
-            let mut threshold_items: Vec<CheckMenuItem<tauri::Wry>> = Vec::new();
-            for (pct, label) in &thresholds {
-                let checked = if *pct == 0 {
-                    !initial_settings.notifications_enabled
-                } else {
-                    initial_settings.notifications_enabled
-                        && *pct == initial_settings.notify_threshold
-                };
-                let item = CheckMenuItem::with_id(
-                    app,
-                    format!("notify_{}", pct),
-                    *label,
-                    true,
-                    checked,
-                    None::<&str>,
-                )?;
-                threshold_items.push(item);
-            }
-            let threshold_refs: Vec<&dyn tauri::menu::IsMenuItem<tauri::Wry>> = threshold_items
-                .iter()
-                .map(|i| i as &dyn tauri::menu::IsMenuItem<tauri::Wry>)
-                .collect();
-            let notify_sub = Submenu::with_id_and_items(
-                app,
-                "notify_sub",
-                "Notify At",
-                true,
-                &threshold_refs,
-            )?;
-
-            // Start at login toggle
-            let start_login = CheckMenuItem::with_id(
-                app,
-                "start_login",
-                "Start at Login",
-                true,
-                initial_settings.start_at_login,
-                None::<&str>,
-            )?;
-            let open_settings =
-                MenuItem::with_id(app, "open_settings", "Settings...", true, None::<&str>)?;
-
-            let sep2 = PredefinedMenuItem::separator(app)?;
-            let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-
-            let menu = Menu::with_items(
-                app,
-                &[
-                    &show_hide,
-                    &sep1,
-                    &refresh_now,
-                    &refresh_sub,
-                    &notify_sub,
-                    &start_login,
-                    &open_settings,
-                    &sep2,
-                    &quit,
-                ],
-            )?;
-
-            // Build tray (text-only, no icon)
-            let menu_ref = menu.clone();
-            let _tray = TrayIconBuilder::with_id("main")
-                .title("C:--% X:--% OR:--")
-                .tooltip("Usage Widget")
-                .menu(&menu)
-                .show_menu_on_left_click(true)
-                .on_menu_event(move |app, event| {
-                    let id = event.id().as_ref();
-                    match id {
-                        "show_hide" => {
-                            if let Some(w) = app.get_webview_window("main") {
-                                if w.is_visible().unwrap_or(false) {
-                                    let _ = w.hide();
-                                } else {
-                                    let _ = w.show();
-                                    let _ = w.set_focus();
-                                }
-                            }
-                        }
-                        "refresh_now" => {
-                            let _ = app.emit("usage-refresh-tick", ());
-                        }
-                        "open_settings" => {
-                            if let Some(w) = app.get_webview_window("main") {
-                                let _ = w.show();
-                                let _ = w.set_focus();
-                            }
-                            let _ = app.emit("open-settings", ());
-                        }
-                        "quit" => {
-                            app.exit(0);
-                        }
-                        s if s.starts_with("interval_") => {
-                            if let Ok(secs) = s.strip_prefix("interval_").unwrap().parse::<u64>() {
-                                let ss = app.state::<SettingsState>();
-                                let _ = ss.update(|s| s.refresh_interval_secs = secs);
-                                // Update radio checks - uncheck all, check selected
-                                for (v, _) in &intervals {
-                                    let item_id = format!("interval_{}", v);
-                                    if let Some(item) = menu_ref.get(&item_id) {
-                                        if let Some(check) = item.as_check_menuitem() {
-                                            let _ = check.set_checked(*v == secs);
-                                        }
-                                    }
-                                }
-                                // Notify frontend about interval change
-                                let _ = app.emit("refresh-interval-changed", secs);
-                            }
-                        }
-                        s if s.starts_with("notify_") => {
-                            if let Ok(pct) = s.strip_prefix("notify_").unwrap().parse::<u32>() {
-                                let ss = app.state::<SettingsState>();
-                                let _ = ss.update(|s| {
-                                    if pct == 0 {
-                                        s.notifications_enabled = false;
-                                    } else {
-                                        s.notifications_enabled = true;
-                                        s.notify_threshold = pct;
-                                    }
-                                });
-                                // Update radio checks
-                                for (v, _) in &thresholds {
-                                    let item_id = format!("notify_{}", v);
-                                    if let Some(item) = menu_ref.get(&item_id) {
-                                        if let Some(check) = item.as_check_menuitem() {
-                                            let expected = if *v == 0 {
-                                                pct == 0
-                                            } else {
-                                                pct != 0 && *v == pct
-                                            };
-                                            let _ = check.set_checked(expected);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        "start_login" => {
-                            let ss = app.state::<SettingsState>();
-                            let new_val = !ss.get().start_at_login;
-                            let _ = ss.update(|s| s.start_at_login = new_val);
-                            // Toggle autostart
-                            {
-                                use tauri_plugin_autostart::ManagerExt;
-                                let mgr = app.autolaunch();
-                                if new_val {
-                                    let _ = mgr.enable();
-                                } else {
-                                    let _ = mgr.disable();
-                                }
-                            }
-                            // Update check state
-                            if let Some(item) = menu_ref.get("start_login") {
-                                if let Some(check) = item.as_check_menuitem() {
-                                    let _ = check.set_checked(new_val);
-                                }
+            // Build tray item(s) and context menu
+            tray::build(app)?;
+            widget_window::attach(app.handle());
+            notification_permission::check(app.handle());
+
+            // Regain access to a hand-picked Cookies file before it is read
+            if let Some(saved) = &initial_settings.claude_cookies_bookmark {
+                match bookmark::resolve(saved) {
+                    Ok((path, renewed)) => {
+                        let _ = app.state::<SettingsState>().update(|s| {
+                            s.claude_cookies_path = Some(path.to_string_lossy().into_owned());
+                            if renewed.is_some() {
+                                s.claude_cookies_bookmark = renewed;
                             }
-                        }
-                        _ => {}
+                        });
                     }
-                })
-                .build(app)?;
-
-            // Auto-refresh timer - reads interval from settings dynamically
-            let handle = app.handle().clone();
-            tauri::async_runtime::spawn(async move {
-                loop {
-                    let secs = {
-                        let ss = handle.state::<SettingsState>();
-                        ss.get().refresh_interval_secs
-                    };
-                    tokio::time::sleep(std::time::Duration::from_secs(secs)).await;
-                    let _ = handle.emit("usage-refresh-tick", ());
+                    Err(e) => log::warn!("Claude Cookies bookmark: {}", e),
                 }
-            });
+            }
+
+            // Providers without credentials stay out of scheduling until set up
+            onboarding::detect(app.handle());
+            startup::spawn(app.handle());
+
+            // Background refresh loop - emits typed provider-fetch-* events
+            scheduler::spawn(app.handle());
+            scheduler::spawn_wake_watcher(app.handle());
+            smoothing::spawn(app.handle());
+            history::spawn_pruning(app.handle());
+
+            // Answers /usage in the Telegram chat while that channel is on
+            telegram::spawn_listener(app.handle());
 
             Ok(())
         })
@@ -416,14 +1576,135 @@ pub fn run() {
             get_cached_codex,
             fetch_openrouter_credits,
             get_cached_openrouter,
+            refresh_provider,
+            refresh_all,
+            set_paused,
+            get_pause_status,
+            clear_backoff,
+            get_provider_health,
+            get_fetch_stats,
+            get_diagnostics_report,
             get_openrouter_key_status,
             set_openrouter_key,
             clear_openrouter_key,
-            update_tray_text,
+            fetch_litellm_budget,
+            get_cached_litellm,
+            set_litellm_gateway,
+            clear_litellm_gateway,
+            fetch_ollama_activity,
+            get_cached_ollama,
+            set_ollama_monitor,
+            get_custom_provider_templates,
+            add_custom_provider,
+            remove_custom_provider,
+            fetch_custom_provider,
+            get_cached_custom_providers,
             toggle_pin,
+            set_hide_on_blur,
+            set_widget_opacity,
+            set_click_through,
+            set_dock_icon,
+            set_window_mode,
+            set_mini_corner,
+            set_all_spaces,
+            set_language,
+            get_usage_description,
+            set_currency_display,
+            set_startup_summary,
+            set_launch_behavior,
+            set_tray_max_chars,
+            set_tray_indicator,
+            set_tray_pacing,
+            set_tray_flash,
+            set_smooth_tray,
+            get_pacing_status,
+            set_provider_label,
+            get_provider_labels,
+            set_left_click_action,
+            cycle_tray_provider,
+            set_budgets,
+            get_budget_progress,
+            start_work_session,
+            stop_work_session,
+            get_work_sessions,
+            set_privacy_mode,
+            open_presentation_window,
+            get_presentation_snapshot,
+            get_preview_snapshot,
+            set_hover_preview,
             get_settings,
             get_refresh_interval,
+            set_refresh_interval,
+            get_usage_history,
+            get_usage_records,
+            get_usage_heatmap,
+            import_usage_history,
+            get_imported_usage,
+            get_database_stats,
+            backup_app_data,
+            restore_app_data,
+            compact_history,
+            set_history_retention,
+            set_failover,
+            set_tray_layout,
+            get_annotations,
+            get_usage_by_project,
+            get_codex_activity,
+            get_capacity_estimate,
+            get_comparison_summary,
+            get_stale_providers,
+            get_notification_permission,
+            get_advice,
+            get_cost_equivalent,
+            generate_monthly_report,
+            set_sync_dir,
+            get_machine_usage,
+            get_merged_history,
+            set_alert_style,
+            set_debug_tools,
+            fetch_raw,
+            set_focus_behavior,
+            set_muted_metrics,
+            set_metric_threshold,
+            set_notification_rearm,
+            set_reminders,
+            get_suppressed_alerts,
+            set_email_settings,
+            set_smtp_password,
+            clear_smtp_password,
+            set_discord_webhook,
+            clear_discord_webhook,
+            set_discord_enabled,
+            set_telegram_bot,
+            clear_telegram_bot,
+            set_telegram_enabled,
+            set_shell_hook,
+            test_alert_channel,
+            set_capacity_file,
+            set_widget_data,
+            install_claude_hooks,
+            uninstall_claude_hooks,
+            set_team_settings,
+            set_team_token,
+            get_team_aggregate,
+            set_network_options,
+            set_active_hours,
+            get_capability_report,
+            choose_claude_cookies,
+            reset_claude_cookies,
+            grant_keychain_access,
+            complete_onboarding,
+            reconfigure_providers,
+            get_next_resets,
+            add_reset_to_calendar,
+            set_calendar_sync,
+            set_reset_anchor,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                widget_window::remember_visibility(app);
+            }
+        });
 }
@@ -0,0 +1,165 @@
+//! Fetches the budget and spend of a key on a LiteLLM proxy (or another
+//! self-hosted gateway speaking the same admin API), so teams that route all
+//! traffic through a gateway see its budget next to the SaaS limits.
+//!
+//! Calls `GET {base_url}/key/info` with the key as a bearer token, which
+//! reports on the calling key. The base URL comes from settings; the key from
+//! the Keychain, with `LITELLM_API_KEY` as a fallback for terminal/dev
+//! workflows.
+
+use std::sync::Mutex;
+
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::http_cache::{self, Fetched, Validators};
+use crate::http_client;
+use crate::keychain;
+use crate::secret::Secret;
+
+/// Keychain account of the gateway key.
+pub const KEY_ACCOUNT: &str = "litellm_api_key";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GatewayBudgetData {
+    /// Key alias, or the gateway's masked key name
+    pub key_alias: Option<String>,
+    pub spend: f64,
+    /// None when the key has no budget
+    pub max_budget: Option<f64>,
+    pub remaining: Option<f64>,
+    /// Share of the budget spent; 0 without a budget
+    pub percent_used: f64,
+    /// Budget period as configured on the gateway, e.g. "30d"
+    pub budget_duration: Option<String>,
+    /// RFC 3339, if the budget resets
+    pub budget_resets_at: Option<String>,
+    pub fetched_at: String,
+    /// Amounts replaced with zeros (privacy mode)
+    #[serde(default)]
+    pub redacted: bool,
+}
+
+pub struct LiteLlmState {
+    pub last_data: Mutex<Option<GatewayBudgetData>>,
+}
+
+impl LiteLlmState {
+    pub fn new() -> Self {
+        Self {
+            last_data: Mutex::new(None),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct KeyInfoResponse {
+    info: Option<KeyInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KeyInfo {
+    #[serde(default)]
+    key_alias: Option<String>,
+    #[serde(default)]
+    key_name: Option<String>,
+    #[serde(default)]
+    spend: Option<Value>,
+    #[serde(default)]
+    max_budget: Option<Value>,
+    #[serde(default)]
+    budget_duration: Option<String>,
+    #[serde(default)]
+    budget_reset_at: Option<String>,
+}
+
+fn value_to_f64(v: Option<Value>) -> Option<f64> {
+    match v? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+pub fn read_key() -> Result<Option<Secret>, String> {
+    if let Some(key) = keychain::read_secret(KEY_ACCOUNT)? {
+        return Ok(Some(key));
+    }
+    Ok(std::env::var("LITELLM_API_KEY")
+        .ok()
+        .map(|k| Secret::from_bytes(k.into_bytes()))
+        .filter(|k| !k.is_empty()))
+}
+
+fn key_info_request(client: &Client, base_url: &str, key: &Secret) -> RequestBuilder {
+    client
+        .get(format!("{}/key/info", base_url))
+        .bearer_auth(key.expose())
+        .header("Accept", "application/json")
+}
+
+fn credentials(base_url: Option<&str>) -> Result<(String, Secret), String> {
+    let base_url = base_url.ok_or("No LiteLLM gateway URL is set")?;
+    let key = read_key()?.ok_or("No LiteLLM key is set")?;
+    Ok((http_client::normalize_base_url(base_url)?, key))
+}
+
+/// The key info payload as returned, for debugging schema changes.
+pub async fn fetch_raw(client: &Client, base_url: Option<&str>) -> Result<Value, String> {
+    let (base_url, key) = credentials(base_url)?;
+    http_client::raw_json("LiteLLM", key_info_request(client, &base_url, &key)).await
+}
+
+pub async fn fetch_budget(
+    client: &Client,
+    base_url: Option<&str>,
+    validators: Option<&Validators>,
+) -> Result<Fetched<GatewayBudgetData>, String> {
+    let (base_url, key) = credentials(base_url)?;
+
+    let req = key_info_request(client, &base_url, &key);
+    let resp = http_cache::conditional(req, validators)
+        .send()
+        .await
+        .map_err(|e| http_client::request_error("LiteLLM", e))?;
+
+    if resp.status() == StatusCode::NOT_MODIFIED {
+        return Ok(Fetched::NotModified);
+    }
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        return Err(format!(
+            "LiteLLM gateway returned {}: {}",
+            status,
+            body.chars().take(200).collect::<String>()
+        ));
+    }
+
+    let fresh = Validators::from_response(&resp);
+    let payload: KeyInfoResponse = resp
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse LiteLLM response: {}", e))?;
+    let info = payload.info.ok_or("The LiteLLM response has no key info")?;
+
+    let spend = value_to_f64(info.spend).unwrap_or(0.0);
+    let max_budget = value_to_f64(info.max_budget).filter(|b| *b > 0.0);
+    let percent_used = max_budget.map_or(0.0, |b| (spend / b * 100.0).min(100.0));
+
+    Ok(Fetched::Fresh(
+        GatewayBudgetData {
+            key_alias: info.key_alias.or(info.key_name).filter(|a| !a.is_empty()),
+            spend,
+            max_budget,
+            remaining: max_budget.map(|b| (b - spend).max(0.0)),
+            percent_used,
+            budget_duration: info.budget_duration,
+            budget_resets_at: info.budget_reset_at,
+            fetched_at: chrono::Utc::now().to_rfc3339(),
+            redacted: false,
+        },
+        fresh,
+    ))
+}
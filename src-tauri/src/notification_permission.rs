@@ -0,0 +1,120 @@
+//! Watches whether macOS lets the app show notifications at all, so alerts
+//! don't silently vanish when they are turned off in System Settings.
+//!
+//! The notification plugin reports desktop permission as always granted, so
+//! the status is read from the notification center preferences instead: the
+//! app's entry under `apps` in `com.apple.ncprefs` carries an "allow
+//! notifications" bit in its `flags`. No entry means macOS hasn't asked yet.
+//!
+//! Checked at startup and before each notification. A change emits
+//! `notification-permission-changed`, and while notifications are off the tray
+//! menu shows an item that opens the Notifications settings.
+
+use std::process::Command;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::tray;
+
+/// "Allow notifications" in an app's ncprefs flags.
+const ALLOW_FLAG: u64 = 1 << 25;
+
+/// System Settings pane with the per-app notification switches.
+const SETTINGS_URL: &str = "x-apple.systempreferences:com.apple.preference.notifications";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    Granted,
+    /// Turned off in System Settings
+    Denied,
+    /// macOS hasn't asked the user yet
+    NotDetermined,
+    /// The preferences couldn't be read
+    Unknown,
+}
+
+pub struct PermissionState {
+    last: Mutex<Option<Permission>>,
+}
+
+impl PermissionState {
+    pub fn new() -> Self {
+        Self {
+            last: Mutex::new(None),
+        }
+    }
+}
+
+/// `flags` of the `apps` entry whose `bundle-id` is `bundle_id`, from the
+/// old-style plist text `defaults read` prints.
+fn app_flags(plist: &str, bundle_id: &str) -> Option<u64> {
+    let needle = format!("\"bundle-id\" = \"{}\";", bundle_id);
+    let mut depth = 0i32;
+    let mut in_entry = false;
+    let mut flags = None;
+    for line in plist.lines() {
+        let line = line.trim();
+        // Entries of the `apps` array sit at depth 2
+        if depth == 2 {
+            if line == needle {
+                in_entry = true;
+            } else if let Some(v) = line.strip_prefix("flags = ") {
+                flags = v.trim_end_matches(';').parse().ok();
+            }
+        }
+        depth += line.matches(['{', '(']).count() as i32;
+        depth -= line.matches(['}', ')']).count() as i32;
+        if depth < 2 {
+            if in_entry {
+                return flags;
+            }
+            flags = None;
+        }
+    }
+    None
+}
+
+fn read_permission(bundle_id: &str) -> Permission {
+    let Ok(out) = Command::new("defaults")
+        .args(["read", "com.apple.ncprefs", "apps"])
+        .output()
+    else {
+        return Permission::Unknown;
+    };
+    if !out.status.success() {
+        return Permission::Unknown;
+    }
+    match app_flags(&String::from_utf8_lossy(&out.stdout), bundle_id) {
+        Some(flags) if flags & ALLOW_FLAG != 0 => Permission::Granted,
+        Some(_) => Permission::Denied,
+        None => Permission::NotDetermined,
+    }
+}
+
+/// Reads the current permission, and on a change emits
+/// `notification-permission-changed` and updates the tray hint.
+pub fn check(app: &AppHandle) -> Permission {
+    let permission = read_permission(&app.config().identifier);
+    let state = app.state::<PermissionState>();
+    let previous = state.last.lock().unwrap().replace(permission);
+    if previous != Some(permission) {
+        if permission == Permission::Denied {
+            log::warn!("Notifications are turned off for the app in System Settings");
+        }
+        tray::set_notification_hint(app, permission == Permission::Denied);
+        let _ = app.emit("notification-permission-changed", permission);
+    }
+    permission
+}
+
+/// Opens the Notifications pane of System Settings.
+pub fn open_settings() -> Result<(), String> {
+    Command::new("open")
+        .arg(SETTINGS_URL)
+        .status()
+        .map_err(|e| format!("Failed to open System Settings: {}", e))
+        .map(|_| ())
+}
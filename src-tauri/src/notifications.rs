@@ -1,98 +1,456 @@
 //! Tracks per-metric threshold crossings and fires macOS notifications
-//! only when a metric transitions from below to above the threshold.
+//! only when a metric transitions from below to above the threshold. A
+//! metric re-arms only after dropping a few points below the threshold, and
+//! never alerts again sooner than the minimum re-notify interval, so values
+//! hovering around the threshold don't flap; a crossing inside that interval
+//! is sent by the first fetch after it ends, if the metric is still above.
+//! Optionally, a metric that stays above a critical level keeps reminding
+//! every few minutes with growing urgency.
+//! Both say how much the metric rose since the previous fetch.
+//! Also raises a separate alert when usage climbs while the user is away, and
+//! an informational one when a provider reports a different plan.
+//!
+//! Threshold, limit-reached and reminder alerts are also forwarded to any
+//! external channels the user enabled (see `alerts`).
+//!
+//! While a macOS Focus mode is on, routine alerts (and optionally critical
+//! ones) are held back and summarized in one notification once Focus ends.
+//! Focus is polled while anything is held, so the summary arrives shortly
+//! after it ends rather than with the next fetch.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-use tauri::AppHandle;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
 use tauri_plugin_notification::NotificationExt;
 
+use crate::alerts::{self, Alert, AlertKind};
+use crate::focus;
+use crate::history::HistoryState;
+use crate::i18n;
+use crate::notification_permission;
+use crate::privacy;
+use crate::settings::{Settings, SettingsState};
 use crate::usage_fetcher::UsageData;
 
+/// How often Focus is checked while alerts are held back.
+const FOCUS_POLL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Threshold crossings and informational notes
+    Routine,
+    /// Limit reached or other events that need attention now
+    Critical,
+}
+
+/// Shows a notification with the sound chosen for its severity.
+pub fn send(app: &AppHandle, severity: Severity, title: &str, body: &str) {
+    let s = app.state::<SettingsState>().get();
+    if s.suppress_during_focus
+        && (severity == Severity::Routine || !s.focus_allow_critical)
+        && focus::is_active()
+    {
+        app.state::<NotificationState>()
+            .suppressed
+            .lock()
+            .unwrap()
+            .push(SuppressedAlert {
+                ts: chrono::Utc::now().timestamp(),
+                critical: severity == Severity::Critical,
+                title: title.into(),
+                body: body.into(),
+            });
+        watch_focus(app);
+        return;
+    }
+
+    // Still sent when off, but the check raises the tray hint
+    notification_permission::check(app);
+
+    let style = match severity {
+        Severity::Routine => s.routine_alert,
+        Severity::Critical => s.critical_alert,
+    };
+
+    let mut builder = app.notification().builder().title(title).body(body);
+    if style.silent {
+        builder = builder.silent();
+    } else {
+        builder = builder.sound(style.sound.unwrap_or_else(|| "default".into()));
+    }
+    let _ = builder.show();
+}
+
+/// An alert held back because Focus was on.
+#[derive(Debug, Clone, Serialize)]
+pub struct SuppressedAlert {
+    /// Unix timestamp (seconds)
+    pub ts: i64,
+    pub critical: bool,
+    pub title: String,
+    pub body: String,
+}
+
+/// Alert state for one metric.
+#[derive(Debug, Clone, Copy, Default)]
+struct Mark {
+    /// Already notified for the current crossing
+    notified: bool,
+    last_sent: Option<Instant>,
+    /// Reminders sent since the crossing
+    reminders: u32,
+}
+
 /// Tracks which metrics have already triggered a notification so we
 /// don't spam the user on every refresh while they're above threshold.
 pub struct NotificationState {
-    /// Maps metric key -> whether we've already notified for this crossing
-    notified: Mutex<HashMap<String, bool>>,
+    /// Maps metric key -> alert state for the current crossing
+    notified: Mutex<HashMap<String, Mark>>,
+    /// Maps metric key -> percent and Unix time at the previous fetch, for
+    /// the change shown in alerts
+    previous: Mutex<HashMap<String, (f64, i64)>>,
+    /// Alerts suppressed during Focus, oldest first
+    suppressed: Mutex<Vec<SuppressedAlert>>,
+    /// A task is polling for the end of Focus
+    watching: Mutex<bool>,
+    /// Maps provider -> percents when the user went away, for idle spikes
+    away: Mutex<HashMap<String, AwayBaseline>>,
+}
+
+/// Usage when the user went away, and the metrics already alerted since.
+struct AwayBaseline {
+    percents: HashMap<String, f64>,
+    alerted: HashSet<String>,
 }
 
 impl NotificationState {
     pub fn new() -> Self {
         Self {
             notified: Mutex::new(HashMap::new()),
+            previous: Mutex::new(HashMap::new()),
+            suppressed: Mutex::new(Vec::new()),
+            watching: Mutex::new(false),
+            away: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn suppressed(&self) -> Vec<SuppressedAlert> {
+        self.suppressed.lock().unwrap().clone()
+    }
+}
+
+/// Polls Focus until it ends, then flushes the held alerts. Only one poller
+/// runs at a time.
+fn watch_focus(app: &AppHandle) {
+    {
+        let state = app.state::<NotificationState>();
+        let mut watching = state.watching.lock().unwrap();
+        if *watching {
+            return;
         }
+        *watching = true;
     }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(FOCUS_POLL).await;
+            let state = app.state::<NotificationState>();
+            if state.suppressed.lock().unwrap().is_empty() || !focus::is_active() {
+                *state.watching.lock().unwrap() = false;
+                break;
+            }
+        }
+        flush_suppressed(&app);
+    });
+}
+
+/// Once Focus has ended, sends one summary of the alerts it held back.
+pub fn flush_suppressed(app: &AppHandle) {
+    let state = app.state::<NotificationState>();
+    if state.suppressed.lock().unwrap().is_empty() || focus::is_active() {
+        return;
+    }
+    let alerts = std::mem::take(&mut *state.suppressed.lock().unwrap());
+    let title = if alerts.len() == 1 {
+        i18n::t("notify.focus_one").to_string()
+    } else {
+        i18n::tf("notify.focus_many", &[&alerts.len()])
+    };
+    let body = alerts
+        .iter()
+        .map(|a| a.title.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let severity = if alerts.iter().any(|a| a.critical) {
+        Severity::Critical
+    } else {
+        Severity::Routine
+    };
+    send(app, severity, &title, &body);
 }
 
 struct Metric {
+    /// "session", "weekly", "sonnet", "extra", or a model family like "opus"
+    metric: String,
     key: String,
     label: String,
     percent: f64,
     reset_info: String,
 }
 
-/// Check usage data against threshold and fire notifications for any
-/// metrics that just crossed above it. Call this after every successful fetch.
-pub fn check_and_notify(
-    app: &AppHandle,
-    provider: &str,
-    data: &UsageData,
-    threshold: u32,
-    enabled: bool,
-    state: &NotificationState,
-) {
-    if !enabled || threshold == 0 {
-        return;
+/// The rise since the previous fetch, e.g. "(+6% in last 5 min)", or None
+/// without an earlier sample or a rise that rounds to a whole point.
+fn delta_text(previous: Option<(f64, i64)>, percent: f64, now: i64) -> Option<String> {
+    let (before, at) = previous?;
+    let rise = (percent - before).round();
+    if rise < 1.0 {
+        return None;
     }
+    let mins = ((now - at) / 60).max(1);
+    Some(if mins < 120 {
+        i18n::tf("notify.delta_minutes", &[&format!("{:.0}", rise), &mins])
+    } else {
+        i18n::tf("notify.delta_hours", &[&format!("{:.0}", rise), &(mins / 60)])
+    })
+}
 
-    let threshold_f = threshold as f64;
+/// Adds the rise since the previous fetch to an alert title, if there was one.
+fn with_delta(title: String, delta: &Option<String>) -> String {
+    match delta {
+        Some(d) => format!("{} {}", title, d),
+        None => title,
+    }
+}
 
-    let metrics = [
+/// Every alertable metric of `data`, with its display label.
+fn metrics(provider: &str, data: &UsageData, settings: &Settings) -> Vec<Metric> {
+    let name = settings.display_name(provider);
+    let mut metrics = vec![
         Metric {
+            metric: "session".into(),
             key: format!("{}_session", provider),
-            label: format!("{} session", provider),
+            label: i18n::tf("notify.metric_session", &[&name]),
             percent: data.session.percent_used,
             reset_info: data.session.reset_info.clone(),
         },
         Metric {
+            metric: "weekly".into(),
             key: format!("{}_weekly", provider),
-            label: format!("{} weekly", provider),
+            label: i18n::tf("notify.metric_weekly", &[&name]),
             percent: data.weekly_all.percent_used,
             reset_info: data.weekly_all.reset_info.clone(),
         },
         Metric {
+            metric: "sonnet".into(),
             key: format!("{}_sonnet", provider),
             label: data.weekly_sonnet.label.clone(),
             percent: data.weekly_sonnet.percent_used,
             reset_info: data.weekly_sonnet.reset_info.clone(),
         },
         Metric {
+            metric: "extra".into(),
             key: format!("{}_extra", provider),
-            label: format!("{} extra usage", provider),
+            label: i18n::tf("notify.metric_extra", &[&name]),
             percent: data.extra.percent_used,
-            reset_info: data.extra.reset_date.clone(),
+            reset_info: format!(
+                "{} - {}",
+                privacy::money(settings.privacy_mode, data.extra.dollars_spent),
+                data.extra.reset_date
+            ),
         },
     ];
+    metrics.extend(data.model_windows.iter().map(|w| Metric {
+        metric: w.family.clone(),
+        key: format!("{}_{}", provider, w.family),
+        label: format!("{} {}", name, w.metric.label),
+        percent: w.metric.percent_used,
+        reset_info: w.metric.reset_info.clone(),
+    }));
+    metrics
+}
+
+/// Check usage data against threshold and fire notifications for any
+/// metrics that just crossed above it. Call this after every successful fetch.
+/// Metrics listed in `settings.muted_metrics` never alert, and
+/// `settings.metric_thresholds` overrides the threshold per metric.
+pub fn check_and_notify(
+    app: &AppHandle,
+    provider: &str,
+    data: &UsageData,
+    settings: &Settings,
+    state: &NotificationState,
+) {
+    if !settings.notifications_enabled {
+        return;
+    }
+
+    let min_gap = Duration::from_secs(settings.renotify_min_secs);
+    let muted = &settings.muted_metrics;
+    let reminder_level = settings.reminder_level as f64;
+    let reminder_gap = Duration::from_secs(settings.reminder_interval_mins * 60);
+    let metrics = metrics(provider, data, settings);
 
     let mut notified = state.notified.lock().unwrap();
+    let now = chrono::Utc::now().timestamp();
+    let deltas: HashMap<String, Option<String>> = {
+        let mut previous = state.previous.lock().unwrap();
+        metrics
+            .iter()
+            .map(|m| {
+                let before = previous.insert(m.key.clone(), (m.percent, now));
+                (m.key.clone(), delta_text(before, m.percent, now))
+            })
+            .collect()
+    };
+
+    for m in metrics
+        .iter()
+        .filter(|m| !muted.contains(&m.metric) && !muted.contains(&m.key))
+    {
+        let threshold = settings.threshold_for(&m.key, &m.metric);
+        if threshold == 0 {
+            continue;
+        }
+        let threshold_f = threshold as f64;
+        let rearm_below = threshold_f - settings.notify_hysteresis_points as f64;
+        let mark = notified.entry(m.key.clone()).or_default();
+
+        if m.percent >= threshold_f && !mark.notified {
+            // Crossed above threshold - fire notification, or if one went out
+            // too recently (the metric is flapping around the threshold),
+            // leave the crossing pending for the first fetch after the gap
+            if mark.last_sent.is_some_and(|t| t.elapsed() < min_gap) {
+                continue;
+            }
+            mark.notified = true;
+            let title = with_delta(
+                i18n::tf(
+                    "notify.threshold",
+                    &[&m.label, &format!("{:.0}", m.percent)],
+                ),
+                &deltas[&m.key],
+            );
+            let (severity, kind) = if m.percent >= 100.0 {
+                (Severity::Critical, AlertKind::LimitReached)
+            } else {
+                (Severity::Routine, AlertKind::Threshold)
+            };
+            send(app, severity, &title, &m.reset_info);
+            alerts::dispatch(
+                app,
+                Alert::new(kind, severity, provider, &title, &m.reset_info)
+                    .with_metric(&m.label, m.percent, data),
+            );
+            mark.last_sent = Some(Instant::now());
+        } else if mark.notified
+            && settings.reminders_enabled
+            && m.percent >= reminder_level
+            && mark.last_sent.is_some_and(|t| t.elapsed() >= reminder_gap)
+        {
+            // Still above the critical level - remind, more urgently each time
+            mark.reminders += 1;
+            let title = with_delta(
+                i18n::tf(
+                    "notify.reminder",
+                    &[&m.label, &format!("{:.0}", m.percent), &mark.reminders],
+                ),
+                &deltas[&m.key],
+            );
+            let severity = if mark.reminders >= 2 || m.percent >= 100.0 {
+                Severity::Critical
+            } else {
+                Severity::Routine
+            };
+            send(app, severity, &title, &m.reset_info);
+            alerts::dispatch(
+                app,
+                Alert::new(AlertKind::Reminder, severity, provider, &title, &m.reset_info)
+                    .with_metric(&m.label, m.percent, data),
+            );
+            mark.last_sent = Some(Instant::now());
+        } else if m.percent < rearm_below && mark.notified {
+            // Dropped clearly below threshold - re-arm
+            mark.notified = false;
+            mark.reminders = 0;
+        }
+    }
+}
+
+/// Fire a stronger alert for any metric that rose by at least
+/// `settings.idle_spike_points` since the user went away from the computer,
+/// which usually means an unattended agent is burning quota. The baseline is
+/// the last sample before going away; each metric alerts once per away period.
+pub fn check_idle_spike(
+    app: &AppHandle,
+    provider: &str,
+    data: &UsageData,
+    away: bool,
+    settings: &Settings,
+    state: &NotificationState,
+) {
+    let mut baselines = state.away.lock().unwrap();
+    if !away {
+        baselines.remove(provider);
+        return;
+    }
+    let baseline = baselines.entry(provider.into()).or_insert_with(|| {
+        let mut percents = app.state::<HistoryState>().latest(provider);
+        if percents.is_empty() {
+            percents = data.metric_percents().into_iter().collect();
+        }
+        AwayBaseline {
+            percents,
+            alerted: HashSet::new(),
+        }
+    });
+    let min_points = settings.idle_spike_points;
+    if !settings.notifications_enabled || min_points == 0 {
+        return;
+    }
 
-    for m in &metrics {
-        let was_notified = notified.get(&m.key).copied().unwrap_or(false);
-
-        if m.percent >= threshold_f && !was_notified {
-            // Crossed above threshold - fire notification
-            let title = format!("{} at {:.0}%", m.label, m.percent);
-            let body = m.reset_info.clone();
-            let _ = app
-                .notification()
-                .builder()
-                .title(&title)
-                .body(&body)
-                .show();
-            notified.insert(m.key.clone(), true);
-        } else if m.percent < threshold_f && was_notified {
-            // Dropped back below threshold - reset
-            notified.insert(m.key.clone(), false);
+    for m in metrics(provider, data, settings) {
+        let Some(start) = baseline.percents.get_mut(&m.metric) else {
+            continue;
+        };
+        // A window that reset while away counts from its new low
+        if m.percent < *start {
+            *start = m.percent;
         }
+        let rise = m.percent - *start;
+        if rise >= min_points as f64 && baseline.alerted.insert(m.metric.clone()) {
+            let title = i18n::tf("notify.idle_spike", &[&m.label, &format!("{:.0}", rise)]);
+            let body = i18n::tf("notify.idle_spike_body", &[&format!("{:.0}", m.percent)]);
+            send(app, Severity::Critical, &title, &body);
+        }
+    }
+}
+
+/// Notes a plan change (upgrade/downgrade) in history and tells the user, so
+/// limits that suddenly behave differently are explainable. The first plan
+/// seen for a provider is recorded silently.
+pub fn check_plan_change(
+    app: &AppHandle,
+    provider: &str,
+    data: &UsageData,
+    history: &HistoryState,
+    enabled: bool,
+) {
+    let Some(plan) = data.plan.as_deref() else {
+        return;
+    };
+    let previous = history.latest_annotation(provider, "plan");
+    if previous.as_deref() == Some(plan) {
+        return;
+    }
+    let _ = history.annotate(provider, "plan", plan);
+    if let (Some(old), true) = (previous, enabled) {
+        let name = app.state::<SettingsState>().get().display_name(provider);
+        let title = i18n::tf("notify.plan_changed", &[&name]);
+        let body = i18n::tf("notify.plan_changed_body", &[&old, &plan]);
+        send(app, Severity::Routine, &title, &body);
     }
 }
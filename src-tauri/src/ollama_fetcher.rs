@@ -0,0 +1,225 @@
+//! Watches a local Ollama server, so it is visible when work runs on a local
+//! model instead of using cloud quota. Purely informational: nothing here
+//! counts against a limit.
+//!
+//! Calls `GET {base_url}/api/ps`, which lists the loaded models. Ollama has no
+//! request counter, but it pushes a model's `expires_at` forward on every
+//! request, so a poll that sees it move (or a newly loaded model) counts as a
+//! request. Several requests between two polls count once, and models kept
+//! loaded forever (`keep_alive: -1`) never move, so the count is a lower bound.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use reqwest::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::http_client;
+
+/// Where `ollama serve` listens unless `OLLAMA_HOST` says otherwise.
+pub const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+const DEFAULT_PORT: &str = "11434";
+
+/// How far back `requests_last_hour` looks.
+const REQUEST_WINDOW_MINS: i64 = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalModel {
+    pub name: String,
+    pub size_bytes: u64,
+    /// Part of the model held in GPU memory
+    pub vram_bytes: u64,
+    /// RFC 3339; when Ollama unloads the model if it stays unused
+    pub expires_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalActivityData {
+    /// False when nothing answers at the base URL
+    pub running: bool,
+    pub models: Vec<LocalModel>,
+    /// Polls in the last hour that saw a model used (a lower bound on requests)
+    pub requests_last_hour: u32,
+    /// RFC 3339, last time a model was seen in use
+    pub last_active_at: Option<String>,
+    pub fetched_at: String,
+}
+
+/// Per-model expiry at the last poll, and the polls that saw a request.
+#[derive(Default)]
+struct Activity {
+    expiries: HashMap<String, DateTime<FixedOffset>>,
+    requests: VecDeque<DateTime<Utc>>,
+    last_active_at: Option<DateTime<Utc>>,
+}
+
+pub struct OllamaState {
+    pub last_data: Mutex<Option<LocalActivityData>>,
+    activity: Mutex<Activity>,
+}
+
+impl OllamaState {
+    pub fn new() -> Self {
+        Self {
+            last_data: Mutex::new(None),
+            activity: Mutex::new(Activity::default()),
+        }
+    }
+
+    /// Counts the models whose expiry moved since the last poll and builds
+    /// the data for this poll.
+    pub fn observe(&self, running: bool, models: Vec<LocalModel>) -> LocalActivityData {
+        let now = Utc::now();
+        let mut activity = self.activity.lock().unwrap();
+        let mut expiries = HashMap::new();
+        for m in &models {
+            let Some(expires) = m
+                .expires_at
+                .as_deref()
+                .and_then(|e| DateTime::parse_from_rfc3339(e).ok())
+            else {
+                continue;
+            };
+            let used = activity
+                .expiries
+                .get(&m.name)
+                .is_none_or(|previous| expires > *previous);
+            if used {
+                activity.requests.push_back(now);
+                activity.last_active_at = Some(now);
+            }
+            expiries.insert(m.name.clone(), expires);
+        }
+        activity.expiries = expiries;
+
+        let cutoff = now - Duration::minutes(REQUEST_WINDOW_MINS);
+        while activity.requests.front().is_some_and(|t| *t < cutoff) {
+            activity.requests.pop_front();
+        }
+
+        LocalActivityData {
+            running,
+            models,
+            requests_last_hour: activity.requests.len() as u32,
+            last_active_at: activity.last_active_at.map(|t| t.to_rfc3339()),
+            fetched_at: now.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PsResponse {
+    #[serde(default)]
+    models: Vec<PsModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PsModel {
+    name: String,
+    #[serde(default)]
+    size: u64,
+    #[serde(default)]
+    size_vram: u64,
+    #[serde(default)]
+    expires_at: Option<String>,
+}
+
+/// The server `OLLAMA_HOST` names (e.g. "0.0.0.0:11434", "http://box:8080",
+/// "[::1]:11434"), as a base URL to connect to, or `DEFAULT_BASE_URL`. As in
+/// Ollama, a host without a scheme defaults to port 11434 and one with a
+/// scheme to that scheme's port. A host that listens on every interface is
+/// reached on localhost.
+fn default_base_url() -> String {
+    let Ok(host) = std::env::var("OLLAMA_HOST") else {
+        return DEFAULT_BASE_URL.into();
+    };
+    let host = host.trim().trim_end_matches('/');
+    if host.is_empty() {
+        return DEFAULT_BASE_URL.into();
+    }
+    let (scheme, rest, default_port) = match host.split_once("://") {
+        Some((scheme, rest)) => (scheme, rest, None),
+        None => ("http", host, Some(DEFAULT_PORT)),
+    };
+    let (name, port) = split_host_port(rest);
+    let name = match name {
+        "" | "0.0.0.0" | "::" => "localhost".to_string(),
+        // IPv6 addresses are bracketed in URLs
+        name if name.contains(':') => format!("[{}]", name),
+        name => name.to_string(),
+    };
+    match port.or(default_port) {
+        Some(port) => format!("{}://{}:{}", scheme, name, port),
+        None => format!("{}://{}", scheme, name),
+    }
+}
+
+/// Splits "host:port", "[v6]:port", "[v6]" or a bare IPv6 address into the
+/// host without brackets and the port, if one was given.
+fn split_host_port(s: &str) -> (&str, Option<&str>) {
+    let is_port = |p: &&str| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit());
+    if let Some(rest) = s.strip_prefix('[') {
+        return match rest.split_once(']') {
+            Some((name, tail)) => (name, tail.strip_prefix(':').filter(is_port)),
+            None => (rest, None),
+        };
+    }
+    match s.split_once(':') {
+        // More than one colon without brackets is a bare IPv6 address
+        Some((name, port)) if !port.contains(':') => (name, Some(port).filter(is_port)),
+        _ => (s, None),
+    }
+}
+
+/// `base_url` from settings, else the `OLLAMA_HOST` server.
+fn resolve_base_url(base_url: Option<&str>) -> Result<String, String> {
+    http_client::normalize_base_url(&base_url.map_or_else(default_base_url, String::from))
+}
+
+fn ps_request(client: &Client, base_url: &str) -> RequestBuilder {
+    client
+        .get(format!("{}/api/ps", base_url))
+        .header("Accept", "application/json")
+}
+
+/// The `/api/ps` payload as returned, for debugging schema changes.
+pub async fn fetch_raw(client: &Client, base_url: Option<&str>) -> Result<Value, String> {
+    let base_url = resolve_base_url(base_url)?;
+    http_client::raw_json("Ollama", ps_request(client, &base_url)).await
+}
+
+/// The loaded models, or `None` when the server isn't running. Ollama being
+/// off is a normal state, not a failure worth backing off from.
+pub async fn fetch_models(
+    client: &Client,
+    base_url: Option<&str>,
+) -> Result<Option<Vec<LocalModel>>, String> {
+    let base_url = resolve_base_url(base_url)?;
+    let resp = match ps_request(client, &base_url).send().await {
+        Ok(resp) => resp,
+        Err(e) if e.is_connect() => return Ok(None),
+        Err(e) => return Err(http_client::request_error("Ollama", e)),
+    };
+    if !resp.status().is_success() {
+        return Err(format!("Ollama returned {}", resp.status()));
+    }
+    let payload: PsResponse = resp
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Ollama response: {}", e))?;
+
+    Ok(Some(
+        payload
+            .models
+            .into_iter()
+            .map(|m| LocalModel {
+                name: m.name,
+                size_bytes: m.size,
+                vram_bytes: m.size_vram,
+                expires_at: m.expires_at,
+            })
+            .collect(),
+    ))
+}
@@ -0,0 +1,189 @@
+//! Capability detection for the first-run setup wizard.
+//!
+//! Checks what each provider needs on this Mac without fetching anything or
+//! touching encrypted data, so the report is quick and never triggers a
+//! Keychain prompt for the Claude cookie key. [`reconfigure`] re-runs the
+//! detection as a recovery path after signing in to a provider again.
+//!
+//! Providers found unconfigured at startup are left out of scheduling (and so
+//! the tray) instead of failing on every tick. They are checked again when the
+//! user asks, or when their credential files appear.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::cookie_reader::SafeStorageState;
+use crate::provider::Provider;
+use crate::scheduler::{self, SchedulerState};
+use crate::settings::{Settings, SettingsState};
+use crate::usage_fetcher::UsageState;
+use crate::{codex_fetcher, cookie_reader, litellm_fetcher, openrouter_keychain};
+
+const CLAUDE_APP: &str = "Applications/Claude.app";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderCapability {
+    pub provider: Provider,
+    /// The prerequisite software is present, so the provider can be set up here
+    pub available: bool,
+    /// Credentials were found; the provider should work without further steps
+    pub configured: bool,
+    /// What was found, or what the user still needs to do
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CapabilityReport {
+    pub providers: Vec<ProviderCapability>,
+    pub onboarding_complete: bool,
+}
+
+fn claude_capability(settings: &Settings) -> ProviderCapability {
+    // System-wide or per-user install
+    let app_installed = Path::new("/").join(CLAUDE_APP).exists()
+        || dirs::home_dir().is_some_and(|home| home.join(CLAUDE_APP).exists());
+    let picked = settings.claude_cookies_path.as_deref().map(Path::new);
+    let signed_in = cookie_reader::sources(picked).iter().any(|p| p.exists());
+
+    let detail = match (app_installed, signed_in) {
+        (_, true) => "Claude desktop app session found",
+        (true, false) => "Claude desktop app installed - sign in to it once",
+        (false, false) => "Install the Claude desktop app and sign in",
+    };
+    ProviderCapability {
+        provider: Provider::Claude,
+        available: app_installed || signed_in,
+        configured: signed_in,
+        detail: detail.into(),
+    }
+}
+
+fn codex_capability() -> ProviderCapability {
+    let auth = codex_fetcher::auth_path();
+    let cli_dir = auth
+        .as_ref()
+        .and_then(|p| p.parent())
+        .is_some_and(|d| d.exists());
+    let logged_in = auth.is_some_and(|p| p.exists());
+
+    let detail = match (cli_dir, logged_in) {
+        (_, true) => "Codex CLI credentials found",
+        (true, false) => "Codex CLI found - run `codex --login`",
+        (false, false) => "Install the Codex CLI and run `codex --login`",
+    };
+    ProviderCapability {
+        provider: Provider::Codex,
+        available: cli_dir,
+        configured: logged_in,
+        detail: detail.into(),
+    }
+}
+
+fn openrouter_capability() -> ProviderCapability {
+    let keychain = openrouter_keychain::get_openrouter_key_status()
+        .map(|s| s.configured)
+        .unwrap_or(false);
+    let env = std::env::var("OPENROUTER_API_KEY").is_ok_and(|k| !k.trim().is_empty());
+
+    let detail = if keychain {
+        "API key stored in Keychain"
+    } else if env {
+        "API key found in OPENROUTER_API_KEY"
+    } else {
+        "Optional - paste an OpenRouter API key in Settings"
+    };
+    ProviderCapability {
+        provider: Provider::OpenRouter,
+        available: true,
+        configured: keychain || env,
+        detail: detail.into(),
+    }
+}
+
+fn litellm_capability(settings: &Settings) -> ProviderCapability {
+    let has_url = settings.litellm_base_url.is_some();
+    let has_key = litellm_fetcher::read_key().is_ok_and(|k| k.is_some());
+
+    let detail = match (has_url, has_key) {
+        (true, true) => "Gateway URL and key set",
+        (true, false) => "Add the gateway key in Settings",
+        (false, _) => "Optional - add a LiteLLM gateway URL and key in Settings",
+    };
+    ProviderCapability {
+        provider: Provider::LiteLlm,
+        available: true,
+        configured: has_url && has_key,
+        detail: detail.into(),
+    }
+}
+
+fn ollama_capability(settings: &Settings) -> ProviderCapability {
+    let detail = if settings.ollama_enabled {
+        "Watching the local Ollama server"
+    } else {
+        "Optional - turn on the Ollama monitor in Settings"
+    };
+    ProviderCapability {
+        provider: Provider::Ollama,
+        available: true,
+        configured: settings.ollama_enabled,
+        detail: detail.into(),
+    }
+}
+
+pub fn capability_report(settings: &Settings) -> CapabilityReport {
+    CapabilityReport {
+        providers: vec![
+            claude_capability(settings),
+            codex_capability(),
+            openrouter_capability(),
+            litellm_capability(settings),
+            ollama_capability(settings),
+        ],
+        onboarding_complete: settings.onboarding_complete,
+    }
+}
+
+/// Builds the report and marks the providers without credentials as not
+/// configured, so the scheduler skips them.
+pub fn detect(app: &AppHandle) -> CapabilityReport {
+    let report = capability_report(&app.state::<SettingsState>().get());
+    let missing = report
+        .providers
+        .iter()
+        .filter(|c| !c.configured)
+        .map(|c| c.provider)
+        .collect::<HashSet<_>>();
+    scheduler::cancel_fetches(app, |p| missing.contains(&p));
+    app.state::<SchedulerState>().set_unconfigured(missing);
+    report
+}
+
+/// Marks unconfigured providers whose credential files have appeared as
+/// configured. Only checks files, so it is cheap enough for every tick.
+pub fn recheck_files(app: &AppHandle) {
+    let scheduler = app.state::<SchedulerState>();
+    let settings = app.state::<SettingsState>().get();
+    for capability in [claude_capability(&settings), codex_capability()] {
+        if capability.configured {
+            scheduler.set_configured(capability.provider);
+        }
+    }
+}
+
+/// Re-detects credentials, forgets cached Keychain lookups, cookie sources
+/// and per-provider errors and backoff, then fetches everything afresh.
+/// Emits the new report as `providers-reconfigured`.
+pub fn reconfigure(app: &AppHandle) -> CapabilityReport {
+    app.state::<SafeStorageState>().reset();
+    *app.state::<UsageState>().cookie_source.lock().unwrap() = None;
+    app.state::<SchedulerState>().reset_health();
+
+    let report = detect(app);
+    let _ = app.emit("providers-reconfigured", &report);
+    scheduler::refresh_now(app);
+    report
+}
@@ -5,11 +5,14 @@
 
 use std::sync::Mutex;
 
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder, StatusCode};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::http_cache::{self, Fetched, Validators};
+use crate::http_client;
 use crate::openrouter_keychain;
+use crate::secret::Secret;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenRouterCreditsData {
@@ -17,6 +20,9 @@ pub struct OpenRouterCreditsData {
     pub total_usage: f64,
     pub remaining_credits: f64,
     pub fetched_at: String,
+    /// Amounts replaced with zeros (privacy mode)
+    #[serde(default)]
+    pub redacted: bool,
 }
 
 pub struct OpenRouterState {
@@ -50,31 +56,48 @@ fn value_to_f64(v: Option<Value>) -> f64 {
     }
 }
 
-fn read_openrouter_key() -> Result<String, String> {
+fn read_openrouter_key() -> Result<Secret, String> {
     if let Some(key) = openrouter_keychain::read_openrouter_api_key()? {
         return Ok(key);
     }
 
     let key = std::env::var("OPENROUTER_API_KEY")
         .map_err(|_| "OPENROUTER_API_KEY is not set".to_string())?;
-    let trimmed = key.trim();
-    if trimmed.is_empty() {
+    let key = Secret::from_bytes(key.into_bytes());
+    if key.is_empty() {
         return Err("OPENROUTER_API_KEY is empty".into());
     }
-    Ok(trimmed.to_string())
+    Ok(key)
 }
 
-pub async fn fetch_openrouter_credits(client: &Client) -> Result<OpenRouterCreditsData, String> {
-    let key = read_openrouter_key()?;
-
-    let resp = client
+fn credits_request(client: &Client, key: &Secret) -> RequestBuilder {
+    client
         .get("https://openrouter.ai/api/v1/credits")
-        .header("Authorization", format!("Bearer {}", key))
+        .bearer_auth(key.expose())
         .header("Accept", "application/json")
+}
+
+/// The credits payload as returned, for debugging schema changes.
+pub async fn fetch_raw(client: &Client) -> Result<Value, String> {
+    let key = read_openrouter_key()?;
+    http_client::raw_json("OpenRouter", credits_request(client, &key)).await
+}
+
+pub async fn fetch_openrouter_credits(
+    client: &Client,
+    validators: Option<&Validators>,
+) -> Result<Fetched<OpenRouterCreditsData>, String> {
+    let key = read_openrouter_key()?;
+
+    let req = credits_request(client, &key);
+    let resp = http_cache::conditional(req, validators)
         .send()
         .await
-        .map_err(|e| format!("OpenRouter request failed: {}", e))?;
+        .map_err(|e| http_client::request_error("OpenRouter", e))?;
 
+    if resp.status() == StatusCode::NOT_MODIFIED {
+        return Ok(Fetched::NotModified);
+    }
     if !resp.status().is_success() {
         let status = resp.status();
         let body = resp.text().await.unwrap_or_default();
@@ -85,6 +108,7 @@ pub async fn fetch_openrouter_credits(client: &Client) -> Result<OpenRouterCredi
         ));
     }
 
+    let fresh = Validators::from_response(&resp);
     let payload: OpenRouterCreditsResponse = resp
         .json()
         .await
@@ -99,10 +123,14 @@ pub async fn fetch_openrouter_credits(client: &Client) -> Result<OpenRouterCredi
     let total_usage = value_to_f64(data.total_usage);
     let remaining_credits = (total_credits - total_usage).max(0.0);
 
-    Ok(OpenRouterCreditsData {
-        total_credits,
-        total_usage,
-        remaining_credits,
-        fetched_at: chrono::Utc::now().to_rfc3339(),
-    })
+    Ok(Fetched::Fresh(
+        OpenRouterCreditsData {
+            total_credits,
+            total_usage,
+            remaining_credits,
+            fetched_at: chrono::Utc::now().to_rfc3339(),
+            redacted: false,
+        },
+        fresh,
+    ))
 }
@@ -3,6 +3,8 @@
 use serde::Serialize;
 use std::process::Command;
 
+use crate::secret::Secret;
+
 const SERVICE: &str = "com.israelmirsky.claude-codex-usage.openrouter";
 const ACCOUNT: &str = "openrouter_api_key";
 
@@ -22,7 +24,7 @@ fn mask_key(key: &str) -> String {
     format!("{start}...{end}")
 }
 
-pub fn read_openrouter_api_key() -> Result<Option<String>, String> {
+pub fn read_openrouter_api_key() -> Result<Option<Secret>, String> {
     let out = Command::new("security")
         .args([
             "find-generic-password",
@@ -36,7 +38,7 @@ pub fn read_openrouter_api_key() -> Result<Option<String>, String> {
         .map_err(|e| format!("Failed to query macOS Keychain: {}", e))?;
 
     if out.status.success() {
-        let key = String::from_utf8_lossy(&out.stdout).trim().to_string();
+        let key = Secret::from_bytes(out.stdout);
         if key.is_empty() {
             Ok(None)
         } else {
@@ -61,28 +63,21 @@ pub fn set_openrouter_api_key(api_key: &str) -> Result<(), String> {
         return Err("API key cannot be empty".into());
     }
 
-    let out = Command::new("security")
-        .args([
-            "add-generic-password",
-            "-a",
-            ACCOUNT,
-            "-s",
-            SERVICE,
-            "-w",
-            key,
-            "-U",
-        ])
-        .output()
-        .map_err(|e| format!("Failed to write to macOS Keychain: {}", e))?;
+    // Through Security.framework so the key never shows up in argv
+    write_password(key.as_bytes())
+        .map_err(|e| format!("Failed to save OpenRouter key to Keychain: {}", e))
+}
 
-    if out.status.success() {
-        Ok(())
-    } else {
-        Err(format!(
-            "Failed to save OpenRouter key to Keychain: {}",
-            String::from_utf8_lossy(&out.stderr).trim()
-        ))
-    }
+/// Adds the item, or updates it when it already exists.
+#[cfg(target_os = "macos")]
+fn write_password(secret: &[u8]) -> Result<(), String> {
+    security_framework::passwords::set_generic_password(SERVICE, ACCOUNT, secret)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn write_password(_secret: &[u8]) -> Result<(), String> {
+    Err("the Keychain is only available on macOS".into())
 }
 
 pub fn clear_openrouter_api_key() -> Result<(), String> {
@@ -111,7 +106,7 @@ pub fn get_openrouter_key_status() -> Result<OpenRouterKeyStatus, String> {
     Ok(match key {
         Some(raw) => OpenRouterKeyStatus {
             configured: true,
-            masked_key: Some(mask_key(&raw)),
+            masked_key: Some(mask_key(raw.expose())),
         },
         None => OpenRouterKeyStatus {
             configured: false,
@@ -0,0 +1,97 @@
+//! Weekly pacing: spreading the 7-day window evenly allows 100/7 = 14.3% a
+//! day. Usage is compared against that line to tell whether the user is ahead
+//! of pace (spending faster) or behind it, shown as a `+`/`-` after the
+//! provider's menu bar text when enabled.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::codex_fetcher::CodexState;
+use crate::usage_fetcher::{UsageData, UsageState};
+
+const WINDOW_DAYS: f64 = 7.0;
+/// Points either side of the line that still count as on pace.
+const TOLERANCE_POINTS: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pace {
+    Ahead,
+    OnTrack,
+    Behind,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PacingStatus {
+    pub provider: String,
+    pub used_percent: f64,
+    /// Where usage would be now at an even daily rate
+    pub ideal_percent: f64,
+    pub per_day_percent: f64,
+    /// `used_percent - ideal_percent`; positive means ahead of pace
+    pub difference: f64,
+    pub pace: Pace,
+}
+
+/// Pacing of a provider's weekly window, if it reports when the window resets.
+pub fn status(provider: &str, data: &UsageData, now: DateTime<Utc>) -> Option<PacingStatus> {
+    let weekly = &data.weekly_all;
+    let reset = DateTime::parse_from_rfc3339(weekly.resets_at.as_deref()?)
+        .ok()?
+        .with_timezone(&Utc);
+    let start = reset - Duration::days(WINDOW_DAYS as i64);
+    let elapsed_days = ((now - start).num_seconds() as f64 / 86_400.0).clamp(0.0, WINDOW_DAYS);
+
+    let per_day = 100.0 / WINDOW_DAYS;
+    let ideal = per_day * elapsed_days;
+    let difference = weekly.percent_used - ideal;
+    let pace = if difference > TOLERANCE_POINTS {
+        Pace::Ahead
+    } else if difference < -TOLERANCE_POINTS {
+        Pace::Behind
+    } else {
+        Pace::OnTrack
+    };
+    Some(PacingStatus {
+        provider: provider.to_string(),
+        used_percent: weekly.percent_used,
+        ideal_percent: ideal,
+        per_day_percent: per_day,
+        difference,
+        pace,
+    })
+}
+
+/// Pacing for every provider with cached weekly data.
+pub fn statuses(app: &AppHandle) -> Vec<PacingStatus> {
+    let now = Utc::now();
+    ["Claude", "Codex"]
+        .into_iter()
+        .filter_map(|p| status(p, &cached(app, p)?, now))
+        .collect()
+}
+
+fn cached(app: &AppHandle, provider: &str) -> Option<UsageData> {
+    match provider {
+        "Claude" => app.state::<UsageState>().last_data.lock().unwrap().clone(),
+        "Codex" => app.state::<CodexState>().last_data.lock().unwrap().clone(),
+        _ => None,
+    }
+}
+
+/// Menu bar marker for a provider id ("claude", "codex"): `+` ahead of pace,
+/// `-` behind it, nothing when on pace or unknown.
+pub fn marker(app: &AppHandle, provider: &str) -> &'static str {
+    let name = match provider {
+        "claude" => "Claude",
+        "codex" => "Codex",
+        _ => return "",
+    };
+    let pace = cached(app, name).and_then(|d| status(name, &d, Utc::now()));
+    match pace.map(|s| s.pace) {
+        Some(Pace::Ahead) => "+",
+        Some(Pace::Behind) => "-",
+        _ => "",
+    }
+}
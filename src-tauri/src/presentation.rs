@@ -0,0 +1,87 @@
+//! Read-only mini window for screen sharing: an always-on-top strip showing
+//! only usage percentages, with no account details or dollar amounts.
+//!
+//! The window never receives full provider data: it reads a redacted
+//! snapshot and gets updates through its own `presentation-updated` event.
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::codex_fetcher::CodexState;
+use crate::i18n;
+use crate::openrouter_fetcher::OpenRouterState;
+use crate::usage_fetcher::{UsageData, UsageState};
+
+pub const LABEL: &str = "presentation";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PresentationRow {
+    pub provider: &'static str,
+    pub session_percent: Option<f64>,
+    pub weekly_percent: Option<f64>,
+}
+
+fn usage_row(provider: &'static str, data: &UsageData) -> PresentationRow {
+    PresentationRow {
+        provider,
+        session_percent: Some(data.session.percent_used),
+        weekly_percent: Some(data.weekly_all.percent_used),
+    }
+}
+
+/// Percentages of every provider with data. OpenRouter credits are shown as
+/// the share used, never as amounts.
+pub fn snapshot(app: &AppHandle) -> Vec<PresentationRow> {
+    let mut rows = Vec::new();
+    if let Some(d) = app.state::<UsageState>().last_data.lock().unwrap().as_ref() {
+        rows.push(usage_row("Claude", d));
+    }
+    if let Some(d) = app.state::<CodexState>().last_data.lock().unwrap().as_ref() {
+        rows.push(usage_row("Codex", d));
+    }
+    if let Some(d) = app
+        .state::<OpenRouterState>()
+        .last_data
+        .lock()
+        .unwrap()
+        .as_ref()
+    {
+        if d.total_credits > 0.0 {
+            rows.push(PresentationRow {
+                provider: "OpenRouter",
+                session_percent: None,
+                weekly_percent: Some(d.total_usage / d.total_credits * 100.0),
+            });
+        }
+    }
+    rows
+}
+
+/// Pushes a fresh snapshot to the window, if it is open.
+pub fn refresh(app: &AppHandle) {
+    if app.get_webview_window(LABEL).is_some() {
+        let _ = app.emit_to(LABEL, "presentation-updated", snapshot(app));
+    }
+}
+
+/// Opens the window, or brings it forward if it is already open.
+pub fn open(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(LABEL) {
+        window.show().map_err(|e| e.to_string())?;
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+    WebviewWindowBuilder::new(
+        app,
+        LABEL,
+        WebviewUrl::App("index.html#presentation".into()),
+    )
+    .title(i18n::t("window.usage"))
+    .inner_size(220.0, 96.0)
+    .resizable(false)
+    .decorations(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .build()
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,239 @@
+//! Hover preview: resting the pointer on a menu bar item for a moment opens a
+//! small window under it with the full breakdown of every provider, and
+//! moving the pointer away closes it again. Handy for a quick look without
+//! clicking through to the widget.
+//!
+//! The window is created on first use and then only shown and hidden. It gets
+//! its data through its own `preview-updated` event.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{
+    AppHandle, Emitter, Manager, PhysicalPosition, Rect, WebviewUrl, WebviewWindow,
+    WebviewWindowBuilder,
+};
+
+use crate::codex_fetcher::CodexState;
+use crate::i18n;
+use crate::litellm_fetcher::LiteLlmState;
+use crate::openrouter_fetcher::OpenRouterState;
+use crate::privacy;
+use crate::provider::Provider;
+use crate::scheduler::SchedulerState;
+use crate::settings::SettingsState;
+use crate::usage_fetcher::{UsageData, UsageMetric, UsageState};
+use crate::widget_window;
+
+pub const LABEL: &str = "preview";
+/// Range accepted for the hover delay.
+pub const MIN_DELAY_MS: u64 = 100;
+pub const MAX_DELAY_MS: u64 = 3000;
+
+/// Logical size of the preview window
+const SIZE: (f64, f64) = (260.0, 220.0);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreviewLine {
+    pub label: String,
+    /// "42%" or an amount
+    pub value: String,
+    /// Reset time or other context, if any
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreviewSection {
+    /// Display name of the provider
+    pub provider: String,
+    pub lines: Vec<PreviewLine>,
+    /// The last refresh failed, so the lines may be stale
+    pub failing: bool,
+}
+
+pub struct PreviewState {
+    /// Bumped on every enter and leave, so a pending show knows it's stale
+    generation: Mutex<u64>,
+}
+
+impl PreviewState {
+    pub fn new() -> Self {
+        Self {
+            generation: Mutex::new(0),
+        }
+    }
+
+    fn bump(&self) -> u64 {
+        let mut generation = self.generation.lock().unwrap();
+        *generation += 1;
+        *generation
+    }
+}
+
+fn metric_line(metric: &UsageMetric) -> PreviewLine {
+    PreviewLine {
+        label: metric.label.clone(),
+        value: format!("{:.0}%", metric.percent_used),
+        detail: Some(metric.reset_info.clone()),
+    }
+}
+
+fn usage_lines(app: &AppHandle, data: &UsageData) -> Vec<PreviewLine> {
+    let mut lines = vec![metric_line(&data.session), metric_line(&data.weekly_all)];
+    // The third window only counts when the API reported one
+    if data.weekly_sonnet.resets_at.is_some() {
+        lines.push(metric_line(&data.weekly_sonnet));
+    }
+    lines.extend(data.model_windows.iter().map(|w| metric_line(&w.metric)));
+    lines.push(PreviewLine {
+        label: i18n::t("preview.extra").into(),
+        value: privacy::money(privacy::is_on(app), data.extra.dollars_spent),
+        detail: Some(
+            i18n::t(if data.extra.enabled {
+                "tooltip.on"
+            } else {
+                "tooltip.off"
+            })
+            .into(),
+        ),
+    });
+    lines
+}
+
+/// Every metric of every provider with cached data, in tray order.
+pub fn snapshot(app: &AppHandle) -> Vec<PreviewSection> {
+    let settings = app.state::<SettingsState>().get();
+    let scheduler = app.state::<SchedulerState>();
+    let section = |provider: Provider, lines| PreviewSection {
+        provider: settings.display_name(provider.name()),
+        lines,
+        failing: scheduler.is_failing(provider),
+    };
+    let mut sections = Vec::new();
+    if let Some(d) = app.state::<UsageState>().last_data.lock().unwrap().as_ref() {
+        sections.push(section(Provider::Claude, usage_lines(app, d)));
+    }
+    if let Some(d) = app.state::<CodexState>().last_data.lock().unwrap().as_ref() {
+        sections.push(section(Provider::Codex, usage_lines(app, d)));
+    }
+    if let Some(d) = app
+        .state::<OpenRouterState>()
+        .last_data
+        .lock()
+        .unwrap()
+        .as_ref()
+    {
+        let on = privacy::is_on(app);
+        let line = PreviewLine {
+            label: i18n::t("preview.credits").into(),
+            value: privacy::money(on, d.remaining_credits),
+            detail: Some(i18n::tf(
+                "tooltip.used_of",
+                &[
+                    &privacy::money(on, d.total_usage),
+                    &privacy::money(on, d.total_credits),
+                ],
+            )),
+        };
+        sections.push(section(Provider::OpenRouter, vec![line]));
+    }
+    if let Some(d) = app.state::<LiteLlmState>().last_data.lock().unwrap().as_ref() {
+        let on = privacy::is_on(app);
+        let line = match (d.remaining, d.max_budget) {
+            (Some(remaining), Some(budget)) => PreviewLine {
+                label: i18n::t("preview.budget").into(),
+                value: privacy::money(on, remaining),
+                detail: Some(i18n::tf(
+                    "tooltip.used_of",
+                    &[&privacy::money(on, d.spend), &privacy::money(on, budget)],
+                )),
+            },
+            _ => PreviewLine {
+                label: i18n::t("preview.spent").into(),
+                value: privacy::money(on, d.spend),
+                detail: d.budget_duration.clone(),
+            },
+        };
+        sections.push(section(Provider::LiteLlm, vec![line]));
+    }
+    sections
+}
+
+/// Pushes a fresh snapshot to the window while it is showing.
+pub fn refresh(app: &AppHandle) {
+    let showing = app
+        .get_webview_window(LABEL)
+        .is_some_and(|w| w.is_visible().unwrap_or(false));
+    if showing {
+        let _ = app.emit_to(LABEL, "preview-updated", snapshot(app));
+    }
+}
+
+fn build(app: &AppHandle) -> Result<WebviewWindow, String> {
+    WebviewWindowBuilder::new(app, LABEL, WebviewUrl::App("index.html#preview".into()))
+        .title(i18n::t("window.usage"))
+        .inner_size(SIZE.0, SIZE.1)
+        .resizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .visible(false)
+        .focused(false)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Shows the preview centered under the menu bar item at `rect`, unless the
+/// widget is already open.
+fn show(app: &AppHandle, rect: Rect) -> Result<(), String> {
+    let widget_open = app
+        .get_webview_window(widget_window::LABEL)
+        .is_some_and(|w| w.is_visible().unwrap_or(false));
+    if widget_open {
+        return Ok(());
+    }
+    let window = match app.get_webview_window(LABEL) {
+        Some(w) => w,
+        None => build(app)?,
+    };
+    let scale = window.scale_factor().map_err(|e| e.to_string())?;
+    let position = rect.position.to_physical::<f64>(scale);
+    let size = rect.size.to_physical::<f64>(scale);
+    let x = position.x + size.width / 2.0 - SIZE.0 * scale / 2.0;
+    let y = position.y + size.height;
+    window
+        .set_position(PhysicalPosition::new(x, y))
+        .map_err(|e| e.to_string())?;
+    let _ = app.emit_to(LABEL, "preview-updated", snapshot(app));
+    window.show().map_err(|e| e.to_string())
+}
+
+/// The pointer entered a menu bar item: shows the preview once it has rested
+/// there for the configured delay.
+pub fn hover(app: &AppHandle, rect: Rect) {
+    let settings = app.state::<SettingsState>().get();
+    if !settings.hover_preview {
+        return;
+    }
+    let generation = app.state::<PreviewState>().bump();
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(settings.hover_preview_delay_ms)).await;
+        if *handle.state::<PreviewState>().generation.lock().unwrap() != generation {
+            return;
+        }
+        if let Err(e) = show(&handle, rect) {
+            log::warn!("Failed to show the hover preview: {}", e);
+        }
+    });
+}
+
+/// The pointer left the menu bar item: cancels a pending preview and hides
+/// the one showing.
+pub fn leave(app: &AppHandle) {
+    app.state::<PreviewState>().bump();
+    if let Some(window) = app.get_webview_window(LABEL) {
+        let _ = window.hide();
+    }
+}
@@ -0,0 +1,265 @@
+//! Public pay-per-token API pricing, used to express subscription usage as
+//! what it would have cost on metered API billing.
+//!
+//! Prices are USD per million tokens and matched by model-name substring, most
+//! specific first. They are approximate list prices and only meant for
+//! plan-comparison estimates.
+//!
+//! The weekly equivalent takes the tokens the current weekly percentage stands
+//! for from `calibration` (tokens per point × points used) and prices them at
+//! the model mix of the last seven days of logs.
+//!
+//! Costs over a period also count days imported from other trackers, on the
+//! dates local logs don't cover.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::claude_logs::{self, TokenEntry};
+use crate::codex_logs::{self, CodexEvent, CodexEventKind, CodexTokens};
+use crate::calibration::{self, CapacityEstimate};
+use crate::history::HistoryState;
+
+struct ModelPrice {
+    /// Substring matched against the model id
+    pattern: &'static str,
+    input: f64,
+    output: f64,
+    cache_write: f64,
+    cache_read: f64,
+}
+
+const CLAUDE_PRICES: &[ModelPrice] = &[
+    ModelPrice { pattern: "opus-4-5", input: 5.0, output: 25.0, cache_write: 6.25, cache_read: 0.50 },
+    ModelPrice { pattern: "opus", input: 15.0, output: 75.0, cache_write: 18.75, cache_read: 1.50 },
+    ModelPrice { pattern: "sonnet", input: 3.0, output: 15.0, cache_write: 3.75, cache_read: 0.30 },
+    ModelPrice { pattern: "haiku-4", input: 1.0, output: 5.0, cache_write: 1.25, cache_read: 0.10 },
+    ModelPrice { pattern: "haiku", input: 0.80, output: 4.0, cache_write: 1.0, cache_read: 0.08 },
+];
+
+const OPENAI_PRICES: &[ModelPrice] = &[
+    ModelPrice { pattern: "mini", input: 0.25, output: 2.0, cache_write: 0.25, cache_read: 0.025 },
+    ModelPrice { pattern: "gpt-5", input: 1.25, output: 10.0, cache_write: 1.25, cache_read: 0.125 },
+    ModelPrice { pattern: "o3", input: 2.0, output: 8.0, cache_write: 2.0, cache_read: 0.50 },
+];
+
+/// Finds the price row for `model`, falling back to the `fallback` pattern's
+/// row for models we don't know about yet.
+fn lookup<'a>(table: &'a [ModelPrice], model: &str, fallback: &str) -> &'a ModelPrice {
+    let model = model.to_lowercase();
+    table
+        .iter()
+        .find(|p| model.contains(p.pattern))
+        .or_else(|| table.iter().find(|p| p.pattern == fallback))
+        .unwrap_or(&table[0])
+}
+
+fn per_million(tokens: u64, price: f64) -> f64 {
+    tokens as f64 * price / 1_000_000.0
+}
+
+fn claude_entry_cost(e: &TokenEntry) -> f64 {
+    let p = lookup(CLAUDE_PRICES, &e.model, "sonnet");
+    per_million(e.input_tokens, p.input)
+        + per_million(e.output_tokens, p.output)
+        + per_million(e.cache_creation_tokens, p.cache_write)
+        + per_million(e.cache_read_tokens, p.cache_read)
+}
+
+fn codex_tokens_cost(t: &CodexTokens) -> f64 {
+    let p = lookup(OPENAI_PRICES, &t.model, "gpt-5");
+    let uncached = t.input.saturating_sub(t.cached_input);
+    per_million(uncached, p.input)
+        + per_million(t.cached_input, p.cache_read)
+        + per_million(t.output, p.output)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelCost {
+    pub model: String,
+    pub tokens: u64,
+    pub cost_usd: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CostEquivalent {
+    pub provider: String,
+    /// Current weekly-window utilization
+    pub weekly_percent: f64,
+    /// Calibrated tokens per weekly point; None until there is enough data
+    pub tokens_per_percent: Option<f64>,
+    /// Tokens the week's usage so far stands for
+    pub tokens: Option<u64>,
+    /// What those tokens would have cost on API pricing
+    pub api_cost_usd: Option<f64>,
+    /// Cost of a fully used weekly window
+    pub projected_full_week_usd: Option<f64>,
+    /// Model mix of the last seven days of logs the price is weighted by
+    pub by_model: Vec<ModelCost>,
+}
+
+/// API-equivalent cost of one provider's logs over a fixed period.
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodCost {
+    pub provider: String,
+    pub tokens: u64,
+    pub api_cost_usd: f64,
+    pub by_model: Vec<ModelCost>,
+}
+
+/// Totals per-model token/cost pairs, most expensive model first.
+fn by_model(items: impl IntoIterator<Item = (String, u64, f64)>) -> Vec<ModelCost> {
+    let mut by_model: HashMap<String, (u64, f64)> = HashMap::new();
+    for (model, tokens, cost) in items {
+        let slot = by_model.entry(model).or_default();
+        slot.0 += tokens;
+        slot.1 += cost;
+    }
+
+    let mut by_model: Vec<ModelCost> = by_model
+        .into_iter()
+        .map(|(model, (tokens, cost_usd))| ModelCost {
+            model,
+            tokens,
+            cost_usd,
+        })
+        .collect();
+    by_model.sort_by(|a, b| b.cost_usd.total_cmp(&a.cost_usd));
+    by_model
+}
+
+/// Prices the weekly window's calibrated tokens at the average cost per token
+/// of `items`, which must count tokens the way `calibration` does.
+fn summarize(
+    provider: &str,
+    items: impl IntoIterator<Item = (String, u64, f64)>,
+    estimates: &[CapacityEstimate],
+) -> CostEquivalent {
+    let by_model = by_model(items);
+    let log_tokens: u64 = by_model.iter().map(|m| m.tokens).sum();
+    let log_cost: f64 = by_model.iter().map(|m| m.cost_usd).sum();
+    let weekly = estimates
+        .iter()
+        .find(|e| e.provider == provider)
+        .and_then(|e| e.windows.iter().find(|w| w.metric == "weekly"));
+    let weekly_percent = weekly.map_or(0.0, |w| w.percent_used);
+    let tokens_per_percent = weekly.and_then(|w| w.tokens_per_percent);
+    let cost_per_token = (log_tokens > 0).then(|| log_cost / log_tokens as f64);
+    let cost_of = |points: f64| Some(tokens_per_percent? * points * cost_per_token?);
+
+    CostEquivalent {
+        provider: provider.into(),
+        weekly_percent,
+        tokens_per_percent,
+        tokens: tokens_per_percent.map(|t| (t * weekly_percent).round() as u64),
+        api_cost_usd: cost_of(weekly_percent),
+        projected_full_week_usd: cost_of(100.0),
+        by_model,
+    }
+}
+
+fn claude_items(entries: &[TokenEntry]) -> impl Iterator<Item = (String, u64, f64)> + '_ {
+    entries.iter().map(|e| {
+        let tokens = e.billable_tokens() + e.cache_read_tokens;
+        (e.model.clone(), tokens, claude_entry_cost(e))
+    })
+}
+
+fn codex_items(events: &[CodexEvent]) -> impl Iterator<Item = (String, u64, f64)> + '_ {
+    events.iter().filter_map(|e| match &e.kind {
+        CodexEventKind::Tokens(t) => Some((t.model.clone(), t.total, codex_tokens_cost(t))),
+        _ => None,
+    })
+}
+
+/// API-equivalent cost of the current weekly window for each provider with
+/// local logs.
+pub fn cost_equivalents(history: &HistoryState) -> Vec<CostEquivalent> {
+    let since = chrono::Utc::now().timestamp() - 7 * 86_400;
+    let estimates = calibration::capacity_estimates(history);
+    let mut out = Vec::new();
+
+    if let Ok(entries) = claude_logs::read_entries(since) {
+        // Calibration counts billable tokens, so cache reads go into the price
+        let items = entries
+            .iter()
+            .map(|e| (e.model.clone(), e.billable_tokens(), claude_entry_cost(e)));
+        out.push(summarize("Claude", items, &estimates));
+    }
+
+    if let Ok(events) = codex_logs::read_events(since) {
+        out.push(summarize("Codex", codex_items(&events), &estimates));
+    }
+
+    out
+}
+
+fn period_cost(provider: &str, items: impl IntoIterator<Item = (String, u64, f64)>) -> PeriodCost {
+    let by_model = by_model(items);
+    PeriodCost {
+        provider: provider.into(),
+        tokens: by_model.iter().map(|m| m.tokens).sum(),
+        api_cost_usd: by_model.iter().map(|m| m.cost_usd).sum(),
+        by_model,
+    }
+}
+
+/// Model name imported days are listed under.
+const IMPORTED_MODEL: &str = "imported";
+
+fn local_date(ts: i64) -> String {
+    chrono::DateTime::from_timestamp(ts, 0)
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Imported days of `provider` between `since` and `until`, one item per day.
+/// Dates the `logged` timestamps fall on are skipped, as is a second import
+/// of the same date, so no day counts twice. Days imported without a cost
+/// count their tokens at zero.
+fn imported_items(
+    history: &HistoryState,
+    provider: &str,
+    since: i64,
+    until: i64,
+    logged: impl Iterator<Item = i64>,
+) -> Vec<(String, u64, f64)> {
+    let mut seen: HashSet<String> = logged.map(local_date).collect();
+    let (first, end) = (local_date(since), local_date(until));
+    history
+        .daily_tokens(Some(provider))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|d| d.date >= first && d.date < end && seen.insert(d.date.clone()))
+        .map(|d| (IMPORTED_MODEL.to_string(), d.total_tokens, d.cost_usd.unwrap_or(0.0)))
+        .collect()
+}
+
+/// API-equivalent cost between `since` and `until` (unix seconds) for each
+/// provider with local logs or imported days.
+pub fn costs_between(history: &HistoryState, since: i64, until: i64) -> Vec<PeriodCost> {
+    let mut out = Vec::new();
+    let entries = claude_logs::read_entries(since).map(|mut entries| {
+        entries.retain(|e| e.ts < until);
+        entries
+    });
+    let logged = entries.iter().flatten().map(|e| e.ts);
+    let imported = imported_items(history, "Claude", since, until, logged);
+    if entries.is_ok() || !imported.is_empty() {
+        let items = entries.iter().flat_map(|e| claude_items(e));
+        out.push(period_cost("Claude", items.chain(imported)));
+    }
+
+    let events = codex_logs::read_events(since).map(|mut events| {
+        events.retain(|e| e.ts < until);
+        events
+    });
+    let logged = events.iter().flatten().map(|e| e.ts);
+    let imported = imported_items(history, "Codex", since, until, logged);
+    if events.is_ok() || !imported.is_empty() {
+        let items = events.iter().flat_map(|e| codex_items(e));
+        out.push(period_cost("Codex", items.chain(imported)));
+    }
+    out
+}
@@ -0,0 +1,165 @@
+//! Privacy mode: while on, dollar amounts, plan names and account details are
+//! replaced with placeholders in the menu bar, tooltips and everything sent
+//! to the frontend. Account details include org ids quoted in errors, machine
+//! names, project paths and team sizes. Percentages stay visible. Data is only
+//! redacted on the way out; cached and recorded values are untouched.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use tauri::{AppHandle, Manager};
+
+use crate::claude_logs::ProjectUsage;
+use crate::custom_provider::{CustomProviderData, ValueKind};
+use crate::history::Annotation;
+use crate::i18n;
+use crate::litellm_fetcher::GatewayBudgetData;
+use crate::openrouter_fetcher::OpenRouterCreditsData;
+use crate::provider::ProviderData;
+use crate::settings::SettingsState;
+use crate::sync::{MachineSample, MergedUsage};
+use crate::team::TeamAggregate;
+use crate::usage_fetcher::UsageData;
+
+pub const PLACEHOLDER: &str = "\u{2022}\u{2022}\u{2022}";
+
+pub fn is_on(app: &AppHandle) -> bool {
+    app.state::<SettingsState>().get().privacy_mode
+}
+
+/// "$12.34" in the user's number and currency format, or "$•••" while
+/// redacting.
+pub fn money(on: bool, amount: f64) -> String {
+    if on {
+        i18n::currency(PLACEHOLDER)
+    } else {
+        i18n::money(amount)
+    }
+}
+
+pub fn usage(on: bool, mut data: UsageData) -> UsageData {
+    if on {
+        data.extra.dollars_spent = 0.0;
+        data.plan = data.plan.map(|_| PLACEHOLDER.into());
+        data.plan_tier = None;
+        data.seat = None;
+        data.redacted = true;
+    }
+    data
+}
+
+pub fn credits(on: bool, mut data: OpenRouterCreditsData) -> OpenRouterCreditsData {
+    if on {
+        data.total_credits = 0.0;
+        data.total_usage = 0.0;
+        data.remaining_credits = 0.0;
+        data.redacted = true;
+    }
+    data
+}
+
+pub fn gateway(on: bool, mut data: GatewayBudgetData) -> GatewayBudgetData {
+    if on {
+        data.spend = 0.0;
+        data.max_budget = data.max_budget.map(|_| 0.0);
+        data.remaining = data.remaining.map(|_| 0.0);
+        data.key_alias = None;
+        data.redacted = true;
+    }
+    data
+}
+
+/// Balances are hidden; quota percentages stay.
+pub fn custom(on: bool, mut data: CustomProviderData) -> CustomProviderData {
+    if on && data.kind == ValueKind::Balance {
+        data.value = 0.0;
+        data.redacted = true;
+    }
+    data
+}
+
+pub fn provider_data(on: bool, data: ProviderData) -> ProviderData {
+    match data {
+        ProviderData::Usage(d) => ProviderData::Usage(Box::new(usage(on, *d))),
+        ProviderData::Credits(d) => ProviderData::Credits(credits(on, d)),
+        ProviderData::Gateway(d) => ProviderData::Gateway(gateway(on, d)),
+        ProviderData::Local(d) => ProviderData::Local(d),
+    }
+}
+
+/// Masks the org id in Claude API URLs, which request errors quote.
+pub fn error(on: bool, message: String) -> String {
+    const MARKER: &str = "/organizations/";
+    if !on || !message.contains(MARKER) {
+        return message;
+    }
+    let mut out = String::with_capacity(message.len());
+    let mut rest = message.as_str();
+    while let Some(i) = rest.find(MARKER) {
+        let (head, tail) = rest.split_at(i + MARKER.len());
+        out.push_str(head);
+        out.push_str(PLACEHOLDER);
+        let end = tail
+            .find(|c: char| matches!(c, '/' | '?' | ')') || c.is_whitespace())
+            .unwrap_or(tail.len());
+        rest = &tail[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// A stand-in for a machine name, the same wherever that machine shows up.
+fn machine_label(name: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    name.hash(&mut hasher);
+    format!("{} {:04X}", PLACEHOLDER, hasher.finish() & 0xFFFF)
+}
+
+pub fn machines(on: bool, mut usage: MergedUsage) -> MergedUsage {
+    if on {
+        for m in usage.machines.iter_mut() {
+            m.machine = machine_label(&m.machine);
+        }
+    }
+    usage
+}
+
+pub fn machine_samples(on: bool, mut list: Vec<MachineSample>) -> Vec<MachineSample> {
+    if on {
+        for s in list.iter_mut() {
+            s.machine = machine_label(&s.machine);
+        }
+    }
+    list
+}
+
+/// Projects are numbered in list order instead of named by their paths.
+pub fn projects(on: bool, mut list: Vec<ProjectUsage>) -> Vec<ProjectUsage> {
+    if on {
+        for (i, p) in list.iter_mut().enumerate() {
+            p.name = format!("{} {}", PLACEHOLDER, i + 1);
+            p.project = p.name.clone();
+        }
+    }
+    list
+}
+
+/// Hides how many members the team has; its percentages stay.
+pub fn team(on: bool, mut aggregate: TeamAggregate) -> TeamAggregate {
+    if on {
+        for p in aggregate.providers.iter_mut() {
+            p.members = 0;
+        }
+        aggregate.redacted = true;
+    }
+    aggregate
+}
+
+/// Hides plan names recorded as history annotations.
+pub fn annotations(on: bool, mut list: Vec<Annotation>) -> Vec<Annotation> {
+    if on {
+        for a in list.iter_mut().filter(|a| a.kind == "plan") {
+            a.text = PLACEHOLDER.into();
+        }
+    }
+    list
+}
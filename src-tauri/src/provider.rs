@@ -0,0 +1,358 @@
+//! Provider identities and the shared fetch path used by both the IPC fetch
+//! commands and the background scheduler.
+//!
+//! Each `fetch_*` function calls the provider's fetcher, updates its cached
+//! state, and runs the post-fetch pipeline (history, alerts, tray tooltips).
+//! Callers go through `scheduler::refresh_provider`, which adds backoff and
+//! single-flight on top of `fetch`.
+
+use std::path::Path;
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::codex_fetcher::{self, CodexState};
+use crate::cookie_reader::{ClaudeCookies, SafeStorageState};
+use crate::history::HistoryState;
+use crate::http_cache::{Fetched, HttpCacheState, Validators};
+use crate::litellm_fetcher::{self, GatewayBudgetData, LiteLlmState};
+use crate::notifications::{self, NotificationState};
+use crate::ollama_fetcher::{self, LocalActivityData, OllamaState};
+use crate::openrouter_fetcher::{self, OpenRouterCreditsData, OpenRouterState};
+use crate::settings::SettingsState;
+use crate::usage_fetcher::{self, PlanTier, UsageData, UsageState};
+use crate::{
+    budgets, calendar_sync, capacity, cookie_reader, failover, i18n, idle, presentation, preview,
+    resets, sync, team, tray, watchdog, widget_data,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    Claude,
+    Codex,
+    OpenRouter,
+    #[serde(rename = "litellm")]
+    LiteLlm,
+    Ollama,
+}
+
+impl Provider {
+    pub const ALL: [Provider; 5] = [
+        Provider::Claude,
+        Provider::Codex,
+        Provider::OpenRouter,
+        Provider::LiteLlm,
+        Provider::Ollama,
+    ];
+
+    /// Display name, also used as the provider key in history and notifications.
+    pub fn name(self) -> &'static str {
+        match self {
+            Provider::Claude => "Claude",
+            Provider::Codex => "Codex",
+            Provider::OpenRouter => "OpenRouter",
+            Provider::LiteLlm => "LiteLLM",
+            Provider::Ollama => "Ollama",
+        }
+    }
+}
+
+/// Fresh data from any provider; serialized as the inner type.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum ProviderData {
+    Usage(Box<UsageData>),
+    Credits(OpenRouterCreditsData),
+    Gateway(GatewayBudgetData),
+    Local(LocalActivityData),
+}
+
+/// Post-fetch pipeline shared by all rate-limit providers: records the sample
+/// in history (tagged active/idle) and fires any idle-spike or threshold alerts.
+fn record_usage(app: &AppHandle, provider: &str, data: &UsageData) {
+    let s = app.state::<SettingsState>().get();
+    let history = app.state::<HistoryState>();
+    notifications::flush_suppressed(app);
+
+    let idle_secs = idle::idle_seconds();
+    let away = idle::is_idle(idle_secs, s.idle_after_secs);
+    notifications::check_idle_spike(
+        app,
+        provider,
+        data,
+        away,
+        &s,
+        &app.state::<NotificationState>(),
+    );
+    let _ = history.record(provider, data, !away, idle_secs);
+    notifications::check_plan_change(app, provider, data, &history, s.notifications_enabled);
+    sync::publish(app);
+    team::publish(app, provider, data);
+
+    notifications::check_and_notify(app, provider, data, &s, &app.state::<NotificationState>());
+    failover::check(app, provider, data);
+    budgets::check(app, provider, data);
+    calendar_sync::sync(app);
+    tray::flash_on_limit(app, provider, data);
+    tray::update_tooltips(app);
+}
+
+/// Validators to send for `provider`, only while there is cached data to fall
+/// back on if the server answers 304.
+fn validators<T>(app: &AppHandle, provider: Provider, cached: &Option<T>) -> Option<Validators> {
+    cached
+        .as_ref()
+        .and_then(|_| app.state::<HttpCacheState>().get(provider))
+}
+
+/// Reads the Claude cookies, moving on to the next cookie database when one
+/// doesn't decrypt plausibly or claude.ai rejects its session. The source that
+/// last worked is used without the extra check.
+async fn claude_cookies(app: &AppHandle, client: &Client) -> Result<ClaudeCookies, String> {
+    let state = app.state::<UsageState>();
+    let settings = app.state::<SettingsState>().get();
+    let safe_storage = app.state::<SafeStorageState>();
+    let picked = settings.claude_cookies_path.as_deref().map(Path::new);
+    let mut sources = cookie_reader::sources(picked);
+    let known = state.cookie_source.lock().unwrap().clone();
+    if let Some(known) = &known {
+        if let Some(i) = sources.iter().position(|s| s == known) {
+            sources[..=i].rotate_right(1);
+        }
+    }
+
+    let mut last_err = None;
+    for source in sources {
+        let read = cookie_reader::read_claude_cookies(
+            &source,
+            &safe_storage,
+            settings.keychain_access_granted,
+        );
+        let cookies = match read {
+            Ok(cookies) => cookies,
+            Err(e) => {
+                last_err = Some(e.to_string());
+                continue;
+            }
+        };
+        if known.as_ref() == Some(&cookies.source) {
+            return Ok(cookies);
+        }
+        match usage_fetcher::verify_session(&cookies, client).await {
+            Ok(()) => {
+                *state.cookie_source.lock().unwrap() = Some(cookies.source.clone());
+                return Ok(cookies);
+            }
+            Err(e) => last_err = Some(e),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| "Claude desktop app cookies not found".into()))
+}
+
+async fn fetch_claude(app: &AppHandle) -> Result<UsageData, String> {
+    let state = app.state::<UsageState>();
+    let client = state.client();
+    let cookies = claude_cookies(app, &client).await?;
+    let cached = state.last_data.lock().unwrap().clone();
+    let validators = validators(app, Provider::Claude, &cached);
+    let fetched = usage_fetcher::fetch_usage(&cookies, &client, validators.as_ref()).await;
+    // Re-verify the source on the next fetch rather than trusting it again
+    if fetched.is_err() {
+        *state.cookie_source.lock().unwrap() = None;
+    }
+    match fetched? {
+        Fetched::Fresh(mut data, fresh) => {
+            // The plan lives on the organization and rarely changes, so it is
+            // looked up hourly; keep the last known one if that lookup fails
+            data.plan = match state.cached_plan(&cookies.org_id) {
+                Some(plan) => plan,
+                None => match usage_fetcher::fetch_plan(&cookies, &client).await {
+                    Ok(plan) => {
+                        state.set_plan(&cookies.org_id, plan.clone());
+                        plan
+                    }
+                    Err(_) => cached.as_ref().and_then(|c| c.plan.clone()),
+                },
+            };
+            data.plan_tier = data.plan.as_deref().and_then(PlanTier::from_plan);
+            resets::apply_anchor(&app.state::<SettingsState>().get(), Provider::Claude, &mut data);
+            app.state::<HttpCacheState>().set(Provider::Claude, fresh);
+            *state.last_data.lock().unwrap() = Some(data.clone());
+            record_usage(app, Provider::Claude.name(), &data);
+            Ok(data)
+        }
+        Fetched::NotModified => {
+            let data = cached
+                .ok_or("Claude returned 304 without cached data")?
+                .revalidated();
+            *state.last_data.lock().unwrap() = Some(data.clone());
+            record_usage(app, Provider::Claude.name(), &data);
+            Ok(data)
+        }
+    }
+}
+
+async fn fetch_codex(app: &AppHandle) -> Result<UsageData, String> {
+    let client = app.state::<UsageState>().client();
+    let state = app.state::<CodexState>();
+    let cached = state.last_data.lock().unwrap().clone();
+    let validators = validators(app, Provider::Codex, &cached);
+    match codex_fetcher::fetch_codex_usage(&client, validators.as_ref()).await? {
+        Fetched::Fresh(mut data, fresh) => {
+            resets::apply_anchor(&app.state::<SettingsState>().get(), Provider::Codex, &mut data);
+            app.state::<HttpCacheState>().set(Provider::Codex, fresh);
+            *state.last_data.lock().unwrap() = Some(data.clone());
+            record_usage(app, Provider::Codex.name(), &data);
+            Ok(data)
+        }
+        Fetched::NotModified => {
+            let cached = cached.ok_or("Codex returned 304 without cached data")?;
+            // A reached session limit is marked around the countdown; keep it
+            let marker = i18n::tf("usage.limit_reached", &[&""]);
+            let at_limit = cached.session.reset_info.starts_with(&marker);
+            let mut data = cached.revalidated();
+            if at_limit {
+                data.session.reset_info =
+                    i18n::tf("usage.limit_reached", &[&data.session.reset_info]);
+            }
+            *state.last_data.lock().unwrap() = Some(data.clone());
+            record_usage(app, Provider::Codex.name(), &data);
+            Ok(data)
+        }
+    }
+}
+
+async fn fetch_openrouter(app: &AppHandle) -> Result<OpenRouterCreditsData, String> {
+    let client = app.state::<UsageState>().client();
+    let state = app.state::<OpenRouterState>();
+    let cached = state.last_data.lock().unwrap().clone();
+    let validators = validators(app, Provider::OpenRouter, &cached);
+    match openrouter_fetcher::fetch_openrouter_credits(&client, validators.as_ref()).await? {
+        Fetched::Fresh(data, fresh) => {
+            app.state::<HttpCacheState>()
+                .set(Provider::OpenRouter, fresh);
+            let _ = app
+                .state::<HistoryState>()
+                .record_credits(Provider::OpenRouter.name(), data.total_usage);
+            *state.last_data.lock().unwrap() = Some(data.clone());
+            tray::update_tooltips(app);
+            Ok(data)
+        }
+        Fetched::NotModified => {
+            cached.ok_or_else(|| "OpenRouter returned 304 without cached data".into())
+        }
+    }
+}
+
+async fn fetch_litellm(app: &AppHandle) -> Result<GatewayBudgetData, String> {
+    let client = app.state::<UsageState>().client();
+    let state = app.state::<LiteLlmState>();
+    let base_url = app.state::<SettingsState>().get().litellm_base_url;
+    let cached = state.last_data.lock().unwrap().clone();
+    let validators = validators(app, Provider::LiteLlm, &cached);
+    match litellm_fetcher::fetch_budget(&client, base_url.as_deref(), validators.as_ref()).await? {
+        Fetched::Fresh(data, fresh) => {
+            app.state::<HttpCacheState>().set(Provider::LiteLlm, fresh);
+            let _ = app
+                .state::<HistoryState>()
+                .record_credits(Provider::LiteLlm.name(), data.spend);
+            *state.last_data.lock().unwrap() = Some(data.clone());
+            tray::update_tooltips(app);
+            Ok(data)
+        }
+        Fetched::NotModified => {
+            cached.ok_or_else(|| "LiteLLM returned 304 without cached data".into())
+        }
+    }
+}
+
+/// Local activity isn't quota, so it skips history and alerts.
+async fn fetch_ollama(app: &AppHandle) -> Result<LocalActivityData, String> {
+    let client = app.state::<UsageState>().client();
+    let state = app.state::<OllamaState>();
+    let base_url = app.state::<SettingsState>().get().ollama_base_url;
+    let models = ollama_fetcher::fetch_models(&client, base_url.as_deref()).await?;
+    let data = state.observe(models.is_some(), models.unwrap_or_default());
+    *state.last_data.lock().unwrap() = Some(data.clone());
+    tray::update_tooltips(app);
+    Ok(data)
+}
+
+pub async fn fetch(app: &AppHandle, provider: Provider) -> Result<ProviderData, String> {
+    let result = match provider {
+        Provider::Claude => fetch_claude(app)
+            .await
+            .map(|d| ProviderData::Usage(Box::new(d))),
+        Provider::Codex => fetch_codex(app)
+            .await
+            .map(|d| ProviderData::Usage(Box::new(d))),
+        Provider::OpenRouter => fetch_openrouter(app).await.map(ProviderData::Credits),
+        Provider::LiteLlm => fetch_litellm(app).await.map(ProviderData::Gateway),
+        Provider::Ollama => fetch_ollama(app).await.map(ProviderData::Local),
+    };
+    if let Ok(ProviderData::Usage(data)) = &result {
+        watchdog::check(app, provider, data);
+    }
+    if result.is_ok() {
+        if app.state::<SettingsState>().get().capacity_file {
+            let _ = capacity::write(app);
+        }
+        presentation::refresh(app);
+        preview::refresh(app);
+        widget_data::refresh(app);
+    }
+    result
+}
+
+/// Object keys whose values are replaced in raw payloads.
+const SECRET_KEYS: [&str; 9] = [
+    "token", "secret", "password", "cookie", "key", "auth", "email", "account", "user_id",
+];
+
+/// Blanks anything that looks like a credential or an account identifier, so
+/// raw payloads can be pasted into issues.
+fn redact_secrets(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (k, v) in map.iter_mut() {
+                let k = k.to_lowercase();
+                if SECRET_KEYS.iter().any(|s| k.contains(s)) && !v.is_null() {
+                    *v = "[redacted]".into();
+                } else {
+                    redact_secrets(v);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        serde_json::Value::String(s) if s.starts_with("sk-") || s.starts_with("Bearer ") => {
+            *s = "[redacted]".into();
+        }
+        _ => {}
+    }
+}
+
+/// The provider's payload as returned, with secrets blanked. Bypasses the
+/// cache, history and alerts entirely.
+pub async fn fetch_raw(app: &AppHandle, provider: Provider) -> Result<serde_json::Value, String> {
+    let client = app.state::<UsageState>().client();
+    let mut raw = match provider {
+        Provider::Claude => {
+            let cookies = claude_cookies(app, &client).await?;
+            usage_fetcher::fetch_raw(&cookies, &client).await?
+        }
+        Provider::Codex => codex_fetcher::fetch_raw(&client).await?,
+        Provider::OpenRouter => openrouter_fetcher::fetch_raw(&client).await?,
+        Provider::LiteLlm => {
+            let base_url = app.state::<SettingsState>().get().litellm_base_url;
+            litellm_fetcher::fetch_raw(&client, base_url.as_deref()).await?
+        }
+        Provider::Ollama => {
+            let base_url = app.state::<SettingsState>().get().ollama_base_url;
+            ollama_fetcher::fetch_raw(&client, base_url.as_deref()).await?
+        }
+    };
+    redact_secrets(&mut raw);
+    Ok(raw)
+}
@@ -0,0 +1,291 @@
+//! Monthly usage report: each metric's peak and growth over the month, the
+//! days a limit was hit, OpenRouter spend, and what the month would have cost
+//! on API pricing (imported days included). Saved under `reports/` in the
+//! app's data directory as JSON for tools and as Markdown for reading.
+//!
+//! Dollar amounts and plans are left out while privacy mode is on. The
+//! Markdown is written in the app's language; the JSON keeps raw values.
+
+use std::path::PathBuf;
+
+use chrono::{Datelike, Local, NaiveDate, TimeZone};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::history::HistoryState;
+use crate::i18n;
+use crate::pricing::{self, PeriodCost};
+use crate::privacy;
+use crate::provider::Provider;
+use crate::usage_fetcher::PlanTier;
+
+const REPORTS_DIR: &str = "reports";
+/// Metrics in report order
+const METRICS: [&str; 6] = ["session", "weekly", "sonnet", "opus", "haiku", "extra"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricSummary {
+    pub metric: String,
+    pub peak_percent: f64,
+    /// Unix timestamp (seconds) of the peak
+    pub peak_at: i64,
+    /// Percentage points the metric grew over the month, resets excluded
+    pub consumed: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderSummary {
+    pub provider: String,
+    /// Plan at the end of the month ("Max 20x", or as the provider names it)
+    pub plan: Option<String>,
+    pub metrics: Vec<MetricSummary>,
+    /// Local dates (YYYY-MM-DD) on which any metric reached 100%
+    pub limit_hit_days: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonthlyReport {
+    /// YYYY-MM
+    pub month: String,
+    /// RFC 3339
+    pub generated_at: String,
+    pub providers: Vec<ProviderSummary>,
+    pub openrouter_spend_usd: Option<f64>,
+    pub api_equivalent: Vec<PeriodCost>,
+    /// Amounts and plans were left out because privacy mode is on
+    pub amounts_hidden: bool,
+}
+
+/// A generated report and where its files were written.
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedReport {
+    pub report: MonthlyReport,
+    pub json_path: String,
+    pub markdown_path: String,
+}
+
+/// First day of `month` ("YYYY-MM").
+fn parse_month(month: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d")
+        .map_err(|_| format!("\"{}\" is not a month like 2025-06", month))
+}
+
+/// Local midnight at the start of `date`, as unix seconds.
+fn local_start(date: NaiveDate) -> Result<i64, String> {
+    Local
+        .from_local_datetime(&date.and_time(chrono::NaiveTime::MIN))
+        .earliest()
+        .map(|t| t.timestamp())
+        .ok_or_else(|| format!("No local midnight on {}", date))
+}
+
+/// The last full month before the current one, as "YYYY-MM".
+pub fn previous_month() -> String {
+    let first = Local::now().date_naive().with_day(1).unwrap_or_default();
+    let last_month = first.pred_opt().unwrap_or(first);
+    last_month.format("%Y-%m").to_string()
+}
+
+fn summarize_provider(
+    provider: &str,
+    plan: Option<String>,
+    samples: &[crate::history::Sample],
+    hits: &[(String, String, i64)],
+) -> Option<ProviderSummary> {
+    let metrics: Vec<MetricSummary> = METRICS
+        .iter()
+        .filter_map(|metric| {
+            let series: Vec<_> = samples
+                .iter()
+                .filter(|s| s.provider == provider && s.metric == *metric)
+                .collect();
+            let peak = series
+                .iter()
+                .max_by(|a, b| a.percent.total_cmp(&b.percent))?;
+            let consumed = series
+                .windows(2)
+                .map(|w| (w[1].percent - w[0].percent).max(0.0))
+                .sum();
+            Some(MetricSummary {
+                metric: metric.to_string(),
+                peak_percent: peak.percent,
+                peak_at: peak.ts,
+                consumed,
+            })
+        })
+        .collect();
+    if metrics.is_empty() {
+        return None;
+    }
+    let mut limit_hit_days: Vec<String> = hits
+        .iter()
+        .filter(|(p, _, _)| p == provider)
+        .filter_map(|(_, _, ts)| chrono::DateTime::from_timestamp(*ts, 0))
+        .map(|t| t.with_timezone(&Local).format("%Y-%m-%d").to_string())
+        .collect();
+    limit_hit_days.dedup();
+    Some(ProviderSummary {
+        provider: provider.into(),
+        plan,
+        metrics,
+        limit_hit_days,
+    })
+}
+
+/// Builds the report for `month` ("YYYY-MM") from history and local logs.
+pub fn build(app: &AppHandle, month: &str) -> Result<MonthlyReport, String> {
+    let first = parse_month(month)?;
+    let next = first
+        .checked_add_months(chrono::Months::new(1))
+        .ok_or("Month out of range")?;
+    let (since, until) = (local_start(first)?, local_start(next)?);
+
+    let history = app.state::<HistoryState>();
+    let samples: Vec<_> = history
+        .samples_since(None, since)?
+        .into_iter()
+        .filter(|s| s.ts < until)
+        .collect();
+    let hits = history.limit_hits_between(since, until)?;
+    let amounts_hidden = privacy::is_on(app);
+    let plan_at_end = |p: Provider| {
+        let plan = history.annotation_before(p.name(), "plan", until)?;
+        let tier = PlanTier::from_plan(&plan).or_else(|| PlanTier::from_codex(&plan));
+        Some(tier.map_or(plan, |t| t.label().to_string()))
+    };
+    let providers = [Provider::Claude, Provider::Codex]
+        .into_iter()
+        .filter_map(|p| {
+            let plan = if amounts_hidden { None } else { plan_at_end(p) };
+            summarize_provider(p.name(), plan, &samples, &hits)
+        })
+        .collect();
+
+    let (openrouter_spend_usd, api_equivalent) = if amounts_hidden {
+        (None, Vec::new())
+    } else {
+        (
+            history.credit_spend(Provider::OpenRouter.name(), since, until),
+            pricing::costs_between(&history, since, until),
+        )
+    };
+
+    Ok(MonthlyReport {
+        month: first.format("%Y-%m").to_string(),
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        providers,
+        openrouter_spend_usd,
+        api_equivalent,
+        amounts_hidden,
+    })
+}
+
+fn date_of(ts: i64) -> String {
+    chrono::DateTime::from_timestamp(ts, 0)
+        .map(|t| t.with_timezone(&Local).format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Display name of a metric in `METRICS`.
+fn metric_label(metric: &str) -> String {
+    let key = match metric {
+        "session" => "usage.session",
+        "weekly" => "usage.weekly",
+        "sonnet" => "usage.sonnet_only",
+        "opus" => "usage.opus_only",
+        "haiku" => "usage.haiku_only",
+        "extra" => "report.extra",
+        other => return other.to_string(),
+    };
+    i18n::t(key).to_string()
+}
+
+/// Renders the report as a Markdown document.
+pub fn markdown(report: &MonthlyReport) -> String {
+    let mut out = format!("# {}\n\n", i18n::tf("report.title", &[&report.month]));
+    if report.providers.is_empty() {
+        out.push_str(&format!("{}\n\n", i18n::t("report.empty")));
+    }
+    for p in &report.providers {
+        out.push_str(&format!("## {}\n\n", p.provider));
+        if let Some(plan) = &p.plan {
+            out.push_str(&format!("{}\n\n", i18n::tf("usage.plan", &[plan])));
+        }
+        out.push_str(&format!("{}\n", i18n::t("report.columns")));
+        out.push_str("|---|---|---|---|\n");
+        for m in &p.metrics {
+            out.push_str(&format!(
+                "| {} | {}% | {} | {} |\n",
+                metric_label(&m.metric),
+                i18n::number(m.peak_percent, 0),
+                date_of(m.peak_at),
+                i18n::tf("report.points", &[&i18n::number(m.consumed, 0)])
+            ));
+        }
+        if p.limit_hit_days.is_empty() {
+            out.push_str(&format!("\n{}\n\n", i18n::t("report.no_limits")));
+        } else {
+            let hit = i18n::tf(
+                "report.limits_hit",
+                &[&p.limit_hit_days.len(), &p.limit_hit_days.join(", ")],
+            );
+            out.push_str(&format!("\n{}\n\n", hit));
+        }
+    }
+    if report.amounts_hidden {
+        out.push_str(&format!("{}\n", i18n::t("report.hidden")));
+        return out;
+    }
+    if let Some(spend) = report.openrouter_spend_usd {
+        let spent = i18n::tf("tooltip.spent", &[&i18n::money(spend)]);
+        out.push_str(&format!("## OpenRouter\n\n{}\n\n", spent));
+    }
+    if !report.api_equivalent.is_empty() {
+        out.push_str(&format!("## {}\n\n", i18n::t("report.api_title")));
+        out.push_str(&format!("{}\n\n", i18n::t("report.api_intro")));
+        for c in &report.api_equivalent {
+            let cost = i18n::money(c.api_cost_usd);
+            let tokens = i18n::number(c.tokens as f64, 0);
+            let line = i18n::tf("report.api_line", &[&c.provider, &cost, &tokens]);
+            out.push_str(&format!("- {}\n", line));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Builds the report for `month` and writes it as JSON and Markdown.
+pub fn generate(app: &AppHandle, month: &str) -> Result<SavedReport, String> {
+    let report = build(app, month)?;
+    let dir: PathBuf = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join(REPORTS_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let json_path = dir.join(format!("usage-{}.json", report.month));
+    let json = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+    std::fs::write(&json_path, json).map_err(|e| e.to_string())?;
+    let markdown_path = dir.join(format!("usage-{}.md", report.month));
+    std::fs::write(&markdown_path, markdown(&report)).map_err(|e| e.to_string())?;
+
+    Ok(SavedReport {
+        report,
+        json_path: json_path.display().to_string(),
+        markdown_path: markdown_path.display().to_string(),
+    })
+}
+
+/// Generates last month's report and opens the Markdown file.
+pub fn generate_latest(app: &AppHandle) -> Result<SavedReport, String> {
+    let saved = generate(app, &previous_month())?;
+    let status = std::process::Command::new("open")
+        .arg(&saved.markdown_path)
+        .status()
+        .map_err(|e| format!("Failed to open the report: {}", e))?;
+    if !status.success() {
+        return Err("Failed to open the report".into());
+    }
+    Ok(saved)
+}
@@ -0,0 +1,204 @@
+//! Next weekly reset per provider in local time, every window's next reset
+//! for the tray's "Upcoming Resets" submenu, and calendar export so heavy
+//! users can schedule big runs right after a reset.
+//!
+//! The calendar entry is written as an `.ics` file and handed to the default
+//! calendar app with `open`, which asks the user before adding it.
+//!
+//! A per-provider reset anchor (e.g. ISO weeks, Monday 00:00) fills in the
+//! weekly reset time when the API leaves it out, or replaces it when the user
+//! says the org's window resets elsewhere. It is applied to each fresh fetch,
+//! so pacing, budgets, forecasts and the calendar all see the same cycle, and
+//! again to the cached data whenever it changes; the API's own reset time is
+//! kept alongside, so clearing the anchor restores it.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveTime, TimeZone, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::codex_fetcher::CodexState;
+use crate::i18n;
+use crate::provider::Provider;
+use crate::settings::{ResetAnchor, Settings};
+use crate::usage_fetcher::{self, UsageData, UsageState};
+
+/// Length of the calendar event marking a reset.
+const EVENT_MINUTES: i64 = 30;
+
+/// Local reset times as shown to the user, e.g. "Mon 14 Oct, 09:00".
+pub const DISPLAY_FORMAT: &str = "%a %-d %b, %H:%M";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResetTime {
+    pub provider: Provider,
+    /// RFC 3339 in the local time zone
+    pub resets_at: String,
+    /// e.g. "Mon 14 Oct, 09:00"
+    pub local_display: String,
+    /// Local UTC offset, e.g. "+02:00"
+    pub utc_offset: String,
+}
+
+/// First occurrence of the anchor after `now`.
+pub fn next_anchor(anchor: &ResetAnchor, now: DateTime<Local>) -> Option<DateTime<Utc>> {
+    let time = NaiveTime::from_hms_opt(anchor.hour, anchor.minute, 0)?;
+    let days = (7 + anchor.weekday.num_days_from_monday()
+        - now.weekday().num_days_from_monday())
+        % 7;
+    let day = now.date_naive() + Duration::days(i64::from(days));
+    let mut at = Local.from_local_datetime(&day.and_time(time)).earliest()?;
+    if at <= now {
+        at += Duration::days(7);
+    }
+    Some(at.with_timezone(&Utc))
+}
+
+/// Fills in (or overrides) the weekly reset time of `data` from the
+/// provider's anchor, or puts back the API's own time once the anchor is gone.
+pub fn apply_anchor(settings: &Settings, provider: Provider, data: &mut UsageData) {
+    let anchor = settings.reset_anchors.get(&provider.name().to_lowercase());
+    if anchor.is_none() && data.api_weekly_resets_at.is_none() {
+        return;
+    }
+    let api = data
+        .api_weekly_resets_at
+        .get_or_insert_with(|| data.weekly_all.resets_at.clone())
+        .clone();
+    let anchored = anchor
+        .filter(|a| api.is_none() || a.override_api)
+        .and_then(|a| next_anchor(a, Local::now()))
+        .map(|at| at.to_rfc3339());
+    let resets_at = anchored.or(api);
+    let weekly = &mut data.weekly_all;
+    if weekly.resets_at != resets_at {
+        weekly.reset_info = match &resets_at {
+            Some(_) => usage_fetcher::format_reset(&resets_at),
+            None => i18n::t("usage.no_data").into(),
+        };
+        weekly.resets_at = resets_at;
+    }
+}
+
+fn weekly_reset(data: &UsageData) -> Option<DateTime<Local>> {
+    let at = data.weekly_all.resets_at.as_deref()?;
+    DateTime::parse_from_rfc3339(at)
+        .ok()
+        .map(|t| t.with_timezone(&Local))
+}
+
+/// Next weekly reset for each rate-limited provider with cached data.
+pub fn next_weekly_resets(app: &AppHandle) -> Vec<ResetTime> {
+    let claude = app.state::<UsageState>().last_data.lock().unwrap().clone();
+    let codex = app.state::<CodexState>().last_data.lock().unwrap().clone();
+
+    [(Provider::Claude, claude), (Provider::Codex, codex)]
+        .into_iter()
+        .filter_map(|(provider, data)| {
+            let at = weekly_reset(data.as_ref()?)?;
+            Some(ResetTime {
+                provider,
+                resets_at: at.to_rfc3339(),
+                local_display: at.format(DISPLAY_FORMAT).to_string(),
+                utc_offset: at.format("%:z").to_string(),
+            })
+        })
+        .collect()
+}
+
+/// A window's next reset, for the "Upcoming Resets" submenu.
+#[derive(Debug, Clone)]
+pub struct WindowReset {
+    pub provider: Provider,
+    /// Window label, e.g. "Current session"
+    pub label: String,
+    pub at: DateTime<Local>,
+}
+
+/// Every window of the rate-limited providers with a reset still ahead,
+/// soonest first.
+pub fn upcoming_resets(app: &AppHandle) -> Vec<WindowReset> {
+    let claude = app.state::<UsageState>().last_data.lock().unwrap().clone();
+    let codex = app.state::<CodexState>().last_data.lock().unwrap().clone();
+    let now = Local::now();
+
+    let mut out: Vec<WindowReset> = [(Provider::Claude, claude), (Provider::Codex, codex)]
+        .into_iter()
+        .filter_map(|(provider, data)| Some((provider, data?)))
+        .flat_map(|(provider, data)| {
+            data.windows()
+                .into_iter()
+                .filter_map(|m| {
+                    let at = DateTime::parse_from_rfc3339(m.resets_at.as_deref()?).ok()?;
+                    Some(WindowReset {
+                        provider,
+                        label: m.label.clone(),
+                        at: at.with_timezone(&Local),
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .filter(|r| r.at > now)
+        .collect();
+    out.sort_by_key(|r| r.at);
+    out
+}
+
+fn ics_time(t: DateTime<Utc>) -> String {
+    t.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// A one-event iCalendar document for a weekly reset.
+fn ics_event(provider: Provider, at: DateTime<Utc>) -> String {
+    let end = at + chrono::Duration::minutes(EVENT_MINUTES);
+    [
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".into(),
+        "PRODID:-//Claude Codex Usage//EN".into(),
+        "BEGIN:VEVENT".into(),
+        format!(
+            "UID:{}-weekly-reset-{}@usage-widget",
+            provider.name().to_lowercase(),
+            at.timestamp()
+        ),
+        format!("DTSTAMP:{}", ics_time(Utc::now())),
+        format!("DTSTART:{}", ics_time(at)),
+        format!("DTEND:{}", ics_time(end)),
+        format!("SUMMARY:{} weekly limit resets", provider.name()),
+        "DESCRIPTION:Weekly usage window resets - a good time for big agent runs.".into(),
+        "END:VEVENT".into(),
+        "END:VCALENDAR".into(),
+    ]
+    .join("\r\n")
+        + "\r\n"
+}
+
+/// Writes an `.ics` for `provider`'s next weekly reset into `dir` and opens it
+/// in the default calendar app. Returns the file path.
+pub fn add_to_calendar(app: &AppHandle, provider: Provider, dir: &Path) -> Result<PathBuf, String> {
+    let reset = next_weekly_resets(app)
+        .into_iter()
+        .find(|r| r.provider == provider)
+        .ok_or_else(|| format!("No weekly reset time known for {} yet", provider.name()))?;
+    let at = DateTime::parse_from_rfc3339(&reset.resets_at)
+        .map_err(|e| e.to_string())?
+        .with_timezone(&Utc);
+
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!(
+        "{}-weekly-reset.ics",
+        provider.name().to_lowercase()
+    ));
+    std::fs::write(&path, ics_event(provider, at)).map_err(|e| e.to_string())?;
+
+    let status = Command::new("open")
+        .arg(&path)
+        .status()
+        .map_err(|e| format!("Failed to open calendar file: {}", e))?;
+    if !status.success() {
+        return Err("Failed to open calendar file".into());
+    }
+    Ok(path)
+}
@@ -0,0 +1,566 @@
+//! Background refresh loop. Fetches every provider on the configured interval
+//! and reports progress to the frontend with typed events:
+//!
+//! - `provider-fetch-started` `{ provider }`
+//! - `provider-fetch-succeeded` `{ provider, data }`
+//! - `provider-fetch-failed` `{ provider, error }`
+//! - `provider-fetch-cancelled` `{ provider }`
+//!
+//! Providers that keep failing back off exponentially so a missing login or a
+//! provider outage doesn't turn into a request every interval; providers with
+//! no credentials at all are skipped until they are set up. A full refresh
+//! fetches all providers concurrently, each under a timeout. Fetches of the
+//! same provider are single-flight: callers that arrive while one is running
+//! wait for it and share its result instead of sending another request. A
+//! full refresh cancels any fetch of the same provider still left over from an
+//! earlier one, and pausing or losing a provider's credentials cancels the
+//! fetches they affect.
+//!
+//! Monitoring can be paused, which stops the background loop (manual refreshes
+//! still work) until resumed or until the configured auto-resume delay passes.
+//! The loop also stays idle outside the configured active hours.
+//!
+//! The interval timer doesn't advance while the Mac sleeps, so a separate
+//! watcher notices the wall-clock jump on wake and fetches right away; the
+//! fresh data re-runs the threshold checks, so a limit crossed during sleep
+//! is notified on wake rather than at the next interval.
+//!
+//! The loop's timer is cancellable: changing the interval re-arms it right
+//! away with the new value instead of sleeping out the old one, and a wakeup
+//! (after system sleep, or when monitoring resumes) runs the loop's refresh
+//! immediately and restarts the interval from there.
+//!
+//! Both the interval and the retry delays carry a little random jitter, so
+//! installs with the same settings don't send their requests in lockstep and
+//! a fixed cadence doesn't always land on the same second after a reset.
+
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Listener, Manager};
+use tokio::sync::Notify;
+use tokio::task::{AbortHandle, JoinSet};
+
+use crate::fetch_stats::FetchStatsState;
+use crate::onboarding;
+use crate::privacy;
+use crate::provider::{self, Provider, ProviderData};
+use crate::settings::{Settings, SettingsState};
+use crate::tray;
+
+/// Range accepted for a custom refresh interval.
+pub const MIN_INTERVAL_SECS: u64 = 30;
+pub const MAX_INTERVAL_SECS: u64 = 2 * 60 * 60;
+
+/// First retry delay after a failure; doubles with each consecutive failure.
+const BACKOFF_BASE_SECS: u64 = 60;
+/// Upper bound on the retry delay.
+const BACKOFF_MAX_SECS: u64 = 30 * 60;
+/// Interval jitter as a fraction of the interval, applied either way.
+const INTERVAL_JITTER: f64 = 0.1;
+/// Retry jitter as a fraction of the delay, only ever added.
+const RETRY_JITTER: f64 = 0.2;
+/// Longest a single provider fetch may take before it counts as failed.
+const FETCH_TIMEOUT_SECS: u64 = 30;
+/// How often the wake watcher compares the wall clock.
+const WAKE_TICK_SECS: u64 = 30;
+/// Wall-clock time beyond a tick that means the Mac was asleep.
+const WAKE_SLACK_SECS: i64 = 60;
+
+/// Fetch outcome history for one provider; drives both backoff and health.
+#[derive(Debug, Clone, Default)]
+struct Health {
+    failures: u32,
+    retry_at: Option<Instant>,
+    last_error: Option<(String, chrono::DateTime<chrono::Local>)>,
+    last_success: Option<chrono::DateTime<chrono::Local>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    /// The last fetch succeeded
+    Ok,
+    /// The last fetch failed
+    Error,
+    /// Not fetched yet
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderHealth {
+    pub provider: Provider,
+    /// Credentials were found; unconfigured providers are not fetched
+    pub configured: bool,
+    pub status: HealthStatus,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    /// RFC 3339 timestamps
+    pub last_error_at: Option<String>,
+    pub last_success_at: Option<String>,
+    /// Seconds until the next fetch is allowed while backing off
+    pub retry_in_secs: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+struct Pause {
+    paused: bool,
+    /// Bumped on every change so a stale auto-resume timer can tell it was superseded
+    generation: u64,
+    resumes_at: Option<chrono::DateTime<chrono::Local>>,
+}
+
+/// Whether background monitoring is paused, emitted as `monitoring-paused`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PauseStatus {
+    pub paused: bool,
+    /// RFC 3339 time monitoring resumes on its own, if any
+    pub resumes_at: Option<String>,
+}
+
+/// Result of the most recent fetch of a provider and when it finished.
+type Flight = tokio::sync::Mutex<Option<(Instant, Result<ProviderData, String>)>>;
+
+/// Per-provider health and backoff, single-flight fetch guards, in-flight
+/// refresh tasks, and pause state.
+pub struct SchedulerState {
+    health: Mutex<HashMap<Provider, Health>>,
+    flights: HashMap<Provider, Flight>,
+    in_flight: Mutex<HashMap<Provider, AbortHandle>>,
+    pause: Mutex<Pause>,
+    /// Outside the active-hours schedule as of the last check
+    off_hours: Mutex<bool>,
+    /// Providers without credentials; not fetched until they are configured
+    unconfigured: Mutex<HashSet<Provider>>,
+    /// Interrupts the loop's sleep, to re-arm it or to refresh right away
+    timer: Notify,
+    /// Interval the loop is currently sleeping on
+    armed_secs: Mutex<u64>,
+    /// Set by `wake`: the interrupted loop refreshes before re-arming
+    refresh_requested: Mutex<bool>,
+}
+
+impl SchedulerState {
+    pub fn new() -> Self {
+        Self {
+            health: Mutex::new(HashMap::new()),
+            flights: Provider::ALL
+                .into_iter()
+                .map(|p| (p, tokio::sync::Mutex::new(None)))
+                .collect(),
+            in_flight: Mutex::new(HashMap::new()),
+            pause: Mutex::new(Pause::default()),
+            off_hours: Mutex::new(false),
+            unconfigured: Mutex::new(HashSet::new()),
+            timer: Notify::new(),
+            armed_secs: Mutex::new(0),
+            refresh_requested: Mutex::new(false),
+        }
+    }
+
+    /// Registers a new refresh task for `provider`, aborting the previous one.
+    fn track(&self, provider: Provider, handle: AbortHandle) {
+        if let Some(old) = self.in_flight.lock().unwrap().insert(provider, handle) {
+            old.abort();
+        }
+    }
+
+    /// Forgets `provider`'s task unless a newer refresh has already replaced it.
+    fn untrack(&self, provider: Provider, id: tokio::task::Id) {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if in_flight.get(&provider).map(|h| h.id()) == Some(id) {
+            in_flight.remove(&provider);
+        }
+    }
+
+    /// Aborts the background refresh of every provider `filter` selects and
+    /// returns those that were running.
+    fn cancel(&self, filter: impl Fn(Provider) -> bool) -> Vec<Provider> {
+        let mut cancelled = Vec::new();
+        self.in_flight.lock().unwrap().retain(|p, handle| {
+            if !filter(*p) {
+                return true;
+            }
+            handle.abort();
+            cancelled.push(*p);
+            false
+        });
+        cancelled
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pause.lock().unwrap().paused
+    }
+
+    pub fn is_off_hours(&self) -> bool {
+        *self.off_hours.lock().unwrap()
+    }
+
+    pub fn pause_status(&self) -> PauseStatus {
+        let pause = self.pause.lock().unwrap();
+        PauseStatus {
+            paused: pause.paused,
+            resumes_at: pause.resumes_at.map(|t| t.to_rfc3339()),
+        }
+    }
+
+    /// Time left before `provider` may be fetched again, if it is backing off.
+    fn cooldown_remaining(&self, provider: Provider) -> Option<Duration> {
+        let health = self.health.lock().unwrap();
+        let retry_at = health.get(&provider)?.retry_at?;
+        retry_at.checked_duration_since(Instant::now())
+    }
+
+    fn record_success(&self, provider: Provider) {
+        let mut health = self.health.lock().unwrap();
+        let entry = health.entry(provider).or_default();
+        entry.failures = 0;
+        entry.retry_at = None;
+        entry.last_success = Some(chrono::Local::now());
+    }
+
+    fn record_failure(&self, provider: Provider, error: &str) {
+        let mut health = self.health.lock().unwrap();
+        let entry = health.entry(provider).or_default();
+        entry.failures += 1;
+        let exp = entry.failures.saturating_sub(1).min(10);
+        let delay = Duration::from_secs((BACKOFF_BASE_SECS << exp).min(BACKOFF_MAX_SECS));
+        entry.retry_at = Some(Instant::now() + delay.mul_f64(1.0 + RETRY_JITTER * random_unit()));
+        entry.last_error = Some((error.to_string(), chrono::Local::now()));
+    }
+
+    pub fn is_configured(&self, provider: Provider) -> bool {
+        !self.unconfigured.lock().unwrap().contains(&provider)
+    }
+
+    pub fn set_unconfigured(&self, providers: HashSet<Provider>) {
+        *self.unconfigured.lock().unwrap() = providers;
+    }
+
+    pub fn set_configured(&self, provider: Provider) {
+        self.unconfigured.lock().unwrap().remove(&provider);
+    }
+
+    /// Whether the most recent fetch of `provider` failed.
+    pub fn is_failing(&self, provider: Provider) -> bool {
+        self.health
+            .lock()
+            .unwrap()
+            .get(&provider)
+            .is_some_and(|h| h.failures > 0)
+    }
+
+    /// Lifts `provider`'s backoff, e.g. after the user fixed its credentials.
+    /// The failure count starts over, so a new failure backs off from the
+    /// base delay; the last error is kept until a fetch succeeds.
+    pub fn clear_backoff(&self, provider: Provider) {
+        if let Some(entry) = self.health.lock().unwrap().get_mut(&provider) {
+            entry.failures = 0;
+            entry.retry_at = None;
+        }
+    }
+
+    /// Forgets every provider's failures and backoff.
+    pub fn reset_health(&self) {
+        self.health.lock().unwrap().clear();
+    }
+
+    /// Health summary for every provider, in display order.
+    pub fn provider_health(&self) -> Vec<ProviderHealth> {
+        let health = self.health.lock().unwrap();
+        let unconfigured = self.unconfigured.lock().unwrap();
+        Provider::ALL
+            .into_iter()
+            .map(|provider| {
+                let h = health.get(&provider).cloned().unwrap_or_default();
+                let status = if h.failures > 0 {
+                    HealthStatus::Error
+                } else if h.last_success.is_some() {
+                    HealthStatus::Ok
+                } else {
+                    HealthStatus::Unknown
+                };
+                ProviderHealth {
+                    provider,
+                    configured: !unconfigured.contains(&provider),
+                    status,
+                    consecutive_failures: h.failures,
+                    last_error: h.last_error.as_ref().map(|(e, _)| e.clone()),
+                    last_error_at: h.last_error.as_ref().map(|(_, t)| t.to_rfc3339()),
+                    last_success_at: h.last_success.map(|t| t.to_rfc3339()),
+                    retry_in_secs: h
+                        .retry_at
+                        .and_then(|t| t.checked_duration_since(Instant::now()))
+                        .map(|d| d.as_secs().max(1)),
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FetchStarted {
+    pub provider: Provider,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FetchSucceeded {
+    pub provider: Provider,
+    pub data: ProviderData,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FetchFailed {
+    pub provider: Provider,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FetchCancelled {
+    pub provider: Provider,
+}
+
+/// Cancels the background fetches of the providers `filter` selects, e.g.
+/// when monitoring pauses or a provider loses its credentials.
+pub fn cancel_fetches(app: &AppHandle, filter: impl Fn(Provider) -> bool) {
+    for provider in app.state::<SchedulerState>().cancel(filter) {
+        let _ = app.emit("provider-fetch-cancelled", FetchCancelled { provider });
+    }
+}
+
+/// Fetches one provider, emitting started/succeeded/failed events around it.
+/// Fails fast without a request while the provider is backing off, and shares
+/// the result of a fetch that was already running when this call arrived.
+pub async fn refresh_provider(app: &AppHandle, provider: Provider) -> Result<ProviderData, String> {
+    let state = app.state::<SchedulerState>();
+    if !state.is_configured(provider) {
+        return Err(format!("{} is not configured", provider.name()));
+    }
+    if let Some(wait) = state.cooldown_remaining(provider) {
+        return Err(format!(
+            "{} is cooling down after repeated failures; retry in {}s",
+            provider.name(),
+            wait.as_secs().max(1)
+        ));
+    }
+
+    let requested = Instant::now();
+    let mut flight = state.flights[&provider].lock().await;
+    if let Some((finished, result)) = flight.as_ref() {
+        if *finished >= requested {
+            return result.clone().map_err(|e| privacy::error(privacy::is_on(app), e));
+        }
+    }
+
+    let _ = app.emit("provider-fetch-started", FetchStarted { provider });
+    let timeout = Duration::from_secs(FETCH_TIMEOUT_SECS);
+    let started = Instant::now();
+    let result = match tokio::time::timeout(timeout, provider::fetch(app, provider)).await {
+        Ok(result) => result,
+        Err(_) => Err(format!(
+            "{} request timed out after {}s",
+            provider.name(),
+            FETCH_TIMEOUT_SECS
+        )),
+    };
+    app.state::<FetchStatsState>()
+        .record(provider, started.elapsed(), result.is_ok());
+    match &result {
+        Ok(data) => {
+            state.record_success(provider);
+            let _ = app.emit(
+                "provider-fetch-succeeded",
+                FetchSucceeded {
+                    provider,
+                    data: privacy::provider_data(privacy::is_on(app), data.clone()),
+                },
+            );
+        }
+        Err(error) => {
+            state.record_failure(provider, error);
+            let _ = app.emit(
+                "provider-fetch-failed",
+                FetchFailed {
+                    provider,
+                    error: privacy::error(privacy::is_on(app), error.clone()),
+                },
+            );
+        }
+    }
+    *flight = Some((Instant::now(), result.clone()));
+    tray::update_from_cache(app);
+    result.map_err(|e| privacy::error(privacy::is_on(app), e))
+}
+
+/// Fetches every configured provider not currently backing off, concurrently.
+/// A fetch of the same provider still running from an earlier refresh is
+/// cancelled.
+pub async fn refresh_all(app: &AppHandle) {
+    let state = app.state::<SchedulerState>();
+    let mut set = JoinSet::new();
+    for provider in Provider::ALL.into_iter().filter(|p| state.is_configured(*p)) {
+        let handle = app.clone();
+        let abort = set.spawn(async move {
+            let _ = refresh_provider(&handle, provider).await;
+            provider
+        });
+        state.track(provider, abort);
+    }
+    while let Some(done) = set.join_next_with_id().await {
+        if let Ok((id, provider)) = done {
+            state.untrack(provider, id);
+        }
+    }
+}
+
+/// A random number in [0, 1). Hash seeds are randomized per process, which is
+/// plenty for spreading requests out.
+fn random_unit() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_i64(chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default());
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// `secs` give or take `INTERVAL_JITTER`.
+fn jittered_interval(secs: u64) -> Duration {
+    let factor = 1.0 + INTERVAL_JITTER * (2.0 * random_unit() - 1.0);
+    Duration::from_secs(secs).mul_f64(factor)
+}
+
+/// Starts the auto-refresh loop - reads the interval from settings dynamically.
+pub fn spawn(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        check_schedule(&handle);
+        let state = handle.state::<SchedulerState>();
+        loop {
+            let secs = handle.state::<SettingsState>().get().refresh_interval_secs;
+            *state.armed_secs.lock().unwrap() = secs;
+            tokio::select! {
+                _ = tokio::time::sleep(jittered_interval(secs)) => {}
+                _ = state.timer.notified() => {
+                    let refresh = std::mem::take(&mut *state.refresh_requested.lock().unwrap());
+                    if !refresh {
+                        continue;
+                    }
+                }
+            }
+            let in_hours = check_schedule(&handle);
+            if !in_hours || handle.state::<SchedulerState>().is_paused() {
+                continue;
+            }
+            onboarding::recheck_files(&handle);
+            refresh_all(&handle).await;
+        }
+    });
+
+    // Re-arm as soon as the interval changes, however it was changed
+    let handle = app.clone();
+    app.listen_any("settings-changed", move |event| {
+        let Ok(settings) = serde_json::from_str::<Settings>(event.payload()) else {
+            return;
+        };
+        let state = handle.state::<SchedulerState>();
+        if settings.refresh_interval_secs != *state.armed_secs.lock().unwrap() {
+            state.timer.notify_one();
+        }
+    });
+}
+
+/// Interrupts the loop's sleep to refresh now (within active hours and unless
+/// paused), then restarts the interval.
+pub fn wake(app: &AppHandle) {
+    let state = app.state::<SchedulerState>();
+    *state.refresh_requested.lock().unwrap() = true;
+    state.timer.notify_one();
+}
+
+/// Watches for system wake and runs a catch-up refresh when it happens.
+pub fn spawn_wake_watcher(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last = chrono::Utc::now();
+        loop {
+            tokio::time::sleep(Duration::from_secs(WAKE_TICK_SECS)).await;
+            let now = chrono::Utc::now();
+            let gap = (now - last).num_seconds();
+            last = now;
+            if gap < WAKE_TICK_SECS as i64 + WAKE_SLACK_SECS {
+                continue;
+            }
+            let _ = handle.emit("system-woke", gap);
+            wake(&handle);
+        }
+    });
+}
+
+/// Kicks off an immediate refresh of every provider in the background.
+pub fn refresh_now(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        refresh_all(&handle).await;
+    });
+}
+
+/// Re-evaluates the active-hours schedule, updating the tray when it flips.
+/// Returns whether monitoring is currently within hours.
+pub fn check_schedule(app: &AppHandle) -> bool {
+    let hours = app.state::<SettingsState>().get().active_hours;
+    let in_hours = hours.contains(chrono::Local::now());
+    let changed = {
+        let state = app.state::<SchedulerState>();
+        let mut off_hours = state.off_hours.lock().unwrap();
+        let changed = *off_hours == in_hours;
+        *off_hours = !in_hours;
+        changed
+    };
+    if changed {
+        tray::rerender(app);
+    }
+    in_hours
+}
+
+/// Pauses or resumes background monitoring. A pause lifts itself after the
+/// configured auto-resume delay; resuming refreshes immediately.
+pub fn set_paused(app: &AppHandle, paused: bool) -> PauseStatus {
+    let state = app.state::<SchedulerState>();
+    let resume_after = app.state::<SettingsState>().get().pause_resume_after_secs;
+    let generation = {
+        let mut pause = state.pause.lock().unwrap();
+        pause.paused = paused;
+        pause.generation += 1;
+        pause.resumes_at = (paused && resume_after > 0)
+            .then(|| chrono::Local::now() + chrono::Duration::seconds(resume_after as i64));
+        pause.generation
+    };
+
+    if paused && resume_after > 0 {
+        let handle = app.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(resume_after)).await;
+            let current = handle
+                .state::<SchedulerState>()
+                .pause
+                .lock()
+                .unwrap()
+                .generation;
+            if current == generation {
+                set_paused(&handle, false);
+            }
+        });
+    }
+    if paused {
+        cancel_fetches(app, |_| true);
+    } else {
+        wake(app);
+    }
+
+    tray::set_paused(app, paused);
+    let status = state.pause_status();
+    let _ = app.emit("monitoring-paused", status.clone());
+    status
+}
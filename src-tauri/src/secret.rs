@@ -0,0 +1,60 @@
+//! Wrapper for credentials (session cookies, bearer tokens, API keys) that
+//! wipes its memory when dropped and never prints its value, so a secret
+//! can't end up in an error string by accident.
+
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+
+use zeroize::{Zeroize, Zeroizing};
+
+pub struct Secret(Zeroizing<String>);
+
+impl Secret {
+    pub fn new(value: String) -> Self {
+        Self(Zeroizing::new(value))
+    }
+
+    /// Takes a secret from raw bytes (e.g. `security` output), trimming
+    /// whitespace and wiping the original buffer.
+    pub fn from_bytes(mut bytes: Vec<u8>) -> Self {
+        let value = String::from_utf8_lossy(&bytes).trim().to_string();
+        bytes.zeroize();
+        Self::new(value)
+    }
+
+    /// The value, for the one place that has to send it.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(***)")
+    }
+}
+
+/// Best-effort overwrite of a file with zeros before deleting it, for
+/// temporary copies of credential stores.
+pub fn scrub_file(path: &Path) {
+    if let Ok(len) = std::fs::metadata(path).map(|m| m.len()) {
+        if let Ok(mut f) = std::fs::OpenOptions::new().write(true).open(path) {
+            let zeros = [0u8; 8192];
+            let mut left = len;
+            while left > 0 {
+                let n = left.min(zeros.len() as u64) as usize;
+                if f.write_all(&zeros[..n]).is_err() {
+                    break;
+                }
+                left -= n as u64;
+            }
+            let _ = f.sync_all();
+        }
+    }
+    let _ = std::fs::remove_file(path);
+}
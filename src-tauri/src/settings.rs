@@ -1,13 +1,399 @@
-//! Persists user preferences (refresh interval, notification threshold, autostart)
-//! to a JSON file in the app's data directory.
+//! Persists user preferences (refresh interval, notification and alert options,
+//! network timeouts, autostart) to a JSON file in the app's data directory.
+//!
+//! Writes are atomic (temp file, fsync, rename), and the previous good file is
+//! kept as `settings.json.bak`. If the main file fails to parse at launch, the
+//! backup is loaded instead of silently falling back to defaults.
+//!
+//! Every change is broadcast as `settings-changed` with the full settings, for
+//! the frontend and the tray menu's check items.
 
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+use crate::custom_provider::CustomProvider;
+use crate::provider::Provider;
 
 const SETTINGS_FILE: &str = "settings.json";
+const BACKUP_FILE: &str = "settings.json.bak";
+const TEMP_FILE: &str = "settings.json.tmp";
+
+/// Severity marker in front of each provider's menu bar text.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayIndicator {
+    Off,
+    /// Check mark, warning sign, double exclamation mark
+    Symbols,
+    /// Green, yellow and red dots
+    Dots,
+}
+
+/// Which usage windows a provider contributes to the menu bar text.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayMetrics {
+    /// Session and weekly, e.g. "25/62%"
+    Both,
+    Session,
+    Weekly,
+    /// Left out of the menu bar text
+    Hidden,
+}
+
+/// One provider's place in the menu bar text.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TraySlot {
+    pub provider: Provider,
+    pub metrics: TrayMetrics,
+}
+
+/// What a left click on the menu bar item does (right click always opens the
+/// menu).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeftClickAction {
+    Menu,
+    ToggleWidget,
+    Refresh,
+}
+
+/// Language of backend strings (see `i18n`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    En,
+    De,
+    Fr,
+    Es,
+    Ja,
+}
+
+/// How dollar amounts are labeled (see `i18n::currency`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CurrencyDisplay {
+    /// "$12.34"
+    Symbol,
+    /// "USD 12.34"
+    Code,
+    /// "12.34"
+    Plain,
+}
+
+/// Layout of the widget window.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowMode {
+    Full,
+    /// One-line always-on-top strip with just the percentages
+    Mini,
+}
+
+/// Screen corner the mini strip sits in.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreenCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// A personal budget, e.g. keep Claude weekly under 60% by Thursday.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Budget {
+    /// "Claude" or "Codex"
+    pub provider: String,
+    /// "session" or "weekly"
+    pub metric: String,
+    /// Percentage to stay under
+    pub target_percent: f64,
+    /// Day the target applies by; None means by the window's reset
+    #[serde(default)]
+    pub by_weekday: Option<chrono::Weekday>,
+}
+
+/// When a provider's weekly window resets, in local time. ISO weeks are
+/// Monday 00:00.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ResetAnchor {
+    pub weekday: chrono::Weekday,
+    pub hour: u32,
+    pub minute: u32,
+    /// Use the anchor even when the provider reports its own reset time
+    #[serde(default)]
+    pub override_api: bool,
+}
+
+/// User-chosen names for one provider; empty fields keep the defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProviderLabel {
+    /// Menu bar prefix, e.g. "W" instead of "C"
+    pub tray: String,
+    /// Card title and name in notifications, e.g. "Work Claude"
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertStyle {
+    /// Delivered without a sound
+    pub silent: bool,
+    /// macOS system sound name (e.g. "Glass", "Basso"), or None for the default
+    pub sound: Option<String>,
+}
+
+/// HTTP client tuning shared by all provider requests.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkOptions {
+    /// Seconds to wait for a TCP/TLS connection
+    pub connect_timeout_secs: u64,
+    /// Seconds to wait between bytes of a response
+    pub read_timeout_secs: u64,
+    /// Seconds an idle pooled connection is kept for reuse
+    pub pool_idle_timeout_secs: u64,
+    /// Negotiate HTTP/2 when the server offers it (HTTP/1.1 only otherwise)
+    pub prefer_http2: bool,
+}
+
+impl Default for NetworkOptions {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: 10,
+            read_timeout_secs: 20,
+            pool_idle_timeout_secs: 90,
+            prefer_http2: true,
+        }
+    }
+}
+
+/// Hours during which background monitoring runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActiveHours {
+    /// When false, monitoring runs around the clock
+    pub enabled: bool,
+    /// Local start time, "HH:MM"
+    pub start: String,
+    /// Local end time, "HH:MM"; earlier than `start` spans midnight
+    pub end: String,
+    /// Days monitoring runs on (the day the window starts)
+    pub days: Vec<chrono::Weekday>,
+}
+
+impl Default for ActiveHours {
+    fn default() -> Self {
+        use chrono::Weekday::*;
+        Self {
+            enabled: false,
+            start: "08:00".into(),
+            end: "20:00".into(),
+            days: vec![Mon, Tue, Wed, Thu, Fri],
+        }
+    }
+}
+
+/// SMTP server alerts are emailed through. The password lives in the Keychain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmailSettings {
+    /// Forward threshold and limit alerts by email
+    pub enabled: bool,
+    pub smtp_host: String,
+    /// 587 for STARTTLS, 465 for implicit TLS
+    pub smtp_port: u16,
+    /// SMTP login, or empty for an unauthenticated relay
+    pub username: String,
+    pub from: String,
+    pub to: String,
+}
+
+impl Default for EmailSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            smtp_host: String::new(),
+            smtp_port: 587,
+            username: String::new(),
+            from: String::new(),
+            to: String::new(),
+        }
+    }
+}
 
+/// Self-hosted endpoint for team mode. The optional token lives in the Keychain.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TeamSettings {
+    /// Push anonymized snapshots and read the team aggregate
+    pub enabled: bool,
+    /// Base URL, e.g. "https://usage.example.com/team"
+    pub endpoint: String,
+    /// Random id this install reports under, created when team mode is first set up
+    pub member_id: String,
+}
+
+/// How long history is kept. Raw samples older than `raw_days` are folded
+/// into hourly rollups, which are dropped after `rollup_days`; 0 keeps them
+/// forever.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Retention {
+    pub raw_days: u32,
+    pub rollup_days: u32,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            raw_days: 90,
+            rollup_days: 365,
+        }
+    }
+}
+
+impl Retention {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.rollup_days != 0 && (self.raw_days == 0 || self.rollup_days < self.raw_days) {
+            return Err("Rollups must be kept at least as long as raw samples".into());
+        }
+        Ok(())
+    }
+}
+
+/// One direction work can move in: when `from` runs short and `to` has room,
+/// switching is suggested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailoverPair {
+    pub from: Provider,
+    pub to: Provider,
+}
+
+/// Suggestions to move work to another provider when one is nearly out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Failover {
+    pub enabled: bool,
+    /// The tightest window of `from` is at least this full (percent)
+    pub critical_percent: u32,
+    /// The tightest window of `to` is at most this full (percent)
+    pub max_target_percent: u32,
+    pub pairs: Vec<FailoverPair>,
+}
+
+impl Default for Failover {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            critical_percent: 90,
+            max_target_percent: 50,
+            pairs: vec![
+                FailoverPair {
+                    from: Provider::Claude,
+                    to: Provider::Codex,
+                },
+                FailoverPair {
+                    from: Provider::Codex,
+                    to: Provider::Claude,
+                },
+            ],
+        }
+    }
+}
+
+/// Providers that can appear in the menu bar text, in the default order.
+pub const TRAY_PROVIDERS: [Provider; 3] = [Provider::Claude, Provider::Codex, Provider::OpenRouter];
+
+pub fn validate_tray_layout(layout: &[TraySlot]) -> Result<(), String> {
+    for (i, slot) in layout.iter().enumerate() {
+        if !TRAY_PROVIDERS.contains(&slot.provider) {
+            return Err(format!("{} isn't shown in the menu bar", slot.provider.name()));
+        }
+        if layout[..i].iter().any(|s| s.provider == slot.provider) {
+            return Err(format!("{} is listed twice", slot.provider.name()));
+        }
+        let windows = matches!(slot.metrics, TrayMetrics::Session | TrayMetrics::Weekly);
+        if slot.provider == Provider::OpenRouter && windows {
+            return Err("OpenRouter shows a credit balance, not usage windows".into());
+        }
+    }
+    let hidden = |p: &Provider| {
+        layout
+            .iter()
+            .any(|s| s.provider == *p && s.metrics == TrayMetrics::Hidden)
+    };
+    if TRAY_PROVIDERS.iter().all(hidden) {
+        return Err("At least one provider must stay in the menu bar".into());
+    }
+    Ok(())
+}
+
+impl Failover {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.critical_percent == 0 || self.critical_percent > 100 {
+            return Err("The critical level must be between 1 and 100".into());
+        }
+        if self.max_target_percent >= self.critical_percent {
+            return Err("The other provider's ceiling must be below the critical level".into());
+        }
+        for pair in &self.pairs {
+            if pair.from == pair.to {
+                return Err("A provider can't fail over to itself".into());
+            }
+            let limited = |p: Provider| matches!(p, Provider::Claude | Provider::Codex);
+            if !limited(pair.from) || !limited(pair.to) {
+                return Err("Only Claude and Codex have usage limits to compare".into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Minutes since midnight for an "HH:MM" string.
+fn parse_hhmm(s: &str) -> Option<u32> {
+    let (h, m) = s.trim().split_once(':')?;
+    let (h, m): (u32, u32) = (h.parse().ok()?, m.parse().ok()?);
+    (h < 24 && m < 60).then_some(h * 60 + m)
+}
+
+impl ActiveHours {
+    pub fn validate(&self) -> Result<(), String> {
+        for t in [&self.start, &self.end] {
+            parse_hhmm(t).ok_or_else(|| format!("Invalid time \"{}\", expected HH:MM", t))?;
+        }
+        Ok(())
+    }
+
+    /// Whether `now` falls inside the schedule.
+    pub fn contains(&self, now: chrono::DateTime<chrono::Local>) -> bool {
+        use chrono::{Datelike, Timelike};
+        if !self.enabled {
+            return true;
+        }
+        let (Some(start), Some(end)) = (parse_hhmm(&self.start), parse_hhmm(&self.end)) else {
+            return true;
+        };
+        let minute = now.hour() * 60 + now.minute();
+        let today = now.weekday();
+        if start <= end {
+            self.days.contains(&today) && (start..end).contains(&minute)
+        } else {
+            // Overnight window: the early-morning part belongs to yesterday's window
+            (minute >= start && self.days.contains(&today))
+                || (minute < end && self.days.contains(&today.pred()))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
     /// Refresh interval in seconds (60, 120, 300, 600, 900)
     pub refresh_interval_secs: u64,
@@ -15,8 +401,162 @@ pub struct Settings {
     pub notify_threshold: u32,
     /// Whether notifications are enabled
     pub notifications_enabled: bool,
+    /// Points a metric must drop below the threshold before it can alert again
+    pub notify_hysteresis_points: u32,
+    /// Minimum seconds between two threshold alerts for the same metric
+    pub renotify_min_secs: u64,
+    /// Keep reminding while a metric stays at or above `reminder_level`
+    pub reminders_enabled: bool,
+    /// Percentage at which repeat reminders start
+    pub reminder_level: u32,
+    /// Minutes between repeat reminders
+    pub reminder_interval_mins: u64,
+    /// Metrics that never trigger threshold alerts: a metric name ("sonnet",
+    /// "extra") for every provider, or "<Provider>_<metric>" for one
+    pub muted_metrics: BTreeSet<String>,
+    /// Threshold overrides keyed like `muted_metrics`, so e.g. the Opus window
+    /// can alert earlier than the rest; 0 turns threshold alerts off for it
+    pub metric_thresholds: BTreeMap<String, u32>,
     /// Whether app starts at login
     pub start_at_login: bool,
+    /// Seconds without keyboard/mouse input before the user counts as away
+    pub idle_after_secs: u64,
+    /// Percentage-point rise while away that triggers a spike alert, or 0 to disable
+    pub idle_spike_points: u32,
+    /// Hours of active use with every value unchanged before a provider's data
+    /// counts as stuck, or 0 to disable
+    pub stale_after_hours: u32,
+    /// Synced folder shared with the widget on other Macs, if any
+    pub sync_dir: Option<String>,
+    /// Show one menu bar item per provider instead of a single combined one
+    pub tray_per_provider: bool,
+    /// Style for routine alerts (threshold crossings)
+    pub routine_alert: AlertStyle,
+    /// Style for critical alerts (limit reached, usage while away)
+    pub critical_alert: AlertStyle,
+    /// Timeouts and connection pooling for provider requests
+    pub network: NetworkOptions,
+    /// Seconds after which paused monitoring resumes on its own, or 0 to stay paused
+    pub pause_resume_after_secs: u64,
+    /// Only poll during these hours
+    pub active_hours: ActiveHours,
+    /// Whether the first-run setup wizard has been finished or skipped
+    pub onboarding_complete: bool,
+    /// Hold back alerts while a macOS Focus mode is on
+    pub suppress_during_focus: bool,
+    /// Still deliver critical alerts (limit reached, usage while away) during Focus
+    pub focus_allow_critical: bool,
+    /// Email channel for alerts
+    pub email: EmailSettings,
+    /// Post alerts to the Discord webhook stored in the Keychain
+    pub discord_enabled: bool,
+    /// Send alerts through the Telegram bot stored in the Keychain and answer
+    /// `/usage` in its chat
+    pub telegram_enabled: bool,
+    /// Script run on every alert with the alert as JSON on stdin
+    pub shell_hook: Option<String>,
+    /// Keep ~/.claude-usage/capacity.json up to date for hooks and scripts
+    pub capacity_file: bool,
+    /// Claude session percentage at which the installed Claude Code hook
+    /// steps in
+    pub hook_ceiling: u32,
+    /// Whether the Claude Code hook blocks tool calls above the ceiling
+    /// (otherwise it only warns)
+    pub hook_block: bool,
+    /// Team mode (shared org usage)
+    pub team: TeamSettings,
+    /// Hide dollar amounts, plan names and account details everywhere
+    pub privacy_mode: bool,
+    /// Claude cookie database picked by hand, used when automatic access to
+    /// the default location is denied
+    pub claude_cookies_path: Option<String>,
+    /// Security-scoped bookmark of `claude_cookies_path`, base64, so the
+    /// sandbox grant survives a relaunch
+    pub claude_cookies_bookmark: Option<String>,
+    /// The app has its own Keychain access entry for Claude Safe Storage, so
+    /// the key is read through Security.framework instead of `security`
+    pub keychain_access_granted: bool,
+    /// Longest menu bar title in characters before it is abbreviated; 0 means
+    /// no limit
+    pub tray_max_chars: u32,
+    /// Severity marker per provider: warning at the notification threshold,
+    /// critical at the reminder level
+    pub tray_indicator: TrayIndicator,
+    /// Append `+`/`-` to a provider's menu bar text when weekly usage is
+    /// ahead of or behind an even daily pace
+    pub tray_pacing: bool,
+    /// Flash the menu bar text for a few seconds when a limit is reached
+    pub tray_flash: bool,
+    /// Order of providers in the menu bar text and the windows each shows;
+    /// providers left out follow in the default order, showing both
+    pub tray_layout: Vec<TraySlot>,
+    /// Glide menu bar percentages toward each new value instead of jumping
+    pub smooth_tray: bool,
+    /// Custom labels keyed by provider id ("claude", "codex", "openrouter")
+    pub labels: BTreeMap<String, ProviderLabel>,
+    /// Keep the WidgetKit companion's app-group JSON up to date
+    pub widget_data: bool,
+    /// Left-click behavior of the menu bar item(s)
+    pub left_click: LeftClickAction,
+    /// Personal budgets checked against a linear schedule after each fetch
+    pub budgets: Vec<Budget>,
+    /// Weekly reset anchors keyed by provider id ("claude", "codex"), used
+    /// when the API omits the reset time or the user overrides it
+    pub reset_anchors: BTreeMap<String, ResetAnchor>,
+    /// Keep an "AI limit resets" calendar in Calendar.app in step with the
+    /// weekly resets
+    pub calendar_sync: bool,
+    /// Enables developer commands such as `fetch_raw`
+    pub debug_tools: bool,
+    /// Hide the widget when it loses focus, unless it is pinned
+    pub hide_on_blur: bool,
+    /// Window opacity while pinned, from 0.2 to 1.0
+    pub pinned_opacity: f64,
+    /// Let clicks pass through the pinned widget to the app below
+    pub click_through: bool,
+    pub window_mode: WindowMode,
+    /// Where the mini strip is placed
+    pub mini_corner: ScreenCorner,
+    /// Keep the pinned widget or mini strip on every Space and over fullscreen
+    /// apps
+    pub all_spaces: bool,
+    /// Show the app in the Dock and ⌘-Tab, not just the menu bar
+    pub show_dock_icon: bool,
+    /// Show a preview of every metric while hovering a menu bar item
+    pub hover_preview: bool,
+    /// How long the pointer must rest on the item before the preview opens
+    pub hover_preview_delay_ms: u64,
+    /// Widget pinned on top; restored at launch
+    pub widget_pinned: bool,
+    /// Widget was showing at quit; shown again at launch
+    pub widget_visible: bool,
+    /// Language of menus and notifications; None follows the system
+    pub language: Option<Language>,
+    pub currency_display: CurrencyDisplay,
+    /// Post a one-off summary of provider status and usage at launch
+    pub startup_summary: bool,
+    /// Start with the widget hidden, whatever it was at quit
+    pub launch_minimized: bool,
+    /// Show the widget when the first fresh data arrives after launch
+    pub show_on_first_data: bool,
+    /// Show the widget at launch when any metric is at or above this percent;
+    /// 0 is off
+    pub show_above_percent: u32,
+    /// How long usage history is kept before it is rolled up and pruned
+    pub retention: Retention,
+    /// Suggestions to switch providers when one runs short
+    pub failover: Failover,
+    /// Base URL of a LiteLLM gateway (e.g. "http://localhost:4000"); its key
+    /// lives in the Keychain
+    pub litellm_base_url: Option<String>,
+    /// Watch a local Ollama server for loaded models and activity
+    pub ollama_enabled: bool,
+    /// Base URL of the Ollama server; `None` follows `OLLAMA_HOST`, else the
+    /// default localhost port
+    pub ollama_base_url: Option<String>,
+    /// Bearer-token services read through JSON pointers (see
+    /// `custom_provider`); their keys live in the Keychain
+    pub custom_providers: Vec<CustomProvider>,
 }
 
 impl Default for Settings {
@@ -25,41 +565,175 @@ impl Default for Settings {
             refresh_interval_secs: 300,
             notify_threshold: 80,
             notifications_enabled: true,
+            notify_hysteresis_points: 5,
+            renotify_min_secs: 1800,
+            reminders_enabled: false,
+            reminder_level: 95,
+            reminder_interval_mins: 30,
+            muted_metrics: BTreeSet::new(),
+            metric_thresholds: BTreeMap::new(),
             start_at_login: false,
+            idle_after_secs: 300,
+            idle_spike_points: 10,
+            stale_after_hours: 6,
+            sync_dir: None,
+            tray_per_provider: false,
+            routine_alert: AlertStyle::default(),
+            critical_alert: AlertStyle::default(),
+            network: NetworkOptions::default(),
+            pause_resume_after_secs: 3600,
+            active_hours: ActiveHours::default(),
+            onboarding_complete: false,
+            suppress_during_focus: false,
+            focus_allow_critical: false,
+            email: EmailSettings::default(),
+            discord_enabled: false,
+            telegram_enabled: false,
+            shell_hook: None,
+            capacity_file: false,
+            hook_ceiling: 90,
+            hook_block: false,
+            team: TeamSettings::default(),
+            privacy_mode: false,
+            claude_cookies_path: None,
+            claude_cookies_bookmark: None,
+            keychain_access_granted: false,
+            tray_max_chars: 0,
+            tray_indicator: TrayIndicator::Off,
+            tray_pacing: false,
+            tray_flash: false,
+            tray_layout: TRAY_PROVIDERS
+                .into_iter()
+                .map(|provider| TraySlot {
+                    provider,
+                    metrics: TrayMetrics::Both,
+                })
+                .collect(),
+            smooth_tray: false,
+            labels: BTreeMap::new(),
+            widget_data: false,
+            left_click: LeftClickAction::Menu,
+            budgets: Vec::new(),
+            reset_anchors: BTreeMap::new(),
+            calendar_sync: false,
+            debug_tools: false,
+            hide_on_blur: false,
+            pinned_opacity: 1.0,
+            click_through: false,
+            window_mode: WindowMode::Full,
+            mini_corner: ScreenCorner::TopRight,
+            all_spaces: false,
+            show_dock_icon: true,
+            hover_preview: false,
+            hover_preview_delay_ms: 500,
+            widget_pinned: false,
+            widget_visible: false,
+            language: None,
+            currency_display: CurrencyDisplay::Symbol,
+            startup_summary: true,
+            launch_minimized: false,
+            show_on_first_data: false,
+            show_above_percent: 0,
+            retention: Retention::default(),
+            failover: Failover::default(),
+            litellm_base_url: None,
+            ollama_enabled: false,
+            ollama_base_url: None,
+            custom_providers: Vec::new(),
         }
     }
 }
 
+impl Settings {
+    fn label(&self, provider: &str) -> Option<&ProviderLabel> {
+        self.labels.get(&provider.to_lowercase())
+    }
+
+    /// Alert threshold for a metric: its per-provider override (e.g.
+    /// "claude_opus"), then its per-metric one (e.g. "opus"), then the global
+    /// `notify_threshold`. 0 means no threshold alerts.
+    pub fn threshold_for(&self, key: &str, metric: &str) -> u32 {
+        self.metric_thresholds
+            .get(key)
+            .or_else(|| self.metric_thresholds.get(metric))
+            .copied()
+            .unwrap_or(self.notify_threshold)
+    }
+
+    /// Name to show for `provider` (e.g. "Claude"), or its custom label.
+    pub fn display_name(&self, provider: &str) -> String {
+        self.label(provider)
+            .map(|l| l.name.trim())
+            .filter(|n| !n.is_empty())
+            .unwrap_or(provider)
+            .to_string()
+    }
+
+    /// Menu bar prefix for `provider`, or its custom label.
+    pub fn tray_prefix(&self, provider: &str, default: &str) -> String {
+        self.label(provider)
+            .map(|l| l.tray.trim())
+            .filter(|p| !p.is_empty())
+            .unwrap_or(default)
+            .to_string()
+    }
+}
+
 pub struct SettingsState {
     pub settings: Mutex<Settings>,
     data_dir: PathBuf,
+    app: AppHandle,
 }
 
 impl SettingsState {
-    pub fn new(data_dir: PathBuf) -> Self {
+    pub fn new(data_dir: PathBuf, app: AppHandle) -> Self {
         let settings = Self::load_from(&data_dir).unwrap_or_default();
         Self {
             settings: Mutex::new(settings),
             data_dir,
+            app,
         }
     }
 
-    fn settings_path(data_dir: &PathBuf) -> PathBuf {
+    fn settings_path(data_dir: &Path) -> PathBuf {
         data_dir.join(SETTINGS_FILE)
     }
 
-    fn load_from(data_dir: &PathBuf) -> Option<Settings> {
+    fn read(path: &Path) -> Result<Settings, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    }
+
+    fn load_from(data_dir: &Path) -> Option<Settings> {
         let path = Self::settings_path(data_dir);
-        let content = std::fs::read_to_string(path).ok()?;
-        serde_json::from_str(&content).ok()
+        if !path.exists() {
+            return None;
+        }
+        match Self::read(&path) {
+            Ok(settings) => Some(settings),
+            Err(e) => {
+                log::warn!("settings.json is unreadable ({}), trying the backup", e);
+                Self::read(&data_dir.join(BACKUP_FILE)).ok()
+            }
+        }
     }
 
     pub fn save(&self) -> Result<(), String> {
         std::fs::create_dir_all(&self.data_dir).map_err(|e| e.to_string())?;
         let path = Self::settings_path(&self.data_dir);
+        let tmp = self.data_dir.join(TEMP_FILE);
         let settings = self.settings.lock().unwrap();
         let json = serde_json::to_string_pretty(&*settings).map_err(|e| e.to_string())?;
-        std::fs::write(path, json).map_err(|e| e.to_string())
+        {
+            let mut file = std::fs::File::create(&tmp).map_err(|e| e.to_string())?;
+            file.write_all(json.as_bytes()).map_err(|e| e.to_string())?;
+            file.sync_all().map_err(|e| e.to_string())?;
+        }
+        // Only a file that still parses is worth keeping as the backup
+        if Self::read(&path).is_ok() {
+            std::fs::copy(&path, self.data_dir.join(BACKUP_FILE)).map_err(|e| e.to_string())?;
+        }
+        std::fs::rename(&tmp, &path).map_err(|e| e.to_string())
     }
 
     pub fn update<F: FnOnce(&mut Settings)>(&self, f: F) -> Result<Settings, String> {
@@ -68,7 +742,9 @@ impl SettingsState {
             f(&mut settings);
         }
         self.save()?;
-        Ok(self.settings.lock().unwrap().clone())
+        let settings = self.get();
+        let _ = self.app.emit("settings-changed", &settings);
+        Ok(settings)
     }
 
     pub fn get(&self) -> Settings {
@@ -0,0 +1,48 @@
+//! Runs a user script on every alert, with the alert as JSON on stdin, so
+//! custom automations (pausing a local agent daemon, ...) can react to it.
+
+use std::time::Duration;
+
+use tauri::AppHandle;
+use tauri_plugin_shell::process::CommandEvent;
+use tauri_plugin_shell::ShellExt;
+
+use crate::alerts::Alert;
+
+/// Scripts still running after this are killed.
+const HOOK_TIMEOUT_SECS: u64 = 30;
+
+pub async fn run(app: &AppHandle, script: &str, alert: &Alert) -> Result<(), String> {
+    let json = serde_json::to_vec(alert).map_err(|e| e.to_string())?;
+    let (mut rx, mut child) = app
+        .shell()
+        .command(script)
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", script, e))?;
+    let pid = child.pid();
+    child
+        .write(&json)
+        .map_err(|e| format!("Failed to write to {}: {}", script, e))?;
+    // Dropping the child closes stdin so the script sees EOF
+    drop(child);
+
+    let wait = async {
+        while let Some(event) = rx.recv().await {
+            if let CommandEvent::Terminated(status) = event {
+                return status.code;
+            }
+        }
+        None
+    };
+    match tokio::time::timeout(Duration::from_secs(HOOK_TIMEOUT_SECS), wait).await {
+        Ok(Some(0)) => Ok(()),
+        Ok(Some(code)) => Err(format!("{} exited with status {}", script, code)),
+        Ok(None) => Err(format!("{} was terminated by a signal", script)),
+        Err(_) => {
+            let _ = std::process::Command::new("kill")
+                .arg(pid.to_string())
+                .status();
+            Err(format!("{} timed out", script))
+        }
+    }
+}
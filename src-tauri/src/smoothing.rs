@@ -0,0 +1,107 @@
+//! Optional smoothing of the menu bar percentages. Instead of jumping to each
+//! new value at refresh time, a figure glides linearly from what is shown
+//! toward the latest sample over `GLIDE_SECS`, advanced by a one-second
+//! ticker. Credit balances are never smoothed.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Manager};
+
+use crate::settings::SettingsState;
+use crate::tray::{self, TrayValues};
+
+const GLIDE_SECS: f64 = 60.0;
+const TICK: Duration = Duration::from_secs(1);
+
+struct Glide {
+    from: f64,
+    to: f64,
+    started: Instant,
+}
+
+impl Glide {
+    fn value(&self, now: Instant) -> f64 {
+        let t = (now - self.started).as_secs_f64() / GLIDE_SECS;
+        self.from + (self.to - self.from) * t.min(1.0)
+    }
+
+    fn done(&self, now: Instant) -> bool {
+        (now - self.started).as_secs_f64() >= GLIDE_SECS
+    }
+}
+
+/// Glides keyed by "provider.metric".
+pub struct SmoothingState {
+    glides: Mutex<HashMap<String, Glide>>,
+}
+
+impl SmoothingState {
+    pub fn new() -> Self {
+        Self {
+            glides: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Value to show for `key` now, starting a new glide from the shown value
+    /// when `target` changed. A key seen for the first time starts at its target.
+    fn step(&self, key: &str, target: f64, now: Instant) -> f64 {
+        let mut glides = self.glides.lock().unwrap();
+        match glides.get(key) {
+            Some(g) if g.to == target => g.value(now),
+            current => {
+                let from = current.map(|g| g.value(now)).unwrap_or(target);
+                glides.insert(
+                    key.to_string(),
+                    Glide {
+                        from,
+                        to: target,
+                        started: now,
+                    },
+                );
+                from
+            }
+        }
+    }
+
+    fn gliding(&self) -> bool {
+        let now = Instant::now();
+        self.glides.lock().unwrap().values().any(|g| !g.done(now))
+    }
+}
+
+/// The values to display for `values`: as given with smoothing off, otherwise
+/// partway along each percentage's glide.
+pub fn shown(app: &AppHandle, values: &TrayValues) -> TrayValues {
+    let state = app.state::<SmoothingState>();
+    if !app.state::<SettingsState>().get().smooth_tray {
+        state.glides.lock().unwrap().clear();
+        return values.clone();
+    }
+    let now = Instant::now();
+    let pair = |provider: &str, (session, weekly): (f64, f64)| {
+        (
+            state.step(&format!("{}.session", provider), session, now),
+            state.step(&format!("{}.weekly", provider), weekly, now),
+        )
+    };
+    TrayValues {
+        claude: values.claude.map(|v| pair("claude", v)),
+        codex: values.codex.map(|v| pair("codex", v)),
+        openrouter: values.openrouter,
+    }
+}
+
+/// Re-renders the tray every second while any percentage is still gliding.
+pub fn spawn(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(TICK).await;
+            if handle.state::<SmoothingState>().gliding() {
+                tray::refresh_values(&handle);
+            }
+        }
+    });
+}
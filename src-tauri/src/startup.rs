@@ -0,0 +1,168 @@
+//! One consolidated notification at launch saying what the widget found:
+//! "Claude 72% of weekly - resets Friday; Codex not configured". Without it a
+//! fresh start is silent and users can't tell whether anything is working.
+//! The same text goes to the frontend as `startup-summary`.
+//!
+//! Launch can also bring up the widget on its own: when the first fresh data
+//! arrives, or when any metric is already above a chosen level.
+
+use chrono::{Datelike, Local, Weekday};
+use tauri::{AppHandle, Emitter, Listener, Manager};
+
+use crate::codex_fetcher::CodexState;
+use crate::i18n;
+use crate::notifications::{self, Severity};
+use crate::litellm_fetcher::LiteLlmState;
+use crate::openrouter_fetcher::OpenRouterState;
+use crate::privacy;
+use crate::provider::Provider;
+use crate::scheduler::{self, SchedulerState};
+use crate::settings::SettingsState;
+use crate::usage_fetcher::{UsageData, UsageState};
+use crate::widget_window;
+
+/// "Friday", or "today" when the reset is later today.
+fn reset_day(resets_at: &str) -> Option<String> {
+    let at = chrono::DateTime::parse_from_rfc3339(resets_at)
+        .ok()?
+        .with_timezone(&Local);
+    if at.date_naive() == Local::now().date_naive() {
+        return Some(i18n::t("startup.today").into());
+    }
+    let key = match at.weekday() {
+        Weekday::Mon => "startup.mon",
+        Weekday::Tue => "startup.tue",
+        Weekday::Wed => "startup.wed",
+        Weekday::Thu => "startup.thu",
+        Weekday::Fri => "startup.fri",
+        Weekday::Sat => "startup.sat",
+        Weekday::Sun => "startup.sun",
+    };
+    Some(i18n::t(key).into())
+}
+
+fn usage_line(name: &str, data: &UsageData) -> String {
+    let percent = format!("{:.0}", data.weekly_all.percent_used);
+    match data.weekly_all.resets_at.as_deref().and_then(reset_day) {
+        Some(day) => i18n::tf("startup.weekly", &[&name, &percent, &day]),
+        None => i18n::tf("startup.weekly_no_reset", &[&name, &percent]),
+    }
+}
+
+/// One line per provider: its weekly usage, credit balance, or why there's
+/// nothing to show.
+pub fn summary(app: &AppHandle) -> String {
+    let settings = app.state::<SettingsState>().get();
+    let scheduler = app.state::<SchedulerState>();
+    let lines: Vec<String> = Provider::ALL
+        .into_iter()
+        .filter_map(|provider| {
+            let name = settings.display_name(provider.name());
+            if !scheduler.is_configured(provider) {
+                // The other providers are optional, so their absence isn't news
+                return matches!(provider, Provider::Claude | Provider::Codex)
+                    .then(|| i18n::tf("startup.not_configured", &[&name]));
+            }
+            if scheduler.is_failing(provider) {
+                return Some(i18n::tf("startup.failing", &[&name]));
+            }
+            match provider {
+                Provider::Claude => app
+                    .state::<UsageState>()
+                    .last_data
+                    .lock()
+                    .unwrap()
+                    .as_ref()
+                    .map(|d| usage_line(&name, d)),
+                Provider::Codex => app
+                    .state::<CodexState>()
+                    .last_data
+                    .lock()
+                    .unwrap()
+                    .as_ref()
+                    .map(|d| usage_line(&name, d)),
+                Provider::OpenRouter => app
+                    .state::<OpenRouterState>()
+                    .last_data
+                    .lock()
+                    .unwrap()
+                    .as_ref()
+                    .map(|d| {
+                        let remaining = privacy::money(settings.privacy_mode, d.remaining_credits);
+                        i18n::tf("startup.credits", &[&name, &remaining])
+                    }),
+                Provider::LiteLlm => app
+                    .state::<LiteLlmState>()
+                    .last_data
+                    .lock()
+                    .unwrap()
+                    .as_ref()
+                    .map(|d| match d.remaining {
+                        Some(remaining) => {
+                            let remaining = privacy::money(settings.privacy_mode, remaining);
+                            i18n::tf("startup.credits", &[&name, &remaining])
+                        }
+                        None => {
+                            let spent = privacy::money(settings.privacy_mode, d.spend);
+                            i18n::tf("startup.spent", &[&name, &spent])
+                        }
+                    }),
+                // Local activity says nothing about what's left
+                Provider::Ollama => None,
+            }
+        })
+        .collect();
+    lines.join("; ")
+}
+
+/// Highest percentage across every metric of the providers with data.
+fn peak_percent(app: &AppHandle) -> f64 {
+    let claude = app.state::<UsageState>().last_data.lock().unwrap().clone();
+    let codex = app.state::<CodexState>().last_data.lock().unwrap().clone();
+    [claude, codex]
+        .iter()
+        .flatten()
+        .flat_map(|d| d.metric_percents())
+        .map(|(_, percent)| percent)
+        .fold(0.0, f64::max)
+}
+
+/// Shows the widget without taking focus from the app in front.
+fn reveal(app: &AppHandle) {
+    if let Some(w) = app.get_webview_window(widget_window::LABEL) {
+        let _ = w.show();
+    }
+}
+
+/// Fetches every provider once, then posts the summary and shows the widget
+/// as configured. Outside active hours nothing is fetched and cached data is
+/// used.
+pub fn spawn(app: &AppHandle) {
+    let settings = app.state::<SettingsState>().get();
+    if settings.show_on_first_data {
+        let handle = app.clone();
+        app.once_any("provider-fetch-succeeded", move |_| reveal(&handle));
+    }
+    if !settings.startup_summary && settings.show_above_percent == 0 {
+        return;
+    }
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if scheduler::check_schedule(&handle) {
+            scheduler::refresh_all(&handle).await;
+        }
+        let level = settings.show_above_percent;
+        if level > 0 && peak_percent(&handle) >= level as f64 {
+            reveal(&handle);
+        }
+        if !settings.startup_summary {
+            return;
+        }
+        let body = summary(&handle);
+        if body.is_empty() {
+            return;
+        }
+        notifications::send(&handle, Severity::Routine, i18n::t("startup.title"), &body);
+        let _ = handle.emit("startup-summary", &body);
+    });
+}
@@ -0,0 +1,232 @@
+//! Shares usage between Macs through a user-chosen synced folder (iCloud Drive,
+//! Dropbox, ...).
+//!
+//! Each machine periodically writes `<machine>.json` into the folder with its
+//! recent samples and local token totals, and reads everyone else's file.
+//! Writes happen at most every ten minutes, on a background thread so a slow
+//! cloud drive never holds up a fetch, and go through a temp file and a
+//! rename so another Mac never reads a half-written file. The
+//! provider percentages are account-wide already, but local tokens are
+//! per-machine, so merging them shows how an account limit is being shared.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::codex_logs::CodexEventKind;
+use crate::history::{HistoryState, Sample};
+use crate::settings::SettingsState;
+use crate::{claude_logs, codex_logs};
+
+/// How much history each machine publishes.
+const SHARED_HISTORY_SECS: i64 = 7 * 86_400;
+/// Minimum time between rewrites of this machine's file.
+const WRITE_INTERVAL_SECS: i64 = 600;
+
+pub struct SyncState {
+    /// Unix time of the last write started from this process
+    last_publish: Mutex<Option<i64>>,
+}
+
+impl SyncState {
+    pub fn new() -> Self {
+        Self {
+            last_publish: Mutex::new(None),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MachineFile {
+    pub machine: String,
+    /// Unix timestamp (seconds)
+    pub updated_at: i64,
+    pub claude_tokens_7d: u64,
+    pub codex_tokens_7d: u64,
+    pub samples: Vec<Sample>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MachineUsage {
+    pub machine: String,
+    pub is_local: bool,
+    pub updated_at: i64,
+    pub claude_tokens_7d: u64,
+    pub codex_tokens_7d: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MergedUsage {
+    pub machines: Vec<MachineUsage>,
+    pub claude_tokens_7d: u64,
+    pub codex_tokens_7d: u64,
+    /// Freshest sample per provider/metric across all machines
+    pub latest: Vec<Sample>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MachineSample {
+    pub machine: String,
+    #[serde(flatten)]
+    pub sample: Sample,
+}
+
+/// A stable, human-readable name for this Mac.
+pub fn machine_name() -> String {
+    Command::new("scutil")
+        .args(["--get", "LocalHostName"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|s| !s.is_empty())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .unwrap_or_else(|| "this-mac".into())
+}
+
+fn machine_path(dir: &Path, machine: &str) -> PathBuf {
+    let safe: String = machine
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    dir.join(format!("{}.json", safe))
+}
+
+/// Rewrites this machine's file on a background thread, unless it was
+/// written within the write interval. A no-op without a sync folder. Call
+/// after new samples are recorded.
+pub fn publish(app: &AppHandle) {
+    let Some(dir) = app.state::<SettingsState>().get().sync_dir else {
+        return;
+    };
+    let now = chrono::Utc::now().timestamp();
+    {
+        let state = app.state::<SyncState>();
+        let mut last = state.last_publish.lock().unwrap();
+        if last.is_some_and(|t| now - t < WRITE_INTERVAL_SECS) {
+            return;
+        }
+        *last = Some(now);
+    }
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = write_machine_file(Path::new(&dir), &app.state::<HistoryState>(), now) {
+            log::warn!("Sync folder update failed: {}", e);
+            // Try again after the next fetch
+            *app.state::<SyncState>().last_publish.lock().unwrap() = None;
+        }
+    });
+}
+
+fn write_machine_file(dir: &Path, history: &HistoryState, now: i64) -> Result<(), String> {
+    let machine = machine_name();
+    let path = machine_path(dir, &machine);
+    let since = now - SHARED_HISTORY_SECS;
+    let claude_tokens_7d = claude_logs::read_entries(since)
+        .map(|entries| entries.iter().map(|e| e.billable_tokens()).sum())
+        .unwrap_or(0);
+    let codex_tokens_7d = codex_logs::read_events(since)
+        .map(|events| {
+            events
+                .iter()
+                .map(|e| match &e.kind {
+                    CodexEventKind::Tokens(t) => t.total,
+                    _ => 0,
+                })
+                .sum()
+        })
+        .unwrap_or(0);
+
+    let file = MachineFile {
+        machine,
+        updated_at: now,
+        claude_tokens_7d,
+        codex_tokens_7d,
+        samples: history.samples_since(None, since)?,
+    };
+
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string(&file).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, &path).map_err(|e| e.to_string())
+}
+
+fn read_file(path: &Path) -> Option<MachineFile> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// All machine files in the sync folder, including this machine's own.
+pub fn read_all(dir: &Path) -> Result<Vec<MachineFile>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Cannot read sync folder: {}", e))?;
+    Ok(entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("json"))
+        .filter_map(|p| read_file(&p))
+        .collect())
+}
+
+/// Combined consumption across every machine publishing to the folder.
+pub fn merged_usage(dir: &Path) -> Result<MergedUsage, String> {
+    let files = read_all(dir)?;
+    let local = machine_name();
+
+    let mut latest: HashMap<(String, String), Sample> = HashMap::new();
+    for f in &files {
+        for s in &f.samples {
+            let key = (s.provider.clone(), s.metric.clone());
+            if latest.get(&key).map(|cur| s.ts > cur.ts).unwrap_or(true) {
+                latest.insert(key, s.clone());
+            }
+        }
+    }
+    let mut latest: Vec<Sample> = latest.into_values().collect();
+    latest.sort_by(|a, b| (&a.provider, &a.metric).cmp(&(&b.provider, &b.metric)));
+
+    let machines: Vec<MachineUsage> = files
+        .iter()
+        .map(|f| MachineUsage {
+            machine: f.machine.clone(),
+            is_local: f.machine == local,
+            updated_at: f.updated_at,
+            claude_tokens_7d: f.claude_tokens_7d,
+            codex_tokens_7d: f.codex_tokens_7d,
+        })
+        .collect();
+
+    Ok(MergedUsage {
+        claude_tokens_7d: machines.iter().map(|m| m.claude_tokens_7d).sum(),
+        codex_tokens_7d: machines.iter().map(|m| m.codex_tokens_7d).sum(),
+        machines,
+        latest,
+    })
+}
+
+/// Samples from every machine since `since`, oldest first.
+pub fn merged_samples(
+    dir: &Path,
+    provider: Option<&str>,
+    since: i64,
+) -> Result<Vec<MachineSample>, String> {
+    let mut out: Vec<MachineSample> = read_all(dir)?
+        .into_iter()
+        .flat_map(|f| {
+            let machine = f.machine;
+            f.samples.into_iter().map(move |sample| MachineSample {
+                machine: machine.clone(),
+                sample,
+            })
+        })
+        .filter(|m| m.sample.ts >= since)
+        .filter(|m| provider.map(|p| m.sample.provider == p).unwrap_or(true))
+        .collect();
+    out.sort_by_key(|m| m.sample.ts);
+    Ok(out)
+}
@@ -0,0 +1,139 @@
+//! Team mode: pushes anonymized usage snapshots to a self-hosted endpoint and
+//! reads back the team aggregate, so a lead can see whether a shared org's
+//! weekly cap is being exhausted collectively.
+//!
+//! Snapshots carry only a random member id, the provider and its percentages
+//! and reset time - no account, org, plan, machine name or dollar amounts.
+//! The endpoint is expected to accept `POST <endpoint>/snapshots` and answer
+//! `GET <endpoint>/aggregate` with a [`TeamAggregate`]. An optional bearer
+//! token is kept in the Keychain.
+
+use std::io::Read;
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::http_client;
+use crate::keychain;
+use crate::settings::{SettingsState, TeamSettings};
+use crate::usage_fetcher::{UsageData, UsageState};
+
+pub const TOKEN_ACCOUNT: &str = "team_token";
+
+#[derive(Debug, Serialize)]
+struct Snapshot<'a> {
+    member: &'a str,
+    provider: &'a str,
+    session_percent: f64,
+    weekly_percent: f64,
+    weekly_resets_at: Option<&'a str>,
+    /// RFC 3339
+    at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamProvider {
+    pub provider: String,
+    /// Members that reported within the endpoint's freshness window
+    pub members: u32,
+    /// Share of the org's weekly cap used, if the endpoint knows it
+    pub weekly_percent: Option<f64>,
+    pub avg_session_percent: f64,
+    pub max_session_percent: f64,
+    pub weekly_resets_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamAggregate {
+    pub providers: Vec<TeamProvider>,
+    /// RFC 3339
+    pub updated_at: String,
+    /// Member counts replaced with zeros (privacy mode)
+    #[serde(default)]
+    pub redacted: bool,
+}
+
+/// A random id that identifies this install to the team endpoint without
+/// revealing who or where it is.
+pub fn new_member_id() -> String {
+    let mut bytes = [0u8; 16];
+    let filled = std::fs::File::open("/dev/urandom")
+        .and_then(|mut f| f.read_exact(&mut bytes))
+        .is_ok();
+    if !filled {
+        let nanos = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        bytes[..8].copy_from_slice(&nanos.to_le_bytes());
+        bytes[8..12].copy_from_slice(&std::process::id().to_le_bytes());
+    }
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn url(team: &TeamSettings, path: &str) -> Result<String, String> {
+    let base = team.endpoint.trim().trim_end_matches('/');
+    if !base.starts_with("https://") && !base.starts_with("http://") {
+        return Err("Team endpoint must be an http(s) URL".into());
+    }
+    Ok(format!("{}/{}", base, path))
+}
+
+fn authorized(req: reqwest::RequestBuilder) -> Result<reqwest::RequestBuilder, String> {
+    Ok(match keychain::read_secret(TOKEN_ACCOUNT)? {
+        Some(token) => req.bearer_auth(token.expose()),
+        None => req,
+    })
+}
+
+pub async fn push(
+    client: &Client,
+    team: &TeamSettings,
+    provider: &str,
+    data: &UsageData,
+) -> Result<(), String> {
+    let snapshot = Snapshot {
+        member: &team.member_id,
+        provider,
+        session_percent: data.session.percent_used,
+        weekly_percent: data.weekly_all.percent_used,
+        weekly_resets_at: data.weekly_all.resets_at.as_deref(),
+        at: chrono::Utc::now().to_rfc3339(),
+    };
+    let resp = authorized(client.post(url(team, "snapshots")?))?
+        .json(&snapshot)
+        .send()
+        .await
+        .map_err(|e| http_client::request_error("Team endpoint", e))?;
+    if resp.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("Team endpoint returned {}", resp.status()))
+    }
+}
+
+/// Pushes a snapshot in the background; a failing endpoint never holds up
+/// the fetch.
+pub fn publish(app: &AppHandle, provider: &str, data: &UsageData) {
+    let team = app.state::<SettingsState>().get().team;
+    if !team.enabled || team.endpoint.is_empty() {
+        return;
+    }
+    let client = app.state::<UsageState>().client();
+    let provider = provider.to_string();
+    let data = data.clone();
+    tauri::async_runtime::spawn(async move {
+        let _ = push(&client, &team, &provider, &data).await;
+    });
+}
+
+pub async fn aggregate(client: &Client, team: &TeamSettings) -> Result<TeamAggregate, String> {
+    let resp = authorized(client.get(url(team, "aggregate")?))?
+        .send()
+        .await
+        .map_err(|e| http_client::request_error("Team endpoint", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("Team endpoint returned {}", resp.status()));
+    }
+    resp.json()
+        .await
+        .map_err(|e| format!("Failed to parse team aggregate: {}", e.without_url()))
+}
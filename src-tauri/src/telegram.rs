@@ -0,0 +1,189 @@
+//! Sends alerts through a Telegram bot and answers `/usage` in the configured
+//! chat with the current summary.
+//!
+//! The bot token and chat ID are kept in the Keychain. Incoming messages are
+//! read with `getUpdates` long polling, so no public endpoint is needed;
+//! messages from any other chat are ignored. Polling starts after the latest
+//! update, so commands sent while the app wasn't listening aren't answered
+//! late.
+
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use zeroize::Zeroizing;
+
+use crate::alerts::Alert;
+use crate::keychain;
+use crate::secret::Secret;
+use crate::settings::SettingsState;
+use crate::tray;
+
+pub const TOKEN_ACCOUNT: &str = "telegram_bot_token";
+pub const CHAT_ACCOUNT: &str = "telegram_chat_id";
+
+/// Seconds Telegram holds a `getUpdates` request open waiting for messages.
+const POLL_TIMEOUT_SECS: u64 = 50;
+/// Wait after a failed poll, or between checks while the channel is off.
+const IDLE_SECS: u64 = 60;
+
+#[derive(Deserialize)]
+struct Updates {
+    ok: bool,
+    #[serde(default)]
+    result: Vec<Update>,
+}
+
+#[derive(Deserialize)]
+struct Update {
+    update_id: i64,
+    message: Option<IncomingMessage>,
+}
+
+#[derive(Deserialize)]
+struct IncomingMessage {
+    chat: Chat,
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Chat {
+    id: i64,
+}
+
+/// Bot API URL for `method`; it embeds the token, so it is wiped after use.
+fn api_url(token: &Secret, method: &str) -> Zeroizing<String> {
+    Zeroizing::new(format!(
+        "https://api.telegram.org/bot{}/{}",
+        token.expose(),
+        method
+    ))
+}
+
+fn credentials() -> Result<(Secret, Secret), String> {
+    let token = keychain::read_secret(TOKEN_ACCOUNT)?.ok_or("Telegram bot token is not set")?;
+    let chat = keychain::read_secret(CHAT_ACCOUNT)?.ok_or("Telegram chat ID is not set")?;
+    Ok((token, chat))
+}
+
+async fn send_message(
+    client: &Client,
+    token: &Secret,
+    chat: &str,
+    text: &str,
+) -> Result<(), String> {
+    let resp = client
+        .post(api_url(token, "sendMessage").as_str())
+        .json(&json!({ "chat_id": chat, "text": text }))
+        .send()
+        .await
+        .map_err(|e| format!("Telegram request failed: {}", e.without_url()))?;
+    if resp.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("Telegram returned {}", resp.status()))
+    }
+}
+
+pub async fn send(client: &Client, alert: &Alert) -> Result<(), String> {
+    let (token, chat) = credentials()?;
+    send_message(client, &token, chat.expose(), &alert.text()).await
+}
+
+/// Whether a message asks for the summary (`/usage` or `/usage@BotName`).
+fn is_usage_command(text: &str) -> bool {
+    text.split_whitespace()
+        .next()
+        .and_then(|cmd| cmd.split('@').next())
+        == Some("/usage")
+}
+
+/// Offset just past the latest pending update, skipping everything sent
+/// before now.
+async fn latest_offset(client: &Client) -> Result<i64, String> {
+    let (token, _) = credentials()?;
+    let updates: Updates = client
+        .get(api_url(&token, "getUpdates").as_str())
+        .query(&[("offset", "-1"), ("timeout", "0")])
+        .send()
+        .await
+        .map_err(|e| format!("Telegram poll failed: {}", e.without_url()))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Telegram updates: {}", e))?;
+    if !updates.ok {
+        return Err("Telegram rejected the poll".into());
+    }
+    Ok(updates.result.last().map_or(0, |u| u.update_id + 1))
+}
+
+/// One `getUpdates` round: answers `/usage` and returns the next offset.
+async fn poll_once(app: &AppHandle, client: &Client, offset: i64) -> Result<i64, String> {
+    let (token, chat) = credentials()?;
+    let updates: Updates = client
+        .get(api_url(&token, "getUpdates").as_str())
+        .query(&[
+            ("offset", offset.to_string()),
+            ("timeout", POLL_TIMEOUT_SECS.to_string()),
+            ("allowed_updates", "[\"message\"]".to_string()),
+        ])
+        .send()
+        .await
+        .map_err(|e| format!("Telegram poll failed: {}", e.without_url()))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Telegram updates: {}", e))?;
+    if !updates.ok {
+        return Err("Telegram rejected the poll".into());
+    }
+
+    let mut next = offset;
+    for update in updates.result {
+        next = next.max(update.update_id + 1);
+        let Some(msg) = update.message else {
+            continue;
+        };
+        if msg.chat.id.to_string() != chat.expose() {
+            continue;
+        }
+        if msg.text.as_deref().is_some_and(is_usage_command) {
+            // A failed reply still consumes the update, or it would repeat
+            if let Err(e) = send_message(client, &token, chat.expose(), &tray::summary(app)).await {
+                log::warn!("Telegram reply failed: {}", e);
+            }
+        }
+    }
+    Ok(next)
+}
+
+/// Background loop answering `/usage` while the Telegram channel is enabled.
+pub fn spawn_listener(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let Ok(client) = Client::builder()
+            .timeout(Duration::from_secs(POLL_TIMEOUT_SECS + 20))
+            .build()
+        else {
+            return;
+        };
+        // Found again whenever listening (re)starts
+        let mut offset = None;
+        loop {
+            if !handle.state::<SettingsState>().get().telegram_enabled {
+                offset = None;
+                tokio::time::sleep(Duration::from_secs(IDLE_SECS)).await;
+                continue;
+            }
+            let polled = match offset {
+                Some(offset) => poll_once(&handle, &client, offset).await,
+                None => latest_offset(&client).await,
+            };
+            match polled {
+                Ok(next) => offset = Some(next),
+                Err(_) => tokio::time::sleep(Duration::from_secs(IDLE_SECS)).await,
+            }
+        }
+    });
+}
@@ -0,0 +1,1320 @@
+//! Builds the menu bar item(s) and their context menus, and routes menu events
+//! to settings changes and app actions.
+//!
+//! By default there is a single "main" tray item showing every provider. In
+//! per-provider mode each provider gets its own item (title, tooltip, and a
+//! small menu) so menu bar managers like Bartender can arrange or hide them
+//! independently.
+//!
+//! The title is composed here from cached usage after every fetch; the
+//! webview plays no part in it.
+//!
+//! With a maximum title length set, text that doesn't fit is shortened step by
+//! step: tighter spacing first, then providers drop to their primary metric
+//! (the last provider first), and only then is the text cut with an ellipsis.
+//!
+//! The combined item can also be narrowed to one provider at a time, cycled
+//! with a middle click or `cycle_tray_provider`. Scrolling over the item would
+//! be the natural gesture, but the tray API doesn't deliver scroll events.
+//!
+//! Renders are coalesced: providers finishing within `COALESCE` of each other
+//! produce one title update, and a title that hasn't changed isn't set again,
+//! which keeps the menu bar from flickering at short intervals.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::{
+    menu::{CheckMenuItem, Menu, MenuItem, MenuItemKind, PredefinedMenuItem, Submenu},
+    tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent},
+    App, AppHandle, Emitter, Listener, Manager, Wry,
+};
+
+use crate::accessibility;
+use crate::advisor;
+use crate::codex_fetcher::CodexState;
+use crate::history::HistoryState;
+use crate::i18n;
+use crate::litellm_fetcher::LiteLlmState;
+use crate::notification_permission;
+use crate::ollama_fetcher::OllamaState;
+use crate::onboarding;
+use crate::pacing;
+use crate::preview;
+use crate::openrouter_fetcher::OpenRouterState;
+use crate::privacy;
+use crate::report;
+use crate::resets;
+use crate::provider::Provider;
+use crate::scheduler::{self, SchedulerState};
+use crate::smoothing;
+use crate::settings::{
+    self, LeftClickAction, Settings, SettingsState, TrayIndicator, TrayMetrics, TraySlot,
+};
+use crate::usage_fetcher::{UsageData, UsageState};
+use crate::widget_data;
+use crate::widget_window;
+
+pub const MAIN_TRAY_ID: &str = "main";
+
+/// Prefix shown on the menu bar text while monitoring is paused
+const PAUSED_PREFIX: &str = "\u{23F8} ";
+/// Prefix shown outside the active-hours schedule
+const OFF_HOURS_PREFIX: &str = "\u{1F319} ";
+
+/// Quiet period before a render is drawn, so bursts of updates draw once
+const COALESCE: Duration = Duration::from_millis(250);
+
+/// Marker that blinks in front of the title while the tray flashes
+const FLASH_PREFIX: &str = "\u{26A0}\u{FE0F} ";
+/// Frames of the flash, alternating with and without the marker
+const FLASH_FRAMES: u32 = 10;
+/// How long each flash frame shows
+const FLASH_FRAME: Duration = Duration::from_millis(500);
+
+/// Refresh interval presets (seconds, label)
+const INTERVALS: [u64; 5] = [60, 120, 300, 600, 900];
+
+/// Notification threshold presets (percent, label); 0 means off
+const THRESHOLDS: [u32; 5] = [70, 80, 90, 95, 0];
+
+/// Extra "Refresh Every" item shown while a non-preset interval is set
+const CUSTOM_INTERVAL_ID: &str = "interval_custom";
+
+/// Main menu item ids, their `i18n` keys, and the key that picks them while
+/// the menu is open, for building and relabeling
+const MENU_ITEMS: [(&str, &str, Option<&str>); 16] = [
+    ("notification_hint", "menu.notifications_off", None),
+    ("show_hide", "menu.show_widget", None),
+    ("refresh_now", "menu.refresh_now", Some("R")),
+    ("pause_monitoring", "menu.pause", None),
+    ("resets_sub", "menu.upcoming_resets", None),
+    ("refresh_sub", "menu.refresh_every", None),
+    ("notify_sub", "menu.notify_at", None),
+    ("start_login", "menu.start_login", None),
+    ("tray_per_provider", "menu.separate_items", None),
+    ("privacy_mode", "menu.privacy", None),
+    ("click_through", "menu.click_through", None),
+    ("dock_icon", "menu.dock_icon", None),
+    ("monthly_report", "menu.monthly_report", None),
+    ("reconfigure_providers", "menu.reconfigure", None),
+    ("open_settings", "menu.settings", None),
+    ("quit", "menu.quit", None),
+];
+
+/// One provider's part of the menu bar text.
+#[derive(Debug, Clone)]
+pub struct TraySegment {
+    /// "claude", "codex", or "openrouter"
+    pub provider: &'static str,
+    /// Display name, e.g. "Claude"
+    pub name: &'static str,
+    /// Values without the provider prefix, e.g. "25/62%"
+    pub text: String,
+    /// Primary metric only, used when the title must be shortened, e.g. "25%"
+    pub compact: String,
+    /// Highest usage percentage, for the severity indicator; None for credit
+    /// balances
+    pub percent: Option<f64>,
+}
+
+/// Latest figures behind the menu bar text; None for providers without data.
+#[derive(Debug, Clone, Default)]
+pub struct TrayValues {
+    /// Session and weekly percentages
+    pub claude: Option<(f64, f64)>,
+    pub codex: Option<(f64, f64)>,
+    /// Remaining credits in dollars
+    pub openrouter: Option<f64>,
+}
+
+pub struct TrayState {
+    /// The main tray's context menu, for keeping check items in sync
+    pub menu: Menu<Wry>,
+    /// Latest values as reported, before smoothing
+    values: Mutex<TrayValues>,
+    /// Last rendered segments, so layout changes can re-render immediately
+    segments: Mutex<Vec<TraySegment>>,
+    /// Bumped by every render; a pending draw only runs if it's still current
+    generation: Mutex<u64>,
+    /// Title last set per tray id
+    titles: Mutex<HashMap<String, String>>,
+    /// Provider the combined title is narrowed to, if any
+    focus: Mutex<Option<&'static str>>,
+    /// A flash is running, so a second one doesn't overlap it
+    flashing: Mutex<bool>,
+    /// Providers with a window at 100% as of their last fetch
+    at_limit: Mutex<HashSet<String>>,
+}
+
+fn provider_tray_id(provider: &str) -> String {
+    format!("tray_{}", provider)
+}
+
+/// Localized text of the main menu item `id`.
+fn label(id: &str) -> &'static str {
+    MENU_ITEMS
+        .iter()
+        .find(|(i, _, _)| *i == id)
+        .map(|(_, key, _)| i18n::t(key))
+        .unwrap_or_default()
+}
+
+/// Key that picks the main menu item `id` while the menu is open.
+fn accelerator(id: &str) -> Option<&'static str> {
+    MENU_ITEMS
+        .iter()
+        .find(|(i, _, _)| *i == id)
+        .and_then(|(_, _, key)| *key)
+}
+
+fn interval_label(secs: u64) -> String {
+    if secs.is_multiple_of(60) {
+        i18n::tf("menu.minutes", &[&(secs / 60)])
+    } else {
+        i18n::tf("menu.seconds", &[&secs])
+    }
+}
+
+/// Adds, updates or removes the checked "Custom: 3 min" item, depending on
+/// whether the interval is one of the presets.
+fn sync_custom_interval(app: &AppHandle, menu: &Menu<Wry>, secs: u64) {
+    let Some(sub) = menu.get("refresh_sub") else {
+        return;
+    };
+    let Some(sub) = sub.as_submenu() else {
+        return;
+    };
+    let existing = sub.get(CUSTOM_INTERVAL_ID);
+    if INTERVALS.contains(&secs) {
+        if let Some(item) = existing {
+            let _ = sub.remove(&item);
+        }
+        return;
+    }
+    let text = i18n::tf("menu.custom", &[&interval_label(secs)]);
+    match existing.as_ref().and_then(|i| i.as_check_menuitem()) {
+        Some(item) => {
+            let _ = item.set_text(&text);
+            let _ = item.set_checked(true);
+        }
+        None => {
+            if let Ok(item) =
+                CheckMenuItem::with_id(app, CUSTOM_INTERVAL_ID, &text, true, true, None::<&str>)
+            {
+                let _ = sub.append(&item);
+            }
+        }
+    }
+}
+
+fn threshold_label(pct: u32) -> String {
+    if pct == 0 {
+        i18n::t("menu.off").to_string()
+    } else {
+        format!("{}%", pct)
+    }
+}
+
+/// Builds the main tray item and its context menu.
+pub fn build(app: &App) -> tauri::Result<()> {
+    let initial_settings = app.state::<SettingsState>().get();
+
+    let show_hide = MenuItem::with_id(app, "show_hide", label("show_hide"), true, None::<&str>)?;
+    let sep1 = PredefinedMenuItem::separator(app)?;
+    let refresh_now = MenuItem::with_id(
+        app,
+        "refresh_now",
+        label("refresh_now"),
+        true,
+        accelerator("refresh_now"),
+    )?;
+    let pause = CheckMenuItem::with_id(
+        app,
+        "pause_monitoring",
+        label("pause_monitoring"),
+        true,
+        false,
+        None::<&str>,
+    )?;
+
+    // Filled from cached data after every fetch
+    let resets_sub = Submenu::with_id(app, "resets_sub", label("resets_sub"), true)?;
+
+    // Refresh interval submenu (radio-style check items), picked with 1-5
+    // while the menu is open
+    let mut interval_items: Vec<CheckMenuItem<Wry>> = Vec::new();
+    for (i, secs) in INTERVALS.iter().enumerate() {
+        let item = CheckMenuItem::with_id(
+            app,
+            format!("interval_{}", secs),
+            interval_label(*secs),
+            true,
+            *secs == initial_settings.refresh_interval_secs,
+            Some((i + 1).to_string()),
+        )?;
+        interval_items.push(item);
+    }
+    let interval_refs: Vec<&dyn tauri::menu::IsMenuItem<Wry>> = interval_items
+        .iter()
+        .map(|i| i as &dyn tauri::menu::IsMenuItem<Wry>)
+        .collect();
+    let refresh_sub = Submenu::with_id_and_items(
+        app,
+        "refresh_sub",
+        label("refresh_sub"),
+        true,
+        &interval_refs,
+    )?;
+
+    // Notification threshold submenu (radio-style check items)
+    let mut threshold_items: Vec<CheckMenuItem<Wry>> = Vec::new();
+    for pct in &THRESHOLDS {
+        let checked = if *pct == 0 {
+            !initial_settings.notifications_enabled
+        } else {
+            initial_settings.notifications_enabled && *pct == initial_settings.notify_threshold
+        };
+        let item = CheckMenuItem::with_id(
+            app,
+            format!("notify_{}", pct),
+            threshold_label(*pct),
+            true,
+            checked,
+            None::<&str>,
+        )?;
+        threshold_items.push(item);
+    }
+    let threshold_refs: Vec<&dyn tauri::menu::IsMenuItem<Wry>> = threshold_items
+        .iter()
+        .map(|i| i as &dyn tauri::menu::IsMenuItem<Wry>)
+        .collect();
+    let notify_sub = Submenu::with_id_and_items(
+        app,
+        "notify_sub",
+        label("notify_sub"),
+        true,
+        &threshold_refs,
+    )?;
+
+    // Start at login toggle
+    let start_login = CheckMenuItem::with_id(
+        app,
+        "start_login",
+        label("start_login"),
+        true,
+        initial_settings.start_at_login,
+        None::<&str>,
+    )?;
+    let per_provider = CheckMenuItem::with_id(
+        app,
+        "tray_per_provider",
+        label("tray_per_provider"),
+        true,
+        initial_settings.tray_per_provider,
+        None::<&str>,
+    )?;
+    let privacy_mode = CheckMenuItem::with_id(
+        app,
+        "privacy_mode",
+        label("privacy_mode"),
+        true,
+        initial_settings.privacy_mode,
+        None::<&str>,
+    )?;
+    let click_through = CheckMenuItem::with_id(
+        app,
+        "click_through",
+        label("click_through"),
+        true,
+        initial_settings.click_through,
+        None::<&str>,
+    )?;
+    let dock_icon = CheckMenuItem::with_id(
+        app,
+        "dock_icon",
+        label("dock_icon"),
+        true,
+        initial_settings.show_dock_icon,
+        None::<&str>,
+    )?;
+    let monthly_report = MenuItem::with_id(
+        app,
+        "monthly_report",
+        label("monthly_report"),
+        true,
+        None::<&str>,
+    )?;
+    let reconfigure = MenuItem::with_id(
+        app,
+        "reconfigure_providers",
+        label("reconfigure_providers"),
+        true,
+        None::<&str>,
+    )?;
+    let open_settings =
+        MenuItem::with_id(app, "open_settings", label("open_settings"), true, None::<&str>)?;
+
+    let sep2 = PredefinedMenuItem::separator(app)?;
+    let quit = MenuItem::with_id(app, "quit", label("quit"), true, None::<&str>)?;
+
+    let menu = Menu::with_items(
+        app,
+        &[
+            &show_hide,
+            &sep1,
+            &refresh_now,
+            &pause,
+            &resets_sub,
+            &refresh_sub,
+            &notify_sub,
+            &start_login,
+            &per_provider,
+            &privacy_mode,
+            &click_through,
+            &dock_icon,
+            &monthly_report,
+            &reconfigure,
+            &open_settings,
+            &sep2,
+            &quit,
+        ],
+    )?;
+
+    // Build tray (text-only, no icon). Tray menu listeners are global, so this
+    // one handler also serves the per-provider items' menus.
+    let _tray = TrayIconBuilder::with_id(MAIN_TRAY_ID)
+        .title("C:--% X:--% OR:--")
+        .tooltip(i18n::t("tooltip.app"))
+        .menu(&menu)
+        .show_menu_on_left_click(initial_settings.left_click == LeftClickAction::Menu)
+        .on_menu_event(|app, event| handle_menu_event(app, event.id().as_ref()))
+        .on_tray_icon_event(|tray, event| handle_tray_event(tray.app_handle(), event))
+        .build(app)?;
+
+    // Check items follow the settings however they were changed
+    let handle = app.handle().clone();
+    app.listen_any("settings-changed", move |event| {
+        let settings = serde_json::from_str::<Settings>(event.payload());
+        if let (Ok(settings), Some(tray)) = (settings, handle.try_state::<TrayState>()) {
+            sync_checks(&tray.menu, &settings);
+            sync_custom_interval(&handle, &tray.menu, settings.refresh_interval_secs);
+        }
+    });
+    sync_custom_interval(
+        app.handle(),
+        &menu,
+        initial_settings.refresh_interval_secs,
+    );
+
+    app.manage(TrayState {
+        menu,
+        values: Mutex::new(TrayValues::default()),
+        segments: Mutex::new(Vec::new()),
+        generation: Mutex::new(0),
+        titles: Mutex::new(HashMap::new()),
+        focus: Mutex::new(None),
+        flashing: Mutex::new(false),
+        at_limit: Mutex::new(HashSet::new()),
+    });
+    update_resets_menu(app.handle());
+    Ok(())
+}
+
+fn build_provider_tray(app: &AppHandle, segment: &TraySegment) -> tauri::Result<()> {
+    let show_hide = MenuItem::with_id(app, "show_hide", label("show_hide"), true, None::<&str>)?;
+    let refresh_now = MenuItem::with_id(
+        app,
+        "refresh_now",
+        label("refresh_now"),
+        true,
+        accelerator("refresh_now"),
+    )?;
+    let combine = MenuItem::with_id(
+        app,
+        "tray_per_provider",
+        i18n::t("menu.combine_items"),
+        true,
+        None::<&str>,
+    )?;
+    let sep = PredefinedMenuItem::separator(app)?;
+    let quit = MenuItem::with_id(app, "quit", label("quit"), true, None::<&str>)?;
+    let menu = Menu::with_items(app, &[&show_hide, &refresh_now, &combine, &sep, &quit])?;
+
+    let left_click = app.state::<SettingsState>().get().left_click;
+    TrayIconBuilder::with_id(provider_tray_id(segment.provider))
+        .title(&segment.text)
+        .tooltip(i18n::tf("tooltip.provider_usage", &[&segment.name]))
+        .menu(&menu)
+        .show_menu_on_left_click(left_click == LeftClickAction::Menu)
+        .on_tray_icon_event(|tray, event| handle_tray_event(tray.app_handle(), event))
+        .build(app)?;
+    app.state::<TrayState>()
+        .titles
+        .lock()
+        .unwrap()
+        .insert(provider_tray_id(segment.provider), segment.text.clone());
+    Ok(())
+}
+
+/// Sets a tray's title unless it already shows `text`.
+fn set_title(app: &AppHandle, tray: &TrayIcon, text: &str) -> Result<(), String> {
+    let state = app.state::<TrayState>();
+    let mut titles = state.titles.lock().unwrap();
+    if titles.get(tray.id().as_ref()).map(String::as_str) == Some(text) {
+        return Ok(());
+    }
+    tray.set_title(Some(text)).map_err(|e| e.to_string())?;
+    titles.insert(tray.id().as_ref().to_string(), text.to_string());
+    Ok(())
+}
+
+/// Flashes `provider`'s tray item when a fetch finds one of its windows newly
+/// at 100%. Runs on every fetch, whether or not notifications are on.
+pub fn flash_on_limit(app: &AppHandle, provider: &str, data: &UsageData) {
+    let at_limit = data.metric_percents().iter().any(|(_, p)| *p >= 100.0);
+    let state = app.state::<TrayState>();
+    let reached = {
+        let mut providers = state.at_limit.lock().unwrap();
+        if at_limit {
+            providers.insert(provider.to_string())
+        } else {
+            providers.remove(provider);
+            false
+        }
+    };
+    if reached {
+        flash(app, &provider.to_lowercase());
+    }
+}
+
+/// Blinks a marker in front of the title of `provider`'s tray item (the
+/// combined one unless items are separate) for a few seconds, e.g. when a
+/// limit is reached. Frames bypass the title cache, so renders during the
+/// flash still land and the last frame restores whatever is current.
+fn flash(app: &AppHandle, provider: &str) {
+    let settings = app.state::<SettingsState>().get();
+    if !settings.tray_flash {
+        return;
+    }
+    let id = if settings.tray_per_provider {
+        provider_tray_id(provider)
+    } else {
+        MAIN_TRAY_ID.to_string()
+    };
+    let state = app.state::<TrayState>();
+    {
+        let mut flashing = state.flashing.lock().unwrap();
+        if *flashing {
+            return;
+        }
+        *flashing = true;
+    }
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = handle.state::<TrayState>();
+        for frame in 0..=FLASH_FRAMES {
+            let Some(tray) = handle.tray_by_id(&id) else {
+                break;
+            };
+            let title = state.titles.lock().unwrap().get(&id).cloned();
+            let Some(title) = title else {
+                break;
+            };
+            let text = if frame % 2 == 0 && frame < FLASH_FRAMES {
+                format!("{}{}", FLASH_PREFIX, title)
+            } else {
+                title
+            };
+            let _ = tray.set_title(Some(&text));
+            tokio::time::sleep(FLASH_FRAME).await;
+        }
+        *state.flashing.lock().unwrap() = false;
+    });
+}
+
+fn remove_provider_tray(app: &AppHandle, provider: &str) {
+    let id = provider_tray_id(provider);
+    app.remove_tray_by_id(&id);
+    app.state::<TrayState>().titles.lock().unwrap().remove(&id);
+}
+
+/// Sets the menu bar text from fresh segments, honoring the tray layout
+/// setting. Drawing happens once no other render follows within `COALESCE`.
+pub fn render(app: &AppHandle, segments: Vec<TraySegment>) {
+    let state = app.state::<TrayState>();
+    *state.segments.lock().unwrap() = segments;
+    let generation = {
+        let mut generation = state.generation.lock().unwrap();
+        *generation += 1;
+        *generation
+    };
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(COALESCE).await;
+        let state = handle.state::<TrayState>();
+        if *state.generation.lock().unwrap() != generation {
+            return;
+        }
+        let segments = state.segments.lock().unwrap().clone();
+        let _ = draw(&handle, segments);
+    });
+}
+
+fn draw(app: &AppHandle, segments: Vec<TraySegment>) -> Result<(), String> {
+    let settings = app.state::<SettingsState>().get();
+    let per_provider = settings.tray_per_provider;
+    // Segments are stored as given, so toggling privacy mode re-renders them
+    let segments: Vec<TraySegment> = segments
+        .into_iter()
+        .map(|s| match s.provider {
+            "openrouter" if settings.privacy_mode => TraySegment {
+                text: privacy::money(true, 0.0),
+                compact: privacy::money(true, 0.0),
+                ..s
+            },
+            _ => s,
+        })
+        .map(|s| {
+            let mark = indicator(&settings, s.percent).unwrap_or("");
+            let label = settings.tray_prefix(s.provider, default_prefix(s.provider));
+            let pace = if settings.tray_pacing {
+                pacing::marker(app, s.provider)
+            } else {
+                ""
+            };
+            TraySegment {
+                text: format!("{}{}:{}{}", mark, label, s.text, pace),
+                compact: format!("{}{}:{}", mark, label, s.compact),
+                ..s
+            }
+        })
+        .collect();
+    let max_chars = settings.tray_max_chars as usize;
+    let scheduler = app.state::<SchedulerState>();
+    let prefix = if scheduler.is_paused() {
+        PAUSED_PREFIX
+    } else if scheduler.is_off_hours() {
+        OFF_HOURS_PREFIX
+    } else {
+        ""
+    };
+
+    // With nothing to show yet, the main item stays up so the app can be reached
+    if per_provider && !segments.is_empty() {
+        if let Some(tray) = app.tray_by_id(MAIN_TRAY_ID) {
+            tray.set_visible(false).map_err(|e| e.to_string())?;
+        }
+        for segment in &segments {
+            let segment = &TraySegment {
+                text: fit_title(prefix, std::slice::from_ref(segment), max_chars),
+                ..segment.clone()
+            };
+            match app.tray_by_id(&provider_tray_id(segment.provider)) {
+                Some(tray) => set_title(app, &tray, &segment.text)?,
+                None => build_provider_tray(app, segment).map_err(|e| e.to_string())?,
+            }
+        }
+        update_tooltips(app);
+        // Providers that dropped out (e.g. key cleared) lose their item
+        for provider in ["claude", "codex", "openrouter"] {
+            if !segments.iter().any(|s| s.provider == provider) {
+                remove_provider_tray(app, provider);
+            }
+        }
+    } else {
+        for provider in ["claude", "codex", "openrouter"] {
+            remove_provider_tray(app, provider);
+        }
+        let focus = *app.state::<TrayState>().focus.lock().unwrap();
+        let segments: Vec<TraySegment> = match focus {
+            Some(p) if segments.iter().any(|s| s.provider == p) => {
+                segments.into_iter().filter(|s| s.provider == p).collect()
+            }
+            _ => segments,
+        };
+        if let Some(tray) = app.tray_by_id(MAIN_TRAY_ID) {
+            let text = if segments.is_empty() {
+                format!("{}Usage: --", prefix)
+            } else {
+                fit_title(prefix, &segments, max_chars)
+            };
+            tray.set_visible(true).map_err(|e| e.to_string())?;
+            set_title(app, &tray, &text)?;
+        }
+    }
+    Ok(())
+}
+
+fn default_prefix(provider: &str) -> &'static str {
+    match provider {
+        "claude" => "C",
+        "codex" => "X",
+        _ => "OR",
+    }
+}
+
+/// Severity marker for a provider at `percent`, if indicators are on.
+fn indicator(settings: &Settings, percent: Option<f64>) -> Option<&'static str> {
+    let percent = percent?;
+    let warn = match settings.notify_threshold {
+        0 => 80.0,
+        t => t as f64,
+    };
+    let critical = (settings.reminder_level as f64).max(warn);
+    let level = if percent >= critical {
+        2
+    } else if percent >= warn {
+        1
+    } else {
+        0
+    };
+    let marks = match settings.tray_indicator {
+        TrayIndicator::Off => return None,
+        TrayIndicator::Symbols => ["\u{2713}", "\u{26A0}\u{FE0E}", "\u{203C}\u{FE0E}"],
+        TrayIndicator::Dots => ["\u{1F7E2}", "\u{1F7E1}", "\u{1F534}"],
+    };
+    Some(marks[level])
+}
+
+/// Joins segments into a title of at most `max_chars` characters (0 means no
+/// limit), shortening in the order described in the module docs.
+fn fit_title(prefix: &str, segments: &[TraySegment], max_chars: usize) -> String {
+    let join = |parts: &[&str], sep: &str| format!("{}{}", prefix, parts.join(sep));
+    let fits = |text: &str| max_chars == 0 || text.chars().count() <= max_chars;
+
+    let mut parts: Vec<&str> = segments.iter().map(|s| s.text.as_str()).collect();
+    let full = join(&parts, "  ");
+    if fits(&full) {
+        return full;
+    }
+    let mut text = join(&parts, " ");
+    for i in (0..segments.len()).rev() {
+        if fits(&text) {
+            return text;
+        }
+        parts[i] = &segments[i].compact;
+        text = join(&parts, " ");
+    }
+    if fits(&text) {
+        return text;
+    }
+    let cut: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    format!("{}\u{2026}", cut.trim_end())
+}
+
+/// Multi-line tooltip block for one rate-limit provider.
+fn usage_tooltip(app: &AppHandle, name: &str, data: &UsageData) -> String {
+    let advice = advisor::advise(&app.state::<HistoryState>(), name, data);
+    let hide = privacy::is_on(app);
+    let label = app.state::<SettingsState>().get().display_name(name);
+    let mut lines = vec![format!("{} - {}", label, advice.message)];
+    for m in data.windows() {
+        lines.push(format!("  {}: {:.0}% - {}", m.label, m.percent_used, m.reset_info));
+    }
+    let extra = i18n::tf(
+        "tooltip.extra",
+        &[
+            &privacy::money(hide, data.extra.dollars_spent),
+            &i18n::t(if data.extra.enabled { "tooltip.on" } else { "tooltip.off" }),
+        ],
+    );
+    lines.push(format!("  {}", extra));
+    lines.push(format!(
+        "  {}",
+        i18n::tf("tooltip.updated", &[&format_updated(&data.fetched_at)])
+    ));
+    lines.join("\n")
+}
+
+fn format_updated(fetched_at: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(fetched_at)
+        .map(|t| t.with_timezone(&chrono::Local).format("%H:%M").to_string())
+        .unwrap_or_else(|_| "---".into())
+}
+
+/// Tooltip text per provider from the cached data, in tray order.
+fn provider_tooltips(app: &AppHandle) -> Vec<(&'static str, String)> {
+    let mut out = Vec::new();
+    if let Some(d) = app.state::<UsageState>().last_data.lock().unwrap().as_ref() {
+        out.push(("claude", usage_tooltip(app, "Claude", d)));
+    }
+    if let Some(d) = app.state::<CodexState>().last_data.lock().unwrap().as_ref() {
+        out.push(("codex", usage_tooltip(app, "Codex", d)));
+    }
+    if let Some(d) = app.state::<OpenRouterState>().last_data.lock().unwrap().as_ref() {
+        let hide = privacy::is_on(app);
+        out.push((
+            "openrouter",
+            format!(
+                "OpenRouter\n  {}\n  {}\n  {}",
+                i18n::tf(
+                    "tooltip.remaining",
+                    &[&privacy::money(hide, d.remaining_credits)]
+                ),
+                i18n::tf(
+                    "tooltip.used_of",
+                    &[
+                        &privacy::money(hide, d.total_usage),
+                        &privacy::money(hide, d.total_credits)
+                    ]
+                ),
+                i18n::tf("tooltip.updated", &[&format_updated(&d.fetched_at)])
+            ),
+        ));
+    }
+    if let Some(d) = app.state::<LiteLlmState>().last_data.lock().unwrap().as_ref() {
+        let hide = privacy::is_on(app);
+        let name = app
+            .state::<SettingsState>()
+            .get()
+            .display_name(Provider::LiteLlm.name());
+        let mut lines = vec![name];
+        match (d.remaining, d.max_budget) {
+            (Some(remaining), Some(budget)) => {
+                lines.push(i18n::tf("tooltip.remaining", &[&privacy::money(hide, remaining)]));
+                lines.push(i18n::tf(
+                    "tooltip.used_of",
+                    &[&privacy::money(hide, d.spend), &privacy::money(hide, budget)],
+                ));
+            }
+            _ => lines.push(i18n::tf("tooltip.spent", &[&privacy::money(hide, d.spend)])),
+        }
+        lines.push(i18n::tf("tooltip.updated", &[&format_updated(&d.fetched_at)]));
+        out.push(("litellm", lines.join("\n  ")));
+    }
+    if let Some(d) = app.state::<OllamaState>().last_data.lock().unwrap().as_ref() {
+        let name = app
+            .state::<SettingsState>()
+            .get()
+            .display_name(Provider::Ollama.name());
+        let mut lines = vec![name];
+        if !d.running {
+            lines.push(i18n::t("tooltip.local_off").to_string());
+        } else if d.models.is_empty() {
+            lines.push(i18n::t("tooltip.local_none").to_string());
+        } else {
+            let models: Vec<&str> = d.models.iter().map(|m| m.name.as_str()).collect();
+            lines.push(i18n::tf("tooltip.local_models", &[&models.join(", ")]));
+        }
+        lines.push(i18n::tf(
+            "tooltip.local_requests",
+            &[&d.requests_last_hour.to_string()],
+        ));
+        lines.push(i18n::tf("tooltip.updated", &[&format_updated(&d.fetched_at)]));
+        out.push(("ollama", lines.join("\n  ")));
+    }
+    out
+}
+
+/// Plain-text summary of every provider, as shown in the combined tooltip.
+pub fn summary(app: &AppHandle) -> String {
+    let tooltips = provider_tooltips(app);
+    if tooltips.is_empty() {
+        return i18n::t("tooltip.app").to_string();
+    }
+    tooltips
+        .iter()
+        .map(|(_, t)| t.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Shows or removes the "Notifications Are Off" item at the top of the main
+/// menu.
+pub fn set_notification_hint(app: &AppHandle, show: bool) {
+    let Some(state) = app.try_state::<TrayState>() else {
+        return;
+    };
+    let existing = state.menu.get("notification_hint");
+    match (show, existing) {
+        (true, None) => {
+            let id = "notification_hint";
+            if let Ok(item) = MenuItem::with_id(app, id, label(id), true, None::<&str>) {
+                let _ = state.menu.prepend(&item);
+            }
+        }
+        (false, Some(item)) => {
+            let _ = state.menu.remove(&item);
+        }
+        _ => {}
+    }
+}
+
+/// Lists every window's next reset in the "Upcoming Resets" submenu, or a
+/// placeholder before any reset time is known.
+fn update_resets_menu(app: &AppHandle) {
+    let Some(state) = app.try_state::<TrayState>() else {
+        return;
+    };
+    let Some(sub) = state.menu.get("resets_sub") else {
+        return;
+    };
+    let Some(sub) = sub.as_submenu() else {
+        return;
+    };
+    for item in sub.items().unwrap_or_default() {
+        let _ = sub.remove(&item);
+    }
+
+    let settings = app.state::<SettingsState>().get();
+    let mut lines: Vec<String> = resets::upcoming_resets(app)
+        .into_iter()
+        .map(|r| {
+            format!(
+                "{} {}: {}",
+                settings.display_name(r.provider.name()),
+                r.label,
+                r.at.format(resets::DISPLAY_FORMAT)
+            )
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push(i18n::t("menu.no_resets").to_string());
+    }
+    for (i, text) in lines.iter().enumerate() {
+        let item = MenuItem::with_id(app, format!("reset_{}", i), text, false, None::<&str>);
+        if let Ok(item) = item {
+            let _ = sub.append(&item);
+        }
+    }
+}
+
+/// Refreshes tray tooltips with a full breakdown of every cached metric, the
+/// matching VoiceOver labels and the upcoming resets. Call after each
+/// successful fetch.
+pub fn update_tooltips(app: &AppHandle) {
+    update_resets_menu(app);
+    let tooltips = provider_tooltips(app);
+
+    if let Some(tray) = app.tray_by_id(MAIN_TRAY_ID) {
+        let _ = tray.set_tooltip(Some(&summary(app)));
+        accessibility::set_label(&tray, accessibility::usage_description(app));
+    }
+    for (provider, text) in &tooltips {
+        if let Some(tray) = app.tray_by_id(&provider_tray_id(provider)) {
+            let _ = tray.set_tooltip(Some(text));
+        }
+    }
+    for (provider, text) in accessibility::provider_descriptions(app) {
+        if let Some(tray) = app.tray_by_id(&provider_tray_id(provider)) {
+            accessibility::set_label(&tray, text);
+        }
+    }
+}
+
+/// The indicator only looks at the windows that are shown.
+fn usage_segment(
+    provider: &'static str,
+    name: &'static str,
+    (session, weekly): (f64, f64),
+    metrics: TrayMetrics,
+) -> Option<TraySegment> {
+    let (text, compact, percent) = match metrics {
+        TrayMetrics::Both => (
+            format!("{:.0}/{:.0}%", session, weekly),
+            format!("{:.0}%", session),
+            session.max(weekly),
+        ),
+        TrayMetrics::Session => (format!("{:.0}%", session), format!("{:.0}%", session), session),
+        TrayMetrics::Weekly => (format!("{:.0}%", weekly), format!("{:.0}%", weekly), weekly),
+        TrayMetrics::Hidden => return None,
+    };
+    Some(TraySegment {
+        provider,
+        name,
+        text,
+        compact,
+        percent: Some(percent),
+    })
+}
+
+/// Segments in the configured order, with the windows each provider shows.
+fn segments(values: &TrayValues, layout: &[TraySlot]) -> Vec<TraySegment> {
+    // Providers missing from the layout follow in the default order
+    let slots = layout.iter().copied().chain(
+        settings::TRAY_PROVIDERS
+            .into_iter()
+            .filter(|p| !layout.iter().any(|s| s.provider == *p))
+            .map(|provider| TraySlot {
+                provider,
+                metrics: TrayMetrics::Both,
+            }),
+    );
+    let mut segments = Vec::new();
+    for slot in slots {
+        let segment = match slot.provider {
+            Provider::Claude => values
+                .claude
+                .and_then(|v| usage_segment("claude", "Claude", v, slot.metrics)),
+            Provider::Codex => values
+                .codex
+                .and_then(|v| usage_segment("codex", "Codex", v, slot.metrics)),
+            Provider::OpenRouter => values
+                .openrouter
+                .filter(|_| slot.metrics != TrayMetrics::Hidden)
+                .map(|remaining| TraySegment {
+                    provider: "openrouter",
+                    name: "OpenRouter",
+                    text: format!("${:.2}", remaining),
+                    compact: format!("${:.0}", remaining),
+                    percent: None,
+                }),
+            _ => None,
+        };
+        segments.extend(segment);
+    }
+    segments
+}
+
+/// Stores fresh values and renders them, smoothed if that's enabled.
+pub fn set_values(app: &AppHandle, values: TrayValues) {
+    let shown = smoothing::shown(app, &values);
+    *app.state::<TrayState>().values.lock().unwrap() = values;
+    let layout = app.state::<SettingsState>().get().tray_layout;
+    render(app, segments(&shown, &layout));
+}
+
+/// Composes the menu bar values from cached data, leaving out providers whose
+/// last fetch failed. Runs after every fetch, so the title stays current even
+/// if the webview is gone.
+pub fn update_from_cache(app: &AppHandle) {
+    let scheduler = app.state::<SchedulerState>();
+    let usage = |provider: Provider, data: Option<UsageData>| {
+        data.filter(|_| !scheduler.is_failing(provider))
+            .map(|d| (d.session.percent_used, d.weekly_all.percent_used))
+    };
+    let claude = app.state::<UsageState>().last_data.lock().unwrap().clone();
+    let codex = app.state::<CodexState>().last_data.lock().unwrap().clone();
+    let openrouter = app.state::<OpenRouterState>().last_data.lock().unwrap().clone();
+    set_values(
+        app,
+        TrayValues {
+            claude: usage(Provider::Claude, claude),
+            codex: usage(Provider::Codex, codex),
+            openrouter: openrouter
+                .filter(|_| !scheduler.is_failing(Provider::OpenRouter))
+                .map(|d| d.remaining_credits),
+        },
+    );
+}
+
+/// Renders the stored values again, advancing any smoothing glide.
+pub fn refresh_values(app: &AppHandle) {
+    let values = app.state::<TrayState>().values.lock().unwrap().clone();
+    set_values(app, values);
+}
+
+/// Re-renders the last segments, e.g. after a layout or schedule change.
+pub fn rerender(app: &AppHandle) {
+    let segments = app.state::<TrayState>().segments.lock().unwrap().clone();
+    render(app, segments);
+}
+
+/// Turns privacy mode on or off and re-renders everything that shows amounts.
+/// The frontend reloads its data on `privacy-mode-changed`.
+pub fn set_privacy_mode(app: &AppHandle, enabled: bool) -> Result<Settings, String> {
+    let settings = app
+        .state::<SettingsState>()
+        .update(|s| s.privacy_mode = enabled)?;
+    rerender(app);
+    update_tooltips(app);
+    widget_data::refresh(app);
+    let _ = app.emit("privacy-mode-changed", enabled);
+    Ok(settings)
+}
+
+/// Turns click-through of the pinned widget on or off.
+pub fn set_click_through(app: &AppHandle, enabled: bool) -> Result<Settings, String> {
+    let settings = app
+        .state::<SettingsState>()
+        .update(|s| s.click_through = enabled)?;
+    widget_window::apply_hud(app)?;
+    Ok(settings)
+}
+
+/// Shows or hides the app's Dock icon.
+pub fn set_dock_icon(app: &AppHandle, visible: bool) -> Result<Settings, String> {
+    let settings = app
+        .state::<SettingsState>()
+        .update(|s| s.show_dock_icon = visible)?;
+    widget_window::apply_dock_icon(app)?;
+    Ok(settings)
+}
+
+/// Narrows the combined title to the next provider with data, then back to
+/// all of them. Returns the provider now shown alone, if any.
+pub fn cycle_focus(app: &AppHandle) -> Option<&'static str> {
+    let state = app.state::<TrayState>();
+    let providers: Vec<&'static str> = state
+        .segments
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|s| s.provider)
+        .collect();
+    let next = {
+        let mut focus = state.focus.lock().unwrap();
+        let next = match *focus {
+            None => providers.first().copied(),
+            Some(current) => providers
+                .iter()
+                .position(|p| *p == current)
+                .and_then(|i| providers.get(i + 1))
+                .copied(),
+        };
+        *focus = next;
+        next
+    };
+    rerender(app);
+    next
+}
+
+/// Changes what a left click does on every menu bar item.
+pub fn set_left_click(app: &AppHandle, action: LeftClickAction) -> Result<Settings, String> {
+    let settings = app
+        .state::<SettingsState>()
+        .update(|s| s.left_click = action)?;
+    let show_menu = action == LeftClickAction::Menu;
+    let ids = std::iter::once(MAIN_TRAY_ID.to_string())
+        .chain(["claude", "codex", "openrouter"].map(provider_tray_id));
+    for id in ids {
+        if let Some(tray) = app.tray_by_id(&id) {
+            tray.set_show_menu_on_left_click(show_menu).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(settings)
+}
+
+/// Reflects the pause state in the menu check item and the menu bar text.
+pub fn set_paused(app: &AppHandle, paused: bool) {
+    set_check(&app.state::<TrayState>().menu, "pause_monitoring", paused);
+    rerender(app);
+}
+
+fn set_text(item: &MenuItemKind<Wry>, text: &str) {
+    let _ = match item {
+        MenuItemKind::MenuItem(i) => i.set_text(text),
+        MenuItemKind::Check(i) => i.set_text(text),
+        MenuItemKind::Submenu(i) => i.set_text(text),
+        _ => Ok(()),
+    };
+}
+
+/// Re-labels the main menu and tooltips after a language change. Separate
+/// provider items pick it up when they're next created.
+pub fn relabel(app: &AppHandle) {
+    let menu = &app.state::<TrayState>().menu;
+    for item in menu.items().unwrap_or_default() {
+        if MENU_ITEMS.iter().any(|(id, _, _)| item.id() == *id) {
+            set_text(&item, label(item.id().as_ref()));
+        }
+        let Some(sub) = item.as_submenu() else {
+            continue;
+        };
+        for child in sub.items().unwrap_or_default() {
+            let id = child.id().as_ref();
+            if let Some(secs) = id.strip_prefix("interval_").and_then(|s| s.parse().ok()) {
+                set_text(&child, &interval_label(secs));
+            } else if let Some(pct) = id.strip_prefix("notify_").and_then(|s| s.parse().ok()) {
+                set_text(&child, &threshold_label(pct));
+            }
+        }
+    }
+    let secs = app.state::<SettingsState>().get().refresh_interval_secs;
+    sync_custom_interval(app, menu, secs);
+    update_tooltips(app);
+}
+
+/// Whether the check item `id` should be checked for `settings`, for items
+/// backed by a setting.
+fn checked_for(settings: &Settings, id: &str) -> Option<bool> {
+    if let Some(secs) = id.strip_prefix("interval_").and_then(|s| s.parse::<u64>().ok()) {
+        return Some(secs == settings.refresh_interval_secs);
+    }
+    if let Some(pct) = id.strip_prefix("notify_").and_then(|s| s.parse::<u32>().ok()) {
+        return Some(if pct == 0 {
+            !settings.notifications_enabled
+        } else {
+            settings.notifications_enabled && pct == settings.notify_threshold
+        });
+    }
+    match id {
+        "start_login" => Some(settings.start_at_login),
+        "tray_per_provider" => Some(settings.tray_per_provider),
+        "privacy_mode" => Some(settings.privacy_mode),
+        "click_through" => Some(settings.click_through),
+        "dock_icon" => Some(settings.show_dock_icon),
+        _ => None,
+    }
+}
+
+/// Brings every settings-backed check item, including those in submenus, in
+/// line with `settings`.
+fn sync_checks(menu: &Menu<Wry>, settings: &Settings) {
+    let mut items = menu.items().unwrap_or_default();
+    let children: Vec<MenuItemKind<Wry>> = items
+        .iter()
+        .filter_map(|i| i.as_submenu())
+        .flat_map(|s| s.items().unwrap_or_default())
+        .collect();
+    items.extend(children);
+    for item in items {
+        if let (Some(check), Some(checked)) = (
+            item.as_check_menuitem(),
+            checked_for(settings, item.id().as_ref()),
+        ) {
+            let _ = check.set_checked(checked);
+        }
+    }
+}
+
+fn set_check(menu: &Menu<Wry>, id: &str, checked: bool) {
+    if let Some(item) = menu.get(id) {
+        if let Some(check) = item.as_check_menuitem() {
+            let _ = check.set_checked(checked);
+        }
+    }
+}
+
+fn toggle_widget(app: &AppHandle) {
+    if let Some(w) = app.get_webview_window("main") {
+        if w.is_visible().unwrap_or(false) {
+            let _ = w.hide();
+        } else {
+            let _ = w.show();
+            let _ = w.set_focus();
+        }
+    }
+}
+
+/// Hovering, which opens the preview; left clicks (when they aren't set to
+/// open the menu); and middle clicks, which cycle the provider shown in the
+/// title.
+fn handle_tray_event(app: &AppHandle, event: TrayIconEvent) {
+    let button = match event {
+        TrayIconEvent::Enter { rect, .. } => return preview::hover(app, rect),
+        TrayIconEvent::Leave { .. } => return preview::leave(app),
+        TrayIconEvent::Click {
+            button,
+            button_state: MouseButtonState::Up,
+            ..
+        } => button,
+        _ => return,
+    };
+    // Clicking goes to the menu or the widget, so the preview makes way
+    preview::leave(app);
+    match button {
+        MouseButton::Left => match app.state::<SettingsState>().get().left_click {
+            LeftClickAction::Menu => {}
+            LeftClickAction::ToggleWidget => toggle_widget(app),
+            LeftClickAction::Refresh => scheduler::refresh_now(app),
+        },
+        MouseButton::Middle => {
+            cycle_focus(app);
+        }
+        _ => {}
+    }
+}
+
+fn handle_menu_event(app: &AppHandle, id: &str) {
+    match id {
+        "show_hide" => toggle_widget(app),
+        "notification_hint" => {
+            if let Err(e) = notification_permission::open_settings() {
+                log::warn!("{}", e);
+            }
+        }
+        "refresh_now" => {
+            scheduler::refresh_now(app);
+        }
+        "reconfigure_providers" => {
+            onboarding::reconfigure(app);
+        }
+        "monthly_report" => {
+            // Reads a month of local logs, so keep it off the main thread
+            let app = app.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                if let Err(e) = report::generate_latest(&app) {
+                    log::warn!("Monthly report failed: {}", e);
+                }
+            });
+        }
+        "open_settings" => {
+            if let Some(w) = app.get_webview_window("main") {
+                let _ = w.show();
+                let _ = w.set_focus();
+            }
+            let _ = app.emit("open-settings", ());
+        }
+        "quit" => {
+            app.exit(0);
+        }
+        CUSTOM_INTERVAL_ID => {
+            // Clicking the active custom item would otherwise uncheck it
+            let secs = app.state::<SettingsState>().get().refresh_interval_secs;
+            sync_custom_interval(app, &app.state::<TrayState>().menu, secs);
+        }
+        s if s.starts_with("interval_") => {
+            if let Ok(secs) = s.strip_prefix("interval_").unwrap().parse::<u64>() {
+                let ss = app.state::<SettingsState>();
+                let _ = ss.update(|s| s.refresh_interval_secs = secs);
+                // Notify frontend about interval change
+                let _ = app.emit("refresh-interval-changed", secs);
+            }
+        }
+        s if s.starts_with("notify_") => {
+            if let Ok(pct) = s.strip_prefix("notify_").unwrap().parse::<u32>() {
+                let ss = app.state::<SettingsState>();
+                let _ = ss.update(|s| {
+                    if pct == 0 {
+                        s.notifications_enabled = false;
+                    } else {
+                        s.notifications_enabled = true;
+                        s.notify_threshold = pct;
+                    }
+                });
+            }
+        }
+        "privacy_mode" => {
+            let enabled = !app.state::<SettingsState>().get().privacy_mode;
+            let _ = set_privacy_mode(app, enabled);
+        }
+        "click_through" => {
+            let enabled = !app.state::<SettingsState>().get().click_through;
+            let _ = set_click_through(app, enabled);
+        }
+        "dock_icon" => {
+            let visible = !app.state::<SettingsState>().get().show_dock_icon;
+            let _ = set_dock_icon(app, visible);
+        }
+        "start_login" => {
+            let ss = app.state::<SettingsState>();
+            let new_val = !ss.get().start_at_login;
+            let _ = ss.update(|s| s.start_at_login = new_val);
+            // Toggle autostart
+            {
+                use tauri_plugin_autostart::ManagerExt;
+                let mgr = app.autolaunch();
+                if new_val {
+                    let _ = mgr.enable();
+                } else {
+                    let _ = mgr.disable();
+                }
+            }
+        }
+        "tray_per_provider" => {
+            let ss = app.state::<SettingsState>();
+            let new_val = !ss.get().tray_per_provider;
+            let _ = ss.update(|s| s.tray_per_provider = new_val);
+            rerender(app);
+        }
+        "pause_monitoring" => {
+            let paused = !app.state::<SchedulerState>().is_paused();
+            scheduler::set_paused(app, paused);
+        }
+        _ => {}
+    }
+}
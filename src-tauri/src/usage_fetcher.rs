@@ -2,14 +2,30 @@
 //!
 //! Calls `GET https://claude.ai/api/organizations/{org_id}/usage` using cookies
 //! from the Claude desktop app. Returns session (5-hour), weekly (7-day), and
-//! model-specific utilization percentages along with reset times.
+//! model-specific utilization percentages along with reset times. Besides the
+//! Sonnet window, any other model family with its own weekly cap (Opus on Max
+//! plans) comes through as a metric of its own.
+//!
+//! The endpoint is undocumented and changes without notice, so each window is
+//! parsed on its own: one that no longer matches is logged with its raw JSON
+//! and shows "No data" while the rest still come through. Only a payload with
+//! none of the known windows fails the fetch.
 
+use std::path::PathBuf;
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 use crate::cookie_reader::ClaudeCookies;
+use crate::http_cache::{self, Fetched, Validators};
+use crate::http_client;
+use crate::i18n;
+
+/// How long a looked-up plan is reused before the organization is asked again.
+const PLAN_TTL: Duration = Duration::from_secs(60 * 60);
 
 // --- Types shared with the frontend via Tauri IPC ---
 
@@ -18,8 +34,177 @@ pub struct UsageData {
     pub session: UsageMetric,
     pub weekly_all: UsageMetric,
     pub weekly_sonnet: UsageMetric,
+    /// Weekly windows of other model families, only those the API reported
+    #[serde(default)]
+    pub model_windows: Vec<ModelWindow>,
     pub extra: ExtraUsage,
     pub fetched_at: String,
+    /// Plan reported by the provider (e.g. "plus", "default_claude_max_20x"), if known
+    #[serde(default)]
+    pub plan: Option<String>,
+    /// Claude subscription tier read from `plan`, when it is a known one
+    #[serde(default)]
+    pub plan_tier: Option<PlanTier>,
+    /// Seat in a Team or Enterprise workspace, when the provider reports one
+    #[serde(default)]
+    pub seat: Option<Seat>,
+    /// Amounts and plan replaced with placeholders (privacy mode)
+    #[serde(default)]
+    pub redacted: bool,
+    /// Weekly reset time as the API reported it, once a reset anchor has
+    /// been applied, so clearing the anchor can bring it back
+    #[serde(skip)]
+    pub api_weekly_resets_at: Option<Option<String>>,
+}
+
+impl UsageData {
+    /// Percent used for each tracked metric, keyed the same way across providers.
+    pub fn metric_percents(&self) -> Vec<(String, f64)> {
+        let mut out = vec![
+            ("session".to_string(), self.session.percent_used),
+            ("weekly".to_string(), self.weekly_all.percent_used),
+            ("sonnet".to_string(), self.weekly_sonnet.percent_used),
+        ];
+        out.extend(
+            self.model_windows
+                .iter()
+                .map(|w| (w.family.clone(), w.metric.percent_used)),
+        );
+        out.push(("extra".to_string(), self.extra.percent_used));
+        out
+    }
+
+    /// Every rate-limit window, the model families' last.
+    pub fn windows(&self) -> Vec<&UsageMetric> {
+        let mut out = vec![&self.session, &self.weekly_all, &self.weekly_sonnet];
+        out.extend(self.model_windows.iter().map(|w| &w.metric));
+        out
+    }
+
+    /// Cached data confirmed unchanged by the server: stamped as fetched now,
+    /// with countdowns recomputed from the reset times.
+    pub fn revalidated(mut self) -> Self {
+        self.fetched_at = chrono::Utc::now().to_rfc3339();
+        let metrics = [&mut self.session, &mut self.weekly_all, &mut self.weekly_sonnet];
+        let windows = self.model_windows.iter_mut().map(|w| &mut w.metric);
+        for metric in metrics.into_iter().chain(windows) {
+            if metric.resets_at.is_some() {
+                metric.reset_info = format_reset(&metric.resets_at);
+            }
+        }
+        self
+    }
+}
+
+/// Model families that may have a weekly window of their own besides Sonnet,
+/// reported as `seven_day_<family>`, and the `i18n` key of their label. Each
+/// becomes a metric named after the family.
+pub const MODEL_FAMILIES: [(&str, &str); 2] =
+    [("opus", "usage.opus_only"), ("haiku", "usage.haiku_only")];
+
+/// Subscription tier of a Claude or Codex plan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanTier {
+    Free,
+    /// ChatGPT Plus
+    Plus,
+    Pro,
+    #[serde(rename = "max_5x")]
+    Max5x,
+    #[serde(rename = "max_20x")]
+    Max20x,
+    Team,
+    /// ChatGPT Business
+    Business,
+    Enterprise,
+    /// ChatGPT Edu
+    Edu,
+}
+
+/// Workspace seat of a Team or Enterprise plan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Seat {
+    /// Workspace name, e.g. "Acme"
+    pub workspace: Option<String>,
+    /// Role in the workspace, e.g. "owner" or "member"
+    pub role: Option<String>,
+}
+
+impl PlanTier {
+    /// Tier of a raw plan as `fetch_plan` returns it, either a rate-limit tier
+    /// ("default_claude_max_20x") or a capability list ("claude_pro").
+    pub fn from_plan(plan: &str) -> Option<Self> {
+        let plan = plan.to_lowercase();
+        // Most specific first: "claude_max" alone means the 5x plan
+        [
+            ("max_20x", Self::Max20x),
+            ("max_5x", Self::Max5x),
+            ("claude_max", Self::Max5x),
+            ("enterprise", Self::Enterprise),
+            ("team", Self::Team),
+            ("claude_pro", Self::Pro),
+            ("free", Self::Free),
+        ]
+        .into_iter()
+        .find(|(needle, _)| plan.contains(needle))
+        .map(|(_, tier)| tier)
+    }
+
+    /// Tier of a Codex `plan_type` ("plus", "team", ...).
+    pub fn from_codex(plan_type: &str) -> Option<Self> {
+        Some(match plan_type.to_lowercase().as_str() {
+            "free" => Self::Free,
+            "plus" => Self::Plus,
+            "pro" => Self::Pro,
+            "team" => Self::Team,
+            "business" => Self::Business,
+            "enterprise" => Self::Enterprise,
+            "edu" => Self::Edu,
+            _ => return None,
+        })
+    }
+
+    /// Plans where the account is a seat in a shared workspace.
+    pub fn is_workspace(self) -> bool {
+        matches!(
+            self,
+            Self::Team | Self::Business | Self::Enterprise | Self::Edu
+        )
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Free => "Free",
+            Self::Plus => "Plus",
+            Self::Pro => "Pro",
+            Self::Max5x => "Max 5x",
+            Self::Max20x => "Max 20x",
+            Self::Team => "Team",
+            Self::Business => "Business",
+            Self::Enterprise => "Enterprise",
+            Self::Edu => "Edu",
+        }
+    }
+
+    /// Usage allowance of a Claude tier relative to Pro as Anthropic
+    /// advertises it, for the tiers that have a fixed one.
+    pub fn multiplier(self) -> Option<f64> {
+        match self {
+            Self::Pro => Some(1.0),
+            Self::Max5x => Some(5.0),
+            Self::Max20x => Some(20.0),
+            _ => None,
+        }
+    }
+}
+
+/// Weekly window of one model family.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelWindow {
+    /// Family, also its metric name, e.g. "opus"
+    pub family: String,
+    pub metric: UsageMetric,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +212,9 @@ pub struct UsageMetric {
     pub label: String,
     pub percent_used: f64,
     pub reset_info: String,
+    /// When the window resets (RFC 3339), if the provider reports it
+    #[serde(default)]
+    pub resets_at: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,53 +227,87 @@ pub struct ExtraUsage {
 
 // --- API response types ---
 
-#[derive(Deserialize)]
-struct ApiResponse {
-    five_hour: Option<WindowUsage>,
-    seven_day: Option<WindowUsage>,
-    seven_day_sonnet: Option<WindowUsage>,
-    extra_usage: Option<ApiExtraUsage>,
-}
+/// Top-level keys of the usage payload; at least one must be present.
+const USAGE_KEYS: [&str; 4] = ["five_hour", "seven_day", "seven_day_sonnet", "extra_usage"];
+/// Longest raw payload written to the log.
+const LOG_PAYLOAD_CHARS: usize = 2000;
 
 #[derive(Deserialize)]
 struct WindowUsage {
+    #[serde(alias = "percent_used", alias = "utilization_percent")]
     utilization: Option<f64>,
+    #[serde(alias = "reset_at", alias = "resets")]
     resets_at: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct ApiExtraUsage {
+    #[serde(alias = "enabled")]
     is_enabled: Option<bool>,
     monthly_limit: Option<f64>,
     used_credits: Option<f64>,
     utilization: Option<f64>,
 }
 
+#[derive(Deserialize)]
+struct ApiOrganization {
+    rate_limit_tier: Option<String>,
+    #[serde(default)]
+    capabilities: Vec<String>,
+}
+
 // --- State ---
 
 pub struct UsageState {
     pub last_data: Mutex<Option<UsageData>>,
-    pub client: Client,
+    /// Cookie database whose session last authenticated, tried first without
+    /// a separate check
+    pub cookie_source: Mutex<Option<PathBuf>>,
+    /// HTTP client shared by every provider; rebuilt when network settings change
+    client: Mutex<Client>,
+    /// Plan last read from the organization: org id, plan, when
+    plan: Mutex<Option<(String, Option<String>, Instant)>>,
 }
 
 impl UsageState {
-    pub fn new() -> Self {
+    pub fn new(client: Client) -> Self {
         Self {
             last_data: Mutex::new(None),
-            client: Client::new(),
+            cookie_source: Mutex::new(None),
+            client: Mutex::new(client),
+            plan: Mutex::new(None),
         }
     }
+
+    /// The plan of `org_id` if it was looked up within the last hour.
+    pub fn cached_plan(&self, org_id: &str) -> Option<Option<String>> {
+        match self.plan.lock().unwrap().as_ref() {
+            Some((org, plan, at)) if org == org_id && at.elapsed() < PLAN_TTL => Some(plan.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn set_plan(&self, org_id: &str, plan: Option<String>) {
+        *self.plan.lock().unwrap() = Some((org_id.to_string(), plan, Instant::now()));
+    }
+
+    pub fn client(&self) -> Client {
+        self.client.lock().unwrap().clone()
+    }
+
+    pub fn set_client(&self, client: Client) {
+        *self.client.lock().unwrap() = client;
+    }
 }
 
-pub async fn fetch_usage(cookies: &ClaudeCookies, client: &Client) -> Result<UsageData, String> {
+fn usage_request(cookies: &ClaudeCookies, client: &Client) -> RequestBuilder {
     let url = format!(
         "https://claude.ai/api/organizations/{}/usage",
         cookies.org_id
     );
-
-    let resp = client
+    client
         .get(&url)
-        .header("Cookie", &cookies.all_cookies)
+        .header("Cookie", cookies.all_cookies.expose())
         .header("Content-Type", "application/json")
         .header("Accept", "application/json")
         .header("Referer", "https://claude.ai/settings/usage")
@@ -94,61 +316,121 @@ pub async fn fetch_usage(cookies: &ClaudeCookies, client: &Client) -> Result<Usa
             "User-Agent",
             "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36",
         )
+}
+
+/// The usage payload as returned, for debugging schema changes.
+pub async fn fetch_raw(
+    cookies: &ClaudeCookies,
+    client: &Client,
+) -> Result<serde_json::Value, String> {
+    http_client::raw_json("Claude", usage_request(cookies, client)).await
+}
+
+pub async fn fetch_usage(
+    cookies: &ClaudeCookies,
+    client: &Client,
+    validators: Option<&Validators>,
+) -> Result<Fetched<UsageData>, String> {
+    let req = usage_request(cookies, client);
+    let resp = http_cache::conditional(req, validators)
         .send()
         .await
-        .map_err(|e| format!("HTTP request failed: {}", e))?;
+        .map_err(|e| http_client::request_error("Claude", e))?;
 
+    if resp.status() == StatusCode::NOT_MODIFIED {
+        return Ok(Fetched::NotModified);
+    }
+    if matches!(
+        resp.status(),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+    ) {
+        return Err(session_rejected(resp.status()));
+    }
     if !resp.status().is_success() {
         let status = resp.status();
         let body = resp.text().await.unwrap_or_default();
         return Err(format!("API returned {}: {}", status, body));
     }
 
-    let api: ApiResponse = resp
-        .json()
+    let fresh = Validators::from_response(&resp);
+    let body = resp
+        .text()
         .await
-        .map_err(|e| format!("Failed to parse API response: {}", e))?;
+        .map_err(|e| http_client::request_error("Claude", e))?;
+    let raw: serde_json::Value = match serde_json::from_str(&body) {
+        Ok(raw) => raw,
+        Err(e) => {
+            log::warn!("Claude usage response isn't JSON ({}): {}", e, truncated(&body));
+            return Err(format!("Failed to parse API response: {}", e));
+        }
+    };
+    if !USAGE_KEYS.iter().any(|k| raw.get(k).is_some()) {
+        log::warn!("Claude usage response has no known windows: {}", truncated(&body));
+        return Err("Claude usage response has no known windows - the API may have changed".into());
+    }
 
-    let session = match api.five_hour {
+    let session = match parse_part::<WindowUsage>(&raw, "five_hour") {
         Some(w) => UsageMetric {
-            label: "Current session".into(),
+            label: i18n::t("usage.current_session").into(),
             percent_used: w.utilization.unwrap_or(0.0),
             reset_info: format_reset(&w.resets_at),
+            resets_at: w.resets_at,
         },
         None => UsageMetric {
-            label: "Current session".into(),
+            label: i18n::t("usage.current_session").into(),
             percent_used: 0.0,
-            reset_info: "No data".into(),
+            reset_info: i18n::t("usage.no_data").into(),
+            resets_at: None,
         },
     };
 
-    let weekly_all = match api.seven_day {
+    let weekly_all = match parse_part::<WindowUsage>(&raw, "seven_day") {
         Some(w) => UsageMetric {
-            label: "All models".into(),
+            label: i18n::t("usage.all_models").into(),
             percent_used: w.utilization.unwrap_or(0.0),
             reset_info: format_reset(&w.resets_at),
+            resets_at: w.resets_at,
         },
         None => UsageMetric {
-            label: "All models".into(),
+            label: i18n::t("usage.all_models").into(),
             percent_used: 0.0,
-            reset_info: "No data".into(),
+            reset_info: i18n::t("usage.no_data").into(),
+            resets_at: None,
         },
     };
 
-    let weekly_sonnet = match api.seven_day_sonnet {
+    let weekly_sonnet = match parse_part::<WindowUsage>(&raw, "seven_day_sonnet") {
         Some(w) => UsageMetric {
-            label: "Sonnet only".into(),
+            label: i18n::t("usage.sonnet_only").into(),
             percent_used: w.utilization.unwrap_or(0.0),
             reset_info: format_reset(&w.resets_at),
+            resets_at: w.resets_at,
         },
         None => UsageMetric {
-            label: "Sonnet only".into(),
+            label: i18n::t("usage.sonnet_only").into(),
             percent_used: 0.0,
-            reset_info: "No data".into(),
+            reset_info: i18n::t("usage.no_data").into(),
+            resets_at: None,
         },
     };
 
-    let extra = match api.extra_usage {
+    let model_windows = MODEL_FAMILIES
+        .iter()
+        .filter_map(|(family, label)| {
+            let w = parse_part::<WindowUsage>(&raw, &format!("seven_day_{}", family))?;
+            Some(ModelWindow {
+                family: family.to_string(),
+                metric: UsageMetric {
+                    label: i18n::t(label).into(),
+                    percent_used: w.utilization.unwrap_or(0.0),
+                    reset_info: format_reset(&w.resets_at),
+                    resets_at: w.resets_at,
+                },
+            })
+        })
+        .collect();
+
+    let extra = match parse_part::<ApiExtraUsage>(&raw, "extra_usage") {
         Some(eu) => {
             let used = eu.used_credits.unwrap_or(0.0);
             let limit = eu.monthly_limit.unwrap_or(0.0);
@@ -159,7 +441,7 @@ pub async fn fetch_usage(cookies: &ClaudeCookies, client: &Client) -> Result<Usa
                 } else {
                     0.0
                 }),
-                reset_date: "Monthly".into(),
+                reset_date: i18n::t("usage.monthly").into(),
                 enabled: eu.is_enabled.unwrap_or(false),
             }
         }
@@ -171,16 +453,114 @@ pub async fn fetch_usage(cookies: &ClaudeCookies, client: &Client) -> Result<Usa
         },
     };
 
-    Ok(UsageData {
-        session,
-        weekly_all,
-        weekly_sonnet,
-        extra,
-        fetched_at: chrono::Utc::now().to_rfc3339(),
-    })
+    Ok(Fetched::Fresh(
+        UsageData {
+            session,
+            weekly_all,
+            weekly_sonnet,
+            model_windows,
+            extra,
+            fetched_at: chrono::Utc::now().to_rfc3339(),
+            plan: None,
+            plan_tier: None,
+            seat: None,
+            redacted: false,
+            api_weekly_resets_at: None,
+        },
+        fresh,
+    ))
+}
+
+/// Parses one part of the usage payload on its own, so a changed shape costs
+/// that part instead of the whole fetch. Parts that don't match are logged.
+fn parse_part<T: DeserializeOwned>(raw: &serde_json::Value, key: &str) -> Option<T> {
+    let value = raw.get(key).filter(|v| !v.is_null())?;
+    match T::deserialize(value) {
+        Ok(part) => Some(part),
+        Err(e) => {
+            let json = value.to_string();
+            log::warn!("Claude usage field {} didn't parse ({}): {}", key, e, truncated(&json));
+            None
+        }
+    }
+}
+
+fn truncated(text: &str) -> String {
+    match text.char_indices().nth(LOG_PAYLOAD_CHARS) {
+        Some((i, _)) => format!("{}...", &text[..i]),
+        None => text.to_string(),
+    }
+}
+
+fn session_rejected(status: StatusCode) -> String {
+    format!(
+        "Claude rejected the session cookies ({}) - sign in to the Claude desktop app again",
+        status
+    )
+}
+
+/// Checks that the cookies authenticate, with a cheap organization lookup.
+pub async fn verify_session(cookies: &ClaudeCookies, client: &Client) -> Result<(), String> {
+    let url = format!("https://claude.ai/api/organizations/{}", cookies.org_id);
+    let resp = client
+        .get(&url)
+        .header("Cookie", cookies.all_cookies.expose())
+        .header("Accept", "application/json")
+        .header("Origin", "https://claude.ai")
+        .header(
+            "User-Agent",
+            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36",
+        )
+        .send()
+        .await
+        .map_err(|e| http_client::request_error("Claude", e))?;
+    match resp.status() {
+        s if s.is_success() => Ok(()),
+        s @ (StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => Err(session_rejected(s)),
+        s => Err(format!("Organization API returned {}", s)),
+    }
+}
+
+/// Reads the organization's plan from `GET /api/organizations/{org_id}`:
+/// its rate-limit tier, or its plan capabilities when no tier is reported.
+pub async fn fetch_plan(
+    cookies: &ClaudeCookies,
+    client: &Client,
+) -> Result<Option<String>, String> {
+    let url = format!("https://claude.ai/api/organizations/{}", cookies.org_id);
+    let resp = client
+        .get(&url)
+        .header("Cookie", cookies.all_cookies.expose())
+        .header("Accept", "application/json")
+        .header("Referer", "https://claude.ai/settings/usage")
+        .header("Origin", "https://claude.ai")
+        .header(
+            "User-Agent",
+            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36",
+        )
+        .send()
+        .await
+        .map_err(|e| http_client::request_error("Claude", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("Organization API returned {}", resp.status()));
+    }
+    let org: ApiOrganization = resp
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse organization response: {}", e))?;
+
+    let capabilities: Vec<String> = org
+        .capabilities
+        .into_iter()
+        .filter(|c| c.starts_with("claude_"))
+        .collect();
+    Ok(org
+        .rate_limit_tier
+        .filter(|t| !t.is_empty())
+        .or_else(|| (!capabilities.is_empty()).then(|| capabilities.join(","))))
 }
 
-fn format_reset(resets_at: &Option<String>) -> String {
+pub fn format_reset(resets_at: &Option<String>) -> String {
     match resets_at {
         Some(dt) => {
             if let Ok(parsed) = chrono::DateTime::parse_from_rfc3339(dt) {
@@ -189,11 +569,11 @@ fn format_reset(resets_at: &Option<String>) -> String {
                 let hours = diff.num_hours();
                 let mins = diff.num_minutes() % 60;
                 if hours > 0 {
-                    format!("Resets in {}h {}m", hours, mins)
+                    i18n::tf("reset.in_hours", &[&hours, &mins])
                 } else if mins > 0 {
-                    format!("Resets in {}m", mins)
+                    i18n::tf("reset.in_minutes", &[&mins])
                 } else {
-                    "Resets soon".into()
+                    i18n::t("reset.soon").into()
                 }
             } else {
                 dt.clone()
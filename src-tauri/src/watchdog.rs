@@ -0,0 +1,150 @@
+//! Notices when a provider's numbers stop moving while the user is at the
+//! computer: every fetch succeeds, yet nothing has changed for hours. That is
+//! what a silently broken fetcher looks like (a stale session that still
+//! answers, a cache that never refreshes, 0% forever), so it gets a
+//! notification and a `data-stale` event.
+//!
+//! Only time spent active counts, so a night away or a weekend off doesn't
+//! trigger it. Each stuck stretch warns once; any change re-arms it.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::i18n;
+use crate::idle;
+use crate::notifications::{self, Severity};
+use crate::provider::Provider;
+use crate::settings::SettingsState;
+use crate::usage_fetcher::UsageData;
+
+/// Gaps between fetches longer than this (sleep, paused monitoring) don't
+/// count as active time even when the user is active at both ends.
+const MAX_GAP_SECS: i64 = 30 * 60;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleProvider {
+    pub provider: Provider,
+    /// Hours of active use without any change
+    pub unchanged_hours: f64,
+    /// RFC 3339, when the values last changed
+    pub unchanged_since: String,
+}
+
+struct Track {
+    /// Percentages and reset times at the last change
+    values: Vec<String>,
+    since: DateTime<Utc>,
+    last_seen: DateTime<Utc>,
+    active_secs: i64,
+    warned: bool,
+}
+
+pub struct WatchdogState {
+    tracks: Mutex<HashMap<Provider, Track>>,
+}
+
+impl WatchdogState {
+    pub fn new() -> Self {
+        Self {
+            tracks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Providers that have been flat past the threshold.
+    pub fn stale(&self) -> Vec<StaleProvider> {
+        let tracks = self.tracks.lock().unwrap();
+        Provider::ALL
+            .into_iter()
+            .filter_map(|p| {
+                let t = tracks.get(&p).filter(|t| t.warned)?;
+                Some(stale_provider(p, t))
+            })
+            .collect()
+    }
+}
+
+fn stale_provider(provider: Provider, track: &Track) -> StaleProvider {
+    StaleProvider {
+        provider,
+        unchanged_hours: track.active_secs as f64 / 3600.0,
+        unchanged_since: track.since.to_rfc3339(),
+    }
+}
+
+/// What has to change for the data to count as alive; `fetched_at` always
+/// does, so it is left out.
+fn fingerprint(data: &UsageData) -> Vec<String> {
+    let mut values: Vec<String> = data
+        .metric_percents()
+        .into_iter()
+        .map(|(metric, percent)| format!("{}={}", metric, percent))
+        .collect();
+    values.extend(
+        data.windows()
+            .into_iter()
+            .map(|m| format!("{:?}", m.resets_at)),
+    );
+    values
+}
+
+/// Records a successful fetch of `provider` (304s included) and warns when
+/// it has been flat for too long. A no-op while `stale_after_hours` is 0.
+pub fn check(app: &AppHandle, provider: Provider, data: &UsageData) {
+    let settings = app.state::<SettingsState>().get();
+    let state = app.state::<WatchdogState>();
+    let mut tracks = state.tracks.lock().unwrap();
+    if settings.stale_after_hours == 0 {
+        tracks.clear();
+        return;
+    }
+
+    let now = Utc::now();
+    let values = fingerprint(data);
+    let track = match tracks.get_mut(&provider) {
+        Some(t) if t.values == values => t,
+        _ => {
+            tracks.insert(
+                provider,
+                Track {
+                    values,
+                    since: now,
+                    last_seen: now,
+                    active_secs: 0,
+                    warned: false,
+                },
+            );
+            return;
+        }
+    };
+
+    let gap = (now - track.last_seen).num_seconds();
+    track.last_seen = now;
+    let active = !idle::is_idle(idle::idle_seconds(), settings.idle_after_secs);
+    if active && gap <= MAX_GAP_SECS {
+        track.active_secs += gap;
+    }
+    if track.warned || track.active_secs < i64::from(settings.stale_after_hours) * 3600 {
+        return;
+    }
+    track.warned = true;
+    let stale = stale_provider(provider, track);
+    drop(tracks);
+
+    if settings.notifications_enabled {
+        let name = settings.display_name(provider.name());
+        notifications::send(
+            app,
+            Severity::Routine,
+            &i18n::tf("notify.stale", &[&name]),
+            &i18n::tf(
+                "notify.stale_body",
+                &[&format!("{:.0}", stale.unchanged_hours)],
+            ),
+        );
+    }
+    let _ = app.emit("data-stale", stale);
+}
@@ -0,0 +1,144 @@
+//! Opt-in usage snapshot in a shared app-group container for a WidgetKit
+//! companion extension (Notification Center / desktop widgets). The format is
+//! documented in `docs/widgetkit.md`.
+//!
+//! The file is rewritten after every fetch and whenever privacy mode or a
+//! provider label changes, replaced atomically like `capacity.json`. Amounts
+//! and plan details are left out while privacy mode is on, since widgets stay
+//! visible on the desktop.
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::codex_fetcher::CodexState;
+use crate::openrouter_fetcher::OpenRouterState;
+use crate::settings::{Settings, SettingsState};
+use crate::usage_fetcher::{UsageData, UsageMetric, UsageState};
+
+/// App group shared with the widget extension; both must carry it in their
+/// `com.apple.security.application-groups` entitlement.
+pub const APP_GROUP: &str = "group.com.israelmirsky.claude-codex-usage";
+const FILE: &str = "usage.json";
+
+#[derive(Serialize)]
+struct WidgetWindow {
+    label: String,
+    used_percent: f64,
+    /// RFC 3339, if the provider reports it
+    resets_at: Option<String>,
+}
+
+impl From<&UsageMetric> for WidgetWindow {
+    fn from(m: &UsageMetric) -> Self {
+        Self {
+            label: m.label.clone(),
+            used_percent: m.percent_used,
+            resets_at: m.resets_at.clone(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct WidgetProvider {
+    /// "claude", "codex" or "openrouter"
+    id: &'static str,
+    /// Display name, with the user's custom label applied
+    name: String,
+    session: Option<WidgetWindow>,
+    weekly: Option<WidgetWindow>,
+    /// OpenRouter credits left in dollars; None while privacy mode is on
+    remaining_credits: Option<f64>,
+    fetched_at: String,
+}
+
+#[derive(Serialize)]
+struct WidgetData {
+    /// Bumped on incompatible changes to the format
+    version: u32,
+    updated_at: String,
+    privacy_mode: bool,
+    providers: Vec<WidgetProvider>,
+}
+
+pub fn path() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| {
+        h.join("Library/Group Containers")
+            .join(APP_GROUP)
+            .join(FILE)
+    })
+}
+
+fn usage_provider(settings: &Settings, id: &'static str, data: &UsageData) -> WidgetProvider {
+    let default = if id == "claude" { "Claude" } else { "Codex" };
+    WidgetProvider {
+        id,
+        name: settings.display_name(default),
+        session: Some((&data.session).into()),
+        weekly: Some((&data.weekly_all).into()),
+        remaining_credits: None,
+        fetched_at: data.fetched_at.clone(),
+    }
+}
+
+/// Rewrites the widget file from the cached data of every provider.
+pub fn write(app: &AppHandle) -> Result<(), String> {
+    let settings = app.state::<SettingsState>().get();
+    let mut providers = Vec::new();
+    if let Some(d) = app.state::<UsageState>().last_data.lock().unwrap().as_ref() {
+        providers.push(usage_provider(&settings, "claude", d));
+    }
+    if let Some(d) = app.state::<CodexState>().last_data.lock().unwrap().as_ref() {
+        providers.push(usage_provider(&settings, "codex", d));
+    }
+    if let Some(d) = app
+        .state::<OpenRouterState>()
+        .last_data
+        .lock()
+        .unwrap()
+        .as_ref()
+    {
+        providers.push(WidgetProvider {
+            id: "openrouter",
+            name: settings.display_name("OpenRouter"),
+            session: None,
+            weekly: None,
+            remaining_credits: (!settings.privacy_mode).then_some(d.remaining_credits),
+            fetched_at: d.fetched_at.clone(),
+        });
+    }
+
+    let data = WidgetData {
+        version: 1,
+        updated_at: chrono::Utc::now().to_rfc3339(),
+        privacy_mode: settings.privacy_mode,
+        providers,
+    };
+    let json = serde_json::to_string_pretty(&data).map_err(|e| e.to_string())?;
+
+    let path = path().ok_or("No home directory")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, &path).map_err(|e| e.to_string())
+}
+
+/// Rewrites the file if the integration is on; errors are ignored so a
+/// missing container never affects the fetch.
+pub fn refresh(app: &AppHandle) {
+    if app.state::<SettingsState>().get().widget_data {
+        let _ = write(app);
+    }
+}
+
+/// Removes the file when the integration is turned off, so the widget shows
+/// its empty state instead of stale numbers.
+pub fn remove() -> Result<(), String> {
+    match path().map(std::fs::remove_file) {
+        Some(Err(e)) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,283 @@
+//! Behavior of the main widget window beyond showing and hiding it from the
+//! tray.
+//!
+//! With "hide when it loses focus" on, the widget acts like a popover and
+//! hides as soon as another app is activated, unless it is pinned.
+//!
+//! A pinned widget can float over other windows as a passive HUD: partly
+//! transparent, and optionally click-through so clicks reach the app below.
+//! Click-through can always be turned off again from the tray menu, since the
+//! widget itself can't be clicked while it's on.
+//!
+//! In mini mode the window shrinks to a one-line always-on-top strip with just
+//! the percentages, placed in the chosen corner of the screen. The mode is
+//! saved and restored at launch; the frontend switches layout on
+//! `settings-changed`.
+//!
+//! Optionally the floating widget (pinned, or in mini mode) follows you to
+//! every Space and stays over fullscreen apps, such as a fullscreen IDE.
+//!
+//! Without its Dock icon the app lives only in the menu bar (an accessory
+//! app): no Dock tile and no entry in ⌘-Tab.
+//!
+//! Whether the widget was pinned, and whether it was showing at quit, is
+//! saved and restored at the next launch, unless set to launch minimized.
+
+use std::sync::Mutex;
+
+use tauri::{AppHandle, LogicalSize, Manager, PhysicalPosition, WebviewWindow, WindowEvent};
+
+use crate::settings::{ScreenCorner, Settings, SettingsState, WindowMode};
+
+pub const LABEL: &str = "main";
+/// Lowest opacity allowed, so the widget never disappears entirely
+pub const MIN_OPACITY: f64 = 0.2;
+
+/// Logical sizes of the two layouts; full matches `tauri.conf.json`
+const FULL_SIZE: (f64, f64) = (280.0, 400.0);
+const MINI_SIZE: (f64, f64) = (240.0, 28.0);
+/// Gap between the mini strip and the screen edges, in logical pixels
+const MINI_MARGIN: f64 = 12.0;
+
+pub struct WidgetWindowState {
+    /// Pinned with `toggle_pin`: always on top, decorated, never auto-hidden
+    pinned: Mutex<bool>,
+}
+
+impl WidgetWindowState {
+    pub fn new() -> Self {
+        Self {
+            pinned: Mutex::new(false),
+        }
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        *self.pinned.lock().unwrap()
+    }
+}
+
+pub fn set_pinned(app: &AppHandle, window: &WebviewWindow, pinned: bool) -> Result<(), String> {
+    window
+        .set_always_on_top(pinned)
+        .map_err(|e| e.to_string())?;
+    window.set_decorations(pinned).map_err(|e| e.to_string())?;
+    *app.state::<WidgetWindowState>().pinned.lock().unwrap() = pinned;
+    app.state::<SettingsState>()
+        .update(|s| s.widget_pinned = pinned)?;
+    apply_hud(app)?;
+    apply_spaces(app)
+}
+
+/// Applies the HUD opacity and click-through while pinned; an unpinned widget
+/// is always opaque and clickable.
+pub fn apply_hud(app: &AppHandle) -> Result<(), String> {
+    let Some(window) = app.get_webview_window(LABEL) else {
+        return Ok(());
+    };
+    let settings = app.state::<SettingsState>().get();
+    let pinned = app.state::<WidgetWindowState>().is_pinned();
+    window
+        .set_ignore_cursor_events(pinned && settings.click_through)
+        .map_err(|e| e.to_string())?;
+    set_alpha(&window, if pinned { settings.pinned_opacity } else { 1.0 })
+}
+
+#[cfg(target_os = "macos")]
+fn set_alpha(window: &WebviewWindow, alpha: f64) -> Result<(), String> {
+    let w = window.clone();
+    window
+        .run_on_main_thread(move || {
+            if let Ok(ptr) = w.ns_window() {
+                // SAFETY: `ns_window` is the live NSWindow behind this window,
+                // and it is only touched on the main thread
+                let ns_window = unsafe { &*ptr.cast::<objc2_app_kit::NSWindow>() };
+                ns_window.setAlphaValue(alpha);
+            }
+        })
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn set_alpha(_window: &WebviewWindow, _alpha: f64) -> Result<(), String> {
+    Ok(())
+}
+
+/// Shows the floating widget on all Spaces and over fullscreen apps when
+/// `all_spaces` is on; otherwise it stays on its own Space.
+pub fn apply_spaces(app: &AppHandle) -> Result<(), String> {
+    let Some(window) = app.get_webview_window(LABEL) else {
+        return Ok(());
+    };
+    let settings = app.state::<SettingsState>().get();
+    let floating =
+        app.state::<WidgetWindowState>().is_pinned() || settings.window_mode == WindowMode::Mini;
+    let everywhere = floating && settings.all_spaces;
+    window
+        .set_visible_on_all_workspaces(everywhere)
+        .map_err(|e| e.to_string())?;
+    set_fullscreen_auxiliary(&window, everywhere)
+}
+
+/// Switches between a regular app and a menu-bar-only accessory app, per the
+/// `show_dock_icon` setting.
+#[cfg(target_os = "macos")]
+pub fn apply_dock_icon(app: &AppHandle) -> Result<(), String> {
+    let policy = if app.state::<SettingsState>().get().show_dock_icon {
+        tauri::ActivationPolicy::Regular
+    } else {
+        tauri::ActivationPolicy::Accessory
+    };
+    app.set_activation_policy(policy).map_err(|e| e.to_string())
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn apply_dock_icon(_app: &AppHandle) -> Result<(), String> {
+    Ok(())
+}
+
+/// Lets the window share the Space of a fullscreen app, which joining all
+/// Spaces alone doesn't.
+#[cfg(target_os = "macos")]
+fn set_fullscreen_auxiliary(window: &WebviewWindow, enabled: bool) -> Result<(), String> {
+    use objc2_app_kit::NSWindowCollectionBehavior;
+
+    let w = window.clone();
+    window
+        .run_on_main_thread(move || {
+            if let Ok(ptr) = w.ns_window() {
+                // SAFETY: as in `set_alpha`
+                let ns_window = unsafe { &*ptr.cast::<objc2_app_kit::NSWindow>() };
+                let mut behavior = ns_window.collectionBehavior();
+                behavior.set(NSWindowCollectionBehavior::FullScreenAuxiliary, enabled);
+                ns_window.setCollectionBehavior(behavior);
+            }
+        })
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn set_fullscreen_auxiliary(_window: &WebviewWindow, _enabled: bool) -> Result<(), String> {
+    Ok(())
+}
+
+/// Places the mini strip in `corner` of the work area of the window's screen.
+fn move_to_corner(window: &WebviewWindow, corner: ScreenCorner) -> Result<(), String> {
+    let Some(monitor) = window.current_monitor().map_err(|e| e.to_string())? else {
+        return Ok(());
+    };
+    let area = monitor.work_area();
+    let scale = monitor.scale_factor();
+    let margin = (MINI_MARGIN * scale) as i32;
+    let width = (MINI_SIZE.0 * scale) as i32;
+    let height = (MINI_SIZE.1 * scale) as i32;
+    let left = area.position.x + margin;
+    let right = area.position.x + area.size.width as i32 - width - margin;
+    let top = area.position.y + margin;
+    let bottom = area.position.y + area.size.height as i32 - height - margin;
+    let (x, y) = match corner {
+        ScreenCorner::TopLeft => (left, top),
+        ScreenCorner::TopRight => (right, top),
+        ScreenCorner::BottomLeft => (left, bottom),
+        ScreenCorner::BottomRight => (right, bottom),
+    };
+    window
+        .set_position(PhysicalPosition::new(x, y))
+        .map_err(|e| e.to_string())
+}
+
+/// Sizes and places the window for the saved mode.
+fn apply_mode(app: &AppHandle, settings: &Settings) -> Result<(), String> {
+    let Some(window) = app.get_webview_window(LABEL) else {
+        return Ok(());
+    };
+    match settings.window_mode {
+        WindowMode::Mini => {
+            window
+                .set_size(LogicalSize::new(MINI_SIZE.0, MINI_SIZE.1))
+                .map_err(|e| e.to_string())?;
+            window.set_always_on_top(true).map_err(|e| e.to_string())?;
+            move_to_corner(&window, settings.mini_corner)?;
+            window.show().map_err(|e| e.to_string())?;
+        }
+        WindowMode::Full => {
+            let pinned = app.state::<WidgetWindowState>().is_pinned();
+            window
+                .set_size(LogicalSize::new(FULL_SIZE.0, FULL_SIZE.1))
+                .map_err(|e| e.to_string())?;
+            window
+                .set_always_on_top(pinned)
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    apply_spaces(app)
+}
+
+/// Switches between the full widget and the mini strip.
+pub fn set_mode(app: &AppHandle, mode: WindowMode) -> Result<Settings, String> {
+    let settings = app
+        .state::<SettingsState>()
+        .update(|s| s.window_mode = mode)?;
+    apply_mode(app, &settings)?;
+    Ok(settings)
+}
+
+pub fn set_mini_corner(app: &AppHandle, corner: ScreenCorner) -> Result<Settings, String> {
+    let settings = app
+        .state::<SettingsState>()
+        .update(|s| s.mini_corner = corner)?;
+    if settings.window_mode == WindowMode::Mini {
+        apply_mode(app, &settings)?;
+    }
+    Ok(settings)
+}
+
+/// Saves whether the widget is showing, for `attach` to restore. Call when
+/// the app is about to quit.
+pub fn remember_visibility(app: &AppHandle) {
+    let Some(window) = app.get_webview_window(LABEL) else {
+        return;
+    };
+    let visible = window.is_visible().unwrap_or(false);
+    if let Err(e) = app
+        .state::<SettingsState>()
+        .update(|s| s.widget_visible = visible)
+    {
+        log::warn!("Failed to save widget visibility: {}", e);
+    }
+}
+
+/// Hooks the widget's window events and restores the Dock icon and the saved
+/// mode, pin and visibility. Call once during setup.
+pub fn attach(app: &AppHandle) {
+    let Some(window) = app.get_webview_window(LABEL) else {
+        return;
+    };
+    let settings = app.state::<SettingsState>().get();
+    let _ = apply_dock_icon(app);
+    if settings.widget_pinned {
+        let _ = set_pinned(app, &window, true);
+    }
+    if settings.window_mode == WindowMode::Mini {
+        let _ = apply_mode(app, &settings);
+    }
+    if settings.launch_minimized {
+        let _ = window.hide();
+    } else if settings.widget_visible {
+        let _ = window.show();
+    }
+    let handle = app.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Focused(false) = event {
+            // The Mini HUD floats like a pinned window, so it stays up too
+            let settings = handle.state::<SettingsState>().get();
+            let hide = settings.hide_on_blur
+                && settings.window_mode != WindowMode::Mini
+                && !handle.state::<WidgetWindowState>().is_pinned();
+            if hide {
+                if let Some(w) = handle.get_webview_window(LABEL) {
+                    let _ = w.hide();
+                }
+            }
+        }
+    });
+}
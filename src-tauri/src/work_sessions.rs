@@ -0,0 +1,82 @@
+//! Pomodoro-style work sessions: name a stretch of work ("auth refactor"),
+//! and when it stops the widget reports how much of each limit it consumed,
+//! e.g. "Claude: 14% of session, 3% of weekly". Both ends refresh the
+//! providers first so the snapshots are current; sessions live in history.db.
+
+use tauri::{AppHandle, Manager};
+
+use crate::history::{HistoryState, WorkSession};
+use crate::i18n;
+use crate::notifications::{self, Severity};
+use crate::scheduler;
+use crate::settings::{Settings, SettingsState};
+
+/// Providers with percentage windows worth snapshotting.
+const PROVIDERS: [&str; 2] = ["Claude", "Codex"];
+
+pub async fn start(app: &AppHandle, name: &str) -> Result<WorkSession, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Give the session a name".into());
+    }
+    let history = app.state::<HistoryState>();
+    if let Some(active) = history.active_work_session()? {
+        return Err(format!("\"{}\" is still running", active.name));
+    }
+    scheduler::refresh_all(app).await;
+    history.start_work_session(name, &PROVIDERS)
+}
+
+/// Stops the running session and posts its summary as a routine notification.
+pub async fn stop(app: &AppHandle) -> Result<WorkSession, String> {
+    let history = app.state::<HistoryState>();
+    if history.active_work_session()?.is_none() {
+        return Err("No work session is running".into());
+    }
+    scheduler::refresh_all(app).await;
+    let session = history.stop_work_session()?;
+    let settings = app.state::<SettingsState>().get();
+    notifications::send(
+        app,
+        Severity::Routine,
+        &i18n::tf("work.finished", &[&session.name]),
+        &summary(&settings, &session),
+    );
+    Ok(session)
+}
+
+/// "Claude: 14% of session, 3% of weekly; Codex: 0% of session, 1% of weekly"
+pub fn summary(settings: &Settings, session: &WorkSession) -> String {
+    let parts: Vec<String> = PROVIDERS
+        .iter()
+        .filter_map(|provider| {
+            let metrics: Vec<String> = ["session", "weekly"]
+                .iter()
+                .filter_map(|metric| {
+                    let u = session
+                        .usage
+                        .iter()
+                        .find(|u| u.provider == *provider && u.metric == *metric)?;
+                    let key = if *metric == "session" {
+                        "work.of_session"
+                    } else {
+                        "work.of_weekly"
+                    };
+                    Some(i18n::tf(key, &[&format!("{:.0}", u.consumed?)]))
+                })
+                .collect();
+            (!metrics.is_empty()).then(|| {
+                format!(
+                    "{}: {}",
+                    settings.display_name(provider),
+                    metrics.join(", ")
+                )
+            })
+        })
+        .collect();
+    if parts.is_empty() {
+        i18n::t("work.no_data").into()
+    } else {
+        parts.join("; ")
+    }
+}
@@ -1,8 +1,9 @@
-import { useState, useEffect, useCallback } from "react";
+import { useState, useEffect, useCallback, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import UsagePanel from "./components/UsagePanel";
 import SettingsModal from "./components/SettingsModal";
+import MiniStrip from "./components/MiniStrip";
 
 type Provider = "claude" | "codex" | "both";
 
@@ -10,6 +11,7 @@ interface UsageCategory {
   label: string;
   percent_used: number;
   reset_info: string;
+  resets_at?: string | null;
 }
 
 interface ExtraData {
@@ -23,8 +25,13 @@ interface UsageData {
   session: UsageCategory;
   weekly_all: UsageCategory;
   weekly_sonnet: UsageCategory;
+  model_windows?: { family: string; metric: UsageCategory }[];
   extra: ExtraData;
   fetched_at: string;
+  plan?: string | null;
+  plan_tier?: string | null;
+  seat?: { workspace?: string | null; role?: string | null } | null;
+  redacted?: boolean;
 }
 
 interface OpenRouterCreditsData {
@@ -32,6 +39,34 @@ interface OpenRouterCreditsData {
   total_usage: number;
   remaining_credits: number;
   fetched_at: string;
+  redacted?: boolean;
+}
+
+interface GatewayBudgetData {
+  key_alias: string | null;
+  spend: number;
+  max_budget: number | null;
+  remaining: number | null;
+  percent_used: number;
+  budget_duration: string | null;
+  budget_resets_at: string | null;
+  fetched_at: string;
+  redacted?: boolean;
+}
+
+interface LocalModel {
+  name: string;
+  size_bytes: number;
+  vram_bytes: number;
+  expires_at: string | null;
+}
+
+interface LocalActivityData {
+  running: boolean;
+  models: LocalModel[];
+  requests_last_hour: number;
+  last_active_at: string | null;
+  fetched_at: string;
 }
 
 interface OpenRouterKeyStatus {
@@ -39,12 +74,44 @@ interface OpenRouterKeyStatus {
   masked_key: string | null;
 }
 
+type ProviderId = "claude" | "codex" | "openrouter" | "litellm" | "ollama";
+
+interface FetchStartedEvent {
+  provider: ProviderId;
+}
+
+interface FetchSucceededEvent {
+  provider: ProviderId;
+  data: UsageData | OpenRouterCreditsData | GatewayBudgetData | LocalActivityData;
+}
+
+interface FetchFailedEvent {
+  provider: ProviderId;
+  error: string;
+}
+
+type ProviderLabels = Record<ProviderId, string>;
+
+type WindowMode = "full" | "mini";
+
+const DEFAULT_LABELS: ProviderLabels = {
+  claude: "Claude",
+  codex: "Codex",
+  openrouter: "OpenRouter",
+  litellm: "LiteLLM",
+  ollama: "Ollama",
+};
+
 function App() {
   const [provider, setProvider] = useState<Provider>("claude");
   const [claudeData, setClaudeData] = useState<UsageData | null>(null);
   const [codexData, setCodexData] = useState<UsageData | null>(null);
   const [openRouterData, setOpenRouterData] = useState<OpenRouterCreditsData | null>(null);
   const [openRouterError, setOpenRouterError] = useState<string | null>(null);
+  const [gatewayData, setGatewayData] = useState<GatewayBudgetData | null>(null);
+  const [gatewayError, setGatewayError] = useState<string | null>(null);
+  const [localData, setLocalData] = useState<LocalActivityData | null>(null);
+  const [localError, setLocalError] = useState<string | null>(null);
   const [openRouterKeyStatus, setOpenRouterKeyStatus] = useState<OpenRouterKeyStatus | null>(null);
   const [settingsOpen, setSettingsOpen] = useState(false);
   const [settingsSaving, setSettingsSaving] = useState(false);
@@ -54,6 +121,8 @@ function App() {
   const [error, setError] = useState<string | null>(null);
   const [pinned, setPinned] = useState(false);
   const [refreshing, setRefreshing] = useState(false);
+  const [labels, setLabels] = useState<ProviderLabels>(DEFAULT_LABELS);
+  const [windowMode, setWindowMode] = useState<WindowMode>("full");
 
   const data = provider === "claude" ? claudeData : provider === "codex" ? codexData : claudeData;
 
@@ -66,69 +135,82 @@ function App() {
     }
   }, []);
 
-  const fetchUsage = useCallback(async () => {
-    try {
-      setRefreshing(true);
-      setError(null);
-
-      // Fetch both providers in parallel
-      const [claudeResult, codexResult, openRouterResult] = await Promise.allSettled([
-        invoke<UsageData>("fetch_claude_usage"),
-        invoke<UsageData>("fetch_codex_usage"),
-        invoke<OpenRouterCreditsData>("fetch_openrouter_credits"),
-      ]);
+  const inFlightRef = useRef(new Set<ProviderId>());
+  const providerRef = useRef(provider);
+  providerRef.current = provider;
 
-      if (claudeResult.status === "fulfilled") {
-        setClaudeData(claudeResult.value);
+  // Store fresh provider data; the backend updates the tray itself
+  const applySuccess = useCallback(
+    (
+      p: ProviderId,
+      value: UsageData | OpenRouterCreditsData | GatewayBudgetData | LocalActivityData,
+    ) => {
+      if (p === "openrouter") {
+        setOpenRouterData(value as OpenRouterCreditsData);
+        setOpenRouterError(null);
+        return;
       }
-      if (codexResult.status === "fulfilled") {
-        setCodexData(codexResult.value);
+      if (p === "litellm") {
+        setGatewayData(value as GatewayBudgetData);
+        setGatewayError(null);
+        return;
       }
-      if (openRouterResult.status === "fulfilled") {
-        setOpenRouterData(openRouterResult.value);
-        setOpenRouterError(null);
+      if (p === "ollama") {
+        setLocalData(value as LocalActivityData);
+        setLocalError(null);
+        return;
+      }
+      const usage = value as UsageData;
+      if (p === providerRef.current) setError(null);
+      if (p === "claude") {
+        setClaudeData(usage);
       } else {
-        const reason = String(openRouterResult.reason);
-        const missingKey = reason.toLowerCase().includes("openrouter_api_key is not set")
-          || reason.toLowerCase().includes("openrouter_api_key is empty");
-        setOpenRouterData(null);
-        setOpenRouterError(missingKey ? null : reason);
+        setCodexData(usage);
       }
+    },
+    [],
+  );
 
-      // Update tray text with both providers
-      const cSession = claudeResult.status === "fulfilled"
-        ? Math.round(claudeResult.value.session.percent_used) : null;
-      const cWeekly = claudeResult.status === "fulfilled"
-        ? Math.round(claudeResult.value.weekly_all.percent_used) : null;
-      const xSession = codexResult.status === "fulfilled"
-        ? Math.round(codexResult.value.session.percent_used) : null;
-      const xWeekly = codexResult.status === "fulfilled"
-        ? Math.round(codexResult.value.weekly_all.percent_used) : null;
-      const openRouterRemaining = openRouterResult.status === "fulfilled"
-        ? openRouterResult.value.remaining_credits
-        : null;
-
-      await invoke("update_tray_text", {
-        claudeSession: cSession ?? -1,
-        claudeWeekly: cWeekly ?? -1,
-        codexSession: xSession ?? -1,
-        codexWeekly: xWeekly ?? -1,
-        openrouterRemaining: openRouterRemaining ?? -1,
-      });
-
-      // Show error only if the active provider failed
-      if (provider === "claude" && claudeResult.status === "rejected") {
-        setError(String(claudeResult.reason));
-      } else if (provider === "codex" && codexResult.status === "rejected") {
-        setError(String(codexResult.reason));
-      }
+  const applyFailure = useCallback((p: ProviderId, reason: string) => {
+    if (p === "openrouter") {
+      const lower = reason.toLowerCase();
+      const missingKey = lower.includes("openrouter_api_key is not set")
+        || lower.includes("openrouter_api_key is empty");
+      setOpenRouterData(null);
+      setOpenRouterError(missingKey ? null : reason);
+      return;
+    }
+    if (p === "litellm") {
+      // "No LiteLLM gateway URL/key is set" just means it isn't in use
+      const notSetUp = reason.startsWith("No LiteLLM");
+      setGatewayData(null);
+      setGatewayError(notSetUp ? null : reason);
+      return;
+    }
+    if (p === "ollama") {
+      setLocalError(reason);
+      return;
+    }
+    // Show error only if the active provider failed
+    if (p === providerRef.current) setError(reason);
+  }, []);
+
+  const fetchUsage = useCallback(async () => {
+    try {
+      setRefreshing(true);
+      setError(null);
+
+      // The backend fetches all providers concurrently; results arrive as
+      // provider-fetch-* events
+      await invoke("refresh_all");
     } catch (err) {
       setError(err instanceof Error ? err.message : String(err));
     } finally {
+      inFlightRef.current.clear();
       setLoading(false);
       setRefreshing(false);
     }
-  }, [provider]);
+  }, []);
 
   const handleTogglePin = useCallback(async () => {
     const next = !pinned;
@@ -140,6 +222,17 @@ function App() {
     }
   }, [pinned]);
 
+  // Skips any retry backoff; the result arrives as provider-fetch-* events
+  const handleRetry = useCallback(async () => {
+    if (provider === "both") return;
+    setError(null);
+    try {
+      await invoke("clear_backoff", { provider });
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    }
+  }, [provider]);
+
   const handleSwitchProvider = useCallback((p: Provider) => {
     setProvider(p);
     setError(null);
@@ -189,8 +282,7 @@ function App() {
     }
   }, [loadOpenRouterKeyStatus, fetchUsage]);
 
-  useEffect(() => {
-    // Try cached data first
+  const loadCached = useCallback(() => {
     invoke<UsageData | null>("get_cached_claude").then((cached) => {
       if (cached) { setClaudeData(cached); setLoading(false); }
     });
@@ -200,22 +292,90 @@ function App() {
     invoke<OpenRouterCreditsData | null>("get_cached_openrouter").then((cached) => {
       if (cached) { setOpenRouterData(cached); }
     });
+    invoke<GatewayBudgetData | null>("get_cached_litellm").then((cached) => {
+      if (cached) { setGatewayData(cached); }
+    });
+    invoke<LocalActivityData | null>("get_cached_ollama").then((cached) => {
+      if (cached) { setLocalData(cached); }
+    });
+  }, []);
+
+  useEffect(() => {
+    // Try cached data first
+    loadCached();
     loadOpenRouterKeyStatus();
+    invoke<ProviderLabels>("get_provider_labels").then(setLabels);
+    invoke<{ window_mode: WindowMode; widget_pinned: boolean }>("get_settings").then((s) => {
+      setWindowMode(s.window_mode);
+      setPinned(s.widget_pinned);
+    });
 
     fetchUsage();
 
-    const unlistenUsage = listen("usage-refresh-tick", () => {
-      fetchUsage();
+    // Background refreshes are driven by the backend scheduler
+    const finishFetch = (p: ProviderId) => {
+      inFlightRef.current.delete(p);
+      if (inFlightRef.current.size === 0) {
+        setRefreshing(false);
+        setLoading(false);
+      }
+    };
+    const unlistenStarted = listen<FetchStartedEvent>("provider-fetch-started", (e) => {
+      inFlightRef.current.add(e.payload.provider);
+      setRefreshing(true);
+    });
+    const unlistenSucceeded = listen<FetchSucceededEvent>("provider-fetch-succeeded", (e) => {
+      applySuccess(e.payload.provider, e.payload.data);
+      finishFetch(e.payload.provider);
+    });
+    const unlistenFailed = listen<FetchFailedEvent>("provider-fetch-failed", (e) => {
+      applyFailure(e.payload.provider, e.payload.error);
+      finishFetch(e.payload.provider);
+    });
+    const unlistenCancelled = listen<FetchStartedEvent>("provider-fetch-cancelled", (e) => {
+      finishFetch(e.payload.provider);
     });
     const unlistenSettings = listen("open-settings", () => {
       handleOpenSettings();
     });
+    // Cached data comes back redacted (or not) after a privacy mode toggle
+    const unlistenPrivacy = listen("privacy-mode-changed", () => {
+      loadCached();
+    });
+    const unlistenLabels = listen<ProviderLabels>("provider-labels-changed", (e) => {
+      setLabels(e.payload);
+    });
+    const unlistenMode = listen<{ window_mode: WindowMode; widget_pinned: boolean }>(
+      "settings-changed",
+      (e) => {
+        setWindowMode(e.payload.window_mode);
+        setPinned(e.payload.widget_pinned);
+      },
+    );
 
     return () => {
-      unlistenUsage.then((fn) => fn());
+      unlistenStarted.then((fn) => fn());
+      unlistenSucceeded.then((fn) => fn());
+      unlistenFailed.then((fn) => fn());
+      unlistenCancelled.then((fn) => fn());
       unlistenSettings.then((fn) => fn());
+      unlistenPrivacy.then((fn) => fn());
+      unlistenLabels.then((fn) => fn());
+      unlistenMode.then((fn) => fn());
     };
-  }, [fetchUsage, loadOpenRouterKeyStatus, handleOpenSettings]);
+  }, [fetchUsage, loadCached, loadOpenRouterKeyStatus, handleOpenSettings, applySuccess, applyFailure]);
+
+  if (windowMode === "mini") {
+    return (
+      <MiniStrip
+        claudeData={claudeData}
+        codexData={codexData}
+        claudeLabel={labels.claude}
+        codexLabel={labels.codex}
+        onExpand={() => invoke("set_window_mode", { mode: "full" })}
+      />
+    );
+  }
 
   return (
     <>
@@ -225,14 +385,21 @@ function App() {
         codexData={codexData}
         openRouterData={openRouterData}
         openRouterError={openRouterError}
+        gatewayData={gatewayData}
+        gatewayError={gatewayError}
+        localData={localData}
+        localError={localError}
         loading={loading}
         error={error}
         pinned={pinned}
         refreshing={refreshing}
         provider={provider}
+        labels={labels}
         onRefresh={fetchUsage}
         onTogglePin={handleTogglePin}
+        onMinimize={() => invoke("set_window_mode", { mode: "mini" })}
         onSwitchProvider={handleSwitchProvider}
+        onRetry={handleRetry}
       />
       <SettingsModal
         open={settingsOpen}
@@ -5,6 +5,7 @@ interface ExtraUsageProps {
   percent: number;
   resetDate: string;
   enabled: boolean;
+  redacted?: boolean;
 }
 
 export default function ExtraUsage({
@@ -12,6 +13,7 @@ export default function ExtraUsage({
   percent,
   resetDate,
   enabled,
+  redacted,
 }: ExtraUsageProps) {
   const clamped = Math.min(100, Math.max(0, percent));
 
@@ -19,7 +21,7 @@ export default function ExtraUsage({
     <div className="usage-bar">
       <div className="usage-bar__header">
         <span className="usage-bar__label">
-          ${dollarsSpent.toFixed(2)} spent
+          {redacted ? "$\u2022\u2022\u2022" : `$${dollarsSpent.toFixed(2)}`} spent
         </span>
         <span className="usage-bar__percent">
           <span
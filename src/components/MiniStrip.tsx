@@ -0,0 +1,45 @@
+import "./MiniStrip.css";
+
+interface UsageCategory {
+  percent_used: number;
+}
+
+interface UsageData {
+  session: UsageCategory;
+  weekly_all: UsageCategory;
+}
+
+interface MiniStripProps {
+  claudeData: UsageData | null;
+  codexData: UsageData | null;
+  claudeLabel: string;
+  codexLabel: string;
+  onExpand: () => void;
+}
+
+function formatPair(data: UsageData | null) {
+  if (!data) return "--";
+  return `${Math.round(data.session.percent_used)}/${Math.round(data.weekly_all.percent_used)}%`;
+}
+
+export default function MiniStrip({
+  claudeData,
+  codexData,
+  claudeLabel,
+  codexLabel,
+  onExpand,
+}: MiniStripProps) {
+  return (
+    <div
+      className="mini-strip"
+      data-tauri-drag-region
+      onDoubleClick={onExpand}
+      title="Double-click to expand"
+    >
+      <span className="mini-strip__label">{claudeLabel}</span>
+      <span className="mini-strip__value">{formatPair(claudeData)}</span>
+      <span className="mini-strip__label">{codexLabel}</span>
+      <span className="mini-strip__value">{formatPair(codexData)}</span>
+    </div>
+  );
+}
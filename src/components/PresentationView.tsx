@@ -0,0 +1,42 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import "./PresentationView.css";
+
+interface PresentationRow {
+  provider: string;
+  session_percent: number | null;
+  weekly_percent: number | null;
+}
+
+function formatPercent(value: number | null) {
+  return value === null ? "--" : `${Math.round(value)}%`;
+}
+
+export default function PresentationView() {
+  const [rows, setRows] = useState<PresentationRow[]>([]);
+
+  useEffect(() => {
+    invoke<PresentationRow[]>("get_presentation_snapshot").then(setRows);
+    const unlisten = listen<PresentationRow[]>("presentation-updated", (e) =>
+      setRows(e.payload),
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  return (
+    <div className="presentation" data-tauri-drag-region>
+      {rows.length === 0 && <div className="presentation__empty">No data yet</div>}
+      {rows.map((row) => (
+        <div key={row.provider} className="presentation__row" data-tauri-drag-region>
+          <span className="presentation__provider">{row.provider}</span>
+          <span className="presentation__value">
+            {formatPercent(row.session_percent)} / {formatPercent(row.weekly_percent)}
+          </span>
+        </div>
+      ))}
+    </div>
+  );
+}
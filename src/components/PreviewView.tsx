@@ -0,0 +1,49 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import "./PreviewView.css";
+
+interface PreviewLine {
+  label: string;
+  value: string;
+  detail: string | null;
+}
+
+interface PreviewSection {
+  provider: string;
+  lines: PreviewLine[];
+  failing: boolean;
+}
+
+export default function PreviewView() {
+  const [sections, setSections] = useState<PreviewSection[]>([]);
+
+  useEffect(() => {
+    invoke<PreviewSection[]>("get_preview_snapshot").then(setSections);
+    const unlisten = listen<PreviewSection[]>("preview-updated", (e) => setSections(e.payload));
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  return (
+    <div className="preview">
+      {sections.length === 0 && <div className="preview__empty">No data yet</div>}
+      {sections.map((section) => (
+        <div key={section.provider} className="preview__section">
+          <div className="preview__provider">
+            {section.provider}
+            {section.failing && <span className="preview__failing"> ⚠</span>}
+          </div>
+          {section.lines.map((line) => (
+            <div key={line.label} className="preview__line">
+              <span className="preview__label">{line.label}</span>
+              <span className="preview__value">{line.value}</span>
+              {line.detail && <span className="preview__detail">{line.detail}</span>}
+            </div>
+          ))}
+        </div>
+      ))}
+    </div>
+  );
+}
@@ -8,6 +8,7 @@ interface UsageCategory {
   label: string;
   percent_used: number;
   reset_info: string;
+  resets_at?: string | null;
 }
 
 interface ExtraData {
@@ -21,8 +22,13 @@ interface UsageData {
   session: UsageCategory;
   weekly_all: UsageCategory;
   weekly_sonnet: UsageCategory;
+  model_windows?: { family: string; metric: UsageCategory }[];
   extra: ExtraData;
   fetched_at: string;
+  plan?: string | null;
+  plan_tier?: string | null;
+  seat?: { workspace?: string | null; role?: string | null } | null;
+  redacted?: boolean;
 }
 
 interface OpenRouterCreditsData {
@@ -30,6 +36,34 @@ interface OpenRouterCreditsData {
   total_usage: number;
   remaining_credits: number;
   fetched_at: string;
+  redacted?: boolean;
+}
+
+interface GatewayBudgetData {
+  key_alias: string | null;
+  spend: number;
+  max_budget: number | null;
+  remaining: number | null;
+  percent_used: number;
+  budget_duration: string | null;
+  budget_resets_at: string | null;
+  fetched_at: string;
+  redacted?: boolean;
+}
+
+interface LocalModel {
+  name: string;
+  size_bytes: number;
+  vram_bytes: number;
+  expires_at: string | null;
+}
+
+interface LocalActivityData {
+  running: boolean;
+  models: LocalModel[];
+  requests_last_hour: number;
+  last_active_at: string | null;
+  fetched_at: string;
 }
 
 interface UsagePanelProps {
@@ -38,20 +72,54 @@ interface UsagePanelProps {
   codexData: UsageData | null;
   openRouterData: OpenRouterCreditsData | null;
   openRouterError: string | null;
+  gatewayData: GatewayBudgetData | null;
+  gatewayError: string | null;
+  localData: LocalActivityData | null;
+  localError: string | null;
   loading: boolean;
   error: string | null;
   pinned: boolean;
   refreshing: boolean;
   provider: Provider;
+  labels: { claude: string; codex: string; litellm: string; ollama: string };
   onRefresh: () => void;
   onTogglePin: () => void;
+  onMinimize: () => void;
   onSwitchProvider: (p: Provider) => void;
+  onRetry: () => void;
+}
+
+const PLAN_TIERS: Record<string, string> = {
+  free: "Free",
+  plus: "Plus",
+  pro: "Pro",
+  max_5x: "Max 5x",
+  max_20x: "Max 20x",
+  team: "Team",
+  business: "Business",
+  enterprise: "Enterprise",
+  edu: "Edu",
+};
+
+function PlanBadge({ data }: { data: UsageData }) {
+  const tier = data.plan_tier;
+  if (!tier || !PLAN_TIERS[tier]) return null;
+  const seat = [data.seat?.workspace, data.seat?.role].filter(Boolean).join(" \u00b7 ");
+  return (
+    <span className="usage-panel__plan" title={seat || undefined}>
+      {PLAN_TIERS[tier]}
+      {data.seat?.workspace ? ` \u00b7 ${data.seat.workspace}` : ""}
+    </span>
+  );
 }
 
 function ProviderSection({ title, data }: { title: string; data: UsageData }) {
   return (
     <>
-      <div className="usage-panel__provider-header">{title}</div>
+      <div className="usage-panel__provider-header">
+        {title}
+        <PlanBadge data={data} />
+      </div>
       <UsageBar
         label={data.session.label}
         percent={data.session.percent_used}
@@ -66,6 +134,12 @@ function ProviderSection({ title, data }: { title: string; data: UsageData }) {
   );
 }
 
+const HIDDEN = "$\u2022\u2022\u2022";
+
+function money(amount: number, redacted?: boolean) {
+  return redacted ? HIDDEN : `$${amount.toFixed(2)}`;
+}
+
 function OpenRouterSection({
   data,
   error,
@@ -80,15 +154,15 @@ function OpenRouterSection({
         <>
           <div className="usage-panel__credit-row">
             <span>Remaining</span>
-            <strong>${data.remaining_credits.toFixed(2)}</strong>
+            <strong>{money(data.remaining_credits, data.redacted)}</strong>
           </div>
           <div className="usage-panel__credit-row">
             <span>Used</span>
-            <span>${data.total_usage.toFixed(2)}</span>
+            <span>{money(data.total_usage, data.redacted)}</span>
           </div>
           <div className="usage-panel__credit-row">
             <span>Total</span>
-            <span>${data.total_credits.toFixed(2)}</span>
+            <span>{money(data.total_credits, data.redacted)}</span>
           </div>
         </>
       ) : error ? (
@@ -100,20 +174,130 @@ function OpenRouterSection({
   );
 }
 
+// Only shown once a gateway is set up, unlike the OpenRouter hint
+function GatewaySection({
+  title,
+  data,
+  error,
+}: {
+  title: string;
+  data: GatewayBudgetData | null;
+  error: string | null;
+}) {
+  if (!data && !error) return null;
+  return (
+    <div className="usage-panel__section">
+      <div className="usage-panel__section-title">{title} budget</div>
+      {data ? (
+        <>
+          {data.remaining !== null && data.max_budget !== null ? (
+            <>
+              <UsageBar
+                label={data.key_alias || "Key budget"}
+                percent={data.percent_used}
+                resetInfo={
+                  data.budget_resets_at
+                    ? `Resets ${new Date(data.budget_resets_at).toLocaleDateString()}`
+                    : data.budget_duration || "---"
+                }
+              />
+              <div className="usage-panel__credit-row">
+                <span>Remaining</span>
+                <strong>{money(data.remaining, data.redacted)}</strong>
+              </div>
+              <div className="usage-panel__credit-row">
+                <span>Budget</span>
+                <span>{money(data.max_budget, data.redacted)}</span>
+              </div>
+            </>
+          ) : null}
+          <div className="usage-panel__credit-row">
+            <span>Spent</span>
+            <span>{money(data.spend, data.redacted)}</span>
+          </div>
+        </>
+      ) : (
+        <div className="usage-panel__error-inline">{error}</div>
+      )}
+    </div>
+  );
+}
+
+function gigabytes(bytes: number): string {
+  return `${(bytes / 1e9).toFixed(1)} GB`;
+}
+
+// Informational only: local models don't use any cloud quota
+function LocalSection({
+  title,
+  data,
+  error,
+}: {
+  title: string;
+  data: LocalActivityData | null;
+  error: string | null;
+}) {
+  if (!data && !error) return null;
+  return (
+    <div className="usage-panel__section">
+      <div className="usage-panel__section-title">{title} (local)</div>
+      {data ? (
+        <>
+          {!data.running ? (
+            <div className="usage-panel__credit-row">
+              <span>Not running</span>
+            </div>
+          ) : data.models.length === 0 ? (
+            <div className="usage-panel__credit-row">
+              <span>No models loaded</span>
+            </div>
+          ) : (
+            data.models.map((m) => (
+              <div className="usage-panel__credit-row" key={m.name}>
+                <span>{m.name}</span>
+                <span>{gigabytes(m.vram_bytes || m.size_bytes)}</span>
+              </div>
+            ))
+          )}
+          <div className="usage-panel__credit-row">
+            <span>Requests (last hour)</span>
+            <strong>{data.requests_last_hour}</strong>
+          </div>
+          {data.last_active_at && (
+            <div className="usage-panel__credit-row">
+              <span>Last active</span>
+              <span>{new Date(data.last_active_at).toLocaleTimeString()}</span>
+            </div>
+          )}
+        </>
+      ) : (
+        <div className="usage-panel__error-inline">{error}</div>
+      )}
+    </div>
+  );
+}
+
 export default function UsagePanel({
   data,
   claudeData,
   codexData,
   openRouterData,
   openRouterError,
+  gatewayData,
+  gatewayError,
+  localData,
+  localError,
   loading,
   error,
   pinned,
   refreshing,
   provider,
+  labels,
   onRefresh,
   onTogglePin,
+  onMinimize,
   onSwitchProvider,
+  onRetry,
 }: UsagePanelProps) {
   const isBoth = provider === "both";
 
@@ -124,13 +308,13 @@ export default function UsagePanel({
           className={`usage-panel__tab ${provider === "claude" ? "usage-panel__tab--active" : ""}`}
           onClick={() => onSwitchProvider("claude")}
         >
-          Claude
+          {labels.claude}
         </button>
         <button
           className={`usage-panel__tab ${provider === "codex" ? "usage-panel__tab--active" : ""}`}
           onClick={() => onSwitchProvider("codex")}
         >
-          Codex
+          {labels.codex}
         </button>
         <button
           className={`usage-panel__tab ${provider === "both" ? "usage-panel__tab--active" : ""}`}
@@ -153,10 +337,22 @@ export default function UsagePanel({
           >
             {pinned ? "\uD83D\uDCCD" : "\uD83D\uDCCC"}
           </button>
+          <button className="usage-panel__btn" onClick={onMinimize} title="Mini strip">
+            &#8863;
+          </button>
         </div>
       </div>
 
-      {error && <div className="usage-panel__error">{error}</div>}
+      {error && (
+        <div className="usage-panel__error">
+          {error}
+          {!isBoth && (
+            <button className="usage-panel__retry" onClick={onRetry}>
+              Try again now
+            </button>
+          )}
+        </div>
+      )}
 
       {loading && !data && !isBoth && (
         <div className="usage-panel__loading">Loading...</div>
@@ -170,15 +366,17 @@ export default function UsagePanel({
         <>
           {claudeData && (
             <div className="usage-panel__section">
-              <ProviderSection title="Claude" data={claudeData} />
+              <ProviderSection title={labels.claude} data={claudeData} />
             </div>
           )}
           {codexData && (
             <div className="usage-panel__section">
-              <ProviderSection title="Codex" data={codexData} />
+              <ProviderSection title={labels.codex} data={codexData} />
             </div>
           )}
           <OpenRouterSection data={openRouterData} error={openRouterError} />
+          <GatewaySection title={labels.litellm} data={gatewayData} error={gatewayError} />
+          <LocalSection title={labels.ollama} data={localData} error={localError} />
           <div className="usage-panel__footer">
             Updated: {new Date(
               claudeData?.fetched_at || codexData?.fetched_at || ""
@@ -191,7 +389,10 @@ export default function UsagePanel({
       {!isBoth && data && (
         <>
           <div className="usage-panel__section">
-            <div className="usage-panel__section-title">Session</div>
+            <div className="usage-panel__section-title">
+              Session
+              <PlanBadge data={data} />
+            </div>
             <UsageBar
               label={data.session.label}
               percent={data.session.percent_used}
@@ -211,6 +412,14 @@ export default function UsagePanel({
               percent={data.weekly_sonnet.percent_used}
               resetInfo={data.weekly_sonnet.reset_info}
             />
+            {data.model_windows?.map((w) => (
+              <UsageBar
+                key={w.family}
+                label={w.metric.label}
+                percent={w.metric.percent_used}
+                resetInfo={w.metric.reset_info}
+              />
+            ))}
           </div>
 
           <div className="usage-panel__section">
@@ -220,10 +429,13 @@ export default function UsagePanel({
               percent={data.extra.percent_used}
               resetDate={data.extra.reset_date}
               enabled={data.extra.enabled}
+              redacted={data.redacted}
             />
           </div>
 
           <OpenRouterSection data={openRouterData} error={openRouterError} />
+          <GatewaySection title={labels.litellm} data={gatewayData} error={gatewayError} />
+          <LocalSection title={labels.ollama} data={localData} error={localError} />
 
           <div className="usage-panel__footer">
             Updated: {new Date(data.fetched_at).toLocaleTimeString()}
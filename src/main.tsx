@@ -1,10 +1,18 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
 import App from "./App";
+import PresentationView from "./components/PresentationView";
+import PreviewView from "./components/PreviewView";
 import "./styles.css";
 
 ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
   <React.StrictMode>
-    <App />
+    {window.location.hash === "#presentation" ? (
+      <PresentationView />
+    ) : window.location.hash === "#preview" ? (
+      <PreviewView />
+    ) : (
+      <App />
+    )}
   </React.StrictMode>,
 );